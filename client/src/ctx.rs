@@ -155,6 +155,7 @@ impl SessionContext {
             tenant: Some(tenant),
             db: Some(db),
             chunked: None,
+            chunk_size: None,
             target_partitions,
         };
 
@@ -194,6 +195,9 @@ impl SessionContext {
         let param = WriteParam {
             tenant: None,
             db: self.session_config.database.clone(),
+            consistency: None,
+            precision: None,
+            lenient: None,
         };
 
         // let param = &[("db", &self.session_config.database)];
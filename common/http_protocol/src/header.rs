@@ -1,5 +1,5 @@
 // re-export const header names
-pub use reqwest::header::{HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+pub use reqwest::header::{HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE};
 
 /// value
 pub const APPLICATION_PREFIX: &str = "application/";
@@ -11,6 +11,11 @@ pub const APPLICATION_TABLE: &str = "application/table";
 pub const APPLICATION_STAR: &str = "application/*";
 pub const STAR_STAR: &str = "*/*";
 
+/// content encoding
+pub const GZIP: &str = "gzip";
+pub const ZSTD: &str = "zstd";
+pub const IDENTITY: &str = "identity";
+
 /// basic auth
 pub const BASIC_PREFIX: &str = "Basic ";
 pub const BEARER_PREFIX: &str = "Bearer ";
@@ -1,3 +1,4 @@
+use models::consistency_level::ConsistencyLevel;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -5,7 +6,10 @@ use serde::{Deserialize, Serialize};
 pub struct SqlParam {
     pub tenant: Option<String>,
     pub db: Option<String>,
-    pub chunked: Option<String>,
+    // Send the result in chunks of at most chunk_size rows as the query executes,
+    // only for the csv, tsv and ndjson formats.
+    pub chunked: Option<bool>,
+    pub chunk_size: Option<usize>,
     // Number of partitions for query execution. Increasing partitions can increase concurrency.
     pub target_partitions: Option<usize>,
 }
@@ -15,4 +19,55 @@ pub struct SqlParam {
 pub struct WriteParam {
     pub tenant: Option<String>,
     pub db: String,
+    // Consistency level of the write: any, one, quorum or all. Defaults to any.
+    pub consistency: Option<ConsistencyLevel>,
+    // Precision of the timestamps of the lines: ns, us, ms or s. The timestamps are in the
+    // precision of the database if it is not given.
+    pub precision: Option<String>,
+    // Write the valid lines and report the bad ones, instead of rejecting the whole body.
+    pub lenient: Option<bool>,
+}
+
+/// Parameters of `/write` of the InfluxDB 1.x api.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct InfluxWriteParam {
+    pub db: String,
+    // Retention policy, ignored.
+    pub rp: Option<String>,
+    pub precision: Option<String>,
+    pub consistency: Option<ConsistencyLevel>,
+    pub u: Option<String>,
+    pub p: Option<String>,
+}
+
+/// Parameters of `/api/v2/write` of the InfluxDB 2.x api.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct InfluxV2WriteParam {
+    // Mapped to the tenant.
+    pub org: Option<String>,
+    // Mapped to the database, a `db/rp` bucket is mapped to `db`.
+    pub bucket: String,
+    pub precision: Option<String>,
+}
+
+/// Parameters of `/api/put` of the OpenTSDB api.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct OpenTsdbPutParam {
+    pub tenant: Option<String>,
+    pub db: Option<String>,
+}
+
+/// Parameters of `/query` of the InfluxDB 1.x api.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct InfluxQueryParam {
+    pub db: Option<String>,
+    pub q: Option<String>,
+    // Returns the timestamps as the integers of the precision instead of RFC3339 strings.
+    pub epoch: Option<String>,
+    pub u: Option<String>,
+    pub p: Option<String>,
 }
@@ -21,3 +21,38 @@ impl ErrorResponse {
         }
     }
 }
+
+/// The body of a write which rejected some of the lines, the other lines are written.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PartialWriteResponse {
+    error_code: String,
+    error_message: String,
+    accepted: usize,
+    rejected: usize,
+    errors: Vec<LineErrorResponse>,
+}
+
+impl PartialWriteResponse {
+    pub fn new(
+        error_code: &dyn ErrorCode,
+        accepted: usize,
+        errors: Vec<LineErrorResponse>,
+    ) -> PartialWriteResponse {
+        Self {
+            error_code: error_code.code().to_string(),
+            error_message: error_code.message(),
+            accepted,
+            rejected: errors.len(),
+            errors,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct LineErrorResponse {
+    pub line: usize,
+    pub measurement: String,
+    pub reason: String,
+}
@@ -1,7 +1,7 @@
 use snafu::Snafu;
 
 mod parser;
-pub use parser::{FieldValue, Line, Parser};
+pub use parser::{FieldValue, Line, LineError, Parser};
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -16,3 +16,11 @@ pub fn line_protocol_to_lines(lines: &str, default_time: i64) -> Result<Vec<Line
     let parser = Parser::new(default_time);
     parser.parse(lines)
 }
+
+pub fn line_protocol_to_lines_lenient(
+    lines: &str,
+    default_time: i64,
+) -> (Vec<Line>, Vec<LineError>) {
+    let parser = Parser::new(default_time);
+    parser.parse_lenient(lines)
+}
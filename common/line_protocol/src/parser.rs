@@ -22,6 +22,50 @@ impl Parser {
         Ok(ret)
     }
 
+    /// Parses the lines one by one, a bad line is collected as an error instead of failing
+    /// the lines after it. The empty lines and the comments are skipped.
+    pub fn parse_lenient<'a>(&self, lines: &'a str) -> (Vec<Line<'a>>, Vec<LineError>) {
+        let mut ret: Vec<Line> = Vec::new();
+        let mut errors: Vec<LineError> = Vec::new();
+        for (idx, buf) in lines.split('\n').enumerate() {
+            let trimmed = buf.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            match self.next_line(buf, 0) {
+                // nothing but whitespaces may follow the timestamp
+                Ok(Some((_, pos))) if !buf.get(pos..).map_or(true, |r| r.trim().is_empty()) => {
+                    errors.push(LineError {
+                        line: idx + 1,
+                        measurement: next_measurement(buf)
+                            .map(|m| m.0.to_string())
+                            .unwrap_or_default(),
+                        reason: Error::Parse {
+                            pos,
+                            content: String::from(buf),
+                        }
+                        .to_string(),
+                    })
+                }
+                Ok(Some((line, _))) => ret.push(line),
+                Ok(None) => errors.push(LineError {
+                    line: idx + 1,
+                    measurement: String::new(),
+                    reason: "missing measurement".to_string(),
+                }),
+                Err(e) => errors.push(LineError {
+                    line: idx + 1,
+                    measurement: next_measurement(buf)
+                        .map(|m| m.0.to_string())
+                        .unwrap_or_default(),
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        (ret, errors)
+    }
+
     fn next_line<'a>(&self, buf: &'a str, position: usize) -> Result<Option<(Line<'a>, usize)>> {
         if position > buf.len() {
             return Ok(None);
@@ -86,6 +130,14 @@ impl Parser {
     }
 }
 
+/// A line rejected by the lenient parsing, the line numbers start from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    pub line: usize,
+    pub measurement: String,
+    pub reason: String,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum FieldValue {
     U64(u64),
@@ -435,28 +487,25 @@ fn parse_string_field(buf: &str) -> Result<FieldValue> {
     }
 }
 
+/// Returns the timestamp after the spaces and the length of it with the character ending it,
+/// none if the next token is not a timestamp ended by a whitespace or the end of the buffer.
 fn next_timestamp(buf: &str) -> Option<(&str, usize)> {
-    let mut exists_timestamp = false;
-    let (mut tok_begin, mut tok_end) = (0, buf.len());
-    for (i, c) in buf.chars().enumerate() {
-        if exists_timestamp {
-            // Timestamp end character
-            if !c.is_numeric() {
-                tok_end = i;
-                break;
-            }
-        } else {
-            // Timestamp begin character
-            if c == '-' || c.is_numeric() {
-                tok_begin = i;
-                exists_timestamp = true;
-            }
-        }
-    }
-    if exists_timestamp {
-        Some((&buf[tok_begin..tok_end], tok_end + 1))
+    let tok_begin = buf.len() - buf.trim_start_matches(|c| c == ' ' || c == '\t').len();
+    let digits_begin = if buf[tok_begin..].starts_with('-') {
+        tok_begin + 1
     } else {
-        None
+        tok_begin
+    };
+    let tok_end = buf[digits_begin..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(buf.len(), |i| digits_begin + i);
+    if tok_end == digits_begin {
+        return None;
+    }
+
+    match buf[tok_end..].chars().next() {
+        Some(c) if !c.is_ascii_whitespace() => None,
+        _ => Some((&buf[tok_begin..tok_end], tok_end + 1)),
     }
 }
 
@@ -465,7 +514,8 @@ mod test {
     use std::{fs::File, io::Read};
 
     use crate::parser::{
-        next_field_set, next_measurement, next_tag_set, next_timestamp, FieldValue, Line, Parser,
+        next_field_set, next_measurement, next_tag_set, next_timestamp, FieldValue, Line,
+        LineError, Parser,
     };
 
     #[test]
@@ -603,6 +653,47 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_lenient_parser() {
+        let lines = "ma,ta=1 fa=1 1\n# comment\n\nmb,tb=2 fb=abc 2\nmc,tc=3\nmd,td=4 fd=4i 4\n";
+
+        let parser = Parser::new(-1);
+        assert!(parser.parse(lines).is_err());
+
+        let (data, errors) = parser.parse_lenient(lines);
+        let measurements: Vec<&str> = data.iter().map(|l| l.measurement).collect();
+        assert_eq!(measurements, vec!["ma", "md"]);
+        assert_eq!(data[1].fields, vec![("fd", FieldValue::I64(4))]);
+
+        let rejected: Vec<(usize, &str)> = errors
+            .iter()
+            .map(
+                |LineError {
+                     line, measurement, ..
+                 }| (*line, measurement.as_str()),
+            )
+            .collect();
+        assert_eq!(rejected, vec![(4, "mb"), (5, "mc")]);
+    }
+
+    #[test]
+    fn test_lenient_parser_trailing_content() {
+        let lines = "ma,ta=1 fa=1 1 x\nmb,tb=2 fb=2 2x\nmc,tc=3 fc=3 x3\nmd,td=4 fd=4 y\n\
+            me,te=5 fe=5 5 \r\nmf,tf=6 ff=6\n";
+
+        let parser = Parser::new(-1);
+        let (data, errors) = parser.parse_lenient(lines);
+        let accepted: Vec<(&str, i64)> =
+            data.iter().map(|l| (l.measurement, l.timestamp)).collect();
+        assert_eq!(accepted, vec![("me", 5), ("mf", -1)]);
+
+        let rejected: Vec<(usize, &str)> = errors
+            .iter()
+            .map(|e| (e.line, e.measurement.as_str()))
+            .collect();
+        assert_eq!(rejected, vec![(1, "ma"), (2, "mb"), (3, "mc"), (4, "md")]);
+    }
+
     #[test]
     #[ignore]
     fn test_generated_data() {
@@ -1,6 +1,7 @@
 use once_cell::sync::Lazy;
 use prometheus::{
     default_registry, gather, register_histogram_vec, register_int_counter_vec, IntCounterVec,
+    IntGaugeVec,
 };
 use prometheus::{linear_buckets, HistogramOpts, HistogramVec, IntCounter, Opts};
 use std::ops::Not;
@@ -15,6 +16,7 @@ pub const SERVER_HTTP_QUERY: &str = "server_http_query";
 
 pub const SERVER_GRPC: &str = "grpc";
 pub const TSKV_SUBSYSTEM: &str = "tskv";
+pub const COORDINATOR_SUBSYSTEM: &str = "coordinator";
 
 pub static QUERY_SUCCESS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -133,6 +135,29 @@ pub static COMPACTION_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
     .expect("tskv metric cannot be created")
 });
 
+pub static BLOCK_CACHE_HIT: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new("block_cache_hit_total", "total hit num of tsm block cache")
+            .namespace(NAMESPACE)
+            .subsystem(TSKV_SUBSYSTEM),
+        &["type"],
+    )
+    .expect("tskv metric cannot be created")
+});
+
+pub static BLOCK_CACHE_MISS: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "block_cache_miss_total",
+            "total miss num of tsm block cache",
+        )
+        .namespace(NAMESPACE)
+        .subsystem(TSKV_SUBSYSTEM),
+        &["type"],
+    )
+    .expect("tskv metric cannot be created")
+});
+
 pub fn init_tskv_metrics_recorder() {
     default_registry()
         .register(Box::new(COMPACTION_SUCCESS.clone()))
@@ -143,6 +168,12 @@ pub fn init_tskv_metrics_recorder() {
     default_registry()
         .register(Box::new(COMPACTION_DURATION.clone()))
         .expect("tskv metrics collector cannot be registered");
+    default_registry()
+        .register(Box::new(BLOCK_CACHE_HIT.clone()))
+        .expect("tskv metrics collector cannot be registered");
+    default_registry()
+        .register(Box::new(BLOCK_CACHE_MISS.clone()))
+        .expect("tskv metrics collector cannot be registered");
 }
 
 pub fn incr_compaction_success() {
@@ -153,6 +184,16 @@ pub fn incr_compaction_failed() {
     COMPACTION_FAILED.inc();
 }
 
+/// Records a hit of the tsm block cache, the cache_type is "index" or "block".
+pub fn incr_block_cache_hit(cache_type: &str) {
+    BLOCK_CACHE_HIT.with_label_values(&[cache_type]).inc();
+}
+
+/// Records a miss of the tsm block cache, the cache_type is "index" or "block".
+pub fn incr_block_cache_miss(cache_type: &str) {
+    BLOCK_CACHE_MISS.with_label_values(&[cache_type]).inc();
+}
+
 pub fn sample_tskv_compaction_duration(db: &str, ts_family: &str, level: &str, delta: f64) {
     COMPACTION_DURATION
         .with_label_values(&[db, ts_family, level])
@@ -169,3 +210,74 @@ pub fn gather_metrics() -> Vec<u8> {
     }
     buffer
 }
+
+pub static HINTED_OFF_QUEUE_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        Opts::new(
+            "hinted_off_queue_bytes",
+            "bytes in the hinted off queue of the node",
+        )
+        .namespace(NAMESPACE)
+        .subsystem(COORDINATOR_SUBSYSTEM),
+        &["node"],
+    )
+    .expect("coordinator metric cannot be created")
+});
+
+pub static HINTED_OFF_REPLAYED: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "hinted_off_replayed_bytes_total",
+            "total bytes replayed to the node from the hinted off queue",
+        )
+        .namespace(NAMESPACE)
+        .subsystem(COORDINATOR_SUBSYSTEM),
+        &["node"],
+    )
+    .expect("coordinator metric cannot be created")
+});
+
+pub static HINTED_OFF_DROPPED: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "hinted_off_dropped_bytes_total",
+            "total bytes dropped from the hinted off queue of the node",
+        )
+        .namespace(NAMESPACE)
+        .subsystem(COORDINATOR_SUBSYSTEM),
+        &["node", "reason"],
+    )
+    .expect("coordinator metric cannot be created")
+});
+
+pub fn init_coordinator_metrics_recorder() {
+    default_registry()
+        .register(Box::new(HINTED_OFF_QUEUE_SIZE.clone()))
+        .expect("coordinator metrics collector cannot be registered");
+    default_registry()
+        .register(Box::new(HINTED_OFF_REPLAYED.clone()))
+        .expect("coordinator metrics collector cannot be registered");
+    default_registry()
+        .register(Box::new(HINTED_OFF_DROPPED.clone()))
+        .expect("coordinator metrics collector cannot be registered");
+}
+
+pub fn set_hinted_off_queue_size(node_id: u64, size: u64) {
+    HINTED_OFF_QUEUE_SIZE
+        .with_label_values(&[&node_id.to_string()])
+        .set(size as i64);
+}
+
+pub fn incr_hinted_off_replayed(node_id: u64, bytes: u64) {
+    HINTED_OFF_REPLAYED
+        .with_label_values(&[&node_id.to_string()])
+        .inc_by(bytes);
+}
+
+/// Records the bytes dropped without being replayed, the reason is "max_age", "ttl",
+/// "vnode_not_found" or "purge".
+pub fn incr_hinted_off_dropped(node_id: u64, reason: &str, bytes: u64) {
+    HINTED_OFF_DROPPED
+        .with_label_values(&[&node_id.to_string(), reason])
+        .inc_by(bytes);
+}
@@ -1,7 +1,10 @@
-#[allow(dead_code)]
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ConsistencyLevel {
     /// allows for hinted handoff, potentially no write happened yet.
+    #[default]
     Any,
     /// at least one data node acknowledged a write or read.
     One,
@@ -10,3 +13,50 @@ pub enum ConsistencyLevel {
     /// requires all data nodes to acknowledge a write or read.
     All,
 }
+
+impl ConsistencyLevel {
+    /// Returns whether a write to a replication set of `replica` vnodes is acknowledged,
+    /// given the number of vnodes that wrote the data (`acked`)
+    /// and the number of vnodes whose data was accepted by hinted handoff (`hinted_off`).
+    pub fn is_satisfied(&self, replica: usize, acked: usize, hinted_off: usize) -> bool {
+        match self {
+            ConsistencyLevel::Any => acked + hinted_off >= 1,
+            ConsistencyLevel::One => acked >= 1,
+            ConsistencyLevel::Quorum => acked > replica / 2,
+            ConsistencyLevel::All => acked >= replica,
+        }
+    }
+}
+
+impl From<protos::kv_service::ConsistencyLevel> for ConsistencyLevel {
+    fn from(level: protos::kv_service::ConsistencyLevel) -> Self {
+        match level {
+            protos::kv_service::ConsistencyLevel::Any => ConsistencyLevel::Any,
+            protos::kv_service::ConsistencyLevel::One => ConsistencyLevel::One,
+            protos::kv_service::ConsistencyLevel::Quorum => ConsistencyLevel::Quorum,
+            protos::kv_service::ConsistencyLevel::All => ConsistencyLevel::All,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ConsistencyLevel;
+
+    #[test]
+    fn test_is_satisfied() {
+        assert!(ConsistencyLevel::Any.is_satisfied(3, 0, 1));
+        assert!(!ConsistencyLevel::Any.is_satisfied(3, 0, 0));
+
+        assert!(ConsistencyLevel::One.is_satisfied(3, 1, 0));
+        assert!(!ConsistencyLevel::One.is_satisfied(3, 0, 3));
+
+        assert!(ConsistencyLevel::Quorum.is_satisfied(3, 2, 0));
+        assert!(!ConsistencyLevel::Quorum.is_satisfied(3, 1, 2));
+        assert!(ConsistencyLevel::Quorum.is_satisfied(1, 1, 0));
+        assert!(!ConsistencyLevel::Quorum.is_satisfied(4, 2, 0));
+
+        assert!(ConsistencyLevel::All.is_satisfied(3, 3, 0));
+        assert!(!ConsistencyLevel::All.is_satisfied(3, 2, 1));
+    }
+}
@@ -12,6 +12,7 @@ pub type ReplicationSetId = u32;
 pub struct SysInfo {
     pub cpu_load: f64,
     pub disk_free: u64,
+    pub disk_total: u64,
     pub mem_free: u64,
 }
 
@@ -37,6 +38,95 @@ pub struct NodeInfo {
     pub status: u64,
 }
 
+/// The node serves requests and takes the vnodes of new buckets.
+pub const NODE_STATUS_ONLINE: u64 = 0;
+/// The node is being decommissioned, its vnodes are moved to the other nodes
+/// and it's removed from the cluster after the last one is moved.
+pub const NODE_STATUS_DECOMMISSIONING: u64 = 1;
+
+impl NodeInfo {
+    pub fn is_decommissioning(&self) -> bool {
+        self.status == NODE_STATUS_DECOMMISSIONING
+    }
+
+    pub fn status_name(&self) -> &'static str {
+        if self.is_decommissioning() {
+            "DECOMMISSIONING"
+        } else {
+            "ONLINE"
+        }
+    }
+}
+
+/// A node without heartbeats for the time (in nanoseconds) is suspect.
+pub const NODE_SUSPECT_TIMEOUT: i64 = 10_000_000_000;
+/// A node without heartbeats for the time (in nanoseconds) is dead,
+/// its writes are sent to hinted handoff and it's not read from.
+pub const NODE_DEAD_TIMEOUT: i64 = 30_000_000_000;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct NodeHeartbeat {
+    pub node_id: NodeId,
+    pub version: String,
+    pub sys_info: SysInfo,
+    /// The time the meta leader received the heartbeat, set by the leader.
+    pub time: i64,
+    /// The hinted off queues of the node.
+    #[serde(default)]
+    pub hinted_off: Vec<HintedOffQueueStatus>,
+}
+
+/// The writes queued on a node for the target node which failed to receive them.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct HintedOffQueueStatus {
+    pub target_node: NodeId,
+    /// The bytes in the queue.
+    pub size: u64,
+    /// The time of the oldest write in the queue.
+    pub oldest_ts: Option<i64>,
+    pub paused: bool,
+    pub replayed_bytes: u64,
+    /// The bytes replayed per second.
+    pub replay_rate: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeState {
+    Alive,
+    Suspect,
+    Dead,
+}
+
+impl NodeState {
+    /// The state of a node whose last heartbeat was received at `last_heartbeat`.
+    pub fn from_heartbeat(last_heartbeat: i64, now: i64) -> Self {
+        let elapsed = now - last_heartbeat;
+        if elapsed >= NODE_DEAD_TIMEOUT {
+            NodeState::Dead
+        } else if elapsed >= NODE_SUSPECT_TIMEOUT {
+            NodeState::Suspect
+        } else {
+            NodeState::Alive
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeState::Alive => "ALIVE",
+            NodeState::Suspect => "SUSPECT",
+            NodeState::Dead => "DEAD",
+        }
+    }
+}
+
+/// The data node with its last heartbeat and the state derived from it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NodeStatus {
+    pub info: NodeInfo,
+    pub heartbeat: Option<NodeHeartbeat>,
+    pub state: NodeState,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct BucketInfo {
     pub id: u32,
@@ -89,6 +179,53 @@ pub struct DatabaseInfo {
     pub schema: DatabaseSchema,
     pub buckets: Vec<BucketInfo>,
     pub tables: HashMap<String, TableSchema>,
+    #[serde(default)]
+    pub streams: HashMap<String, StreamInfo>,
+}
+
+// CREATE STREAM <stream_name>
+// [WITH [INTERVAL <duration>] [DELAY <duration>]]
+// AS <select> INTO <target_table>
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct StreamInfo {
+    pub tenant: String,
+    pub db: String,
+    pub name: String,
+    pub target_table: String,
+    /// The select statement writing into the target table
+    pub query: String,
+    /// The length of the time window, in nanoseconds
+    pub interval: i64,
+    /// How long to wait for the late data before a window is processed, in nanoseconds
+    pub delay: i64,
+    /// The user executing the query
+    pub owner: String,
+    /// The data node executing the query
+    pub node_id: NodeId,
+    /// The data before the watermark has been processed
+    pub watermark: i64,
+}
+
+impl StreamInfo {
+    /// Returns the time range [start, end) ready to be processed at `now`,
+    /// the end is aligned to the interval and covers at most `max_windows` windows.
+    pub fn next_window(&self, now: i64, max_windows: i64) -> Option<(i64, i64)> {
+        if self.interval <= 0 {
+            return None;
+        }
+
+        let ready = now.saturating_sub(self.delay);
+        let end = ready - ready.rem_euclid(self.interval);
+        let end = end.min(
+            self.watermark
+                .saturating_add(self.interval.saturating_mul(max_windows.max(1))),
+        );
+        if end > self.watermark {
+            Some((self.watermark, end))
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -132,6 +269,10 @@ impl TenantMetaData {
             for (name, item) in val.tables.iter() {
                 info.tables.insert(name.clone(), item.clone());
             }
+
+            for (name, item) in val.streams.iter() {
+                info.streams.insert(name.clone(), item.clone());
+            }
         }
     }
 
@@ -141,7 +282,11 @@ impl TenantMetaData {
         }
 
         for (key, val) in delete.dbs.iter() {
-            if val.schema.is_empty() && val.buckets.is_empty() && val.tables.is_empty() {
+            if val.schema.is_empty()
+                && val.buckets.is_empty()
+                && val.tables.is_empty()
+                && val.streams.is_empty()
+            {
                 self.dbs.remove(key);
                 continue;
             }
@@ -159,6 +304,10 @@ impl TenantMetaData {
             for (name, _) in val.tables.iter() {
                 info.tables.remove(name);
             }
+
+            for (name, _) in val.streams.iter() {
+                info.streams.remove(name);
+            }
         }
     }
 
@@ -172,6 +321,13 @@ impl TenantMetaData {
         None
     }
 
+    pub fn streams(&self) -> Vec<StreamInfo> {
+        self.dbs
+            .values()
+            .flat_map(|db| db.streams.values().cloned())
+            .collect()
+    }
+
     pub fn database_min_ts(&self, name: &str) -> Option<i64> {
         if let Some(db) = self.dbs.get(name) {
             let ttl = db.schema.config.ttl_or_default().to_nanoseconds();
@@ -265,3 +421,41 @@ pub fn allocation_replication_set(
 
     (group, incr_id - begin_seq)
 }
+
+#[cfg(test)]
+mod test {
+    use super::{NodeState, StreamInfo, NODE_DEAD_TIMEOUT, NODE_SUSPECT_TIMEOUT};
+
+    #[test]
+    fn test_stream_next_window() {
+        let mut stream = StreamInfo {
+            interval: 60,
+            delay: 10,
+            watermark: 120,
+            ..Default::default()
+        };
+
+        // the window [120, 180) is not ready until 190
+        assert_eq!(stream.next_window(185, 10), None);
+        assert_eq!(stream.next_window(190, 10), Some((120, 180)));
+        assert_eq!(stream.next_window(1000, 10), Some((120, 960)));
+        assert_eq!(stream.next_window(1000, 2), Some((120, 240)));
+
+        stream.interval = 0;
+        assert_eq!(stream.next_window(1000, 10), None);
+    }
+
+    #[test]
+    fn test_node_state_from_heartbeat() {
+        let now = 100 * NODE_DEAD_TIMEOUT;
+        assert_eq!(NodeState::from_heartbeat(now, now), NodeState::Alive);
+        assert_eq!(
+            NodeState::from_heartbeat(now - NODE_SUSPECT_TIMEOUT, now),
+            NodeState::Suspect
+        );
+        assert_eq!(
+            NodeState::from_heartbeat(now - NODE_DEAD_TIMEOUT, now),
+            NodeState::Dead
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use arrow_schema::{DataType, Field, TimeUnit};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{ColumnType, TskvTableSchema};
+use crate::ValueType;
+
+/// An aggregate function pushed down into the tskv scan.
+///
+/// Each vnode answers the aggregate functions with one row of partial results,
+/// the query node merges the partial results of all vnodes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PushedAggregateFunction {
+    /// Count the rows if the column is None, else count the values of the field.
+    Count(Option<String>),
+    Min(String),
+    Max(String),
+    Sum(String),
+    /// The value of the field with the minimum timestamp.
+    First(String),
+    /// The value of the field with the maximum timestamp.
+    Last(String),
+    /// The value of the tag, the partial results are answered per series if present.
+    Tag(String),
+}
+
+impl PushedAggregateFunction {
+    /// Returns the name of the field column this aggregate function reads.
+    pub fn column(&self) -> Option<&str> {
+        match self {
+            Self::Count(column) => column.as_deref(),
+            Self::Tag(_) => None,
+            Self::Min(column)
+            | Self::Max(column)
+            | Self::Sum(column)
+            | Self::First(column)
+            | Self::Last(column) => Some(column),
+        }
+    }
+
+    /// Returns the fields of the partial result of this aggregate function,
+    /// `First` and `Last` return the timestamp with the value.
+    ///
+    /// Returns None if the aggregate function can't be pushed down on the column.
+    pub fn partial_fields(&self, table_schema: &TskvTableSchema, idx: usize) -> Option<Vec<Field>> {
+        if let Self::Tag(tag) = self {
+            return match table_schema.column(tag)?.column_type {
+                ColumnType::Tag => Some(vec![Field::new(tag, DataType::Utf8, true)]),
+                _ => None,
+            };
+        }
+
+        let value_type = match self.column() {
+            Some(name) => match table_schema.column(name)?.column_type {
+                ColumnType::Field(value_type) => value_type,
+                _ => return None,
+            },
+            None => ValueType::Unknown,
+        };
+        let name = format!("_partial_{}", idx);
+
+        let fields = match self {
+            Self::Count(_) => vec![Field::new(&name, DataType::Int64, false)],
+            Self::Min(_) | Self::Max(_) | Self::Sum(_) => match value_type {
+                ValueType::Float | ValueType::Integer | ValueType::Unsigned => vec![Field::new(
+                    &name,
+                    ColumnType::Field(value_type).into(),
+                    true,
+                )],
+                _ => return None,
+            },
+            Self::First(_) | Self::Last(_) => vec![
+                Field::new(
+                    &format!("{}_time", name),
+                    DataType::Timestamp(TimeUnit::Nanosecond, None),
+                    true,
+                ),
+                Field::new(&name, ColumnType::Field(value_type).into(), true),
+            ],
+            Self::Tag(_) => return None,
+        };
+
+        Some(fields)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_schema::DataType;
+
+    use super::PushedAggregateFunction;
+    use crate::codec::Encoding;
+    use crate::schema::{ColumnType, TableColumn, TskvTableSchema};
+    use crate::ValueType;
+
+    #[test]
+    fn test_partial_fields() {
+        let schema = TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "air".to_string(),
+            vec![
+                TableColumn::new(0, "time".to_string(), ColumnType::Time, Encoding::Default),
+                TableColumn::new(1, "station".to_string(), ColumnType::Tag, Encoding::Default),
+                TableColumn::new(
+                    2,
+                    "pressure".to_string(),
+                    ColumnType::Field(ValueType::Float),
+                    Encoding::Default,
+                ),
+                TableColumn::new(
+                    3,
+                    "visibility".to_string(),
+                    ColumnType::Field(ValueType::String),
+                    Encoding::Default,
+                ),
+            ],
+        );
+
+        let fields = PushedAggregateFunction::Count(None)
+            .partial_fields(&schema, 0)
+            .unwrap();
+        assert_eq!(fields[0].data_type(), &DataType::Int64);
+
+        let fields = PushedAggregateFunction::Sum("pressure".to_string())
+            .partial_fields(&schema, 1)
+            .unwrap();
+        assert_eq!(fields[0].name(), "_partial_1");
+        assert_eq!(fields[0].data_type(), &DataType::Float64);
+
+        let fields = PushedAggregateFunction::Last("visibility".to_string())
+            .partial_fields(&schema, 2)
+            .unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[1].data_type(), &DataType::Utf8);
+
+        assert!(PushedAggregateFunction::Max("visibility".to_string())
+            .partial_fields(&schema, 3)
+            .is_none());
+        assert!(PushedAggregateFunction::Min("station".to_string())
+            .partial_fields(&schema, 3)
+            .is_none());
+
+        let fields = PushedAggregateFunction::Tag("station".to_string())
+            .partial_fields(&schema, 4)
+            .unwrap();
+        assert_eq!(fields[0].name(), "station");
+        assert!(PushedAggregateFunction::Tag("pressure".to_string())
+            .partial_fields(&schema, 4)
+            .is_none());
+    }
+}
@@ -18,6 +18,7 @@ use datafusion::{
 use datafusion_proto::bytes::Serializeable;
 use serde::{Deserialize, Serialize};
 
+use super::aggregate::PushedAggregateFunction;
 use super::transformation::RowExpressionToDomainsVisitor;
 
 pub type PredicateRef = Arc<Predicate>;
@@ -968,6 +969,25 @@ impl Predicate {
         }
         self
     }
+
+    /// Like [`Predicate::push_down_filter`], but fails if the filters can't be converted to
+    /// column domains exactly, e.g. the filters of DELETE must not match more rows.
+    pub fn push_down_exact_filter(
+        mut self,
+        filters: &[Expr],
+        _table_schema: &TskvTableSchema,
+    ) -> Result<Predicate> {
+        self.exprs = filters.to_vec();
+        if let Some(ref expr) = conjunction(filters.to_vec()) {
+            self.pushed_down_domains = RowExpressionToDomainsVisitor::expr_to_exact_column_domains(
+                expr,
+            )
+            .ok_or_else(|| Error::InvalidQueryExprMsg {
+                err: format!("filter can't be converted to exact domains: {}", expr),
+            })?;
+        }
+        Ok(self)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -984,6 +1004,12 @@ pub struct QueryExpr {
     pub filters: Vec<Expr>,
     pub df_schema: SchemaRef,
     pub table_schema: TskvTableSchema,
+    /// The aggregate functions pushed down into the scan, the `df_schema` is the schema
+    /// of the partial results if it's not None.
+    pub aggregates: Option<Vec<PushedAggregateFunction>>,
+    /// The encoded `PlanFragment` executed over the scan, the `df_schema` is the schema
+    /// of the columns scanned if it's not None.
+    pub fragment: Option<Vec<u8>>,
 }
 
 impl QueryExpr {
@@ -1018,6 +1044,19 @@ impl QueryExpr {
         buffer.append(&mut (tmp.len() as u32).to_be_bytes().to_vec());
         buffer.append(&mut tmp.into_bytes());
 
+        let tmp = serde_json::to_string(&option.aggregates).map_err(|err| {
+            Error::InvalidQueryExprMsg {
+                err: err.to_string(),
+            }
+        })?;
+        buffer.append(&mut (tmp.len() as u32).to_be_bytes().to_vec());
+        buffer.append(&mut tmp.into_bytes());
+
+        // an empty fragment means None
+        let mut tmp = option.fragment.clone().unwrap_or_default();
+        buffer.append(&mut (tmp.len() as u32).to_be_bytes().to_vec());
+        buffer.append(&mut tmp);
+
         Ok(buffer)
     }
 
@@ -1068,10 +1107,25 @@ impl QueryExpr {
             }
         })?;
 
+        let data_buf = decode_data_len_val(&mut buffer)?;
+        let aggregates = serde_json::from_slice::<Option<Vec<PushedAggregateFunction>>>(&data_buf)
+            .map_err(|err| Error::InvalidQueryExprMsg {
+                err: err.to_string(),
+            })?;
+
+        let data_buf = decode_data_len_val(&mut buffer)?;
+        let fragment = if data_buf.is_empty() {
+            None
+        } else {
+            Some(data_buf)
+        };
+
         Ok(QueryExpr {
             filters,
             df_schema,
             table_schema,
+            aggregates,
+            fragment,
         })
     }
 }
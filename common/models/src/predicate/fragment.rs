@@ -0,0 +1,191 @@
+use datafusion::{execution::registry::FunctionRegistry, logical_expr::Expr};
+use datafusion_proto::bytes::Serializeable;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// An operator of the plan fragment, applied to the output of the previous one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FragmentStep {
+    Filter(Expr),
+    Projection(Vec<Expr>),
+    /// The data nodes only output the partial states of the aggregate functions
+    Aggregate {
+        group_expr: Vec<Expr>,
+        aggr_expr: Vec<Expr>,
+    },
+    Sort(Vec<Expr>),
+    Limit(usize),
+}
+
+/// The part of a query executed by the data nodes over the scan of their vnodes,
+/// so only the results of the fragment are sent to the query node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanFragment {
+    /// The name of the table scanned, the columns in the expressions are qualified by it
+    pub table_name: String,
+    pub steps: Vec<FragmentStep>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum EncodedStep {
+    Filter(Vec<u8>),
+    Projection(Vec<Vec<u8>>),
+    Aggregate {
+        group_expr: Vec<Vec<u8>>,
+        aggr_expr: Vec<Vec<u8>>,
+    },
+    Sort(Vec<Vec<u8>>),
+    Limit(usize),
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncodedFragment {
+    table_name: String,
+    steps: Vec<EncodedStep>,
+}
+
+impl PlanFragment {
+    pub fn new(table_name: impl Into<String>, steps: Vec<FragmentStep>) -> Self {
+        Self {
+            table_name: table_name.into(),
+            steps,
+        }
+    }
+
+    /// Returns true if the fragment outputs the partial states of an aggregation.
+    pub fn is_partial_aggregate(&self) -> bool {
+        matches!(self.steps.last(), Some(FragmentStep::Aggregate { .. }))
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| {
+                Ok(match step {
+                    FragmentStep::Filter(expr) => EncodedStep::Filter(encode_expr(expr)?),
+                    FragmentStep::Projection(exprs) => {
+                        EncodedStep::Projection(encode_exprs(exprs)?)
+                    }
+                    FragmentStep::Aggregate {
+                        group_expr,
+                        aggr_expr,
+                    } => EncodedStep::Aggregate {
+                        group_expr: encode_exprs(group_expr)?,
+                        aggr_expr: encode_exprs(aggr_expr)?,
+                    },
+                    FragmentStep::Sort(exprs) => EncodedStep::Sort(encode_exprs(exprs)?),
+                    FragmentStep::Limit(fetch) => EncodedStep::Limit(*fetch),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        bincode::serialize(&EncodedFragment {
+            table_name: self.table_name.clone(),
+            steps,
+        })
+        .map_err(|err| Error::InvalidQueryExprMsg {
+            err: err.to_string(),
+        })
+    }
+
+    /// The user defined functions in the expressions are resolved by the registry.
+    pub fn decode(buf: &[u8], registry: &dyn FunctionRegistry) -> Result<Self> {
+        let fragment = bincode::deserialize::<EncodedFragment>(buf).map_err(|err| {
+            Error::InvalidQueryExprMsg {
+                err: err.to_string(),
+            }
+        })?;
+
+        let steps = fragment
+            .steps
+            .iter()
+            .map(|step| {
+                Ok(match step {
+                    EncodedStep::Filter(buf) => FragmentStep::Filter(decode_expr(buf, registry)?),
+                    EncodedStep::Projection(bufs) => {
+                        FragmentStep::Projection(decode_exprs(bufs, registry)?)
+                    }
+                    EncodedStep::Aggregate {
+                        group_expr,
+                        aggr_expr,
+                    } => FragmentStep::Aggregate {
+                        group_expr: decode_exprs(group_expr, registry)?,
+                        aggr_expr: decode_exprs(aggr_expr, registry)?,
+                    },
+                    EncodedStep::Sort(bufs) => FragmentStep::Sort(decode_exprs(bufs, registry)?),
+                    EncodedStep::Limit(fetch) => FragmentStep::Limit(*fetch),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            table_name: fragment.table_name,
+            steps,
+        })
+    }
+}
+
+fn encode_expr(expr: &Expr) -> Result<Vec<u8>> {
+    expr.to_bytes()
+        .map(|bytes| bytes.to_vec())
+        .map_err(|err| Error::InvalidQueryExprMsg {
+            err: err.to_string(),
+        })
+}
+
+fn encode_exprs(exprs: &[Expr]) -> Result<Vec<Vec<u8>>> {
+    exprs.iter().map(encode_expr).collect()
+}
+
+fn decode_expr(buf: &[u8], registry: &dyn FunctionRegistry) -> Result<Expr> {
+    Expr::from_bytes_with_registry(buf, registry).map_err(|err| Error::InvalidQueryExprMsg {
+        err: err.to_string(),
+    })
+}
+
+fn decode_exprs(bufs: &[Vec<u8>], registry: &dyn FunctionRegistry) -> Result<Vec<Expr>> {
+    bufs.iter().map(|buf| decode_expr(buf, registry)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::{
+        logical_expr::{col, count, lit, max},
+        prelude::SessionContext,
+    };
+
+    use super::{FragmentStep, PlanFragment};
+
+    #[test]
+    fn test_fragment_encode_decode() {
+        let fragment = PlanFragment::new(
+            "air",
+            vec![
+                FragmentStep::Filter(col("air.temperature").gt(lit(10_i64))),
+                FragmentStep::Aggregate {
+                    group_expr: vec![col("air.station")],
+                    aggr_expr: vec![count(col("air.temperature")), max(col("air.pressure"))],
+                },
+            ],
+        );
+        assert!(fragment.is_partial_aggregate());
+
+        let buf = fragment.encode().unwrap();
+        let ctx = SessionContext::new();
+        let decoded = PlanFragment::decode(&buf, &ctx).unwrap();
+        assert_eq!(decoded, fragment);
+
+        let fragment = PlanFragment::new(
+            "air",
+            vec![
+                FragmentStep::Sort(vec![col("air.time").sort(false, false)]),
+                FragmentStep::Limit(10),
+            ],
+        );
+        assert!(!fragment.is_partial_aggregate());
+        let buf = fragment.encode().unwrap();
+        assert_eq!(PlanFragment::decode(&buf, &ctx).unwrap(), fragment);
+    }
+}
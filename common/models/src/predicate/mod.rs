@@ -1,2 +1,4 @@
+pub mod aggregate;
 pub mod domain;
+pub mod fragment;
 pub mod transformation;
@@ -1,4 +1,7 @@
-use std::{collections::VecDeque, result};
+use std::{
+    collections::{HashSet, VecDeque},
+    result,
+};
 
 use datafusion::{
     arrow::datatypes::DataType,
@@ -231,6 +234,51 @@ impl RowExpressionToDomainsVisitor<'_> {
             .unwrap_or_else(ColumnDomains::all))
     }
 
+    /// Converts the expression to column domains which match exactly the rows the expression
+    /// selects, none if the expression would be widened, e.g. `!=`, `NOT`, `IS NULL`, or a
+    /// disjunction of different columns.
+    pub fn expr_to_exact_column_domains(expr: &Expr) -> Option<ColumnDomains<Column>> {
+        Self::exact_domain_columns(expr)?;
+        Self::expr_to_column_domains(expr).ok()
+    }
+
+    /// Returns the columns of the expression if it's converted to column domains exactly.
+    fn exact_domain_columns(expr: &Expr) -> Option<HashSet<Column>> {
+        match expr {
+            Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::And,
+                right,
+            }) => {
+                let mut columns = Self::exact_domain_columns(left)?;
+                columns.extend(Self::exact_domain_columns(right)?);
+                Some(columns)
+            }
+            Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::Or,
+                right,
+            }) => {
+                // the union of the domains of different columns matches more rows
+                let mut columns = Self::exact_domain_columns(left)?;
+                columns.extend(Self::exact_domain_columns(right)?);
+                (columns.len() == 1).then_some(columns)
+            }
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                let nsc = NormalizedSimpleComparison::of(
+                    left.as_ref().clone(),
+                    *op,
+                    right.as_ref().clone(),
+                )?;
+                let exact = nsc.op != Operator::NotEq
+                    && !nsc.value.is_null()
+                    && (nsc.is_orderable() || nsc.is_eq_op());
+                exact.then(|| HashSet::from([nsc.column]))
+            }
+            _ => None,
+        }
+    }
+
     /// Convert nsc to RangeValueSet
     ///
     /// Note: nsc must supports ordering, i.e. is_orderable == true
@@ -1286,6 +1334,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_expr_to_exact_column_domains() {
+        let exact = vec![
+            and(
+                binary_expr(col("c1"), Operator::GtEq, lit(1)),
+                binary_expr(col("c2"), Operator::Eq, lit("a")),
+            ),
+            or(
+                binary_expr(col("c1"), Operator::Eq, lit(1)),
+                binary_expr(col("c1"), Operator::Eq, lit(2)),
+            ),
+        ];
+        for expr in exact.iter() {
+            assert!(
+                RowExpressionToDomainsVisitor::expr_to_exact_column_domains(expr).is_some(),
+                "{} is exact",
+                expr
+            );
+        }
+
+        let widened = vec![
+            binary_expr(col("c1"), Operator::NotEq, lit(1)),
+            or(
+                binary_expr(col("c1"), Operator::Eq, lit(1)),
+                binary_expr(col("c2"), Operator::Eq, lit(2)),
+            ),
+            in_list(col("c1"), vec![lit(1), lit(2)], false),
+            col("c1").is_null(),
+            binary_expr(col("c1"), Operator::Gt, random()),
+            binary_expr(col("c1"), Operator::Eq, lit(ScalarValue::Null)),
+        ];
+        for expr in widened.iter() {
+            assert!(
+                RowExpressionToDomainsVisitor::expr_to_exact_column_domains(expr).is_none(),
+                "{} is widened",
+                expr
+            );
+        }
+    }
+
     #[cfg(test)]
     mod test_normalized_simple_comparison {
         use super::*;
@@ -13,6 +13,12 @@ pub struct Config {
     pub log: LogConfig,
     pub security: SecurityConfig,
     pub hintedoff: HintedOffConfig,
+    #[serde(default)]
+    pub repair: RepairConfig,
+    #[serde(default)]
+    pub rebalance: RebalanceConfig,
+    #[serde(default)]
+    pub opentsdb: OpenTsdbConfig,
     pub reporting_disabled: Option<bool>,
 }
 
@@ -23,6 +29,9 @@ impl Config {
         self.wal.override_by_env();
         self.cache.override_by_env();
         self.query.override_by_env();
+        self.repair.override_by_env();
+        self.rebalance.override_by_env();
+        self.opentsdb.override_by_env();
     }
 }
 
@@ -32,6 +41,9 @@ pub struct QueryConfig {
     pub query_sql_limit: u64,
     pub write_sql_limit: u64,
     pub auth_enabled: bool,
+    /// The maximum rows of a chunk when the result of a query is sent in chunks.
+    #[serde(default = "QueryConfig::default_chunk_size")]
+    pub chunk_size: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,9 +108,17 @@ impl WalConfig {
 pub struct CacheConfig {
     pub max_buffer_size: u64,
     pub max_immutable_number: u16,
+    /// Capacity in bytes of the tsm index and data block cache shared by the node,
+    /// 0 disables the cache.
+    #[serde(default = "CacheConfig::default_block_cache_size")]
+    pub block_cache_size: u64,
 }
 
 impl CacheConfig {
+    fn default_block_cache_size() -> u64 {
+        256 * 1024 * 1024
+    }
+
     pub fn override_by_env(&mut self) {
         if let Ok(size) = std::env::var("CNOSDB_CACHE_MAX_BUFFER_SIZE") {
             self.max_buffer_size = size.parse::<u64>().unwrap();
@@ -106,10 +126,17 @@ impl CacheConfig {
         if let Ok(size) = std::env::var("CNOSDB_CACHE_MAX_IMMUTABLE_NUMBER") {
             self.max_immutable_number = size.parse::<u16>().unwrap();
         }
+        if let Ok(size) = std::env::var("CNOSDB_CACHE_BLOCK_CACHE_SIZE") {
+            self.block_cache_size = size.parse::<u64>().unwrap();
+        }
     }
 }
 
 impl QueryConfig {
+    fn default_chunk_size() -> usize {
+        10000
+    }
+
     pub fn override_by_env(&mut self) {
         if let Ok(size) = std::env::var("MAX_SERVER_CONNECTIONS") {
             self.max_server_connections = size.parse::<u32>().unwrap();
@@ -123,6 +150,9 @@ impl QueryConfig {
         if let Ok(val) = std::env::var("AUTH_ENABLED") {
             self.auth_enabled = val.parse::<bool>().unwrap();
         }
+        if let Ok(size) = std::env::var("QUERY_CHUNK_SIZE") {
+            self.chunk_size = size.parse::<usize>().unwrap();
+        }
     }
 }
 
@@ -179,8 +209,12 @@ pub struct ClusterConfig {
 
     pub http_server: String,
     pub grpc_server: String,
+    /// The address of the rpc service between the nodes of the cluster
     pub tcp_server: String,
     pub flight_rpc_server: String,
+    /// The address of the OpenTSDB telnet ingestion service, disabled if empty
+    #[serde(default)]
+    pub opentsdb_server: String,
 }
 
 impl ClusterConfig {
@@ -210,6 +244,10 @@ impl ClusterConfig {
         if let Ok(val) = std::env::var("CNOSDB_FLIGHT_RPC_SERVER") {
             self.flight_rpc_server = val;
         }
+
+        if let Ok(val) = std::env::var("CNOSDB_OPENTSDB_SERVER") {
+            self.opentsdb_server = val;
+        }
     }
 }
 
@@ -217,9 +255,23 @@ impl ClusterConfig {
 pub struct HintedOffConfig {
     pub enable: bool,
     pub path: String,
+    /// The maximum bytes queued for a node, the writes to the node fail when its queue is full.
+    #[serde(default = "HintedOffConfig::default_max_size")]
+    pub max_size: u64,
+    /// Seconds the writes are kept in the queue of a node before they are dropped.
+    #[serde(default = "HintedOffConfig::default_max_age")]
+    pub max_age: u64,
 }
 
 impl HintedOffConfig {
+    fn default_max_size() -> u64 {
+        10 * 1024 * 1024 * 1024
+    }
+
+    fn default_max_age() -> u64 {
+        7 * 24 * 3600
+    }
+
     pub fn override_by_env(&mut self) {
         if let Ok(enable) = std::env::var("CNOSDB_HINTEDOFF_ENABLE") {
             self.enable = enable.parse::<bool>().unwrap();
@@ -227,6 +279,113 @@ impl HintedOffConfig {
         if let Ok(path) = std::env::var("CNOSDB_HINTEDOFF_PATH") {
             self.path = path;
         }
+        if let Ok(size) = std::env::var("CNOSDB_HINTEDOFF_MAX_SIZE") {
+            self.max_size = size.parse::<u64>().unwrap();
+        }
+        if let Ok(age) = std::env::var("CNOSDB_HINTEDOFF_MAX_AGE") {
+            self.max_age = age.parse::<u64>().unwrap();
+        }
+    }
+}
+
+/// The background anti-entropy repair of the replication sets led by the node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairConfig {
+    pub enable: bool,
+    /// Seconds between two rounds of repair.
+    pub interval: u64,
+}
+
+impl Default for RepairConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            interval: 3600,
+        }
+    }
+}
+
+impl RepairConfig {
+    pub fn override_by_env(&mut self) {
+        if let Ok(enable) = std::env::var("CNOSDB_REPAIR_ENABLE") {
+            self.enable = enable.parse::<bool>().unwrap();
+        }
+        if let Ok(interval) = std::env::var("CNOSDB_REPAIR_INTERVAL") {
+            self.interval = interval.parse::<u64>().unwrap();
+        }
+    }
+}
+
+/// Moving the vnodes off the decommissioning nodes and onto the nodes with fewer vnodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceConfig {
+    pub enable: bool,
+    /// Seconds between two rounds of rebalancing.
+    pub interval: u64,
+    /// The maximum number of vnodes moved in a round, the moves of a round run one by one.
+    pub max_moves: usize,
+}
+
+impl Default for RebalanceConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            interval: 300,
+            max_moves: 4,
+        }
+    }
+}
+
+impl RebalanceConfig {
+    pub fn override_by_env(&mut self) {
+        if let Ok(enable) = std::env::var("CNOSDB_REBALANCE_ENABLE") {
+            self.enable = enable.parse::<bool>().unwrap();
+        }
+        if let Ok(interval) = std::env::var("CNOSDB_REBALANCE_INTERVAL") {
+            self.interval = interval.parse::<u64>().unwrap();
+        }
+        if let Ok(max_moves) = std::env::var("CNOSDB_REBALANCE_MAX_MOVES") {
+            self.max_moves = max_moves.parse::<usize>().unwrap();
+        }
+    }
+}
+
+/// The OpenTSDB telnet service writes the data points as the user to the database of
+/// the tenant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenTsdbConfig {
+    pub user: String,
+    #[serde(default)]
+    pub password: String,
+    pub tenant: String,
+    pub database: String,
+}
+
+impl Default for OpenTsdbConfig {
+    fn default() -> Self {
+        Self {
+            user: "root".to_string(),
+            password: String::new(),
+            tenant: "cnosdb".to_string(),
+            database: "public".to_string(),
+        }
+    }
+}
+
+impl OpenTsdbConfig {
+    pub fn override_by_env(&mut self) {
+        if let Ok(user) = std::env::var("CNOSDB_OPENTSDB_USER") {
+            self.user = user;
+        }
+        if let Ok(password) = std::env::var("CNOSDB_OPENTSDB_PASSWORD") {
+            self.password = password;
+        }
+        if let Ok(tenant) = std::env::var("CNOSDB_OPENTSDB_TENANT") {
+            self.tenant = tenant;
+        }
+        if let Ok(database) = std::env::var("CNOSDB_OPENTSDB_DATABASE") {
+            self.database = database;
+        }
     }
 }
 
@@ -242,6 +401,7 @@ max_server_connections = 10240
 query_sql_limit = 16777216   # 16 * 1024 * 1024
 write_sql_limit = 167772160   # 160 * 1024 * 1024
 auth_enabled = false
+chunk_size = 10000
 
 [storage]
 # Directory for summary: $path/summary/
@@ -264,6 +424,7 @@ sync = false
 [cache]
 max_buffer_size = 134217728 # 128 * 1024 * 1024
 max_immutable_number = 4
+block_cache_size = 268435456 # 256 * 1024 * 1024
 
 [log]
 level = 'info'
@@ -283,10 +444,28 @@ flight_rpc_server = '127.0.0.1:31006'
 http_server = '127.0.0.1:31007'
 grpc_server = '127.0.0.1:31008'
 tcp_server = '127.0.0.1:31009'
+opentsdb_server = '127.0.0.1:31010'
 
 [hintedoff]
 enable = true
 path = '/tmp/cnosdb/hh'
+max_size = 10737418240 # 10 * 1024 * 1024 * 1024
+max_age = 604800 # 7 days
+
+[repair]
+enable = false
+interval = 3600 # 1 hour
+
+[rebalance]
+enable = true
+interval = 300 # 5 minutes
+max_moves = 4
+
+[opentsdb]
+user = 'root'
+password = ''
+tenant = 'cnosdb'
+database = 'public'
 
 "#;
 
@@ -100,6 +100,42 @@ pub enum CoordinatorError {
     VnodeNotFound {
         id: u32,
     },
+
+    #[snafu(display(
+        "Write consistency level {:?} not satisfied, replica: {}, written: {}, hinted off: {}, last error: {}",
+        level,
+        replica,
+        written,
+        hinted_off,
+        msg
+    ))]
+    #[error_code(code = 16)]
+    ConsistencyLevelNotSatisfied {
+        level: models::consistency_level::ConsistencyLevel,
+        replica: usize,
+        written: usize,
+        hinted_off: usize,
+        msg: String,
+    },
+
+    #[snafu(display("Request to node {} timed out after {:?}", node_id, timeout))]
+    #[error_code(code = 17)]
+    RequestTimeout {
+        node_id: u64,
+        timeout: std::time::Duration,
+    },
+
+    #[snafu(display("Replication set not found: {}", id))]
+    #[error_code(code = 18)]
+    ReplicationSetNotFound {
+        id: u32,
+    },
+
+    #[snafu(display("Hinted off queue of node {} is full", node_id))]
+    #[error_code(code = 19)]
+    HintedOffQueueFull {
+        node_id: u64,
+    },
 }
 
 impl From<meta::error::MetaError> for CoordinatorError {
@@ -4,9 +4,13 @@ use std::{
     marker::PhantomData,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Instant,
 };
 
 use config::HintedOffConfig;
+use meta::meta_client::{MetaClientRef, MetaRef};
+use models::meta_data::HintedOffQueueStatus;
+use models::utils::now_timestamp;
 use protos::models as fb_models;
 use snafu::prelude::*;
 use tokio::sync::RwLock;
@@ -20,6 +24,7 @@ use tskv::file_system::file_manager::{self, list_dir_names, FileManager};
 use tskv::file_system::{AsyncFile, FileCursor, IFile};
 use tskv::{byte_utils, file_utils};
 
+use crate::command::HintedOffCmd;
 use crate::errors::*;
 use crate::writer::PointWriter;
 
@@ -33,6 +38,9 @@ pub struct HintedOffOption {
     pub enable: bool,
     pub path: String,
     pub node_id: u64,
+    pub max_size: u64,
+    /// In nanoseconds.
+    pub max_age: i64,
 }
 
 impl HintedOffOption {
@@ -41,6 +49,8 @@ impl HintedOffOption {
             node_id,
             enable: config.enable,
             path: format!("{}/{}", config.path, node_id),
+            max_size: config.max_size,
+            max_age: config.max_age as i64 * 1_000_000_000,
         }
     }
 }
@@ -86,20 +96,27 @@ impl HintedOffBlock {
     pub fn size(&self) -> u32 {
         self.data_len + HINTEDOFF_BLOCK_HEADER_SIZE as u32
     }
+
+    /// The bytes of the block in the segment file.
+    pub fn encoded_size(&self) -> u64 {
+        (HINTEDOFF_BLOCK_HEADER_SIZE as u32 + self.tenant_len + self.data_len) as u64
+    }
 }
 
 pub struct HintedOffManager {
     config: HintedOffConfig,
 
+    meta: MetaRef,
     writer: Arc<PointWriter>,
 
     nodes: RwLock<HashMap<u64, Arc<RwLock<HintedOffQueue>>>>,
 }
 
 impl HintedOffManager {
-    pub async fn new(config: HintedOffConfig, writer: Arc<PointWriter>) -> Self {
+    pub async fn new(config: HintedOffConfig, meta: MetaRef, writer: Arc<PointWriter>) -> Self {
         let manager = Self {
             config,
+            meta,
             writer,
             nodes: RwLock::new(HashMap::new()),
         };
@@ -140,6 +157,7 @@ impl HintedOffManager {
 
         tokio::spawn(HintedOffManager::hinted_off_service(
             id,
+            self.meta.clone(),
             self.writer.clone(),
             queue.clone(),
         ));
@@ -147,47 +165,148 @@ impl HintedOffManager {
         Ok(queue)
     }
 
+    /// Pauses, resumes or purges the replay to the node.
+    pub async fn control(&self, node_id: u64, cmd: HintedOffCmd) -> CoordinatorResult<()> {
+        info!("hinted off queue of node {}: {:?}", node_id, cmd);
+
+        let queue = self.get_or_create_queue(node_id).await?;
+        let mut queue = queue.write().await;
+        match cmd {
+            HintedOffCmd::Pause => queue.paused = true,
+            HintedOffCmd::Resume => queue.paused = false,
+            HintedOffCmd::Purge => queue.purge().await?,
+        }
+
+        Ok(())
+    }
+
+    /// Returns the status of the queues of all the nodes.
+    pub async fn status(&self) -> Vec<HintedOffQueueStatus> {
+        let nodes = self.nodes.read().await;
+
+        let mut status = Vec::with_capacity(nodes.len());
+        for queue in nodes.values() {
+            status.push(queue.write().await.status());
+        }
+        status.sort_by_key(|s| s.target_node);
+
+        status
+    }
+
     async fn hinted_off_service(
         node_id: u64,
+        meta: MetaRef,
         writer: Arc<PointWriter>,
         queue: Arc<RwLock<HintedOffQueue>>,
     ) {
         debug!("hinted_off_service started for node: {}", node_id);
 
+        // the meta of the tenants of the blocks replayed until the queue is drained
+        let mut tenant_metas: HashMap<String, Option<MetaClientRef>> = HashMap::new();
         loop {
-            let block_data = queue.write().await.read().await;
+            if queue.read().await.paused {
+                time::sleep(Duration::from_secs(3)).await;
+                continue;
+            }
+
+            let (block_data, epoch, max_age) = {
+                let mut queue = queue.write().await;
+                (queue.read().await, queue.epoch, queue.option.max_age)
+            };
             match block_data {
                 Ok(block) => {
-                    loop {
-                        if writer
-                            .write_to_remote_node(
-                                block.vnode_id,
-                                node_id,
-                                &block.tenant,
-                                block.data.clone(),
-                            )
-                            .await
-                            .is_ok()
-                        {
-                            break;
-                        } else {
-                            info!("hinted_off write data to node {} failed", node_id);
-                            time::sleep(Duration::from_secs(3)).await;
+                    let client = tenant_metas
+                        .entry(block.tenant.clone())
+                        .or_insert_with(|| meta.tenant_manager().tenant_meta(&block.tenant));
+                    let dropped =
+                        HintedOffManager::drop_reason(client.as_ref(), node_id, &block, max_age);
+                    if let Some(reason) = dropped {
+                        warn!(
+                            "hinted_off drop data of vnode {} to node {}: {}",
+                            block.vnode_id, node_id, reason
+                        );
+                    } else {
+                        // stops retrying when the queue is purged
+                        while queue.read().await.epoch == epoch {
+                            if queue.read().await.paused {
+                                time::sleep(Duration::from_secs(3)).await;
+                                continue;
+                            }
+
+                            if writer
+                                .write_to_remote_node(
+                                    block.vnode_id,
+                                    node_id,
+                                    &block.tenant,
+                                    block.data.clone(),
+                                )
+                                .await
+                                .is_ok()
+                            {
+                                break;
+                            } else {
+                                info!("hinted_off write data to node {} failed", node_id);
+                                time::sleep(Duration::from_secs(3)).await;
+                            }
                         }
                     }
 
-                    if let Err(err) = queue.write().await.advance_read_offset(0).await {
+                    let mut queue = queue.write().await;
+                    if queue.epoch != epoch {
+                        continue;
+                    }
+                    if let Err(err) = queue.advance_read_offset(0).await {
                         info!("advance offset {}", err.to_string());
                     }
+                    match dropped {
+                        Some(reason) => {
+                            metrics::incr_hinted_off_dropped(node_id, reason, block.encoded_size())
+                        }
+                        None => queue.replayed(block.encoded_size()),
+                    }
                 }
 
                 Err(err) => {
-                    info!("read hindoff data: {}", err.to_string());
+                    // looks up the meta again for the next blocks
+                    tenant_metas.clear();
+                    debug!("read hindoff data: {}", err.to_string());
                     time::sleep(Duration::from_secs(3)).await;
                 }
             }
         }
     }
+
+    /// Returns the reason if the block is not worth replaying: it is older than the maximum age
+    /// of the queue or the TTL of the database, or the vnode is no longer on the node.
+    ///
+    /// The client is the meta of the tenant of the block, none if the tenant is not found.
+    fn drop_reason(
+        client: Option<&MetaClientRef>,
+        node_id: u64,
+        block: &HintedOffBlock,
+        max_age: i64,
+    ) -> Option<&'static str> {
+        let age = now_timestamp() - block.ts;
+        if age > max_age {
+            return Some("max_age");
+        }
+
+        let client = match client {
+            Some(client) => client,
+            None => return Some("vnode_not_found"),
+        };
+        let info = match client.get_vnode_all_info(block.vnode_id) {
+            Some(info) if info.node_id == node_id => info,
+            _ => return Some("vnode_not_found"),
+        };
+        if let Ok(Some(schema)) = client.get_db_schema(&info.db_name) {
+            if age > schema.config.ttl_or_default().to_nanoseconds() {
+                return Some("ttl");
+            }
+        }
+
+        None
+    }
 }
 
 impl std::fmt::Debug for HintedOffManager {
@@ -201,6 +320,18 @@ pub struct HintedOffQueue {
 
     writer_file: HintedOffWriter,
     reader_file: HintedOffReader,
+
+    /// The bytes not yet replayed.
+    size: u64,
+    /// The time of the block being replayed, none if the queue is empty.
+    oldest_ts: Option<i64>,
+    paused: bool,
+    /// Increased when the queue is purged.
+    epoch: u64,
+
+    replayed_bytes: u64,
+    replay_rate: f64,
+    rate_sample: (Instant, u64),
 }
 
 impl HintedOffQueue {
@@ -229,10 +360,30 @@ impl HintedOffQueue {
         let tmp_file = HintedOffWriter::open(read_fileid, data_dir.join(read_filename)).await?;
         let reader_file = HintedOffReader::new(read_fileid, tmp_file.file.into()).await?;
 
+        // the bytes after the read offset of each segment file
+        let mut size = 0;
+        for name in hh_files.iter() {
+            let mut header = [0_u8; SEGMENT_FILE_HEADER_SIZE];
+            let mut file = std::fs::File::open(data_dir.join(name))?;
+            let len = file.metadata()?.len();
+            if std::io::Read::read_exact(&mut file, &mut header).is_ok() {
+                let offset = byte_utils::decode_be_u32(&header[4..8]) as u64;
+                size += len.saturating_sub(offset);
+            }
+        }
+        metrics::set_hinted_off_queue_size(option.node_id, size);
+
         Ok(HintedOffQueue {
             option,
             writer_file,
             reader_file,
+            size,
+            oldest_ts: None,
+            paused: false,
+            epoch: 0,
+            replayed_bytes: 0,
+            replay_rate: 0.0,
+            rate_sample: (Instant::now(), 0),
         })
     }
 
@@ -266,6 +417,13 @@ impl HintedOffQueue {
 
             let new_file = HintedOffWriter::open(new_file_id, new_file_name).await?;
             self.reader_file = HintedOffReader::new(new_file_id, new_file.file.into()).await?;
+
+            // the replayed segment file is no longer needed
+            let old_file_name =
+                file_utils::make_hinted_off_file(&self.option.path, new_file_id - 1);
+            if let Err(err) = std::fs::remove_file(&old_file_name) {
+                warn!("remove hinted off file {:?} failed: {}", old_file_name, err);
+            }
         }
 
         Ok(())
@@ -276,29 +434,89 @@ impl HintedOffQueue {
             return Ok(());
         }
 
+        if self.size + block.encoded_size() > self.option.max_size {
+            return Err(CoordinatorError::HintedOffQueueFull {
+                node_id: self.option.node_id,
+            });
+        }
+
         self.roll_hinted_off_write_file().await?;
 
-        self.writer_file.write(block).await?;
+        let written = self.writer_file.write(block).await?;
+        self.size += written as u64;
+        self.oldest_ts.get_or_insert(block.ts);
+        metrics::set_hinted_off_queue_size(self.option.node_id, self.size);
 
         Ok(())
     }
 
     pub async fn advance_read_offset(&mut self, offset: u32) -> CoordinatorResult<()> {
-        self.reader_file.advance_read_offset(offset).await
+        let advanced = self.reader_file.advance_read_offset(offset).await?;
+        self.size = self.size.saturating_sub(advanced);
+        metrics::set_hinted_off_queue_size(self.option.node_id, self.size);
+
+        Ok(())
     }
 
     pub async fn read(&mut self) -> CoordinatorResult<HintedOffBlock> {
         self.roll_hinted_off_read_file().await?;
 
         if let Some(val) = self.reader_file.next_hinted_off_block().await {
+            self.oldest_ts = Some(val.ts);
             Ok(val)
         } else {
+            self.oldest_ts = None;
             Err(CoordinatorError::IOErrors {
                 msg: "no data to read".to_string(),
             })
         }
     }
 
+    fn replayed(&mut self, bytes: u64) {
+        self.replayed_bytes += bytes;
+        metrics::incr_hinted_off_replayed(self.option.node_id, bytes);
+    }
+
+    /// Drops all the blocks in the queue, the writes continue in a new segment file.
+    pub async fn purge(&mut self) -> CoordinatorResult<()> {
+        self.writer_file.flush().await?;
+
+        let new_file_id = self.writer_file.id + 1;
+        let new_file_name = file_utils::make_hinted_off_file(&self.option.path, new_file_id);
+        for name in file_manager::list_file_names(&self.option.path) {
+            std::fs::remove_file(PathBuf::from(&self.option.path).join(name))?;
+        }
+
+        self.writer_file = HintedOffWriter::open(new_file_id, &new_file_name).await?;
+        let tmp_file = HintedOffWriter::open(new_file_id, &new_file_name).await?;
+        self.reader_file = HintedOffReader::new(new_file_id, tmp_file.file.into()).await?;
+
+        metrics::incr_hinted_off_dropped(self.option.node_id, "purge", self.size);
+        self.size = 0;
+        self.oldest_ts = None;
+        self.epoch += 1;
+        metrics::set_hinted_off_queue_size(self.option.node_id, self.size);
+
+        Ok(())
+    }
+
+    pub fn status(&mut self) -> HintedOffQueueStatus {
+        let elapsed = self.rate_sample.0.elapsed().as_secs_f64();
+        if elapsed >= 1.0 {
+            self.replay_rate = (self.replayed_bytes - self.rate_sample.1) as f64 / elapsed;
+            self.rate_sample = (Instant::now(), self.replayed_bytes);
+        }
+
+        HintedOffQueueStatus {
+            target_node: self.option.node_id,
+            size: self.size,
+            oldest_ts: self.oldest_ts,
+            paused: self.paused,
+            replayed_bytes: self.replayed_bytes,
+            replay_rate: self.replay_rate,
+        }
+    }
+
     pub async fn close(&mut self) -> CoordinatorResult<()> {
         self.writer_file.flush().await
     }
@@ -401,6 +619,8 @@ impl HintedOffWriter {
 pub struct HintedOffReader {
     id: u64,
     cursor: FileCursor,
+    /// The offset saved in the header.
+    offset: u64,
 
     body_buf: Vec<u8>,
     header_buf: [u8; HINTEDOFF_BLOCK_HEADER_SIZE],
@@ -418,6 +638,7 @@ impl HintedOffReader {
         Ok(Self {
             id,
             cursor,
+            offset: offset as u64,
             header_buf: [0_u8; HINTEDOFF_BLOCK_HEADER_SIZE],
             body_buf: vec![],
         })
@@ -427,12 +648,17 @@ impl HintedOffReader {
         self.cursor.pos() >= self.cursor.len()
     }
 
-    pub async fn advance_read_offset(&mut self, mut offset: u32) -> CoordinatorResult<()> {
+    /// Saves the offset in the header, returns the bytes advanced.
+    pub async fn advance_read_offset(&mut self, mut offset: u32) -> CoordinatorResult<u64> {
         if offset == 0 {
             offset = self.cursor.pos() as u32;
         }
 
-        HintedOffWriter::write_header(self.cursor.file_ref(), offset).await
+        HintedOffWriter::write_header(self.cursor.file_ref(), offset).await?;
+        let advanced = (offset as u64).saturating_sub(self.offset);
+        self.offset = offset as u64;
+
+        Ok(advanced)
     }
 
     pub async fn next_hinted_off_block(&mut self) -> Option<HintedOffBlock> {
@@ -502,6 +728,8 @@ mod test {
             &HintedOffConfig {
                 enable: true,
                 path: dir,
+                max_size: 10 * 1024 * 1024,
+                max_age: 3600,
             },
             1,
         );
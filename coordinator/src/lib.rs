@@ -4,6 +4,10 @@ pub mod command;
 pub mod errors;
 pub mod file_info;
 pub mod hh_queue;
+pub mod node_client;
+pub mod node_health;
 pub mod reader;
+pub mod rebalance;
+pub mod repair;
 pub mod service;
 pub mod writer;
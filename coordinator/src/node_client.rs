@@ -0,0 +1,169 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use config::TLSConfig;
+use meta::meta_client::MetaRef;
+use parking_lot::RwLock;
+use protos::coordinator_service::{
+    coordinator_service_client::CoordinatorServiceClient, StatusResponse,
+};
+use tonic::{
+    transport::{Certificate, Channel, ClientTlsConfig, Endpoint},
+    Code, Request, Status,
+};
+
+use crate::{
+    command::AdminStatementRequest,
+    errors::{CoordinatorError, CoordinatorResult},
+};
+
+pub type NodeClient = CoordinatorServiceClient<Channel>;
+pub type NodeClientsRef = Arc<NodeClients>;
+
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+pub const WRITE_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Copying and moving vnodes download all the files of the vnode.
+pub const ADMIN_REQUEST_TIMEOUT: Duration = Duration::from_secs(3600);
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The clients of the coordinator services of the other nodes.
+/// All the requests to a node are multiplexed on one http2 connection.
+#[derive(Debug)]
+pub struct NodeClients {
+    meta: MetaRef,
+    tls_config: Option<ClientTlsConfig>,
+    channels: RwLock<HashMap<u64, Channel>>,
+}
+
+impl NodeClients {
+    /// The nodes of a cluster share the certificate of the `tls_config`,
+    /// so it's trusted as the root certificate.
+    pub fn new(meta: MetaRef, tls_config: Option<&TLSConfig>) -> CoordinatorResult<Self> {
+        let tls_config = match tls_config {
+            Some(config) => {
+                let cert = std::fs::read(&config.certificate)?;
+                Some(ClientTlsConfig::new().ca_certificate(Certificate::from_pem(cert)))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            meta,
+            tls_config,
+            channels: RwLock::new(HashMap::new()),
+        })
+    }
+
+    pub async fn client(&self, node_id: u64) -> CoordinatorResult<NodeClient> {
+        if let Some(channel) = self.channels.read().get(&node_id) {
+            return Ok(NodeClient::new(channel.clone()));
+        }
+
+        let channel = self.connect(node_id).await?;
+        self.channels.write().insert(node_id, channel.clone());
+
+        Ok(NodeClient::new(channel))
+    }
+
+    async fn connect(&self, node_id: u64) -> CoordinatorResult<Channel> {
+        let info = self.meta.admin_meta().node_info_by_id(node_id)?;
+        let scheme = if self.tls_config.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+
+        let mut endpoint = Endpoint::from_shared(format!("{}://{}", scheme, info.tcp_addr))
+            .map_err(|e| CoordinatorError::IOErrors { msg: e.to_string() })?
+            .connect_timeout(CONNECT_TIMEOUT)
+            .tcp_nodelay(true)
+            .http2_keep_alive_interval(KEEP_ALIVE_INTERVAL)
+            .keep_alive_while_idle(true);
+        if let Some(tls_config) = self.tls_config.as_ref() {
+            endpoint = endpoint
+                .tls_config(tls_config.clone())
+                .map_err(|e| CoordinatorError::IOErrors { msg: e.to_string() })?;
+        }
+
+        endpoint
+            .connect()
+            .await
+            .map_err(|e| CoordinatorError::IOErrors {
+                msg: format!("connect to node {} failed: {}", node_id, e),
+            })
+    }
+
+    /// Executes the admin statement on the node.
+    pub async fn exec_admin_command(
+        &self,
+        node_id: u64,
+        req: &AdminStatementRequest,
+    ) -> CoordinatorResult<StatusResponse> {
+        let mut client = self.client(node_id).await?;
+
+        let mut request = Request::new(req.to_rpc_request()?);
+        request.set_timeout(ADMIN_REQUEST_TIMEOUT);
+        let response = client
+            .exec_admin_command(request)
+            .await
+            .map_err(|status| status_to_error(node_id, status))?;
+
+        Ok(response.into_inner())
+    }
+}
+
+/// The failures of the transport are node failures, the others are failures of the request.
+pub fn status_to_error(node_id: u64, status: Status) -> CoordinatorError {
+    match status.code() {
+        Code::Unavailable | Code::Unknown | Code::Cancelled | Code::DeadlineExceeded => {
+            CoordinatorError::IOErrors {
+                msg: format!(
+                    "request to node {} failed: {:?}, {}",
+                    node_id,
+                    status.code(),
+                    status.message()
+                ),
+            }
+        }
+
+        _ => CoordinatorError::CommonError {
+            msg: status.message().to_string(),
+        },
+    }
+}
+
+/// The status of a failed request served by this node.
+pub fn error_to_status(err: CoordinatorError) -> Status {
+    match err {
+        CoordinatorError::InvalidSerdeMsg { .. } | CoordinatorError::ModelsError { .. } => {
+            Status::invalid_argument(err.to_string())
+        }
+        CoordinatorError::TenantNotFound { .. } | CoordinatorError::VnodeNotFound { .. } => {
+            Status::not_found(err.to_string())
+        }
+
+        _ => Status::internal(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tonic::Status;
+
+    use super::status_to_error;
+    use crate::errors::CoordinatorError;
+
+    #[test]
+    fn test_status_to_error() {
+        let err = status_to_error(1, Status::unavailable("connection refused"));
+        assert!(matches!(err, CoordinatorError::IOErrors { .. }));
+
+        let err = status_to_error(1, Status::deadline_exceeded("timeout"));
+        assert!(matches!(err, CoordinatorError::IOErrors { .. }));
+
+        let err = status_to_error(1, Status::internal("vnode not found"));
+        match err {
+            CoordinatorError::CommonError { msg } => assert_eq!(msg, "vnode not found"),
+            _ => panic!("expect common error"),
+        }
+    }
+}
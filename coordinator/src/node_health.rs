@@ -0,0 +1,217 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+
+use models::meta_data::VnodeInfo;
+
+/// A node that failed recently is not preferred until the cooldown has passed,
+/// after that it will be tried again.
+const FAILURE_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Weight of the latest sample in the latency moving average.
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+
+pub type NodeHealthRef = Arc<NodeHealth>;
+
+#[derive(Debug, Clone, Default)]
+pub struct NodeState {
+    pub consecutive_failures: u32,
+    pub total_failures: u64,
+    pub last_failure: Option<Instant>,
+    /// moving average of the request latency in microseconds
+    pub latency_us: Option<f64>,
+}
+
+impl NodeState {
+    fn is_healthy(&self, cooldown: Duration) -> bool {
+        match self.last_failure {
+            Some(t) if self.consecutive_failures > 0 => t.elapsed() >= cooldown,
+            _ => true,
+        }
+    }
+}
+
+/// Error and latency state of the data nodes, used to choose the replica to read from.
+/// The nodes dead according to meta are not read from or written to.
+#[derive(Debug)]
+pub struct NodeHealth {
+    cooldown: Duration,
+    nodes: RwLock<HashMap<u64, NodeState>>,
+    dead_nodes: RwLock<HashSet<u64>>,
+}
+
+impl Default for NodeHealth {
+    fn default() -> Self {
+        Self::new(FAILURE_COOLDOWN)
+    }
+}
+
+impl NodeHealth {
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            nodes: RwLock::new(HashMap::new()),
+            dead_nodes: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Replaces the dead nodes with the ones reported by meta.
+    pub fn set_dead_nodes(&self, dead_nodes: HashSet<u64>) {
+        *self.dead_nodes.write() = dead_nodes;
+    }
+
+    pub fn is_dead(&self, node_id: u64) -> bool {
+        self.dead_nodes.read().contains(&node_id)
+    }
+
+    pub fn record_success(&self, node_id: u64, latency: Duration) {
+        let mut nodes = self.nodes.write();
+        let state = nodes.entry(node_id).or_default();
+        state.consecutive_failures = 0;
+
+        let sample = latency.as_micros() as f64;
+        state.latency_us = Some(match state.latency_us {
+            Some(avg) => avg + LATENCY_EWMA_ALPHA * (sample - avg),
+            None => sample,
+        });
+    }
+
+    pub fn record_failure(&self, node_id: u64) {
+        let mut nodes = self.nodes.write();
+        let state = nodes.entry(node_id).or_default();
+        state.consecutive_failures += 1;
+        state.total_failures += 1;
+        state.last_failure = Some(Instant::now());
+    }
+
+    pub fn is_healthy(&self, node_id: u64) -> bool {
+        self.nodes
+            .read()
+            .get(&node_id)
+            .map_or(true, |s| s.is_healthy(self.cooldown))
+    }
+
+    pub fn node_state(&self, node_id: u64) -> Option<NodeState> {
+        self.nodes.read().get(&node_id).cloned()
+    }
+
+    /// Orders the replicas of a vnode by preference: the local replica first, then the healthy
+    /// ones by latency, the unhealthy ones are kept at the end as the last resort.
+    /// `seed` rotates the replicas to spread the reads among equivalent replicas.
+    ///
+    /// The replicas on dead nodes are skipped unless all the replicas are on dead nodes.
+    pub fn order_replicas(
+        &self,
+        local_node: u64,
+        replicas: &[VnodeInfo],
+        seed: usize,
+    ) -> Vec<VnodeInfo> {
+        if replicas.is_empty() {
+            return vec![];
+        }
+
+        let mut list = replicas.to_vec();
+        list.rotate_left(seed % replicas.len());
+        {
+            let dead_nodes = self.dead_nodes.read();
+            if list
+                .iter()
+                .any(|vnode| !dead_nodes.contains(&vnode.node_id))
+            {
+                list.retain(|vnode| !dead_nodes.contains(&vnode.node_id));
+            }
+        }
+
+        let nodes = self.nodes.read();
+        // sort_by_cached_key is stable, the rotation breaks the ties
+        list.sort_by_cached_key(|vnode| {
+            let state = nodes.get(&vnode.node_id);
+            let healthy = state.map_or(true, |s| s.is_healthy(self.cooldown));
+            let latency = state.and_then(|s| s.latency_us).unwrap_or(0.0) as u64;
+
+            (!healthy, vnode.node_id != local_node, latency)
+        });
+
+        list
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use models::meta_data::VnodeInfo;
+
+    use super::NodeHealth;
+
+    fn nodes_of(list: &[VnodeInfo]) -> Vec<u64> {
+        list.iter().map(|v| v.node_id).collect()
+    }
+
+    #[test]
+    fn test_order_replicas() {
+        let replicas = vec![
+            VnodeInfo { id: 1, node_id: 1 },
+            VnodeInfo { id: 2, node_id: 2 },
+            VnodeInfo { id: 3, node_id: 3 },
+        ];
+
+        let health = NodeHealth::new(Duration::from_secs(3600));
+        assert_eq!(
+            nodes_of(&health.order_replicas(2, &replicas, 0)),
+            vec![2, 1, 3]
+        );
+        assert_eq!(
+            nodes_of(&health.order_replicas(4, &replicas, 1)),
+            vec![2, 3, 1]
+        );
+
+        health.record_success(1, Duration::from_millis(10));
+        health.record_success(3, Duration::from_millis(1));
+        assert_eq!(
+            nodes_of(&health.order_replicas(4, &replicas, 0)),
+            vec![2, 3, 1]
+        );
+
+        // the failed local node is the last resort
+        health.record_failure(2);
+        assert!(!health.is_healthy(2));
+        assert_eq!(
+            nodes_of(&health.order_replicas(2, &replicas, 0)),
+            vec![3, 1, 2]
+        );
+
+        health.record_success(2, Duration::from_millis(1));
+        assert!(health.is_healthy(2));
+        assert_eq!(health.node_state(2).unwrap().total_failures, 1);
+    }
+
+    #[test]
+    fn test_skip_dead_nodes() {
+        let replicas = vec![
+            VnodeInfo { id: 1, node_id: 1 },
+            VnodeInfo { id: 2, node_id: 2 },
+        ];
+
+        let health = NodeHealth::new(Duration::from_secs(3600));
+        health.set_dead_nodes([1].into_iter().collect());
+        assert!(health.is_dead(1));
+        assert_eq!(nodes_of(&health.order_replicas(1, &replicas, 0)), vec![2]);
+
+        // the dead nodes are the last resort
+        health.set_dead_nodes([1, 2].into_iter().collect());
+        assert_eq!(
+            nodes_of(&health.order_replicas(1, &replicas, 0)),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn test_failure_cooldown() {
+        let health = NodeHealth::new(Duration::from_millis(0));
+        health.record_failure(1);
+        assert!(health.is_healthy(1));
+    }
+}
@@ -1,15 +1,28 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use datafusion::arrow::{datatypes::SchemaRef, record_batch::RecordBatch};
-use futures::future::ok;
+use datafusion::{
+    arrow::{datatypes::SchemaRef, error::ArrowError, record_batch::RecordBatch},
+    physical_plan::stream::RecordBatchStreamAdapter,
+};
+use futures::{
+    future::{ok, BoxFuture, FutureExt},
+    StreamExt,
+};
 use models::{
     meta_data::VnodeInfo,
     predicate::domain::{PredicateRef, QueryArgs, QueryExpr},
     schema::TskvTableSchema,
     utils::now_timestamp,
 };
-use tokio::sync::mpsc::{self, Receiver, Sender};
-use trace::info;
+use tokio::{
+    sync::mpsc::{self, Receiver, Sender},
+    time::timeout,
+};
+use trace::{info, warn};
 use tskv::{
     engine::EngineRef,
     iterator::{filter_to_time_ranges, QueryOption, RowIterator, TableScanMetrics},
@@ -17,14 +30,20 @@ use tskv::{
 };
 
 use crate::{
-    command::{
-        recv_command, send_command, CoordinatorTcpCmd, QueryRecordBatchRequest,
-        FAILED_RESPONSE_CODE,
-    },
+    command::{decode_record_batch, COORDINATOR_PROTOCOL_VERSION},
     errors::{CoordinatorError, CoordinatorResult},
+    node_client::{status_to_error, NodeClientsRef},
+    node_health::{NodeHealth, NodeHealthRef},
+    service::FragmentExecutorRef,
 };
 
 use meta::meta_client::MetaRef;
+use protos::coordinator_service::QueryRecordBatchRequest;
+
+const REMOTE_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// The time to wait for the next response of a remote node,
+/// the first batch may take a while to scan.
+const REMOTE_RESPONSE_TIMEOUT: Duration = Duration::from_secs(300);
 
 #[derive(Debug)]
 pub struct ReaderIterator {
@@ -48,6 +67,9 @@ pub struct QueryExecutor {
 
     kv_inst: EngineRef,
     meta_manager: MetaRef,
+    node_clients: NodeClientsRef,
+    node_health: NodeHealthRef,
+    fragment_executor: Option<FragmentExecutorRef>,
 
     sender: Sender<CoordinatorResult<RecordBatch>>,
 }
@@ -57,51 +79,130 @@ impl QueryExecutor {
         option: QueryOption,
         kv_inst: EngineRef,
         meta_manager: MetaRef,
+        node_clients: NodeClientsRef,
         sender: Sender<CoordinatorResult<RecordBatch>>,
     ) -> Self {
         Self {
             option,
             kv_inst,
             meta_manager,
+            node_clients,
+            node_health: Arc::new(NodeHealth::default()),
+            fragment_executor: None,
             sender,
         }
     }
+
+    /// Shares the node health state between queries to choose the replicas to read from.
+    pub fn with_node_health(mut self, node_health: NodeHealthRef) -> Self {
+        self.node_health = node_health;
+        self
+    }
+
+    /// Executes the plan fragment of the option over the local vnodes.
+    pub fn with_fragment_executor(mut self, fragment_executor: FragmentExecutorRef) -> Self {
+        self.fragment_executor = Some(fragment_executor);
+        self
+    }
+
     pub async fn execute(&self) -> CoordinatorResult<()> {
-        let mut routines = vec![];
-        let mapping = self.map_vnode()?;
-        for (node_id, vnodes) in mapping.iter() {
-            info!(
-                "execute select on node {}, vnode list: {:?}",
-                node_id, vnodes
-            );
-
-            let routine = self.node_executor(*node_id, vnodes.clone());
-            routines.push(routine);
-        }
+        let replicas = self.map_vnode()?;
+        self.execute_replicas(replicas).await
+    }
 
-        futures::future::try_join_all(routines).await?;
+    /// Reads the given vnodes only, without failing over to the other replicas.
+    pub async fn execute_on_vnodes(&self, vnodes: Vec<VnodeInfo>) -> CoordinatorResult<()> {
+        let replicas = vnodes.into_iter().map(|vnode| vec![vnode]).collect();
+        self.execute_replicas(replicas).await
+    }
 
-        Ok(())
+    /// Every item is the ordered replica list of a vnode, the first replica is read.
+    fn execute_replicas(
+        &self,
+        replicas: Vec<Vec<VnodeInfo>>,
+    ) -> BoxFuture<'_, CoordinatorResult<()>> {
+        async move {
+            let mut mapping: HashMap<u64, Vec<Vec<VnodeInfo>>> = HashMap::new();
+            for list in replicas.into_iter() {
+                if let Some(vnode) = list.first() {
+                    mapping.entry(vnode.node_id).or_default().push(list);
+                }
+            }
+
+            let mut routines = vec![];
+            for (node_id, replicas) in mapping.into_iter() {
+                let routine = self.node_executor(node_id, replicas);
+                routines.push(routine);
+            }
+
+            futures::future::try_join_all(routines).await?;
+
+            Ok(())
+        }
+        .boxed()
     }
 
-    async fn node_executor(&self, node_id: u64, vnodes: Vec<VnodeInfo>) -> CoordinatorResult<()> {
+    async fn node_executor(
+        &self,
+        node_id: u64,
+        replicas: Vec<Vec<VnodeInfo>>,
+    ) -> CoordinatorResult<()> {
+        let vnodes: Vec<VnodeInfo> = replicas.iter().map(|list| list[0].clone()).collect();
+        info!(
+            "execute select on node {}, vnode list: {:?}",
+            node_id, vnodes
+        );
+
         if node_id == self.meta_manager.node_id() {
-            self.local_node_executor(vnodes).await
-        } else {
-            self.remote_node_executor(node_id, vnodes).await
+            return self.local_node_executor(vnodes).await;
+        }
+
+        let start = Instant::now();
+        let mut received = false;
+        let err = match self
+            .remote_node_executor(node_id, vnodes, &mut received)
+            .await
+        {
+            Ok(()) => {
+                self.node_health.record_success(node_id, start.elapsed());
+                return Ok(());
+            }
+            Err(err) => err,
+        };
+
+        if !is_node_failure(&err) {
+            return Err(err);
         }
+        self.node_health.record_failure(node_id);
+
+        // the batches already sent to the client can not be taken back
+        let remains: Vec<Vec<VnodeInfo>> = replicas
+            .into_iter()
+            .map(|list| list.into_iter().skip(1).collect::<Vec<_>>())
+            .collect();
+        if received || remains.iter().any(|list| list.is_empty()) {
+            return Err(err);
+        }
+
+        warn!(
+            "select on node {} failed: {}, retry on other replicas",
+            node_id, err
+        );
+        self.execute_replicas(remains).await
     }
 
     async fn remote_node_executor(
         &self,
         node_id: u64,
         vnodes: Vec<VnodeInfo>,
+        received: &mut bool,
     ) -> CoordinatorResult<()> {
-        let mut conn = self
-            .meta_manager
-            .admin_meta()
-            .get_node_conn(node_id)
-            .await?;
+        let mut client = timeout(REMOTE_CONNECT_TIMEOUT, self.node_clients.client(node_id))
+            .await
+            .map_err(|_| CoordinatorError::RequestTimeout {
+                node_id,
+                timeout: REMOTE_CONNECT_TIMEOUT,
+            })??;
 
         let mut vnode_ids = Vec::with_capacity(vnodes.len());
         for item in vnodes.iter() {
@@ -117,51 +218,104 @@ impl QueryExecutor {
             filters: self.option.filter.exprs().to_vec(),
             df_schema: self.option.df_schema.clone(),
             table_schema: self.option.table_schema.clone(),
+            aggregates: self.option.aggregates.clone(),
+            fragment: self.option.fragment.clone(),
+        };
+        let request = QueryRecordBatchRequest {
+            version: COORDINATOR_PROTOCOL_VERSION,
+            args: bincode::serialize(&args)
+                .map_err(|e| CoordinatorError::InvalidSerdeMsg { err: e.to_string() })?,
+            expr: QueryExpr::encode(&expr)?,
         };
-        let req_cmd = QueryRecordBatchRequest { args, expr };
-        send_command(&mut conn, &CoordinatorTcpCmd::QueryRecordBatchCmd(req_cmd)).await?;
 
+        // the remote scan is cancelled once the stream is dropped
+        let mut stream = client
+            .query_record_batch(request)
+            .await
+            .map_err(|status| status_to_error(node_id, status))?
+            .into_inner();
         loop {
-            let rsp_cmd = recv_command(&mut conn).await?;
-            match rsp_cmd {
-                CoordinatorTcpCmd::StatusResponseCmd(rsp) => {
-                    info!("remote node execute status: {:?}", rsp);
-                    if rsp.code == FAILED_RESPONSE_CODE {
-                        return Err(CoordinatorError::CommonError { msg: rsp.data });
-                    }
-
-                    break;
-                }
-
-                CoordinatorTcpCmd::RecordBatchResponseCmd(rsp) => {
-                    let tenant = self.option.tenant.clone();
-                    if let Some(meta_client) =
-                        self.meta_manager.tenant_manager().tenant_meta(&tenant)
-                    {
-                        meta_client
-                            .limiter()
-                            .check_data_out(get_record_batch_memory_size(&rsp.record))
-                            .map_err(|e| CoordinatorError::MetaRequest {
-                                msg: format!("{}", e),
-                            })?;
-                    }
-
-                    self.sender.send(Ok(rsp.record)).await?;
-                }
-                _ => {
-                    return Err(CoordinatorError::UnExpectResponse);
+            let rsp = timeout(REMOTE_RESPONSE_TIMEOUT, stream.message())
+                .await
+                .map_err(|_| CoordinatorError::RequestTimeout {
+                    node_id,
+                    timeout: REMOTE_RESPONSE_TIMEOUT,
+                })?
+                .map_err(|status| status_to_error(node_id, status))?;
+
+            match rsp {
+                Some(rsp) => {
+                    *received = true;
+                    let record = decode_record_batch(&rsp.record_batch)?;
+                    self.send_record_batch(&self.sender, record, true).await?;
                 }
+                None => break,
             }
         }
-        self.meta_manager.admin_meta().put_node_conn(node_id, conn);
 
         Ok(())
     }
 
     pub async fn local_node_executor(&self, vnodes: Vec<VnodeInfo>) -> CoordinatorResult<()> {
+        if let Some(fragment) = self.option.fragment.as_ref() {
+            return self.local_fragment_executor(fragment, vnodes).await;
+        }
+
+        self.scan_local_vnodes(vnodes, &self.sender, true).await
+    }
+
+    /// Executes the plan fragment over the local vnodes, only the output of the fragment is sent.
+    async fn local_fragment_executor(
+        &self,
+        fragment: &[u8],
+        vnodes: Vec<VnodeInfo>,
+    ) -> CoordinatorResult<()> {
+        let executor =
+            self.fragment_executor
+                .as_ref()
+                .ok_or_else(|| CoordinatorError::CommonError {
+                    msg: "no executor of the plan fragments on this node".to_string(),
+                })?;
+
+        let (reader, sender) = ReaderIterator::new();
+        let input = futures::stream::unfold(reader, |mut reader| async move {
+            reader.next().await.map(|item| {
+                let item = item.map_err(|e| ArrowError::ExternalError(Box::new(e)));
+                (item, reader)
+            })
+        });
+        let input = Box::pin(RecordBatchStreamAdapter::new(
+            self.option.df_schema.clone(),
+            input,
+        ));
+
+        // the input ends when the sender is dropped, the scan error is passed to the fragment
+        let scan = async move {
+            if let Err(err) = self.scan_local_vnodes(vnodes, &sender, false).await {
+                let _ = sender.send(Err(err)).await;
+            }
+        };
+        let execute = async {
+            let mut output = executor.execute(fragment, input).await?;
+            while let Some(batch) = output.next().await {
+                self.send_record_batch(&self.sender, batch?, true).await?;
+            }
+            Ok(())
+        };
+
+        let (_, result) = futures::join!(scan, execute);
+        result
+    }
+
+    async fn scan_local_vnodes(
+        &self,
+        vnodes: Vec<VnodeInfo>,
+        sender: &Sender<CoordinatorResult<RecordBatch>>,
+        check_data_out: bool,
+    ) -> CoordinatorResult<()> {
         let mut routines = vec![];
         for vnode in vnodes.iter() {
-            let routine = self.local_vnode_executor(vnode.clone());
+            let routine = self.local_vnode_executor(vnode.clone(), sender, check_data_out);
             routines.push(routine);
         }
 
@@ -170,28 +324,18 @@ impl QueryExecutor {
         Ok(())
     }
 
-    async fn local_vnode_executor(&self, vnode: VnodeInfo) -> CoordinatorResult<()> {
-        let tenant = self.option.tenant.clone();
+    async fn local_vnode_executor(
+        &self,
+        vnode: VnodeInfo,
+        sender: &Sender<CoordinatorResult<RecordBatch>>,
+        check_data_out: bool,
+    ) -> CoordinatorResult<()> {
         let mut iterator =
             RowIterator::new(self.kv_inst.clone(), self.option.clone(), vnode.id).await?;
 
         while let Some(data) = iterator.next().await {
             match data {
-                Ok(val) => {
-                    if let Some(meta_client) = self
-                        .meta_manager
-                        .tenant_manager()
-                        .tenant_meta(tenant.as_str())
-                    {
-                        meta_client
-                            .limiter()
-                            .check_data_out(get_record_batch_memory_size(&val))
-                            .map_err(|e| CoordinatorError::MetaRequest {
-                                msg: format!("{}", e),
-                            })?;
-                    }
-                    self.sender.send(Ok(val)).await?;
-                }
+                Ok(val) => self.send_record_batch(sender, val, check_data_out).await?,
                 Err(err) => {
                     return Err(CoordinatorError::from(err));
                 }
@@ -201,7 +345,33 @@ impl QueryExecutor {
         Ok(())
     }
 
-    fn map_vnode(&self) -> CoordinatorResult<HashMap<u64, Vec<VnodeInfo>>> {
+    async fn send_record_batch(
+        &self,
+        sender: &Sender<CoordinatorResult<RecordBatch>>,
+        batch: RecordBatch,
+        check_data_out: bool,
+    ) -> CoordinatorResult<()> {
+        if check_data_out {
+            if let Some(meta_client) = self
+                .meta_manager
+                .tenant_manager()
+                .tenant_meta(self.option.tenant.as_str())
+            {
+                meta_client
+                    .limiter()
+                    .check_data_out(get_record_batch_memory_size(&batch))
+                    .map_err(|e| CoordinatorError::MetaRequest {
+                        msg: format!("{}", e),
+                    })?;
+            }
+        }
+        sender.send(Ok(batch)).await?;
+
+        Ok(())
+    }
+
+    /// Returns the replicas of every vnode to read, ordered by preference.
+    fn map_vnode(&self) -> CoordinatorResult<Vec<Vec<VnodeInfo>>> {
         let meta = self
             .meta_manager
             .tenant_manager()
@@ -210,7 +380,11 @@ impl QueryExecutor {
                 name: self.option.tenant.clone(),
             })?;
 
-        let mut vnode_mapping: HashMap<u64, Vec<VnodeInfo>> = HashMap::new();
+        let local_node = self.meta_manager.node_id();
+        let seed = now_timestamp() as usize;
+
+        let mut replica_ids = HashSet::new();
+        let mut replicas = vec![];
         for item in QueryOption::parse_time_ranges(
             self.option.filter.clone(),
             self.option.table_schema.clone(),
@@ -221,26 +395,32 @@ impl QueryExecutor {
                 meta.mapping_bucket(&self.option.table_schema.db, item.min_ts, item.max_ts)?;
             for bucket in buckets.iter() {
                 for repl in bucket.shard_group.iter() {
-                    if repl.vnodes.is_empty() {
+                    if repl.vnodes.is_empty() || !replica_ids.insert(repl.id) {
                         continue;
                     }
 
-                    let random = now_timestamp() as usize % repl.vnodes.len();
-                    let vnode = repl.vnodes[random].clone();
-
-                    let list = vnode_mapping.entry(vnode.node_id).or_default();
-                    list.push(vnode);
+                    replicas.push(
+                        self.node_health
+                            .order_replicas(local_node, &repl.vnodes, seed),
+                    );
                 }
             }
         }
-        for (_, list) in vnode_mapping.iter_mut() {
-            list.dedup_by(|a, b| a.id == b.id);
-        }
 
-        Ok(vnode_mapping)
+        Ok(replicas)
     }
 }
 
+/// Errors of the connection to a node, the vnodes can be read from other replicas.
+fn is_node_failure(err: &CoordinatorError) -> bool {
+    matches!(
+        err,
+        CoordinatorError::IOErrors { .. }
+            | CoordinatorError::RequestTimeout { .. }
+            | CoordinatorError::UnExpectResponse
+    )
+}
+
 fn get_record_batch_memory_size(record_batch: &RecordBatch) -> usize {
     record_batch
         .columns()
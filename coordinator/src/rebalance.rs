@@ -0,0 +1,315 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use meta::error::MetaResult;
+use meta::meta_client::MetaRef;
+use models::meta_data::{NodeId, NodeState, NodeStatus, ReplicationSetId, VnodeId};
+use serde::{Deserialize, Serialize};
+
+/// A replica of a replication set placed on a data node.
+#[derive(Debug, Clone)]
+pub struct VnodePlacement {
+    pub tenant: String,
+    pub repl_set: ReplicationSetId,
+    pub vnode_id: VnodeId,
+    pub node_id: NodeId,
+}
+
+/// Moves the vnode from the source node to the target node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RebalanceMove {
+    pub tenant: String,
+    pub vnode_id: VnodeId,
+    pub source: NodeId,
+    pub target: NodeId,
+}
+
+/// Returns the replicas of all the replication sets of the cluster.
+pub fn collect_placements(meta: &MetaRef) -> MetaResult<Vec<VnodePlacement>> {
+    let mut placements = vec![];
+    for tenant in meta.tenant_manager().tenants()? {
+        let client = match meta.tenant_manager().tenant_meta(tenant.name()) {
+            Some(client) => client,
+            None => continue,
+        };
+
+        for db in client.list_databases()? {
+            let info = match client.get_db_info(&db)? {
+                Some(info) => info,
+                None => continue,
+            };
+            for bucket in info.buckets.iter() {
+                for repl_set in bucket.shard_group.iter() {
+                    for vnode in repl_set.vnodes.iter() {
+                        placements.push(VnodePlacement {
+                            tenant: tenant.name().to_string(),
+                            repl_set: repl_set.id,
+                            vnode_id: vnode.id,
+                            node_id: vnode.node_id,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(placements)
+}
+
+/// Plans at most `max_moves` moves, a node never gets two replicas of a replication set.
+///
+/// The vnodes of the decommissioning nodes are moved first, each to the online node with the
+/// fewest vnodes. Then the vnodes are moved from the online node with the most vnodes to the
+/// one with the fewest, until their numbers of vnodes differ by at most one.
+///
+/// Only the alive nodes take part in the moves, the suspect and dead nodes can neither send
+/// nor receive the vnodes.
+pub fn plan_rebalance(
+    nodes: &[NodeStatus],
+    placements: &[VnodePlacement],
+    max_moves: usize,
+) -> Vec<RebalanceMove> {
+    let decommissioning: HashSet<NodeId> = nodes
+        .iter()
+        .filter(|n| n.state == NodeState::Alive && n.info.is_decommissioning())
+        .map(|n| n.info.id)
+        .collect();
+    let mut planner = Planner::new(nodes, placements);
+
+    for placement in placements
+        .iter()
+        .filter(|p| decommissioning.contains(&p.node_id))
+    {
+        if planner.moves.len() >= max_moves {
+            return planner.moves;
+        }
+
+        let occupied = &planner.replicas[&placement.repl_set];
+        let target = planner
+            .load
+            .iter()
+            .filter(|(id, _)| !occupied.contains(id))
+            .min_by_key(|(id, count)| (**count, **id))
+            .map(|(id, _)| *id);
+        if let Some(target) = target {
+            planner.apply(placement, target);
+        }
+    }
+
+    while planner.moves.len() < max_moves {
+        let heaviest = planner
+            .load
+            .iter()
+            .max_by_key(|(id, count)| (**count, u64::MAX - **id));
+        let lightest = planner
+            .load
+            .iter()
+            .min_by_key(|(id, count)| (**count, **id));
+        let (heaviest, lightest) = match (heaviest, lightest) {
+            (Some((h, h_count)), Some((l, l_count))) if *h_count > *l_count + 1 => (*h, *l),
+            _ => break,
+        };
+
+        let candidate = placements.iter().find(|p| {
+            p.node_id == heaviest
+                && !planner.moved.contains(&p.vnode_id)
+                && !planner.replicas[&p.repl_set].contains(&lightest)
+        });
+        match candidate {
+            Some(placement) => planner.apply(placement, lightest),
+            None => break,
+        }
+    }
+
+    planner.moves
+}
+
+struct Planner {
+    /// The number of vnodes of the alive online nodes.
+    load: BTreeMap<NodeId, usize>,
+    /// The nodes of the replicas of the replication sets.
+    replicas: HashMap<ReplicationSetId, HashSet<NodeId>>,
+    moved: HashSet<VnodeId>,
+    moves: Vec<RebalanceMove>,
+}
+
+impl Planner {
+    fn new(nodes: &[NodeStatus], placements: &[VnodePlacement]) -> Self {
+        let mut load: BTreeMap<NodeId, usize> = nodes
+            .iter()
+            .filter(|n| n.state == NodeState::Alive && !n.info.is_decommissioning())
+            .map(|n| (n.info.id, 0))
+            .collect();
+        let mut replicas: HashMap<ReplicationSetId, HashSet<NodeId>> = HashMap::new();
+        for placement in placements.iter() {
+            if let Some(count) = load.get_mut(&placement.node_id) {
+                *count += 1;
+            }
+            replicas
+                .entry(placement.repl_set)
+                .or_default()
+                .insert(placement.node_id);
+        }
+
+        Self {
+            load,
+            replicas,
+            moved: HashSet::new(),
+            moves: vec![],
+        }
+    }
+
+    fn apply(&mut self, placement: &VnodePlacement, target: NodeId) {
+        if let Some(count) = self.load.get_mut(&placement.node_id) {
+            *count -= 1;
+        }
+        *self.load.entry(target).or_default() += 1;
+        let nodes = self.replicas.entry(placement.repl_set).or_default();
+        nodes.remove(&placement.node_id);
+        nodes.insert(target);
+        self.moved.insert(placement.vnode_id);
+
+        self.moves.push(RebalanceMove {
+            tenant: placement.tenant.clone(),
+            vnode_id: placement.vnode_id,
+            source: placement.node_id,
+            target,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use models::meta_data::{
+        NodeInfo, NodeState, NodeStatus, NODE_STATUS_DECOMMISSIONING, NODE_STATUS_ONLINE,
+    };
+
+    use super::{plan_rebalance, VnodePlacement};
+
+    fn make_node(id: u64, status: u64) -> NodeStatus {
+        make_node_in_state(id, status, NodeState::Alive)
+    }
+
+    fn make_node_in_state(id: u64, status: u64, state: NodeState) -> NodeStatus {
+        NodeStatus {
+            info: NodeInfo {
+                id,
+                status,
+                ..Default::default()
+            },
+            heartbeat: None,
+            state,
+        }
+    }
+
+    /// The replication sets are given as the nodes of their replicas.
+    fn make_placements(repl_sets: &[&[u64]]) -> Vec<VnodePlacement> {
+        let mut placements = vec![];
+        let mut vnode_id = 100;
+        for (idx, nodes) in repl_sets.iter().enumerate() {
+            for node_id in nodes.iter() {
+                vnode_id += 1;
+                placements.push(VnodePlacement {
+                    tenant: "cnosdb".to_string(),
+                    repl_set: idx as u32,
+                    vnode_id,
+                    node_id: *node_id,
+                });
+            }
+        }
+
+        placements
+    }
+
+    #[test]
+    fn test_balanced_cluster() {
+        let nodes = vec![
+            make_node(1, NODE_STATUS_ONLINE),
+            make_node(2, NODE_STATUS_ONLINE),
+        ];
+        let placements = make_placements(&[&[1, 2], &[2, 1], &[1], &[2]]);
+        assert!(plan_rebalance(&nodes, &placements, 10).is_empty());
+    }
+
+    #[test]
+    fn test_drain_decommissioning_node() {
+        let nodes = vec![
+            make_node(1, NODE_STATUS_ONLINE),
+            make_node(2, NODE_STATUS_ONLINE),
+            make_node(3, NODE_STATUS_DECOMMISSIONING),
+        ];
+        let placements = make_placements(&[&[1, 3], &[3, 2], &[1, 2]]);
+
+        let moves = plan_rebalance(&nodes, &placements, 10);
+        assert_eq!(moves.len(), 2);
+        // node 1 already has a replica of the first replication set
+        assert_eq!(
+            (moves[0].vnode_id, moves[0].source, moves[0].target),
+            (102, 3, 2)
+        );
+        assert_eq!(
+            (moves[1].vnode_id, moves[1].source, moves[1].target),
+            (103, 3, 1)
+        );
+    }
+
+    #[test]
+    fn test_spread_onto_new_node() {
+        let nodes = vec![
+            make_node(1, NODE_STATUS_ONLINE),
+            make_node(2, NODE_STATUS_ONLINE),
+            make_node(3, NODE_STATUS_ONLINE),
+        ];
+        let placements = make_placements(&[&[1, 2], &[2, 1], &[1, 2]]);
+
+        let moves = plan_rebalance(&nodes, &placements, 10);
+        assert_eq!(moves.len(), 2);
+        assert!(moves.iter().all(|m| m.target == 3));
+        let sources: Vec<u64> = moves.iter().map(|m| m.source).collect();
+        assert_eq!(sources, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_max_moves() {
+        let nodes = vec![
+            make_node(1, NODE_STATUS_ONLINE),
+            make_node(2, NODE_STATUS_DECOMMISSIONING),
+        ];
+        let placements = make_placements(&[&[2], &[2], &[2]]);
+
+        let moves = plan_rebalance(&nodes, &placements, 2);
+        assert_eq!(moves.len(), 2);
+        assert!(moves.iter().all(|m| m.source == 2 && m.target == 1));
+    }
+
+    #[test]
+    fn test_skip_dead_nodes() {
+        let nodes = vec![
+            make_node(1, NODE_STATUS_ONLINE),
+            make_node(2, NODE_STATUS_ONLINE),
+            make_node(3, NODE_STATUS_DECOMMISSIONING),
+            make_node_in_state(4, NODE_STATUS_ONLINE, NodeState::Dead),
+            make_node_in_state(5, NODE_STATUS_ONLINE, NodeState::Suspect),
+        ];
+        let placements = make_placements(&[&[1, 3], &[1, 4], &[1, 5], &[1, 2]]);
+
+        // nodes 4 and 5 have the fewest vnodes, but neither receives the vnodes
+        let moves = plan_rebalance(&nodes, &placements, 10);
+        assert_eq!(moves.len(), 2);
+        assert_eq!(
+            (moves[0].vnode_id, moves[0].source, moves[0].target),
+            (102, 3, 2)
+        );
+        assert_eq!(
+            (moves[1].vnode_id, moves[1].source, moves[1].target),
+            (103, 1, 2)
+        );
+
+        // a dead decommissioning node can not send its vnodes
+        let nodes = vec![
+            make_node(1, NODE_STATUS_ONLINE),
+            make_node_in_state(2, NODE_STATUS_DECOMMISSIONING, NodeState::Dead),
+        ];
+        let placements = make_placements(&[&[2], &[2]]);
+        assert!(plan_rebalance(&nodes, &placements, 10).is_empty());
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+
+use meta::meta_client::MetaClientRef;
+use models::meta_data::{ReplicationSet, VnodeInfo};
+use serde::{Deserialize, Serialize};
+use trace::info;
+use tskv::TableHashTreeNode;
+
+use crate::command::{AdminStatementRequest, AdminStatementType};
+use crate::errors::{CoordinatorError, CoordinatorResult};
+use crate::node_client::NodeClients;
+
+/// The hash tree of a replica of a replication set.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VnodeHashTree {
+    pub vnode: VnodeInfo,
+    pub tables: Vec<TableHashTreeNode>,
+}
+
+/// Copies the data of a table in the time ranges from the source replica to the target.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RepairTask {
+    pub table: String,
+    pub source: VnodeInfo,
+    pub target: VnodeInfo,
+    /// [min_ts, max_ts)
+    pub time_ranges: Vec<(i64, i64)>,
+}
+
+/// Returns the database and the replication set of the id.
+pub fn find_replication_set(
+    meta: &MetaClientRef,
+    id: u32,
+) -> CoordinatorResult<(String, ReplicationSet)> {
+    for db in meta.list_databases()? {
+        let info = match meta.get_db_info(&db)? {
+            Some(info) => info,
+            None => continue,
+        };
+        for bucket in info.buckets.iter() {
+            if let Some(repl_set) = bucket.shard_group.iter().find(|set| set.id == id) {
+                return Ok((db, repl_set.clone()));
+            }
+        }
+    }
+
+    Err(CoordinatorError::ReplicationSetNotFound { id })
+}
+
+/// Fetches the hash trees of all replicas, every replica must be available.
+pub async fn get_hash_trees(
+    node_clients: &NodeClients,
+    tenant: &str,
+    db: &str,
+    repl_set: &ReplicationSet,
+) -> CoordinatorResult<Vec<VnodeHashTree>> {
+    let mut requests = Vec::with_capacity(repl_set.vnodes.len());
+    for vnode in repl_set.vnodes.iter() {
+        let req = AdminStatementRequest {
+            tenant: tenant.to_string(),
+            stmt: AdminStatementType::GetVnodeHashTree {
+                db: db.to_string(),
+                vnode_id: vnode.id,
+            },
+        };
+        requests.push(async move {
+            let rsp = node_clients.exec_admin_command(vnode.node_id, &req).await?;
+            let tables = serde_json::from_str::<Vec<TableHashTreeNode>>(&rsp.data)
+                .map_err(|e| CoordinatorError::InvalidSerdeMsg { err: e.to_string() })?;
+
+            Ok::<_, CoordinatorError>(VnodeHashTree {
+                vnode: vnode.clone(),
+                tables,
+            })
+        });
+    }
+
+    futures::future::try_join_all(requests).await
+}
+
+/// Compares the hash trees of the replicas and repairs the mismatching time ranges,
+/// returns the executed repairs.
+pub async fn repair_replication_set(
+    node_clients: &NodeClients,
+    tenant: &str,
+    db: &str,
+    repl_set: &ReplicationSet,
+) -> CoordinatorResult<Vec<RepairTask>> {
+    if repl_set.vnodes.len() < 2 {
+        return Ok(vec![]);
+    }
+
+    let trees = get_hash_trees(node_clients, tenant, db, repl_set).await?;
+    let tasks = find_repair_tasks(&trees);
+    for task in tasks.iter() {
+        info!(
+            "repair replication set {}: copy {}.{} {:?} from vnode {} to vnode {}",
+            repl_set.id, db, task.table, task.time_ranges, task.source.id, task.target.id
+        );
+
+        let req = AdminStatementRequest {
+            tenant: tenant.to_string(),
+            stmt: AdminStatementType::RepairVnode {
+                db: db.to_string(),
+                vnode_id: task.target.id,
+                source: task.source.clone(),
+                table: task.table.clone(),
+                time_ranges: task.time_ranges.clone(),
+            },
+        };
+        node_clients
+            .exec_admin_command(task.target.node_id, &req)
+            .await?;
+    }
+
+    Ok(tasks)
+}
+
+/// Finds the (table, time range) whose hashes differ between the replicas.
+///
+/// The data is copied from the replicas that agree with the majority, if there is no majority
+/// the replicas are merged by copying the data of each one to the others. Repairs never delete
+/// data, a replica with extra data in a time range keeps it.
+///
+/// If the majority has no data in a time range, the data is deleted (by DELETE or TTL) and the
+/// time range is skipped, the data of the stale replicas is not copied back.
+pub fn find_repair_tasks(trees: &[VnodeHashTree]) -> Vec<RepairTask> {
+    let replica = trees.len();
+
+    // the hashes of the columns of each replica in the time range of a table
+    let mut ranges: BTreeMap<(&str, i64, i64), Vec<Vec<(&str, [u8; 32])>>> = BTreeMap::new();
+    for (idx, tree) in trees.iter().enumerate() {
+        for table in tree.tables.iter() {
+            for column in table.columns.iter() {
+                for node in column.values.iter() {
+                    ranges
+                        .entry((table.table.as_str(), node.min_ts, node.max_ts))
+                        .or_insert_with(|| vec![vec![]; replica])[idx]
+                        .push((column.column.as_str(), node.hash));
+                }
+            }
+        }
+    }
+
+    let mut tasks: BTreeMap<(&str, usize, usize), Vec<(i64, i64)>> = BTreeMap::new();
+    for ((table, min_ts, max_ts), mut hashes) in ranges.into_iter() {
+        hashes.iter_mut().for_each(|h| h.sort_unstable());
+
+        // the replicas with the same hashes
+        let mut groups: Vec<Vec<usize>> = vec![];
+        for idx in 0..replica {
+            match groups.iter_mut().find(|g| hashes[g[0]] == hashes[idx]) {
+                Some(group) => group.push(idx),
+                None => groups.push(vec![idx]),
+            }
+        }
+        if groups.len() == 1 {
+            continue;
+        }
+
+        let sources: Vec<usize> = match groups.iter().find(|g| g.len() * 2 > replica) {
+            Some(majority) if hashes[majority[0]].is_empty() => continue,
+            Some(majority) => vec![majority[0]],
+            None => groups
+                .iter()
+                .filter(|g| !hashes[g[0]].is_empty())
+                .map(|g| g[0])
+                .collect(),
+        };
+        for source in sources {
+            for target in 0..replica {
+                if hashes[target] != hashes[source] {
+                    let time_ranges = tasks.entry((table, source, target)).or_default();
+                    match time_ranges.last_mut() {
+                        // merge the adjacent time ranges
+                        Some(last) if last.1 == min_ts => last.1 = max_ts,
+                        _ => time_ranges.push((min_ts, max_ts)),
+                    }
+                }
+            }
+        }
+    }
+
+    tasks
+        .into_iter()
+        .map(|((table, source, target), time_ranges)| RepairTask {
+            table: table.to_string(),
+            source: trees[source].vnode.clone(),
+            target: trees[target].vnode.clone(),
+            time_ranges,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use models::meta_data::VnodeInfo;
+    use tskv::{ColumnHashTreeNode, TableHashTreeNode, TimeRangeHashTreeNode};
+
+    use super::{find_repair_tasks, VnodeHashTree};
+
+    fn make_tree(vnode_id: u32, ranges: &[(i64, i64, u8)]) -> VnodeHashTree {
+        let values = ranges
+            .iter()
+            .map(|(min_ts, max_ts, hash)| TimeRangeHashTreeNode {
+                min_ts: *min_ts,
+                max_ts: *max_ts,
+                hash: [*hash; 32],
+            })
+            .collect();
+
+        VnodeHashTree {
+            vnode: VnodeInfo {
+                id: vnode_id,
+                node_id: vnode_id as u64,
+            },
+            tables: vec![TableHashTreeNode {
+                table: "tb".to_string(),
+                columns: vec![ColumnHashTreeNode {
+                    column: "value".to_string(),
+                    values,
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_consistent_replicas() {
+        let trees = vec![
+            make_tree(1, &[(0, 10, 1), (10, 20, 2)]),
+            make_tree(2, &[(0, 10, 1), (10, 20, 2)]),
+            make_tree(3, &[(0, 10, 1), (10, 20, 2)]),
+        ];
+        assert!(find_repair_tasks(&trees).is_empty());
+    }
+
+    #[test]
+    fn test_repair_from_majority() {
+        let trees = vec![
+            make_tree(1, &[(0, 10, 1), (10, 20, 2), (20, 30, 3), (40, 50, 5)]),
+            make_tree(2, &[(0, 10, 1), (10, 20, 9), (20, 30, 9), (40, 50, 5)]),
+            make_tree(3, &[(0, 10, 1), (10, 20, 2), (20, 30, 3), (40, 50, 5)]),
+        ];
+
+        let tasks = find_repair_tasks(&trees);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].table, "tb");
+        assert_eq!(tasks[0].source.id, 1);
+        assert_eq!(tasks[0].target.id, 2);
+        assert_eq!(tasks[0].time_ranges, vec![(10, 30)]);
+    }
+
+    #[test]
+    fn test_repair_missing_range() {
+        let trees = vec![
+            make_tree(1, &[(0, 10, 1), (20, 30, 3)]),
+            make_tree(2, &[(0, 10, 1)]),
+        ];
+
+        let tasks = find_repair_tasks(&trees);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].source.id, 1);
+        assert_eq!(tasks[0].target.id, 2);
+        assert_eq!(tasks[0].time_ranges, vec![(20, 30)]);
+    }
+
+    #[test]
+    fn test_skip_deleted_by_majority() {
+        let trees = vec![
+            make_tree(1, &[(0, 10, 1)]),
+            make_tree(2, &[(0, 10, 1), (10, 20, 2)]),
+            make_tree(3, &[(0, 10, 1)]),
+        ];
+        assert!(find_repair_tasks(&trees).is_empty());
+    }
+
+    #[test]
+    fn test_repair_without_majority() {
+        let trees = vec![make_tree(1, &[(0, 10, 1)]), make_tree(2, &[(0, 10, 2)])];
+
+        let mut tasks = find_repair_tasks(&trees);
+        tasks.sort_by_key(|t| t.source.id);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!((tasks[0].source.id, tasks[0].target.id), (1, 2));
+        assert_eq!((tasks[1].source.id, tasks[1].target.id), (2, 1));
+        assert_eq!(tasks[0].time_ranges, vec![(0, 10)]);
+    }
+}
@@ -1,36 +1,44 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::sync::Arc;
 
-use config::{ClusterConfig, HintedOffConfig};
+use config::{ClusterConfig, HintedOffConfig, RebalanceConfig, RepairConfig, TLSConfig};
 use models::consistency_level::ConsistencyLevel;
-use models::meta_data::{BucketInfo, DatabaseInfo, ExpiredBucketInfo, VnodeAllInfo};
-use models::predicate::domain::{ColumnDomains, PredicateRef};
+use models::meta_data::{
+    BucketInfo, DatabaseInfo, ExpiredBucketInfo, NodeHeartbeat, NodeState, StreamInfo, VnodeAllInfo,
+};
+use models::oid::Identifier;
+use models::predicate::domain::{ColumnDomains, Predicate, PredicateRef, QueryExpr};
 use models::schema::{DatabaseSchema, TableSchema, TskvTableSchema};
 use models::*;
 
 use protos::kv_service::WritePointsRpcRequest;
 use snafu::ResultExt;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::oneshot;
 
-use trace::info;
+use trace::{info, warn};
 use tskv::engine::{EngineRef, MockEngine};
 use tskv::TimeRange;
 
-use meta::meta_client::{MetaClientRef, MetaRef, RemoteMetaManager};
+use meta::error::MetaError;
+use meta::meta_client::{MetaClientRef, MetaRef, RemoteAdminMeta, RemoteMetaManager};
 use meta::meta_client_mock::{MockMetaClient, MockMetaManager};
 
 use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::SendableRecordBatchStream;
 use tskv::iterator::QueryOption;
 
 use crate::command::*;
 use crate::errors::*;
 use crate::file_info::*;
 use crate::hh_queue::HintedOffManager;
+use crate::node_client::{NodeClients, NodeClientsRef};
+use crate::node_health::{NodeHealth, NodeHealthRef};
 use crate::reader::{QueryExecutor, ReaderIterator};
+use crate::rebalance;
+use crate::repair::{self, RepairTask, VnodeHashTree};
 use crate::writer::{PointWriter, VnodeMapping};
 
 pub type CoordinatorRef = Arc<dyn Coordinator>;
@@ -41,6 +49,8 @@ pub trait Coordinator: Send + Sync + Debug {
     fn meta_manager(&self) -> MetaRef;
     fn store_engine(&self) -> EngineRef;
     fn tenant_meta(&self, tenant: &str) -> Option<MetaClientRef>;
+    /// The clients of the coordinator services of the other nodes.
+    fn node_clients(&self) -> NodeClientsRef;
     async fn write_points(
         &self,
         tenant: String,
@@ -55,14 +65,81 @@ pub trait Coordinator: Send + Sync + Debug {
 
     async fn read_record(&self, option: QueryOption) -> CoordinatorResult<ReaderIterator>;
 
+    /// The executor of the plan fragments in the read requests.
+    fn fragment_executor(&self) -> Option<FragmentExecutorRef>;
+
+    /// Delete the data of the series matched by `filters` from every vnode of the table,
+    /// returns the number of series affected.
+    async fn delete_from_table(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: &str,
+        filters: Vec<Expr>,
+    ) -> CoordinatorResult<u64>;
+
     async fn vnode_manager(
         &self,
         tenant: &str,
         vnode_id: u32,
         cmd_type: VnodeManagerCmdType,
     ) -> CoordinatorResult<()>;
+
+    /// Returns the hash trees of the replicas of the replication set.
+    async fn checksum_replication_set(
+        &self,
+        tenant: &str,
+        repl_id: u32,
+    ) -> CoordinatorResult<Vec<VnodeHashTree>>;
+
+    /// Re-syncs the time ranges whose hashes differ between the replicas of the replication set,
+    /// returns the executed repairs.
+    async fn repair_replication_set(
+        &self,
+        tenant: &str,
+        repl_id: u32,
+    ) -> CoordinatorResult<Vec<RepairTask>>;
+
+    /// The hinted off queues of this node, none if the node does not queue writes.
+    fn hinted_off_manager(&self) -> Option<Arc<HintedOffManager>>;
+
+    /// Pauses, resumes or purges the hinted off queues of the node on all the live nodes.
+    async fn hinted_off_control(&self, node_id: u64, cmd: HintedOffCmd) -> CoordinatorResult<()>;
+}
+
+pub type StreamExecutorRef = Arc<dyn StreamExecutor>;
+
+/// Executes the queries of the streams, implemented by the query engine.
+#[async_trait::async_trait]
+pub trait StreamExecutor: Send + Sync + Debug {
+    /// Aggregates the data in the time range [start, end) with the query of the stream,
+    /// and writes the results into the target table.
+    async fn execute(&self, stream: &StreamInfo, start: i64, end: i64) -> CoordinatorResult<()>;
 }
 
+pub type FragmentExecutorRef = Arc<dyn FragmentExecutor>;
+
+/// Executes the plan fragments pushed down to the vnodes, implemented by the query engine.
+#[async_trait::async_trait]
+pub trait FragmentExecutor: Send + Sync + Debug {
+    /// Executes the encoded `PlanFragment` over the batches scanned from the vnodes.
+    async fn execute(
+        &self,
+        fragment: &[u8],
+        input: SendableRecordBatchStream,
+    ) -> CoordinatorResult<SendableRecordBatchStream>;
+}
+
+/// The maximum number of windows a stream processes at a time,
+/// the stream catches up in several rounds after a long downtime.
+const STREAM_MAX_WINDOWS: i64 = 60;
+/// The window failed is retried after the backoff, doubled by each failure up to the max.
+const STREAM_MIN_BACKOFF: tokio::time::Duration = tokio::time::Duration::from_secs(1);
+const STREAM_MAX_BACKOFF: tokio::time::Duration = tokio::time::Duration::from_secs(300);
+
+/// The interval of the heartbeats sent to meta, see `NODE_SUSPECT_TIMEOUT`.
+const HEARTBEAT_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(3);
+
 #[derive(Debug, Default)]
 pub struct MockCoordinator {}
 
@@ -84,6 +161,13 @@ impl Coordinator for MockCoordinator {
         Some(Arc::new(MockMetaClient::default()))
     }
 
+    fn node_clients(&self) -> NodeClientsRef {
+        Arc::new(
+            NodeClients::new(Arc::new(MockMetaManager::default()), None)
+                .expect("mock node clients"),
+        )
+    }
+
     async fn write_points(
         &self,
         tenant: String,
@@ -105,6 +189,20 @@ impl Coordinator for MockCoordinator {
         Ok(it)
     }
 
+    fn fragment_executor(&self) -> Option<FragmentExecutorRef> {
+        None
+    }
+
+    async fn delete_from_table(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: &str,
+        filters: Vec<Expr>,
+    ) -> CoordinatorResult<u64> {
+        Ok(0)
+    }
+
     async fn vnode_manager(
         &self,
         tenant: &str,
@@ -113,6 +211,30 @@ impl Coordinator for MockCoordinator {
     ) -> CoordinatorResult<()> {
         Ok(())
     }
+
+    async fn checksum_replication_set(
+        &self,
+        tenant: &str,
+        repl_id: u32,
+    ) -> CoordinatorResult<Vec<VnodeHashTree>> {
+        Ok(vec![])
+    }
+
+    async fn repair_replication_set(
+        &self,
+        tenant: &str,
+        repl_id: u32,
+    ) -> CoordinatorResult<Vec<RepairTask>> {
+        Ok(vec![])
+    }
+
+    fn hinted_off_manager(&self) -> Option<Arc<HintedOffManager>> {
+        None
+    }
+
+    async fn hinted_off_control(&self, node_id: u64, cmd: HintedOffCmd) -> CoordinatorResult<()> {
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -122,6 +244,9 @@ pub struct CoordService {
     kv_inst: EngineRef,
     writer: Arc<PointWriter>,
     handoff: Arc<HintedOffManager>,
+    node_clients: NodeClientsRef,
+    node_health: NodeHealthRef,
+    fragment_executor: FragmentExecutorRef,
     coord_sender: Sender<CoordinatorIntCmd>,
 }
 
@@ -130,18 +255,27 @@ impl CoordService {
         kv_inst: EngineRef,
         cluster: ClusterConfig,
         handoff_cfg: HintedOffConfig,
-    ) -> Arc<Self> {
+        fragment_executor: FragmentExecutorRef,
+        tls_config: Option<TLSConfig>,
+    ) -> CoordinatorResult<Arc<Self>> {
         let meta_manager: MetaRef = Arc::new(RemoteMetaManager::new(cluster.clone()));
+        let node_clients = Arc::new(NodeClients::new(meta_manager.clone(), tls_config.as_ref())?);
+
+        let node_health = Arc::new(NodeHealth::default());
 
         let (hh_sender, hh_receiver) = mpsc::channel(1024);
         let point_writer = Arc::new(PointWriter::new(
             cluster.node_id,
             kv_inst.clone(),
             meta_manager.clone(),
+            node_clients.clone(),
+            node_health.clone(),
             hh_sender,
         ));
 
-        let hh_manager = Arc::new(HintedOffManager::new(handoff_cfg, point_writer.clone()).await);
+        let hh_manager = Arc::new(
+            HintedOffManager::new(handoff_cfg, meta_manager.clone(), point_writer.clone()).await,
+        );
         tokio::spawn(HintedOffManager::write_handoff_job(
             hh_manager.clone(),
             hh_receiver,
@@ -155,12 +289,16 @@ impl CoordService {
             meta: meta_manager,
             writer: point_writer,
             handoff: hh_manager,
+            node_clients,
+            node_health,
+            fragment_executor,
         });
 
         tokio::spawn(CoordService::db_ttl_service(coord.clone()));
+        tokio::spawn(CoordService::heartbeat_service(coord.clone()));
         tokio::spawn(CoordService::coord_service(coord.clone(), coord_receiver));
 
-        coord
+        Ok(coord)
     }
 
     async fn coord_service(coord: Arc<CoordService>, mut requests: Receiver<CoordinatorIntCmd>) {
@@ -188,6 +326,13 @@ impl CoordService {
                         sender,
                     ));
                 }
+                CoordinatorIntCmd::DeleteFromTableCmd(req, sender) => {
+                    tokio::spawn(CoordService::delete_from_table_request(
+                        coord.clone(),
+                        req,
+                        sender,
+                    ));
+                }
             }
         }
     }
@@ -206,6 +351,250 @@ impl CoordService {
         }
     }
 
+    /// Sends the heartbeats of this node to meta and refreshes the dead nodes
+    /// skipped by the reads and the writes.
+    async fn heartbeat_service(coord: Arc<CoordService>) {
+        loop {
+            let heartbeat = NodeHeartbeat {
+                node_id: coord.node_id,
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                sys_info: RemoteAdminMeta::sys_info(),
+                time: 0,
+                hinted_off: coord.handoff.status().await,
+            };
+            if let Err(err) = coord.meta.admin_meta().heartbeat(&heartbeat) {
+                warn!("heartbeat: send heartbeat failed: {}", err);
+            }
+
+            match coord.meta.admin_meta().data_nodes_status() {
+                Ok(nodes) => coord.node_health.set_dead_nodes(
+                    nodes
+                        .iter()
+                        .filter(|n| n.state == NodeState::Dead)
+                        .map(|n| n.info.id)
+                        .collect(),
+                ),
+                Err(err) => warn!("heartbeat: get data nodes status failed: {}", err),
+            }
+
+            tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+        }
+    }
+
+    /// Starts executing the streams owned by this node with the executor.
+    pub fn start_stream_service(coord: Arc<CoordService>, executor: StreamExecutorRef) {
+        tokio::spawn(CoordService::stream_service(coord, executor));
+    }
+
+    async fn stream_service(coord: Arc<CoordService>, executor: StreamExecutorRef) {
+        // (tenant, db, name) -> (backoff, retry time) of the streams failed
+        let mut failures: HashMap<
+            (String, String, String),
+            (tokio::time::Duration, tokio::time::Instant),
+        > = HashMap::new();
+        loop {
+            tokio::time::sleep(STREAM_MIN_BACKOFF).await;
+
+            let streams = match coord.meta.streams() {
+                Ok(streams) => streams,
+                Err(err) => {
+                    warn!("list streams failed: {}", err);
+                    continue;
+                }
+            };
+            failures.retain(|key, _| {
+                streams
+                    .iter()
+                    .any(|s| s.tenant == key.0 && s.db == key.1 && s.name == key.2)
+            });
+
+            let now = models::utils::now_timestamp();
+            for stream in streams {
+                if stream.node_id != coord.node_id {
+                    continue;
+                }
+
+                let key = (
+                    stream.tenant.clone(),
+                    stream.db.clone(),
+                    stream.name.clone(),
+                );
+                if let Some((_, retry_at)) = failures.get(&key) {
+                    if tokio::time::Instant::now() < *retry_at {
+                        continue;
+                    }
+                }
+
+                if let Some((start, end)) = stream.next_window(now, STREAM_MAX_WINDOWS) {
+                    match coord.process_stream(&executor, &stream, start, end).await {
+                        Ok(()) => {
+                            failures.remove(&key);
+                        }
+                        Err(err) => {
+                            let backoff = failures
+                                .get(&key)
+                                .map_or(STREAM_MIN_BACKOFF, |(backoff, _)| {
+                                    (*backoff * 2).min(STREAM_MAX_BACKOFF)
+                                });
+                            warn!(
+                                "process stream {}.{} [{}, {}) failed, retry in {:?}: {}",
+                                stream.db, stream.name, start, end, backoff, err
+                            );
+                            failures.insert(key, (backoff, tokio::time::Instant::now() + backoff));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Starts repairing the replication sets led by this node periodically.
+    pub fn start_repair_service(coord: Arc<CoordService>, config: RepairConfig) {
+        if config.enable {
+            tokio::spawn(CoordService::repair_service(coord, config));
+        }
+    }
+
+    async fn repair_service(coord: Arc<CoordService>, config: RepairConfig) {
+        let interval = tokio::time::Duration::from_secs(config.interval);
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let tenants = match coord.meta.tenant_manager().tenants() {
+                Ok(tenants) => tenants,
+                Err(err) => {
+                    warn!("repair: list tenants failed: {}", err);
+                    continue;
+                }
+            };
+            for tenant in tenants.iter() {
+                if let Err(err) = coord.repair_tenant(tenant.name()).await {
+                    warn!("repair: repair tenant {} failed: {}", tenant.name(), err);
+                }
+            }
+        }
+    }
+
+    /// The replication set is led by the node of its first replica. The buckets still
+    /// being written to are skipped, their replicas differ until the writes are flushed.
+    async fn repair_tenant(&self, tenant: &str) -> CoordinatorResult<()> {
+        let meta = self
+            .tenant_meta(tenant)
+            .ok_or(CoordinatorError::TenantNotFound {
+                name: tenant.to_string(),
+            })?;
+
+        let now = models::utils::now_timestamp();
+        for db in meta.list_databases()? {
+            let info = match meta.get_db_info(&db)? {
+                Some(info) => info,
+                None => continue,
+            };
+            for bucket in info.buckets.iter().filter(|b| b.end_time < now) {
+                for repl_set in bucket.shard_group.iter() {
+                    match repl_set.vnodes.first() {
+                        Some(vnode) if vnode.node_id == self.node_id => {}
+                        _ => continue,
+                    }
+
+                    if let Err(err) =
+                        repair::repair_replication_set(&self.node_clients, tenant, &db, repl_set)
+                            .await
+                    {
+                        warn!(
+                            "repair: repair replication set {} of {}.{} failed: {}",
+                            repl_set.id, tenant, db, err
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Starts moving the vnodes between the data nodes periodically.
+    pub fn start_rebalance_service(coord: Arc<CoordService>, config: RebalanceConfig) {
+        if config.enable {
+            tokio::spawn(CoordService::rebalance_service(coord, config));
+        }
+    }
+
+    async fn rebalance_service(coord: Arc<CoordService>, config: RebalanceConfig) {
+        let interval = tokio::time::Duration::from_secs(config.interval);
+        loop {
+            tokio::time::sleep(interval).await;
+
+            if let Err(err) = coord.rebalance(config.max_moves).await {
+                warn!("rebalance: rebalance failed: {}", err);
+            }
+        }
+    }
+
+    /// The rebalancing is led by the alive online node with the smallest id. The decommissioning
+    /// nodes without vnodes are removed from the cluster, then the planned moves are executed
+    /// one by one.
+    async fn rebalance(&self, max_moves: usize) -> CoordinatorResult<()> {
+        let nodes = self.meta.admin_meta().data_nodes_status()?;
+        let leader = nodes
+            .iter()
+            .filter(|n| n.state == NodeState::Alive && !n.info.is_decommissioning())
+            .map(|n| n.info.id)
+            .min();
+        if leader != Some(self.node_id) {
+            return Ok(());
+        }
+
+        let placements = rebalance::collect_placements(&self.meta)?;
+        for node in nodes.iter().filter(|n| n.info.is_decommissioning()) {
+            if placements.iter().all(|p| p.node_id != node.info.id) {
+                info!(
+                    "rebalance: remove decommissioned data node {}",
+                    node.info.id
+                );
+                self.meta.admin_meta().del_data_node(node.info.id)?;
+            }
+        }
+
+        for mv in rebalance::plan_rebalance(&nodes, &placements, max_moves) {
+            info!(
+                "rebalance: move vnode {} of {} from node {} to node {}",
+                mv.vnode_id, mv.tenant, mv.source, mv.target
+            );
+
+            let req = VnodeManagerRequest {
+                tenant: mv.tenant.clone(),
+                vnode_id: mv.vnode_id,
+                cmd_type: VnodeManagerCmdType::Move(mv.target),
+            };
+            if let Err(err) = self.warp_vnode_manager_request(&req).await {
+                warn!("rebalance: move vnode {} failed: {}", mv.vnode_id, err);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn process_stream(
+        &self,
+        executor: &StreamExecutorRef,
+        stream: &StreamInfo,
+        start: i64,
+        end: i64,
+    ) -> CoordinatorResult<()> {
+        executor.execute(stream, start, end).await?;
+
+        let meta = self
+            .tenant_meta(&stream.tenant)
+            .ok_or(CoordinatorError::TenantNotFound {
+                name: stream.tenant.clone(),
+            })?;
+
+        meta.update_stream_watermark(&stream.db, &stream.name, end)?;
+
+        Ok(())
+    }
+
     async fn delete_expired_bucket(&self, info: &ExpiredBucketInfo) -> CoordinatorResult<()> {
         for repl_set in info.bucket.shard_group.iter() {
             for vnode in repl_set.vnodes.iter() {
@@ -217,8 +606,7 @@ impl CoordService {
                     },
                 };
 
-                let cmd = CoordinatorTcpCmd::AdminStatementCmd(req);
-                self.exec_on_node(vnode.node_id, cmd).await?;
+                self.exec_on_node(vnode.node_id, &req).await?;
             }
         }
 
@@ -274,8 +662,7 @@ impl CoordService {
 
         let mut requests = vec![];
         for node in nodes.iter() {
-            let cmd = CoordinatorTcpCmd::AdminStatementCmd(req.clone());
-            let request = coord.exec_on_node(node.id, cmd.clone());
+            let request = coord.exec_on_node(node.id, &req);
             requests.push(request);
         }
 
@@ -311,7 +698,7 @@ impl CoordService {
     async fn warp_vnode_manager_request(&self, req: &VnodeManagerRequest) -> CoordinatorResult<()> {
         let all_info = self.get_vnode_all_info(&req.tenant, req.vnode_id)?;
 
-        let (tcp_req, req_node_id) = match req.cmd_type {
+        let (admin_req, req_node_id) = match req.cmd_type {
             VnodeManagerCmdType::Copy(node_id) => {
                 if all_info.node_id == node_id {
                     return Err(CoordinatorError::CommonError {
@@ -360,9 +747,89 @@ impl CoordService {
             ),
         };
 
-        let cmd = CoordinatorTcpCmd::AdminStatementCmd(tcp_req);
+        self.exec_on_node(req_node_id, &admin_req).await
+    }
+
+    async fn delete_from_table_request(
+        coord: Arc<CoordService>,
+        req: DeleteFromTableRequest,
+        sender: oneshot::Sender<CoordinatorResult<u64>>,
+    ) {
+        sender
+            .send(coord.warp_delete_from_table_request(&req).await)
+            .expect("success");
+    }
+
+    async fn warp_delete_from_table_request(
+        &self,
+        req: &DeleteFromTableRequest,
+    ) -> CoordinatorResult<u64> {
+        let meta = self
+            .tenant_meta(&req.tenant)
+            .ok_or(CoordinatorError::TenantNotFound {
+                name: req.tenant.clone(),
+            })?;
+        let table_schema =
+            meta.get_tskv_table_schema(&req.db, &req.table)?
+                .ok_or(MetaError::TableNotFound {
+                    table: req.table.clone(),
+                })?;
+
+        let filter = Arc::new(Predicate::default().push_down_filter(&req.filters, &table_schema));
+        let time_ranges = QueryOption::parse_time_ranges(filter, table_schema.clone());
+
+        // every replica of the buckets overlapping the time ranges
+        let mut node_vnodes: HashMap<u64, Vec<u32>> = HashMap::new();
+        for time_range in time_ranges.iter() {
+            let buckets = meta.mapping_bucket(&req.db, time_range.min_ts, time_range.max_ts)?;
+            for bucket in buckets.iter() {
+                for repl_set in bucket.shard_group.iter() {
+                    for vnode in repl_set.vnodes.iter() {
+                        node_vnodes.entry(vnode.node_id).or_default().push(vnode.id);
+                    }
+                }
+            }
+        }
+
+        let expr = QueryExpr::encode(&QueryExpr {
+            filters: req.filters.clone(),
+            df_schema: table_schema.to_arrow_schema(),
+            table_schema,
+            aggregates: None,
+            fragment: None,
+        })?;
+
+        let mut requests = vec![];
+        for (node_id, mut vnode_ids) in node_vnodes.into_iter() {
+            vnode_ids.sort_unstable();
+            vnode_ids.dedup();
+
+            let admin_req = AdminStatementRequest {
+                tenant: req.tenant.clone(),
+                stmt: AdminStatementType::DeleteFromTable {
+                    db: req.db.clone(),
+                    table: req.table.clone(),
+                    vnode_ids,
+                    expr: expr.clone(),
+                },
+            };
+            requests.push(async move {
+                self.node_clients
+                    .exec_admin_command(node_id, &admin_req)
+                    .await
+            });
+        }
+
+        // The same series is stored in every replica and in every bucket it was written to,
+        // data nodes respond with the hash of the affected series keys to deduplicate them.
+        let mut series = HashSet::new();
+        for rsp in futures::future::try_join_all(requests).await? {
+            let hashes = serde_json::from_str::<Vec<u64>>(&rsp.data)
+                .map_err(|e| CoordinatorError::InvalidSerdeMsg { err: e.to_string() })?;
+            series.extend(hashes);
+        }
 
-        self.exec_on_node(req_node_id, cmd).await
+        Ok(series.len() as u64)
     }
 
     async fn select_statement_request(coord: Arc<CoordService>, req: SelectStatementRequest) {
@@ -385,8 +852,11 @@ impl CoordService {
             req.option,
             coord.kv_inst.clone(),
             coord.meta.clone(),
+            coord.node_clients.clone(),
             req.sender.clone(),
-        );
+        )
+        .with_node_health(coord.node_health.clone())
+        .with_fragment_executor(coord.fragment_executor.clone());
 
         if let Err(err) = executor.execute().await {
             info!("select statement execute failed: {}", err.to_string());
@@ -396,23 +866,15 @@ impl CoordService {
         }
     }
 
-    async fn exec_on_node(&self, node_id: u64, cmd: CoordinatorTcpCmd) -> CoordinatorResult<()> {
-        let mut conn = self.meta.admin_meta().get_node_conn(node_id).await?;
-
-        send_command(&mut conn, &cmd).await?;
-        let rsp_cmd = recv_command(&mut conn).await?;
-        if let CoordinatorTcpCmd::StatusResponseCmd(msg) = rsp_cmd {
-            self.meta.admin_meta().put_node_conn(node_id, conn);
-            if msg.code == crate::command::SUCCESS_RESPONSE_CODE {
-                Ok(())
-            } else {
-                Err(CoordinatorError::WriteVnode {
-                    msg: format!("code: {}, msg: {}", msg.code, msg.data),
-                })
-            }
-        } else {
-            Err(CoordinatorError::UnExpectResponse)
-        }
+    async fn exec_on_node(
+        &self,
+        node_id: u64,
+        req: &AdminStatementRequest,
+    ) -> CoordinatorResult<()> {
+        self.node_clients
+            .exec_admin_command(node_id, req)
+            .await
+            .map(|_| ())
     }
 }
 
@@ -435,6 +897,10 @@ impl Coordinator for CoordService {
         self.meta.tenant_manager().tenant_meta(tenant)
     }
 
+    fn node_clients(&self) -> NodeClientsRef {
+        self.node_clients.clone()
+    }
+
     async fn write_points(
         &self,
         tenant: String,
@@ -480,6 +946,33 @@ impl Coordinator for CoordService {
         Ok(iterator)
     }
 
+    fn fragment_executor(&self) -> Option<FragmentExecutorRef> {
+        Some(self.fragment_executor.clone())
+    }
+
+    async fn delete_from_table(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: &str,
+        filters: Vec<Expr>,
+    ) -> CoordinatorResult<u64> {
+        let (sender, receiver) = oneshot::channel();
+
+        let req = DeleteFromTableRequest {
+            tenant: tenant.to_string(),
+            db: db.to_string(),
+            table: table.to_string(),
+            filters,
+        };
+
+        self.coord_sender
+            .send(CoordinatorIntCmd::DeleteFromTableCmd(req, sender))
+            .await?;
+
+        receiver.await?
+    }
+
     async fn vnode_manager(
         &self,
         tenant: &str,
@@ -500,4 +993,55 @@ impl Coordinator for CoordService {
 
         receiver.await?
     }
+
+    async fn checksum_replication_set(
+        &self,
+        tenant: &str,
+        repl_id: u32,
+    ) -> CoordinatorResult<Vec<VnodeHashTree>> {
+        let meta = self
+            .tenant_meta(tenant)
+            .ok_or(CoordinatorError::TenantNotFound {
+                name: tenant.to_string(),
+            })?;
+        let (db, repl_set) = repair::find_replication_set(&meta, repl_id)?;
+
+        repair::get_hash_trees(&self.node_clients, tenant, &db, &repl_set).await
+    }
+
+    async fn repair_replication_set(
+        &self,
+        tenant: &str,
+        repl_id: u32,
+    ) -> CoordinatorResult<Vec<RepairTask>> {
+        let meta = self
+            .tenant_meta(tenant)
+            .ok_or(CoordinatorError::TenantNotFound {
+                name: tenant.to_string(),
+            })?;
+        let (db, repl_set) = repair::find_replication_set(&meta, repl_id)?;
+
+        repair::repair_replication_set(&self.node_clients, tenant, &db, &repl_set).await
+    }
+
+    fn hinted_off_manager(&self) -> Option<Arc<HintedOffManager>> {
+        Some(self.handoff.clone())
+    }
+
+    async fn hinted_off_control(&self, node_id: u64, cmd: HintedOffCmd) -> CoordinatorResult<()> {
+        let req = AdminStatementRequest {
+            tenant: "".to_string(),
+            stmt: AdminStatementType::HintedOff { node_id, cmd },
+        };
+
+        // the dead nodes are skipped, the command is executed again after they are back
+        let nodes = self.meta.admin_meta().data_nodes_status()?;
+        let requests = nodes
+            .iter()
+            .filter(|n| n.info.id != node_id && n.state != NodeState::Dead)
+            .map(|n| self.exec_on_node(n.info.id, &req));
+        futures::future::try_join_all(requests).await?;
+
+        Ok(())
+    }
 }
@@ -1,22 +1,24 @@
 use async_channel as channel;
 use flatbuffers::FlatBufferBuilder;
 use futures::future::ok;
+use futures::stream::{FuturesUnordered, StreamExt};
+use meta::meta_client::{MetaClientRef, MetaRef};
+use models::auth::user::{ROOT, ROOT_PWD};
+use models::consistency_level::ConsistencyLevel;
 use models::meta_data::*;
 use models::utils::now_timestamp;
 use models::RwLockRef;
 use parking_lot::{RwLock, RwLockReadGuard};
+use protos::coordinator_service::WriteVnodeRequest;
 use protos::kv_service::{Meta, WritePointsRpcRequest, WritePointsRpcResponse};
 use protos::models::{FieldBuilder, PointArgs, Points, PointsArgs, TagBuilder};
 use snafu::ResultExt;
 use std::collections::HashMap;
 use std::collections::VecDeque;
-//use std::net::{TcpListener, TcpStream};
-use meta::meta_client::{MetaClientRef, MetaRef};
-use models::auth::user::{ROOT, ROOT_PWD};
 use std::sync::Arc;
-use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::mpsc::Sender;
 use tokio::sync::oneshot;
+use tonic::Request;
 use tskv::engine::EngineRef;
 
 use protos::models as fb_models;
@@ -25,6 +27,8 @@ use crate::command::*;
 use crate::errors::*;
 use crate::hh_queue::HintedOffManager;
 use crate::hh_queue::{HintedOffBlock, HintedOffWriteReq};
+use crate::node_client::{status_to_error, NodeClientsRef, WRITE_REQUEST_TIMEOUT};
+use crate::node_health::NodeHealthRef;
 
 use trace::debug;
 use trace::info;
@@ -153,11 +157,22 @@ impl<'a> Default for VnodeMapping<'a> {
     }
 }
 
-#[derive(Debug)]
+/// The result of writing data to one replica of a replication set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReplicaWriteStatus {
+    /// The vnode wrote the data.
+    Written,
+    /// The vnode is unreachable, the data was accepted by hinted handoff.
+    HintedOff,
+}
+
+#[derive(Debug, Clone)]
 pub struct PointWriter {
     node_id: u64,
     kv_inst: EngineRef,
     meta_manager: MetaRef,
+    node_clients: NodeClientsRef,
+    node_health: NodeHealthRef,
     hh_sender: Sender<HintedOffWriteReq>,
 }
 
@@ -166,12 +181,16 @@ impl PointWriter {
         node_id: u64,
         kv_inst: EngineRef,
         meta_manager: MetaRef,
+        node_clients: NodeClientsRef,
+        node_health: NodeHealthRef,
         hh_sender: Sender<HintedOffWriteReq>,
     ) -> Self {
         Self {
             node_id,
             kv_inst,
             meta_manager,
+            node_clients,
+            node_health,
             hh_sender,
         }
     }
@@ -203,11 +222,13 @@ impl PointWriter {
         for (id, points) in mapping.points.iter_mut() {
             points.finish();
 
-            for vnode in points.repl_set.vnodes.iter() {
-                let request =
-                    self.write_to_node(vnode.id, &req.tenant, vnode.node_id, points.data.clone());
-                requests.push(request);
-            }
+            let request = self.write_to_replication_set(
+                &req.tenant,
+                req.level,
+                &points.repl_set,
+                points.data.clone(),
+            );
+            requests.push(request);
         }
 
         futures::future::try_join_all(requests).await?;
@@ -215,18 +236,80 @@ impl PointWriter {
         Ok(())
     }
 
+    /// Write data to every vnode of the replication set, returns as soon as
+    /// the consistency level is satisfied.
+    ///
+    /// The writes of the remaining vnodes keep running in background.
+    async fn write_to_replication_set(
+        &self,
+        tenant: &str,
+        level: ConsistencyLevel,
+        repl_set: &ReplicationSet,
+        data: Vec<u8>,
+    ) -> CoordinatorResult<()> {
+        let replica = repl_set.vnodes.len();
+        let mut requests = FuturesUnordered::new();
+        for vnode in repl_set.vnodes.iter() {
+            let writer = self.clone();
+            let tenant = tenant.to_string();
+            let (vnode_id, node_id) = (vnode.id, vnode.node_id);
+            let data = data.clone();
+            requests.push(tokio::spawn(async move {
+                writer.write_to_node(vnode_id, &tenant, node_id, data).await
+            }));
+        }
+
+        let (mut written, mut hinted_off) = (0, 0);
+        let mut last_err = None;
+        while let Some(result) = requests.next().await {
+            match result {
+                Ok(Ok(ReplicaWriteStatus::Written)) => written += 1,
+                Ok(Ok(ReplicaWriteStatus::HintedOff)) => hinted_off += 1,
+                Ok(Err(err)) => last_err = Some(err),
+                Err(err) => {
+                    last_err = Some(CoordinatorError::CommonError {
+                        msg: err.to_string(),
+                    })
+                }
+            }
+
+            if level.is_satisfied(replica, written, hinted_off) {
+                return Ok(());
+            }
+        }
+
+        Err(CoordinatorError::ConsistencyLevelNotSatisfied {
+            level,
+            replica,
+            written,
+            hinted_off,
+            msg: last_err.map(|e| e.to_string()).unwrap_or_default(),
+        })
+    }
+
     async fn write_to_node(
         &self,
         vnode_id: u32,
         tenant: &str,
         node_id: u64,
         data: Vec<u8>,
-    ) -> CoordinatorResult<()> {
+    ) -> CoordinatorResult<ReplicaWriteStatus> {
         if node_id == self.node_id {
             let result = self.write_to_local_node(vnode_id, tenant, data).await;
             debug!("write data to local {}({}) {:?}", node_id, vnode_id, result);
 
-            return result;
+            return result.map(|_| ReplicaWriteStatus::Written);
+        }
+
+        if self.node_health.is_dead(node_id) {
+            debug!(
+                "node {} is dead, write {} to hinted handoff",
+                node_id, vnode_id
+            );
+            return self
+                .write_to_handoff(vnode_id, node_id, tenant, data)
+                .await
+                .map(|_| ReplicaWriteStatus::HintedOff);
         }
 
         if let Err(err) = self
@@ -240,11 +323,14 @@ impl PointWriter {
                 err.to_string()
             );
 
-            return self.write_to_handoff(vnode_id, node_id, tenant, data).await;
+            return self
+                .write_to_handoff(vnode_id, node_id, tenant, data)
+                .await
+                .map(|_| ReplicaWriteStatus::HintedOff);
         }
 
         debug!("write data to remote {}({}) success!", node_id, vnode_id);
-        Ok(())
+        Ok(ReplicaWriteStatus::Written)
     }
 
     async fn write_to_handoff(
@@ -274,32 +360,21 @@ impl PointWriter {
         tenant: &str,
         data: Vec<u8>,
     ) -> CoordinatorResult<()> {
-        let mut conn = self
-            .meta_manager
-            .admin_meta()
-            .get_node_conn(node_id)
-            .await?;
+        let mut client = self.node_clients.client(node_id).await?;
 
-        let req_cmd = WriteVnodeRequest {
+        let mut request = Request::new(WriteVnodeRequest {
+            version: COORDINATOR_PROTOCOL_VERSION,
             vnode_id,
             tenant: tenant.to_string(),
             data,
-        };
-        send_command(&mut conn, &CoordinatorTcpCmd::WriteVnodePointCmd(req_cmd)).await?;
-
-        let rsp_cmd = recv_command(&mut conn).await?;
-        if let CoordinatorTcpCmd::StatusResponseCmd(msg) = rsp_cmd {
-            self.meta_manager.admin_meta().put_node_conn(node_id, conn);
-            if msg.code == SUCCESS_RESPONSE_CODE {
-                Ok(())
-            } else {
-                Err(CoordinatorError::WriteVnode {
-                    msg: format!("code: {}, msg: {}", msg.code, msg.data),
-                })
-            }
-        } else {
-            Err(CoordinatorError::UnExpectResponse)
-        }
+        });
+        request.set_timeout(WRITE_REQUEST_TIMEOUT);
+        client
+            .write_vnode_points(request)
+            .await
+            .map_err(|status| status_to_error(node_id, status))?;
+
+        Ok(())
     }
 
     async fn write_to_local_node(
@@ -316,6 +391,7 @@ impl PointWriter {
                 password: None,
             }),
             points: data.clone(),
+            consistency_level: None,
         };
 
         if let Err(err) = self.kv_inst.write(vnode_id, req).await {
@@ -84,6 +84,7 @@ mod test {
                         password: None,
                     }),
                     points,
+                    consistency_level: None,
                 })
                 .await
                 .unwrap();
@@ -3,20 +3,29 @@
 use std::{collections::HashMap, convert::Infallible, net::SocketAddr, sync::Arc};
 
 use coordinator::service::CoordinatorRef;
-use http_protocol::header::{ACCEPT, AUTHORIZATION};
-use http_protocol::parameter::{SqlParam, WriteParam};
+use http_protocol::header::{ACCEPT, AUTHORIZATION, CONTENT_ENCODING, GZIP, IDENTITY, ZSTD};
+use http_protocol::parameter::{
+    InfluxQueryParam, InfluxV2WriteParam, InfluxWriteParam, OpenTsdbPutParam, SqlParam, WriteParam,
+};
 use http_protocol::response::ErrorResponse;
+use http_protocol::status_code::{NO_CONTENT, OK};
 use query::prom::remote_read::PromRemoteSqlServer;
 use spi::server::prom::PromRemoteServerRef;
 
 use super::header::Header;
 use super::Error as HttpError;
+use crate::http::influx::{
+    batches_to_series, bucket_to_db, influx_user_info, merge_query_param, query_table_name,
+    InfluxQueryResponse, InfluxSeries, INFLUXDB_BUILD, INFLUXDB_VERSION, X_INFLUXDB_BUILD,
+    X_INFLUXDB_VERSION,
+};
 use crate::http::response::ResponseBuilder;
 use crate::http::result_format::fetch_record_batches;
 use crate::http::result_format::ResultFormat;
 use crate::http::Error;
 use crate::http::ParseLineProtocolSnafu;
 use crate::http::QuerySnafu;
+use crate::opentsdb::{parse_put_body, write_data_points};
 use crate::server;
 use crate::server::{Service, ServiceHandle};
 use chrono::Local;
@@ -26,24 +35,29 @@ use coordinator::writer::{PointWriter, VnodeMapping};
 use datafusion::arrow::util::pretty::pretty_format_batches;
 use datafusion::parquet::data_type::AsBytes;
 use flatbuffers::FlatBufferBuilder;
-use line_protocol::{line_protocol_to_lines, Line};
+use flate2::read::MultiGzDecoder;
+use line_protocol::{line_protocol_to_lines, line_protocol_to_lines_lenient, Line};
 use meta::meta_client::MetaClientRef;
 use metrics::{gather_metrics, sample_point_write_duration, sample_query_read_duration};
+use models::auth::user::UserInfo;
 use models::consistency_level::ConsistencyLevel;
 use models::error_code::{ErrorCode, UnknownCode, UnknownCodeWithMessage};
-use models::schema::DEFAULT_CATALOG;
+use models::schema::{DatabaseOptions, Precision, DEFAULT_CATALOG, DEFAULT_DATABASE};
 use protos::kv_service::{Meta, WritePointsRpcRequest};
 use protos::models as fb_models;
 use protos::models::{FieldBuilder, Point, PointArgs, Points, PointsArgs, TagBuilder};
 use snafu::ResultExt;
+use spi::query::execution::Output;
 use spi::server::dbms::DBMSRef;
 use spi::service::protocol::ContextBuilder;
 use spi::service::protocol::Query;
+use std::io::Read;
 use std::time::Instant;
 use tokio::sync::oneshot;
 use trace::debug;
 use trace::info;
 use tskv::engine::EngineRef;
+use warp::http::header::HeaderName;
 use warp::hyper::body::Bytes;
 use warp::hyper::Body;
 use warp::reject::MethodNotAllowed;
@@ -64,6 +78,7 @@ pub struct HttpService {
     handle: Option<ServiceHandle<()>>,
     query_body_limit: u64,
     write_body_limit: u64,
+    chunk_size: usize,
 }
 
 impl HttpService {
@@ -75,6 +90,7 @@ impl HttpService {
         tls_config: Option<TLSConfig>,
         query_body_limit: u64,
         write_body_limit: u64,
+        chunk_size: usize,
     ) -> Self {
         let prs = Arc::new(PromRemoteSqlServer::new(dbms.clone()));
 
@@ -88,6 +104,7 @@ impl HttpService {
             handle: None,
             query_body_limit,
             write_body_limit,
+            chunk_size,
         }
     }
 
@@ -132,6 +149,12 @@ impl HttpService {
             .or(self.metrics())
             .or(self.print_meta())
             .or(self.prom_remote_read())
+            .or(self.prom_remote_write())
+            .or(self.influx_ping())
+            .or(self.influx_write())
+            .or(self.influx_v2_write())
+            .or(self.influx_query())
+            .or(self.opentsdb_put())
     }
 
     fn ping(&self) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
@@ -147,6 +170,7 @@ impl HttpService {
 
     fn query(&self) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
         // let dbms = self.dbms.clone();
+        let default_chunk_size = self.chunk_size;
         warp::path!("api" / "v1" / "sql")
             .and(warp::post())
             .and(warp::body::content_length_limit(self.query_body_limit))
@@ -155,12 +179,13 @@ impl HttpService {
             .and(warp::query::<SqlParam>())
             .and(self.with_dbms())
             .and_then(
-                |req: Bytes, header: Header, param: SqlParam, dbms: DBMSRef| async move {
+                move |req: Bytes, header: Header, param: SqlParam, dbms: DBMSRef| async move {
                     let start = Instant::now();
                     debug!(
                         "Receive http sql request, header: {:?}, param: {:?}",
                         header, param
                     );
+                    let chunk_size = param.chunk_size.unwrap_or(default_chunk_size).max(1);
 
                     // Parse req、header and param to construct query request
                     let query =
@@ -168,10 +193,12 @@ impl HttpService {
                             sample_query_read_duration("", "", false, 0.0);
                             reject::custom(e)
                         })?;
-                    let result = sql_handle(&query, header, dbms).await.map_err(|e| {
-                        trace::error!("Failed to handle http sql request, err: {}", e);
-                        reject::custom(e)
-                    });
+                    let result = sql_handle(&query, header, chunk_size, dbms)
+                        .await
+                        .map_err(|e| {
+                            trace::error!("Failed to handle http sql request, err: {}", e);
+                            reject::custom(e)
+                        });
                     let tenant = query.context().tenant();
                     let db = query.context().database();
 
@@ -189,57 +216,242 @@ impl HttpService {
     fn write_line_protocol(
         &self,
     ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        let write_body_limit = self.write_body_limit;
         warp::path!("api" / "v1" / "write")
             .and(warp::post())
             .and(warp::body::content_length_limit(self.write_body_limit))
             .and(warp::body::bytes())
             .and(self.handle_header())
+            .and(header::optional::<String>(CONTENT_ENCODING.as_str()))
             .and(warp::query::<WriteParam>())
             .and(self.with_kv_inst())
             .and(self.with_coord())
             .and_then(
-                |req: Bytes,
-                 header: Header,
-                 param: WriteParam,
-                 kv_inst: EngineRef,
-                 coord: CoordinatorRef| async move {
-                    let start = Instant::now();
+                move |req: Bytes,
+                      header: Header,
+                      encoding: Option<String>,
+                      param: WriteParam,
+                      kv_inst: EngineRef,
+                      coord: CoordinatorRef| async move {
                     let user_info = match header.try_get_basic_auth() {
                         Ok(u) => u,
                         Err(e) => return Err(reject::custom(e)),
                     };
+                    let tenant = param.tenant.as_deref().unwrap_or(DEFAULT_CATALOG);
 
-                    let lines = String::from_utf8_lossy(req.as_ref());
-                    let mut line_protocol_lines =
-                        line_protocol_to_lines(&lines, Local::now().timestamp_nanos())
-                            .context(ParseLineProtocolSnafu)?;
+                    write_line_protocol_body(
+                        &coord,
+                        &user_info,
+                        tenant,
+                        &param.db,
+                        param.precision.as_deref(),
+                        param.consistency.unwrap_or_default(),
+                        req,
+                        encoding.as_deref(),
+                        write_body_limit,
+                        param.lenient.unwrap_or_default(),
+                    )
+                    .await
+                    .map(|_| ResponseBuilder::ok())
+                    .map_err(reject::custom)
+                },
+            )
+    }
 
-                    let points = parse_lines_to_points(&param.db, &mut line_protocol_lines)?;
+    fn influx_ping(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("ping")
+            .and(warp::get().or(warp::head()))
+            .map(|_| {
+                ResponseBuilder::new(NO_CONTENT)
+                    .insert_header((
+                        HeaderName::from_static(X_INFLUXDB_VERSION),
+                        INFLUXDB_VERSION,
+                    ))
+                    .insert_header((HeaderName::from_static(X_INFLUXDB_BUILD), INFLUXDB_BUILD))
+                    .build(vec![])
+            })
+    }
 
-                    let tenant = param.tenant.as_deref().unwrap_or(DEFAULT_CATALOG);
+    fn influx_write(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        let write_body_limit = self.write_body_limit;
+        warp::path!("write")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(self.write_body_limit))
+            .and(warp::body::bytes())
+            .and(header::optional::<String>(AUTHORIZATION.as_str()))
+            .and(header::optional::<String>(CONTENT_ENCODING.as_str()))
+            .and(warp::query::<InfluxWriteParam>())
+            .and(self.with_coord())
+            .and_then(
+                move |req: Bytes,
+                      authorization: Option<String>,
+                      encoding: Option<String>,
+                      param: InfluxWriteParam,
+                      coord: CoordinatorRef| async move {
+                    let user_info = influx_user_info(
+                        authorization.as_deref(),
+                        param.u.as_deref(),
+                        param.p.as_deref(),
+                    )?;
 
-                    let req = WritePointsRpcRequest {
-                        version: 1,
-                        meta: Some(Meta {
-                            tenant: tenant.to_string(),
-                            user: Some(user_info.user.to_string()),
-                            password: Some(user_info.password.to_string()),
-                        }),
-                        points,
-                    };
+                    write_line_protocol_body(
+                        &coord,
+                        &user_info,
+                        DEFAULT_CATALOG,
+                        &param.db,
+                        param.precision.as_deref(),
+                        param.consistency.unwrap_or_default(),
+                        req,
+                        encoding.as_deref(),
+                        write_body_limit,
+                        true,
+                    )
+                    .await
+                    .map(|_| ResponseBuilder::new(NO_CONTENT).build(vec![]))
+                    .map_err(reject::custom)
+                },
+            )
+    }
 
-                    let resp: Result<(), HttpError> = coord
-                        .write_points(tenant.to_string(), ConsistencyLevel::Any, req)
-                        .await
-                        .map_err(|e| e.into());
+    fn influx_v2_write(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        let write_body_limit = self.write_body_limit;
+        warp::path!("api" / "v2" / "write")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(self.write_body_limit))
+            .and(warp::body::bytes())
+            .and(header::optional::<String>(AUTHORIZATION.as_str()))
+            .and(header::optional::<String>(CONTENT_ENCODING.as_str()))
+            .and(warp::query::<InfluxV2WriteParam>())
+            .and(self.with_coord())
+            .and_then(
+                move |req: Bytes,
+                      authorization: Option<String>,
+                      encoding: Option<String>,
+                      param: InfluxV2WriteParam,
+                      coord: CoordinatorRef| async move {
+                    let user_info = influx_user_info(authorization.as_deref(), None, None)?;
+                    let tenant = param.org.as_deref().unwrap_or(DEFAULT_CATALOG);
 
-                    sample_point_write_duration(
+                    write_line_protocol_body(
+                        &coord,
+                        &user_info,
                         tenant,
-                        &param.db,
-                        resp.is_ok(),
+                        bucket_to_db(&param.bucket),
+                        param.precision.as_deref(),
+                        ConsistencyLevel::default(),
+                        req,
+                        encoding.as_deref(),
+                        write_body_limit,
+                        true,
+                    )
+                    .await
+                    .map(|_| ResponseBuilder::new(NO_CONTENT).build(vec![]))
+                    .map_err(reject::custom)
+                },
+            )
+    }
+
+    /// OpenTSDB has no authentication, but the request must have the basic auth of the user
+    /// writing the data points, like the other write apis.
+    fn opentsdb_put(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        let write_body_limit = self.write_body_limit;
+        warp::path!("api" / "put")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(self.write_body_limit))
+            .and(warp::body::bytes())
+            .and(self.handle_header())
+            .and(header::optional::<String>(CONTENT_ENCODING.as_str()))
+            .and(warp::query::<OpenTsdbPutParam>())
+            .and(self.with_coord())
+            .and_then(
+                move |req: Bytes,
+                      header: Header,
+                      encoding: Option<String>,
+                      param: OpenTsdbPutParam,
+                      coord: CoordinatorRef| async move {
+                    let user_info = match header.try_get_basic_auth() {
+                        Ok(u) => u,
+                        Err(e) => return Err(reject::custom(e)),
+                    };
+                    let tenant = param.tenant.as_deref().unwrap_or(DEFAULT_CATALOG);
+                    let db = param.db.as_deref().unwrap_or(DEFAULT_DATABASE);
+
+                    let body = decode_body(req, encoding.as_deref(), write_body_limit)?;
+                    let (data_points, errors) = parse_put_body(body.as_ref())
+                        .map_err(|reason| HttpError::InvalidParam { reason })?;
+
+                    // the valid data points are written and the invalid ones are reported
+                    if !data_points.is_empty() {
+                        write_data_points(&coord, &user_info, tenant, db, &data_points)
+                            .await
+                            .map_err(reject::custom)?;
+                    }
+                    if !errors.is_empty() {
+                        return Err(reject::custom(HttpError::PartialWrite {
+                            accepted: data_points.len(),
+                            errors,
+                        }));
+                    }
+                    Ok(ResponseBuilder::new(NO_CONTENT).build(vec![]))
+                },
+            )
+    }
+
+    fn influx_query(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        let get_param = warp::get().and(warp::query::<InfluxQueryParam>());
+        let post_param = warp::post()
+            .and(warp::query::<InfluxQueryParam>())
+            .and(warp::body::content_length_limit(self.query_body_limit))
+            .and(warp::body::form::<InfluxQueryParam>())
+            .map(merge_query_param);
+
+        warp::path!("query")
+            .and(get_param.or(post_param).unify())
+            .and(header::optional::<String>(AUTHORIZATION.as_str()))
+            .and(self.with_dbms())
+            .and_then(
+                |param: InfluxQueryParam, authorization: Option<String>, dbms: DBMSRef| async move {
+                    let start = Instant::now();
+                    debug!("Receive influx query request, param: {:?}", param);
+
+                    let user_info = influx_user_info(
+                        authorization.as_deref(),
+                        param.u.as_deref(),
+                        param.p.as_deref(),
+                    )?;
+                    let sql = param.q.ok_or_else(|| HttpError::InvalidParam {
+                        reason: "missing the query parameter q".to_string(),
+                    })?;
+                    let epoch = parse_precision(param.epoch.as_deref())?;
+
+                    let user = dbms.authenticate(&user_info, None).context(QuerySnafu)?;
+                    let context = ContextBuilder::new(user).with_database(param.db).build();
+                    let query = Query::new(context, sql);
+
+                    let result = influx_query_handle(&query, epoch, dbms).await;
+                    sample_query_read_duration(
+                        query.context().tenant(),
+                        query.context().database(),
+                        result.is_ok(),
                         start.elapsed().as_millis() as f64,
                     );
-                    resp.map(|_| ResponseBuilder::ok()).map_err(reject::custom)
+
+                    // the errors of the statements are returned in the results as InfluxDB does
+                    let resp = match result {
+                        Ok(series) => InfluxQueryResponse::series(series),
+                        Err(e) => InfluxQueryResponse::error(e.to_string()),
+                    };
+                    Ok::<_, Rejection>(ResponseBuilder::new(OK).json(&resp))
                 },
             )
     }
@@ -333,6 +545,62 @@ impl HttpService {
                 },
             )
     }
+
+    fn prom_remote_write(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("api" / "v1" / "prom" / "write")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(self.write_body_limit))
+            .and(warp::body::bytes())
+            .and(self.handle_header())
+            .and(warp::query::<SqlParam>())
+            .and(self.with_dbms())
+            .and(self.with_coord())
+            .and(self.with_prom_remote_server())
+            .and_then(
+                |req: Bytes,
+                 header: Header,
+                 param: SqlParam,
+                 dbms: DBMSRef,
+                 coord: CoordinatorRef,
+                 prs: PromRemoteServerRef| async move {
+                    let start = Instant::now();
+                    debug!(
+                        "Receive rest prom remote write request, header: {:?}, param: {:?}",
+                        header, param
+                    );
+
+                    // Parse req、header and param to construct write request
+                    let user_info = header.try_get_basic_auth().map_err(reject::custom)?;
+                    let tenant = param.tenant;
+                    let user = dbms
+                        .authenticate(&user_info, tenant.as_deref())
+                        .map_err(|e| reject::custom(HttpError::from(e)))?;
+                    let context = ContextBuilder::new(user)
+                        .with_tenant(tenant)
+                        .with_database(param.db)
+                        .build();
+
+                    let result = prs
+                        .remote_write(&context, coord, req)
+                        .await
+                        .map(|_| ResponseBuilder::ok())
+                        .map_err(|e| {
+                            trace::error!("Failed to handle prom remote write request, err: {}", e);
+                            reject::custom(HttpError::from(e))
+                        });
+
+                    sample_point_write_duration(
+                        context.tenant(),
+                        context.database(),
+                        result.is_ok(),
+                        start.elapsed().as_millis() as f64,
+                    );
+                    result
+                },
+            )
+    }
 }
 
 #[async_trait::async_trait]
@@ -376,6 +644,181 @@ impl Service for HttpService {
     }
 }
 
+async fn influx_query_handle(
+    query: &Query,
+    epoch: Option<i64>,
+    dbms: DBMSRef,
+) -> Result<Option<InfluxSeries>, HttpError> {
+    debug!("prepare to execute: {:?}", query.content());
+
+    let result = dbms.execute(query).await.context(QuerySnafu)?.result();
+    let schema = result.schema();
+    let batches = fetch_record_batches(result)
+        .await
+        .map_err(|e| HttpError::FetchResult {
+            reason: format!("{}", e),
+        })?;
+
+    let name = query_table_name(query.content()).unwrap_or_default();
+    batches_to_series(name, &schema, &batches, epoch).map_err(|e| HttpError::FetchResult {
+        reason: format!("{}", e),
+    })
+}
+
+/// Parses the body as line protocol and writes the points, the timestamps of the lines are
+/// in the precision of the request if it's given, see [`parse_precision`], otherwise in the
+/// precision of the database.
+///
+/// In the lenient mode the valid lines are written even if some lines are bad, like the
+/// partial write of InfluxDB, and the bad lines are returned as [`HttpError::PartialWrite`].
+async fn write_line_protocol_body(
+    coord: &CoordinatorRef,
+    user_info: &UserInfo,
+    tenant: &str,
+    db: &str,
+    precision: Option<&str>,
+    consistency: ConsistencyLevel,
+    body: Bytes,
+    encoding: Option<&str>,
+    body_limit: u64,
+    lenient: bool,
+) -> Result<(), HttpError> {
+    let start = Instant::now();
+
+    let body = decode_body(body, encoding, body_limit)?;
+    let db_precision = get_db_precision(coord, tenant, db)?;
+    let precision = parse_precision(precision)?.unwrap_or(db_precision);
+
+    let lines = String::from_utf8_lossy(body.as_ref());
+    let default_time = Local::now().timestamp_nanos() / precision;
+    let (mut line_protocol_lines, line_errors) = if lenient {
+        line_protocol_to_lines_lenient(&lines, default_time)
+    } else {
+        let lines = line_protocol_to_lines(&lines, default_time).context(ParseLineProtocolSnafu)?;
+        (lines, vec![])
+    };
+    let partial_write = |accepted: usize| HttpError::PartialWrite {
+        accepted,
+        errors: line_errors.clone(),
+    };
+    if line_protocol_lines.is_empty() && !line_errors.is_empty() {
+        return Err(partial_write(0));
+    }
+    normalize_timestamps(&mut line_protocol_lines, precision, db_precision)?;
+
+    let points = parse_lines_to_points(db, &mut line_protocol_lines)?;
+
+    let req = WritePointsRpcRequest {
+        version: 1,
+        meta: Some(Meta {
+            tenant: tenant.to_string(),
+            user: Some(user_info.user.to_string()),
+            password: Some(user_info.password.to_string()),
+        }),
+        points,
+        consistency_level: None,
+    };
+
+    let resp: Result<(), HttpError> = coord
+        .write_points(tenant.to_string(), consistency, req)
+        .await
+        .map_err(|e| e.into());
+
+    sample_point_write_duration(tenant, db, resp.is_ok(), start.elapsed().as_millis() as f64);
+    resp?;
+
+    if !line_errors.is_empty() {
+        return Err(partial_write(line_protocol_lines.len()));
+    }
+    Ok(())
+}
+
+/// Decompresses the body by its content encoding, the decompressed body must not exceed the limit.
+fn decode_body(body: Bytes, encoding: Option<&str>, limit: u64) -> Result<Bytes, HttpError> {
+    let mut decoded = vec![];
+    let res = match encoding.map(|e| e.trim().to_ascii_lowercase()).as_deref() {
+        None | Some("") | Some(IDENTITY) => return Ok(body),
+        Some(GZIP) => MultiGzDecoder::new(body.as_ref())
+            .take(limit + 1)
+            .read_to_end(&mut decoded),
+        Some(ZSTD) => zstd::stream::read::Decoder::new(body.as_ref())
+            .and_then(|decoder| decoder.take(limit + 1).read_to_end(&mut decoded)),
+        Some(other) => {
+            return Err(HttpError::InvalidHeader {
+                reason: format!("content encoding not support: {}", other),
+            })
+        }
+    };
+    res.map_err(|e| HttpError::DecodeBody {
+        reason: e.to_string(),
+    })?;
+
+    if decoded.len() as u64 > limit {
+        return Err(HttpError::PayloadTooLarge { limit });
+    }
+
+    Ok(decoded.into())
+}
+
+/// Returns the nanoseconds of a unit of the precision of the request, none if the precision
+/// is not given.
+fn parse_precision(precision: Option<&str>) -> Result<Option<i64>, HttpError> {
+    match precision.map(|p| p.to_ascii_lowercase()).as_deref() {
+        None => Ok(None),
+        Some("ns") => Ok(Some(1)),
+        Some("us") => Ok(Some(1_000)),
+        Some("ms") => Ok(Some(1_000_000)),
+        Some("s") => Ok(Some(1_000_000_000)),
+        Some(other) => Err(HttpError::InvalidParam {
+            reason: format!("precision must be one of ns, us, ms and s, found {}", other),
+        }),
+    }
+}
+
+/// Returns the nanoseconds of a unit of the precision of the database, the precision
+/// defaults to ns if the database does not exist.
+pub(crate) fn get_db_precision(
+    coord: &CoordinatorRef,
+    tenant: &str,
+    db: &str,
+) -> Result<i64, HttpError> {
+    let schema = match coord.tenant_meta(tenant) {
+        Some(client) => client.get_db_schema(db)?,
+        None => None,
+    };
+    let precision = schema
+        .map(|s| s.config.precision_or_default().clone())
+        .unwrap_or(DatabaseOptions::DEFAULT_PRECISION);
+
+    Ok(match precision {
+        Precision::NS => 1,
+        Precision::US => 1_000,
+        Precision::MS => 1_000_000,
+    })
+}
+
+/// Converts the timestamps of the lines from the precision of the request to the precision
+/// of the database, both given as the nanoseconds of a unit.
+fn normalize_timestamps(lines: &mut [Line], from: i64, to: i64) -> Result<(), HttpError> {
+    if from == to {
+        return Ok(());
+    }
+
+    for line in lines.iter_mut() {
+        line.timestamp = if from > to {
+            line.timestamp
+                .checked_mul(from / to)
+                .ok_or_else(|| HttpError::InvalidParam {
+                    reason: format!("timestamp {} is out of range", line.timestamp),
+                })?
+        } else {
+            line.timestamp / (to / from)
+        };
+    }
+
+    Ok(())
+}
+
 fn parse_lines_to_points<'a>(db: &'a str, lines: &'a mut [Line]) -> Result<Vec<u8>, Error> {
     let mut fbb = FlatBufferBuilder::new();
     let mut point_offsets = Vec::with_capacity(lines.len());
@@ -460,10 +903,16 @@ fn construct_query(
         .authenticate(&user_info, tenant.as_deref())
         .context(QuerySnafu)?;
 
+    let is_streaming = param.chunked.unwrap_or(false)
+        && ResultFormat::try_from(header.get_accept())
+            .map(|fmt| fmt.is_streamable())
+            .unwrap_or(false);
+
     let context = ContextBuilder::new(user)
         .with_tenant(tenant)
         .with_database(param.db)
         .with_target_partitions(param.target_partitions)
+        .with_streaming(is_streaming)
         .build();
 
     Ok(Query::new(
@@ -472,12 +921,25 @@ fn construct_query(
     ))
 }
 
-async fn sql_handle(query: &Query, header: Header, dbms: DBMSRef) -> Result<Response, HttpError> {
+async fn sql_handle(
+    query: &Query,
+    header: Header,
+    chunk_size: usize,
+    dbms: DBMSRef,
+) -> Result<Response, HttpError> {
     debug!("prepare to execute: {:?}", query.content());
 
     let fmt = ResultFormat::try_from(header.get_accept())?;
 
-    let result = dbms.execute(query).await.context(QuerySnafu)?;
+    let result = dbms.execute(query).await.context(QuerySnafu)?.result();
+
+    if let Output::Stream(stream) = &result {
+        if fmt.is_streamable() {
+            if let Some(stream) = stream.take() {
+                return Ok(fmt.wrap_stream_to_response(stream, chunk_size));
+            }
+        }
+    }
 
     let batches = fetch_record_batches(result)
         .await
@@ -511,7 +973,60 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, std::convert::In
 /**************** bottom *****************/
 #[cfg(test)]
 mod test {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use line_protocol::line_protocol_to_lines;
     use tokio::time;
+    use warp::hyper::body::Bytes;
+
+    use super::{decode_body, normalize_timestamps, parse_precision};
+    use crate::http::Error as HttpError;
+
+    #[test]
+    fn test_decode_body() {
+        let body = b"ma,ta=a1 fa=1 1".to_vec();
+
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&body).unwrap();
+        let gzipped = Bytes::from(encoder.finish().unwrap());
+        let decoded = decode_body(gzipped.clone(), Some("gzip"), 1024).unwrap();
+        assert_eq!(decoded.as_ref(), body.as_slice());
+        assert!(matches!(
+            decode_body(gzipped, Some("gzip"), 8),
+            Err(HttpError::PayloadTooLarge { limit: 8 })
+        ));
+
+        let zstded = Bytes::from(zstd::encode_all(body.as_slice(), 0).unwrap());
+        let decoded = decode_body(zstded, Some("zstd"), 1024).unwrap();
+        assert_eq!(decoded.as_ref(), body.as_slice());
+
+        let plain = Bytes::from(body.clone());
+        assert_eq!(decode_body(plain.clone(), None, 1024).unwrap(), plain);
+        assert!(decode_body(plain, Some("br"), 1024).is_err());
+    }
+
+    #[test]
+    fn test_normalize_timestamps() {
+        assert_eq!(parse_precision(None).unwrap(), None);
+        assert_eq!(parse_precision(Some("ns")).unwrap(), Some(1));
+        assert_eq!(parse_precision(Some("s")).unwrap(), Some(1_000_000_000));
+        assert!(parse_precision(Some("h")).is_err());
+
+        let mut lines =
+            line_protocol_to_lines("ma fa=1 1672531200\nma fa=2 1672531201", 0).unwrap();
+        // seconds to milliseconds
+        normalize_timestamps(&mut lines, 1_000_000_000, 1_000_000).unwrap();
+        assert_eq!(lines[0].timestamp, 1672531200000);
+        assert_eq!(lines[1].timestamp, 1672531201000);
+        // milliseconds to seconds
+        normalize_timestamps(&mut lines, 1_000_000, 1_000_000_000).unwrap();
+        assert_eq!(lines[0].timestamp, 1672531200);
+
+        let mut lines = line_protocol_to_lines("ma fa=1 9223372036854775807", 0).unwrap();
+        assert!(normalize_timestamps(&mut lines, 1_000, 1).is_err());
+    }
 
     #[tokio::test]
     async fn test1() {
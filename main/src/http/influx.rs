@@ -0,0 +1,305 @@
+//! Compatibility with the http api of InfluxDB, so that its clients can write to and query CnosDB.
+
+use std::sync::Arc;
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use datafusion::arrow::array::{as_primitive_array, ArrayRef, Int64Array, StringArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Field, Int64Type, Schema, TimeUnit};
+use datafusion::arrow::error::Result as ArrowResult;
+use datafusion::arrow::json::writer::record_batches_to_json_rows;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::sql::sqlparser::ast::{SetExpr, Statement, TableFactor};
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::parser::Parser;
+use http_protocol::parameter::InfluxQueryParam;
+use models::auth::user::UserInfo;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::header::Header;
+use super::Error as HttpError;
+
+pub const INFLUXDB_VERSION: &str = "1.8.10";
+pub const INFLUXDB_BUILD: &str = "OSS";
+pub const X_INFLUXDB_VERSION: &str = "x-influxdb-version";
+pub const X_INFLUXDB_BUILD: &str = "x-influxdb-build";
+
+const TOKEN_PREFIX: &str = "Token ";
+
+/// Returns the user of a request, given by the `u` and `p` parameters, the basic auth or
+/// the `Token <user>:<password>` auth.
+pub fn influx_user_info(
+    authorization: Option<&str>,
+    u: Option<&str>,
+    p: Option<&str>,
+) -> Result<UserInfo, HttpError> {
+    if let Some(user) = u {
+        return Ok(UserInfo {
+            user: user.to_string(),
+            password: p.unwrap_or_default().to_string(),
+            private_key: None,
+        });
+    }
+
+    match authorization {
+        Some(auth) if auth.starts_with(TOKEN_PREFIX) => {
+            let token = &auth[TOKEN_PREFIX.len()..];
+            let (user, password) = token.split_once(':').unwrap_or((token, ""));
+            Ok(UserInfo {
+                user: user.to_string(),
+                password: password.to_string(),
+                private_key: None,
+            })
+        }
+        Some(auth) => Header::with(None, auth.to_string()).try_get_basic_auth(),
+        None => Err(HttpError::ParseAuth {
+            reason: "missing authorization".to_string(),
+        }),
+    }
+}
+
+/// A bucket of the 1.x compatible `db/rp` form is mapped to the database `db`.
+pub fn bucket_to_db(bucket: &str) -> &str {
+    bucket.split('/').next().unwrap_or(bucket)
+}
+
+/// The parameters of the query string take precedence over the ones of the form body.
+pub fn merge_query_param(query: InfluxQueryParam, form: InfluxQueryParam) -> InfluxQueryParam {
+    InfluxQueryParam {
+        db: query.db.or(form.db),
+        q: query.q.or(form.q),
+        epoch: query.epoch.or(form.epoch),
+        u: query.u.or(form.u),
+        p: query.p.or(form.p),
+    }
+}
+
+/// Returns the table queried by the sql, InfluxDB names the series after the measurement.
+pub fn query_table_name(sql: &str) -> Option<String> {
+    let statements = Parser::parse_sql(&GenericDialect {}, sql).ok()?;
+    if let Statement::Query(query) = statements.first()? {
+        if let SetExpr::Select(select) = query.body.as_ref() {
+            if let TableFactor::Table { name, .. } = &select.from.first()?.relation {
+                return name.0.last().map(|ident| ident.value.clone());
+            }
+        }
+    }
+
+    None
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct InfluxQueryResponse {
+    pub results: Vec<InfluxStatementResult>,
+}
+
+impl InfluxQueryResponse {
+    pub fn series(series: Option<InfluxSeries>) -> Self {
+        Self {
+            results: vec![InfluxStatementResult {
+                statement_id: 0,
+                series: series.into_iter().collect(),
+                error: None,
+            }],
+        }
+    }
+
+    pub fn error(error: String) -> Self {
+        Self {
+            results: vec![InfluxStatementResult {
+                statement_id: 0,
+                series: vec![],
+                error: Some(error),
+            }],
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InfluxStatementResult {
+    pub statement_id: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub series: Vec<InfluxSeries>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct InfluxSeries {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    pub columns: Vec<String>,
+    pub values: Vec<Vec<Value>>,
+}
+
+/// Converts the result of a query to a series, none if there is no row.
+///
+/// The timestamps are RFC3339 strings, or the integers in the `epoch` precision given as
+/// the nanoseconds of a unit.
+pub fn batches_to_series(
+    name: String,
+    schema: &Schema,
+    batches: &[RecordBatch],
+    epoch: Option<i64>,
+) -> ArrowResult<Option<InfluxSeries>> {
+    if batches.iter().all(|b| b.num_rows() == 0) {
+        return Ok(None);
+    }
+
+    let columns: Vec<String> = schema.fields().iter().map(|f| f.name().clone()).collect();
+    let mut values = vec![];
+    for batch in batches {
+        let batch = convert_timestamps(batch, epoch)?;
+        for row in record_batches_to_json_rows(std::slice::from_ref(&batch))? {
+            // the null values are left out of the rows
+            values.push(
+                columns
+                    .iter()
+                    .map(|c| row.get(c).cloned().unwrap_or(Value::Null))
+                    .collect(),
+            );
+        }
+    }
+
+    Ok(Some(InfluxSeries {
+        name,
+        columns,
+        values,
+    }))
+}
+
+fn convert_timestamps(batch: &RecordBatch, epoch: Option<i64>) -> ArrowResult<RecordBatch> {
+    let schema = batch.schema();
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(schema.fields().len());
+    for (field, array) in schema.fields().iter().zip(batch.columns()) {
+        let unit_nanos = match field.data_type() {
+            DataType::Timestamp(TimeUnit::Second, _) => 1_000_000_000,
+            DataType::Timestamp(TimeUnit::Millisecond, _) => 1_000_000,
+            DataType::Timestamp(TimeUnit::Microsecond, _) => 1_000,
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => 1,
+            _ => {
+                fields.push(field.clone());
+                arrays.push(array.clone());
+                continue;
+            }
+        };
+
+        let ints = cast(array, &DataType::Int64)?;
+        let nanos = as_primitive_array::<Int64Type>(ints.as_ref())
+            .iter()
+            .map(|v| v.map(|v| v.saturating_mul(unit_nanos)));
+        match epoch {
+            Some(epoch) => {
+                fields.push(Field::new(field.name(), DataType::Int64, true));
+                arrays.push(Arc::new(
+                    nanos.map(|v| v.map(|v| v / epoch)).collect::<Int64Array>(),
+                ));
+            }
+            None => {
+                fields.push(Field::new(field.name(), DataType::Utf8, true));
+                arrays.push(Arc::new(
+                    nanos
+                        .map(|v| {
+                            v.map(|v| {
+                                Utc.timestamp_nanos(v)
+                                    .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                            })
+                        })
+                        .collect::<StringArray>(),
+                ));
+            }
+        }
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::array::{Float64Array, TimestampNanosecondArray};
+    use datafusion::from_slice::FromSlice;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_influx_user_info() {
+        let user = influx_user_info(Some("Token root:123"), None, None).unwrap();
+        assert_eq!(
+            (user.user.as_str(), user.password.as_str()),
+            ("root", "123")
+        );
+
+        let user = influx_user_info(Some("Token root:123"), Some("u1"), Some("p1")).unwrap();
+        assert_eq!((user.user.as_str(), user.password.as_str()), ("u1", "p1"));
+
+        // root:123
+        let user = influx_user_info(Some("Basic cm9vdDoxMjM="), None, None).unwrap();
+        assert_eq!(
+            (user.user.as_str(), user.password.as_str()),
+            ("root", "123")
+        );
+
+        assert!(influx_user_info(None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_query_table_name() {
+        assert_eq!(
+            query_table_name("select * from cpu where time > 0"),
+            Some("cpu".to_string())
+        );
+        assert_eq!(
+            query_table_name("SELECT count(*) FROM public.\"Cpu\""),
+            Some("Cpu".to_string())
+        );
+        assert_eq!(query_table_name("select 1"), None);
+        assert_eq!(bucket_to_db("db/autogen"), "db");
+    }
+
+    #[test]
+    fn test_batches_to_series() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("usage", DataType::Float64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(TimestampNanosecondArray::from_slice([
+                    1672531200000000000,
+                    1672531201500000000,
+                ])),
+                Arc::new(Float64Array::from(vec![Some(0.5), None])),
+            ],
+        )
+        .unwrap();
+
+        let series = batches_to_series("cpu".to_string(), &schema, &[batch.clone()], None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(series.columns, vec!["time", "usage"]);
+        assert_eq!(
+            series.values,
+            vec![
+                vec![json!("2023-01-01T00:00:00Z"), json!(0.5)],
+                vec![json!("2023-01-01T00:00:01.500Z"), Value::Null],
+            ]
+        );
+
+        let series = batches_to_series("cpu".to_string(), &schema, &[batch], Some(1_000_000))
+            .unwrap()
+            .unwrap();
+        assert_eq!(series.values[1][0], json!(1672531201500_i64));
+
+        assert!(batches_to_series("cpu".to_string(), &schema, &[], None)
+            .unwrap()
+            .is_none());
+    }
+}
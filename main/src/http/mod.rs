@@ -9,8 +9,8 @@ use coordinator::errors::CoordinatorError;
 use warp::reject;
 use warp::reply::Response;
 
-use http_protocol::response::ErrorResponse;
-use http_protocol::status_code::UNPROCESSABLE_ENTITY;
+use http_protocol::response::{ErrorResponse, LineErrorResponse, PartialWriteResponse};
+use http_protocol::status_code::{PAYLOAD_TOO_LARGE, UNPROCESSABLE_ENTITY};
 use meta::error::MetaError;
 use spi::QueryError;
 use tskv::TsKv;
@@ -19,6 +19,7 @@ use self::response::ResponseBuilder;
 
 pub mod header;
 pub mod http_service;
+mod influx;
 mod response;
 mod result_format;
 
@@ -71,6 +72,35 @@ pub enum Error {
     NotFoundTenant {
         name: String,
     },
+
+    #[snafu(display("Invalid parameter: {}", reason))]
+    #[error_code(code = 9)]
+    InvalidParam {
+        reason: String,
+    },
+
+    #[snafu(display("Failed to decode the body: {}", reason))]
+    #[error_code(code = 10)]
+    DecodeBody {
+        reason: String,
+    },
+
+    #[snafu(display("The decoded body is larger than {} bytes", limit))]
+    #[error_code(code = 11)]
+    PayloadTooLarge {
+        limit: u64,
+    },
+
+    #[snafu(display(
+        "Partial write: {} lines accepted, {} lines rejected",
+        accepted,
+        errors.len()
+    ))]
+    #[error_code(code = 12)]
+    PartialWrite {
+        accepted: usize,
+        errors: Vec<line_protocol::LineError>,
+    },
 }
 
 impl From<tskv::Error> for Error {
@@ -124,8 +154,23 @@ impl From<&Error> for Response {
             | Error::Coordinator { .. } => {
                 ResponseBuilder::new(UNPROCESSABLE_ENTITY).json(&error_resp)
             }
-            Error::InvalidHeader { .. } | Error::ParseAuth { .. } => {
-                ResponseBuilder::bad_request(&error_resp)
+            Error::InvalidHeader { .. }
+            | Error::ParseAuth { .. }
+            | Error::InvalidParam { .. }
+            | Error::DecodeBody { .. } => ResponseBuilder::bad_request(&error_resp),
+            Error::PayloadTooLarge { .. } => {
+                ResponseBuilder::new(PAYLOAD_TOO_LARGE).json(&error_resp)
+            }
+            Error::PartialWrite { accepted, errors } => {
+                let errors = errors
+                    .iter()
+                    .map(|e| LineErrorResponse {
+                        line: e.line,
+                        measurement: e.measurement.clone(),
+                        reason: e.reason.clone(),
+                    })
+                    .collect();
+                ResponseBuilder::bad_request(&PartialWriteResponse::new(e, *accepted, errors))
             }
             _ => ResponseBuilder::internal_server_error(),
         }
@@ -218,4 +263,23 @@ mod tests {
 
         assert_eq!(content_type, HeaderValue::from_static(APPLICATION_JSON));
     }
+
+    #[test]
+    fn test_partial_write_error() {
+        let resp: Response = Error::PartialWrite {
+            accepted: 1,
+            errors: vec![line_protocol::LineError {
+                line: 2,
+                measurement: "ma".to_string(),
+                reason: "test".to_string(),
+            }],
+        }
+        .into();
+
+        assert_eq!(resp.status(), BAD_REQUEST);
+
+        let content_type = resp.headers().get(CONTENT_TYPE).unwrap();
+
+        assert_eq!(content_type, HeaderValue::from_static(APPLICATION_JSON));
+    }
 }
@@ -2,6 +2,7 @@ use serde::Serialize;
 use warp::http::header::HeaderMap;
 use warp::http::HeaderValue;
 use warp::http::StatusCode;
+use warp::hyper::Body;
 use warp::reply::Response;
 use warp::Reply;
 
@@ -37,7 +38,12 @@ impl ResponseBuilder {
     }
 
     pub fn build(self, body: Vec<u8>) -> Response {
-        let mut res = Response::new(body.into());
+        self.build_body(body.into())
+    }
+
+    /// A body without length, e.g. a streaming body, is sent with chunked transfer encoding.
+    pub fn build_body(self, body: Body) -> Response {
+        let mut res = Response::new(body);
 
         *res.headers_mut() = self.headers;
 
@@ -1,12 +1,14 @@
 use super::Error as HttpError;
 use datafusion::arrow::csv::writer::WriterBuilder;
-use datafusion::arrow::error::Result as ArrowResult;
+use datafusion::arrow::error::{ArrowError, Result as ArrowResult};
 use datafusion::arrow::json::{ArrayWriter, LineDelimitedWriter};
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::arrow::util::pretty::pretty_format_batches;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use futures::{StreamExt, TryStreamExt};
 use spi::query::execution::Output;
-use spi::service::protocol::QueryHandle;
 use std::str::FromStr;
+use warp::hyper::{Body, Bytes};
 use warp::reply::Response;
 
 use crate::http::response::ResponseBuilder;
@@ -30,10 +32,18 @@ macro_rules! batches_to_json {
 }
 
 fn batches_with_sep(batches: &[RecordBatch], delimiter: u8) -> ArrowResult<Vec<u8>> {
+    batches_with_sep_and_headers(batches, delimiter, true)
+}
+
+fn batches_with_sep_and_headers(
+    batches: &[RecordBatch],
+    delimiter: u8,
+    has_headers: bool,
+) -> ArrowResult<Vec<u8>> {
     let mut bytes = vec![];
     {
         let builder = WriterBuilder::new()
-            .has_headers(true)
+            .has_headers(has_headers)
             .with_delimiter(delimiter);
         let mut writer = builder.build(&mut bytes);
         for batch in batches {
@@ -79,6 +89,45 @@ impl ResultFormat {
         }
     }
 
+    /// Whether the result can be sent in chunks, each chunk can be parsed by itself.
+    pub fn is_streamable(&self) -> bool {
+        matches!(self, Self::Csv | Self::Tsv | Self::NdJson)
+    }
+
+    /// Formats a chunk of the result, only the first chunk of csv and tsv has the headers.
+    fn format_chunk(&self, batch: &RecordBatch, is_first: bool) -> ArrowResult<Vec<u8>> {
+        let batches = std::slice::from_ref(batch);
+        match self {
+            Self::Csv => batches_with_sep_and_headers(batches, b',', is_first),
+            Self::Tsv => batches_with_sep_and_headers(batches, b'\t', is_first),
+            _ => self.format_batches(batches),
+        }
+    }
+
+    /// Sends the record batches in chunks of at most `chunk_size` rows as they come out of the
+    /// stream, the response is aborted if the query fails.
+    pub fn wrap_stream_to_response(
+        &self,
+        stream: SendableRecordBatchStream,
+        chunk_size: usize,
+    ) -> Response {
+        let fmt = self.clone();
+        let mut is_first = true;
+        let chunks = stream
+            .map_ok(move |batch| futures::stream::iter(split_batch(batch, chunk_size)))
+            .try_flatten()
+            .map(move |batch| {
+                let bytes = fmt.format_chunk(&batch?, is_first)?;
+                is_first = false;
+                Ok::<_, ArrowError>(Bytes::from(bytes))
+            })
+            .inspect_err(|e| trace::error!("Failed to send the result in chunks, err: {}", e));
+
+        ResponseBuilder::new(OK)
+            .insert_header((CONTENT_TYPE, self.get_http_content_type()))
+            .build_body(Body::wrap_stream(chunks))
+    }
+
     pub fn wrap_batches_to_response(&self, batches: &[RecordBatch]) -> Result<Response, HttpError> {
         let result = self
             .format_batches(batches)
@@ -121,17 +170,28 @@ impl FromStr for ResultFormat {
     }
 }
 
-pub async fn fetch_record_batches(res: QueryHandle) -> ArrowResult<Vec<RecordBatch>> {
-    let query = res.query().clone();
-    trace::trace!("try collect result for: {}", query.content());
+/// Splits the batch into the slices of at most `chunk_size` rows.
+fn split_batch(batch: RecordBatch, chunk_size: usize) -> Vec<ArrowResult<RecordBatch>> {
+    (0..batch.num_rows())
+        .step_by(chunk_size)
+        .map(|offset| Ok(batch.slice(offset, chunk_size.min(batch.num_rows() - offset))))
+        .collect()
+}
 
-    let actual = match res.result() {
-        Output::StreamData(_, stream) => stream,
+pub async fn fetch_record_batches(res: Output) -> ArrowResult<Vec<RecordBatch>> {
+    let actual = match res {
+        Output::StreamData(_, batches) => batches,
+        Output::Stream(stream) => match stream.take() {
+            Some(stream) => stream.try_collect::<Vec<_>>().await?,
+            None => {
+                return Err(ArrowError::InvalidArgumentError(
+                    "the result stream has been taken".to_string(),
+                ))
+            }
+        },
         Output::Nil(_) => vec![],
     };
 
-    trace::trace!("successfully collected result of {}", query.content());
-
     Ok(actual)
 }
 
@@ -210,4 +270,42 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_format_chunks() -> ArrowResult<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from_slice([1, 2, 3])),
+                Arc::new(Int32Array::from_slice([4, 5, 6])),
+            ],
+        )
+        .unwrap();
+
+        let chunks = split_batch(batch, 2)
+            .into_iter()
+            .collect::<ArrowResult<Vec<_>>>()?;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].num_rows(), 2);
+        assert_eq!(chunks[1].num_rows(), 1);
+
+        let fmt = ResultFormat::Csv;
+        assert_eq!(
+            "a,b\n1,4\n2,5\n".as_bytes(),
+            fmt.format_chunk(&chunks[0], true)?
+        );
+        assert_eq!("3,6\n".as_bytes(), fmt.format_chunk(&chunks[1], false)?);
+
+        let fmt = ResultFormat::NdJson;
+        assert_eq!(
+            "{\"a\":3,\"b\":6}\n".as_bytes(),
+            fmt.format_chunk(&chunks[1], false)?
+        );
+        Ok(())
+    }
 }
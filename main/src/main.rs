@@ -2,23 +2,25 @@
 
 use clap::{Parser, Subcommand};
 use coordinator::hh_queue::HintedOffManager;
-use coordinator::service::CoordService;
+use coordinator::service::{CoordService, Coordinator};
 use coordinator::writer::PointWriter;
 use meta::meta_client::{MetaClientRef, MetaRef, RemoteMetaManager};
 use models::meta_data::NodeInfo;
 use once_cell::sync::Lazy;
+use query::fragment_executor::FragmentExecutorImpl;
 use query::instance::make_cnosdbms;
+use query::stream_executor::StreamExecutorImpl;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::runtime::Runtime;
 use trace::{info, init_global_tracing};
 use tskv::TsKv;
 mod flight_sql;
 mod http;
+mod opentsdb;
 mod report;
 mod rpc;
 pub mod server;
 mod signal;
-mod tcp;
 
 static VERSION: Lazy<String> = Lazy::new(|| {
     format!(
@@ -104,11 +106,11 @@ enum SubCommand {
 
 use crate::flight_sql::FlightSqlServiceAdapter;
 use crate::http::http_service::HttpService;
+use crate::opentsdb::tcp_service::OpenTsdbService;
 use crate::report::ReportService;
-use crate::rpc::grpc_service::GrpcService;
-use crate::tcp::tcp_service::TcpService;
+use crate::rpc::grpc_service::{CoordGrpcService, GrpcService};
 use mem_allocator::Jemalloc;
-use metrics::init_tskv_metrics_recorder;
+use metrics::{init_coordinator_metrics_recorder, init_tskv_metrics_recorder};
 
 #[global_allocator]
 static A: Jemalloc = Jemalloc;
@@ -170,7 +172,13 @@ fn main() -> Result<(), std::io::Error> {
         .parse::<SocketAddr>()
         .expect("Invalid http_host");
 
+    let opentsdb_host = match global_config.cluster.opentsdb_server.as_str() {
+        "" => None,
+        addr => Some(addr.parse::<SocketAddr>().expect("Invalid opentsdb_host")),
+    };
+
     init_tskv_metrics_recorder();
+    init_coordinator_metrics_recorder();
 
     runtime.clone().block_on(async move {
         match &cli.subcmd {
@@ -178,6 +186,7 @@ fn main() -> Result<(), std::io::Error> {
                 todo!()
             }
             SubCommand::Run {} => {
+                let tls_config = global_config.security.tls_config;
                 let tskv_options = tskv::Options::from(&global_config);
                 let query_options = tskv::Options::from(&global_config);
                 let kv_inst = Arc::new(
@@ -189,33 +198,59 @@ fn main() -> Result<(), std::io::Error> {
                     kv_inst.clone(),
                     global_config.cluster.clone(),
                     global_config.hintedoff.clone(),
+                    Arc::new(FragmentExecutorImpl::new().expect("make fragment executor")),
+                    tls_config.clone(),
                 )
-                .await;
+                .await
+                .expect("make coordinator service");
 
                 let dbms = Arc::new(
                     make_cnosdbms(kv_inst.clone(), coord_service.clone(), query_options)
                         .expect("make dbms"),
                 );
 
-                let tcp_service = Box::new(TcpService::new(
-                    dbms.clone(),
+                CoordService::start_stream_service(
+                    coord_service.clone(),
+                    Arc::new(StreamExecutorImpl::new(
+                        dbms.clone(),
+                        coord_service.meta_manager(),
+                    )),
+                );
+                CoordService::start_repair_service(
+                    coord_service.clone(),
+                    global_config.repair.clone(),
+                );
+                CoordService::start_rebalance_service(
+                    coord_service.clone(),
+                    global_config.rebalance.clone(),
+                );
+
+                let coord_grpc_service = Box::new(CoordGrpcService::new(
                     coord_service.clone(),
                     tcp_host,
+                    tls_config.clone(),
                 ));
 
-                let tls_config = global_config.security.tls_config;
                 let http_service = Box::new(HttpService::new(
                     dbms.clone(),
                     kv_inst.clone(),
-                    coord_service,
+                    coord_service.clone(),
                     http_host,
                     tls_config.clone(),
                     global_config.query.query_sql_limit,
                     global_config.query.write_sql_limit,
+                    global_config.query.chunk_size,
                 ));
+                let opentsdb_service = opentsdb_host.map(|addr| {
+                    Box::new(OpenTsdbService::new(
+                        coord_service.clone(),
+                        addr,
+                        global_config.opentsdb.clone(),
+                    ))
+                });
                 let grpc_service = Box::new(GrpcService::new(
                     dbms.clone(),
-                    kv_inst.clone(),
+                    coord_service,
                     grpc_host,
                     tls_config.clone(),
                 ));
@@ -230,9 +265,13 @@ fn main() -> Result<(), std::io::Error> {
                 let mut server_builder = server::Builder::default()
                     .add_service(http_service)
                     .add_service(grpc_service)
-                    .add_service(tcp_service)
+                    .add_service(coord_grpc_service)
                     .add_service(flight_sql_service);
 
+                if let Some(opentsdb_service) = opentsdb_service {
+                    server_builder = server_builder.add_service(opentsdb_service);
+                }
+
                 if !global_config.reporting_disabled.unwrap_or(false) {
                     server_builder = server_builder.add_service(report_service);
                 }
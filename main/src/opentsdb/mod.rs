@@ -0,0 +1,324 @@
+//! Ingestion of the OpenTSDB data points, by the telnet `put` command and by the http
+//! `/api/put` api.
+//!
+//! The metric is used as the table name, the tags are used as tags, and the value is
+//! written to the float field `value`.
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use coordinator::service::CoordinatorRef;
+use flatbuffers::FlatBufferBuilder;
+use line_protocol::LineError;
+use metrics::sample_point_write_duration;
+use models::auth::user::UserInfo;
+use models::consistency_level::ConsistencyLevel;
+use protos::kv_service::{Meta, WritePointsRpcRequest};
+use protos::models::{FieldBuilder, FieldType, Point, PointArgs, Points, PointsArgs, TagBuilder};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+use crate::http::http_service::get_db_precision;
+use crate::http::Error as HttpError;
+
+pub mod tcp_service;
+
+const VALUE_FIELD_NAME: &str = "value";
+/// The timestamps with more digits are in milliseconds.
+const MAX_TIMESTAMP_SECONDS: i64 = 9_999_999_999;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DataPoint {
+    pub metric: String,
+    /// Seconds or milliseconds since the epoch.
+    pub timestamp: i64,
+    #[serde(deserialize_with = "deserialize_value")]
+    pub value: f64,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl DataPoint {
+    /// Returns the nanoseconds of the timestamp, none if it does not fit.
+    pub fn timestamp_nanos(&self) -> Option<i64> {
+        if self.timestamp > MAX_TIMESTAMP_SECONDS {
+            self.timestamp.checked_mul(1_000_000)
+        } else {
+            self.timestamp.checked_mul(1_000_000_000)
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.metric.is_empty() {
+            return Err("empty metric".to_string());
+        }
+        if self.tags.is_empty() {
+            return Err(format!("metric {} needs at least one tag", self.metric));
+        }
+        if self.timestamp <= 0 || self.timestamp_nanos().is_none() {
+            return Err(format!("invalid timestamp {}", self.timestamp));
+        }
+        if !self.value.is_finite() {
+            return Err(format!("invalid value {}", self.value));
+        }
+
+        Ok(())
+    }
+}
+
+/// OpenTSDB accepts the value as a number or as a string.
+fn deserialize_value<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Number(n) => n
+            .as_f64()
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid value {}", n))),
+        Value::String(s) => s
+            .parse::<f64>()
+            .map_err(|_| serde::de::Error::custom(format!("invalid value {}", s))),
+        other => Err(serde::de::Error::custom(format!("invalid value {}", other))),
+    }
+}
+
+/// Parses the arguments of the telnet command `put <metric> <timestamp> <value> <tagk=tagv ...>`.
+pub fn parse_put<'a>(mut args: impl Iterator<Item = &'a str>) -> Result<DataPoint, String> {
+    let metric = args.next().ok_or("missing metric")?;
+    let timestamp = args.next().ok_or("missing timestamp")?;
+    let value = args.next().ok_or("missing value")?;
+
+    let mut tags = BTreeMap::new();
+    for tag in args {
+        match tag.split_once('=') {
+            Some((k, v)) if !k.is_empty() && !v.is_empty() => {
+                tags.insert(k.to_string(), v.to_string());
+            }
+            _ => return Err(format!("invalid tag {}", tag)),
+        }
+    }
+
+    let point = DataPoint {
+        metric: metric.to_string(),
+        timestamp: timestamp
+            .parse::<i64>()
+            .map_err(|_| format!("invalid timestamp {}", timestamp))?,
+        value: value
+            .parse::<f64>()
+            .map_err(|_| format!("invalid value {}", value))?,
+        tags,
+    };
+    point.check()?;
+
+    Ok(point)
+}
+
+/// Parses the body of `/api/put`, a data point or an array of data points.
+///
+/// Like the lenient write of the line protocol, the invalid data points are returned as
+/// errors and the line of an error is the position of the data point starting from 1.
+pub fn parse_put_body(body: &[u8]) -> Result<(Vec<DataPoint>, Vec<LineError>), String> {
+    let values = match serde_json::from_slice::<Value>(body).map_err(|e| e.to_string())? {
+        Value::Array(values) => values,
+        value => vec![value],
+    };
+
+    let mut points = Vec::with_capacity(values.len());
+    let mut errors = vec![];
+    for (idx, value) in values.into_iter().enumerate() {
+        let metric = value
+            .get("metric")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let point = serde_json::from_value::<DataPoint>(value)
+            .map_err(|e| e.to_string())
+            .and_then(|point| point.check().map(|_| point));
+        match point {
+            Ok(point) => points.push(point),
+            Err(reason) => errors.push(LineError {
+                line: idx + 1,
+                measurement: metric,
+                reason,
+            }),
+        }
+    }
+
+    Ok((points, errors))
+}
+
+/// Converts the data points to flatbuffers points, the timestamps are in the precision of
+/// the database given as the nanoseconds of a unit.
+pub fn data_points_to_points(db: &str, data_points: &[DataPoint], precision: i64) -> Vec<u8> {
+    let mut fbb = FlatBufferBuilder::new();
+    let mut point_offsets = Vec::with_capacity(data_points.len());
+    for data_point in data_points.iter() {
+        let mut tags = Vec::with_capacity(data_point.tags.len());
+        for (k, v) in data_point.tags.iter() {
+            let fbk = fbb.create_vector(k.as_bytes());
+            let fbv = fbb.create_vector(v.as_bytes());
+            let mut tag_builder = TagBuilder::new(&mut fbb);
+            tag_builder.add_key(fbk);
+            tag_builder.add_value(fbv);
+            tags.push(tag_builder.finish());
+        }
+
+        let fbk = fbb.create_vector(VALUE_FIELD_NAME.as_bytes());
+        let fbv = fbb.create_vector(&data_point.value.to_be_bytes());
+        let mut field_builder = FieldBuilder::new(&mut fbb);
+        field_builder.add_name(fbk);
+        field_builder.add_type_(FieldType::Float);
+        field_builder.add_value(fbv);
+        let fields = vec![field_builder.finish()];
+
+        let point_args = PointArgs {
+            db: Some(fbb.create_vector(db.as_bytes())),
+            tab: Some(fbb.create_vector(data_point.metric.as_bytes())),
+            tags: Some(fbb.create_vector(&tags)),
+            fields: Some(fbb.create_vector(&fields)),
+            timestamp: data_point.timestamp_nanos().unwrap_or_default() / precision,
+        };
+
+        point_offsets.push(Point::create(&mut fbb, &point_args));
+    }
+
+    let fbb_db = fbb.create_vector(db.as_bytes());
+    let points_raw = fbb.create_vector(&point_offsets);
+    let points = Points::create(
+        &mut fbb,
+        &PointsArgs {
+            db: Some(fbb_db),
+            points: Some(points_raw),
+        },
+    );
+    fbb.finish(points, None);
+    fbb.finished_data().to_vec()
+}
+
+pub async fn write_data_points(
+    coord: &CoordinatorRef,
+    user_info: &UserInfo,
+    tenant: &str,
+    db: &str,
+    data_points: &[DataPoint],
+) -> Result<(), HttpError> {
+    let start = Instant::now();
+
+    let precision = get_db_precision(coord, tenant, db)?;
+    let req = WritePointsRpcRequest {
+        version: 1,
+        meta: Some(Meta {
+            tenant: tenant.to_string(),
+            user: Some(user_info.user.to_string()),
+            password: Some(user_info.password.to_string()),
+        }),
+        points: data_points_to_points(db, data_points, precision),
+        consistency_level: None,
+    };
+
+    let resp: Result<(), HttpError> = coord
+        .write_points(tenant.to_string(), ConsistencyLevel::default(), req)
+        .await
+        .map_err(|e| e.into());
+
+    sample_point_write_duration(tenant, db, resp.is_ok(), start.elapsed().as_millis() as f64);
+    resp
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_put() {
+        let point =
+            parse_put("sys.cpu.user 1356998400 42.5 host=web01 cpu=0".split_whitespace()).unwrap();
+        assert_eq!(point.metric, "sys.cpu.user");
+        assert_eq!(point.timestamp_nanos(), Some(1356998400000000000));
+        assert_eq!(point.value, 42.5);
+        assert_eq!(
+            point.tags.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("cpu".to_string(), "0".to_string()),
+                ("host".to_string(), "web01".to_string())
+            ]
+        );
+
+        let point =
+            parse_put("sys.cpu.user 1356998400500 1 host=web01".split_whitespace()).unwrap();
+        assert_eq!(point.timestamp_nanos(), Some(1356998400500000000));
+
+        assert!(parse_put("sys.cpu.user 1356998400 1".split_whitespace()).is_err());
+        assert!(parse_put("sys.cpu.user 1356998400 abc host=web01".split_whitespace()).is_err());
+        assert!(parse_put("sys.cpu.user 1356998400 1 host".split_whitespace()).is_err());
+        assert!(parse_put("sys.cpu.user".split_whitespace()).is_err());
+    }
+
+    #[test]
+    fn test_parse_put_body() {
+        let body = br#"{"metric":"sys.cpu.nice","timestamp":1346846400,"value":18,"tags":{"host":"web01"}}"#;
+        let (points, errors) = parse_put_body(body).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].value, 18.0);
+        assert!(errors.is_empty());
+
+        let body = br#"[
+            {"metric":"sys.cpu.nice","timestamp":1346846400,"value":"1.5","tags":{"host":"web01"}},
+            {"metric":"sys.cpu.nice","timestamp":1346846400000,"value":9,"tags":{"host":"web02"}}
+        ]"#;
+        let (points, errors) = parse_put_body(body).unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].value, 1.5);
+        assert_eq!(points[1].timestamp_nanos(), Some(1346846400000000000));
+        assert!(errors.is_empty());
+
+        // the valid data points are kept
+        let body = br#"[
+            {"metric":"sys.cpu.nice","timestamp":1346846400,"value":18},
+            {"metric":"sys.cpu.user","timestamp":1346846400,"value":1,"tags":{"host":"web01"}},
+            {"metric":"sys.cpu.idle","value":18,"tags":{"host":"web01"}}
+        ]"#;
+        let (points, errors) = parse_put_body(body).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].metric, "sys.cpu.user");
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.line, e.measurement.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(1, "sys.cpu.nice"), (3, "sys.cpu.idle")]
+        );
+
+        let body = br#"{"metric":"sys.cpu.nice","value":18,"tags":{"host":"web01"}}"#;
+        let (points, errors) = parse_put_body(body).unwrap();
+        assert!(points.is_empty());
+        assert_eq!(errors.len(), 1);
+
+        assert!(parse_put_body(b"put sys.cpu.nice").is_err());
+    }
+
+    #[test]
+    fn test_data_points_to_points() {
+        let point = parse_put("cpu 1356998400 0.5 host=web01".split_whitespace()).unwrap();
+        let data = data_points_to_points("public", &[point], 1_000_000);
+
+        let points = flatbuffers::root::<Points>(&data).unwrap();
+        let points = points.points().unwrap();
+        assert_eq!(points.len(), 1);
+
+        let point = points.get(0);
+        assert_eq!(point.tab().unwrap().bytes(), b"cpu");
+        assert_eq!(point.timestamp(), 1356998400000);
+
+        let tags = point.tags().unwrap();
+        assert_eq!(tags.get(0).key().unwrap().bytes(), b"host");
+        assert_eq!(tags.get(0).value().unwrap().bytes(), b"web01");
+
+        let fields = point.fields().unwrap();
+        assert_eq!(fields.get(0).name().unwrap().bytes(), b"value");
+        assert_eq!(
+            fields.get(0).value().unwrap().bytes(),
+            &0.5_f64.to_be_bytes()
+        );
+    }
+}
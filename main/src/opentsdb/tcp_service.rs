@@ -0,0 +1,166 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use config::OpenTsdbConfig;
+use coordinator::service::CoordinatorRef;
+use models::auth::user::UserInfo;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use trace::{debug, info, warn};
+
+use super::{parse_put, write_data_points, DataPoint};
+use crate::server::{self, Service, ServiceHandle};
+use crate::VERSION;
+
+/// The buffered data points are written once the connection has no more lines to read or
+/// the number of them reaches the limit.
+const MAX_BATCH_POINTS: usize = 1000;
+/// The connection is closed if a line is longer, the line break is not counted.
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// Serves the telnet protocol of OpenTSDB, the data points are written as the configured
+/// user to the configured database of the tenant.
+pub struct OpenTsdbService {
+    addr: SocketAddr,
+    coord: CoordinatorRef,
+    config: OpenTsdbConfig,
+    handle: Option<ServiceHandle<()>>,
+}
+
+impl OpenTsdbService {
+    pub fn new(coord: CoordinatorRef, addr: SocketAddr, config: OpenTsdbConfig) -> Self {
+        Self {
+            addr,
+            coord,
+            config,
+            handle: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Service for OpenTsdbService {
+    fn start(&mut self) -> Result<(), server::Error> {
+        let addr = self.addr;
+        let listener = std::net::TcpListener::bind(addr)
+            .and_then(|listener| {
+                listener.set_nonblocking(true)?;
+                TcpListener::from_std(listener)
+            })
+            .map_err(|source| server::Error::BindAddr { addr, source })?;
+        info!("opentsdb server start addr: {}", addr);
+
+        let coord = self.coord.clone();
+        let config = Arc::new(self.config.clone());
+        let (shutdown, rx) = oneshot::channel();
+        let join_handle = tokio::spawn(async move {
+            tokio::select! {
+                _ = accept(listener, coord, config) => {}
+                _ = rx => info!("opentsdb server graceful shutdown!"),
+            }
+        });
+        self.handle = Some(ServiceHandle::new(
+            "opentsdb service".to_string(),
+            join_handle,
+            shutdown,
+        ));
+        Ok(())
+    }
+
+    async fn stop(&mut self, force: bool) {
+        if let Some(stop) = self.handle.take() {
+            stop.shutdown(force).await
+        };
+    }
+}
+
+async fn accept(listener: TcpListener, coord: CoordinatorRef, config: Arc<OpenTsdbConfig>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let coord = coord.clone();
+                let config = config.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, coord, &config).await {
+                        debug!("opentsdb connection from {} closed: {}", peer, e);
+                    }
+                });
+            }
+            Err(e) => warn!("opentsdb server failed to accept a connection: {}", e),
+        }
+    }
+}
+
+/// Handles the commands `put`, `version` and `exit`, like OpenTSDB only the errors are
+/// replied to.
+async fn handle_connection(
+    stream: TcpStream,
+    coord: CoordinatorRef,
+    config: &OpenTsdbConfig,
+) -> std::io::Result<()> {
+    let user_info = UserInfo {
+        user: config.user.clone(),
+        password: config.password.clone(),
+        private_key: None,
+    };
+    let (tenant, db) = (config.tenant.as_str(), config.database.as_str());
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut data_points: Vec<DataPoint> = Vec::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let len = (&mut reader)
+            .take(MAX_LINE_LENGTH as u64 + 1)
+            .read_line(&mut line)
+            .await?;
+        if len == 0 {
+            break;
+        }
+        if len > MAX_LINE_LENGTH && !line.ends_with('\n') {
+            writer
+                .write_all(format!("line longer than {} bytes\n", MAX_LINE_LENGTH).as_bytes())
+                .await?;
+            break;
+        }
+
+        let mut args = line.split_whitespace();
+        match args.next() {
+            Some("put") => match parse_put(args) {
+                Ok(data_point) => data_points.push(data_point),
+                Err(e) => writer.write_all(format!("put: {}\n", e).as_bytes()).await?,
+            },
+            Some("version") => {
+                writer
+                    .write_all(format!("cnosdb {}\n", VERSION.as_str()).as_bytes())
+                    .await?
+            }
+            Some("exit") => break,
+            Some(cmd) => {
+                writer
+                    .write_all(format!("unknown command: {}\n", cmd).as_bytes())
+                    .await?
+            }
+            None => {}
+        }
+
+        if !data_points.is_empty()
+            && (reader.buffer().is_empty() || data_points.len() >= MAX_BATCH_POINTS)
+        {
+            let res = write_data_points(&coord, &user_info, tenant, db, &data_points).await;
+            data_points.clear();
+            if let Err(e) = res {
+                writer.write_all(format!("put: {}\n", e).as_bytes()).await?;
+            }
+        }
+    }
+
+    if !data_points.is_empty() {
+        if let Err(e) = write_data_points(&coord, &user_info, tenant, db, &data_points).await {
+            writer.write_all(format!("put: {}\n", e).as_bytes()).await?;
+        }
+    }
+
+    Ok(())
+}
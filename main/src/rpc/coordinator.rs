@@ -0,0 +1,696 @@
+use std::path::{Component, Path};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use coordinator::command::*;
+use coordinator::errors::{CoordinatorError, CoordinatorResult};
+use coordinator::file_info::{get_file_info, get_files_meta, PathFilesMeta};
+use coordinator::node_client::error_to_status;
+use coordinator::reader::{QueryExecutor, ReaderIterator};
+use coordinator::service::CoordinatorRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::logical_expr::{col, lit};
+use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
+use datafusion::scalar::ScalarValue;
+use futures::Stream;
+use meta::error::MetaError;
+use models::meta_data::VnodeInfo;
+use models::predicate::domain::{Predicate, QueryArgs, QueryExpr};
+use models::schema::TIME_FIELD_NAME;
+use models::ColumnId;
+use protos::coordinator_service::{
+    coordinator_service_server::CoordinatorService, AdminCommandRequest, ApplyVnodeSummaryRequest,
+    DownloadFileRequest, FetchVnodeSummaryRequest, FetchVnodeSummaryResponse, FetchVnodeWalRequest,
+    FileChunkResponse, QueryRecordBatchRequest, RecordBatchResponse, ReleaseVnodeSnapshotRequest,
+    SnapshotVnodeRequest, SnapshotVnodeResponse, StatusResponse, WalCursor, WalEntryResponse,
+    WriteVnodeRequest,
+};
+use protos::kv_service::{Meta, WritePointsRpcRequest};
+use query::utils::point_util::record_batch_to_points_flat_buffer;
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc::{self, Sender};
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+use tonic::{Request, Response, Status};
+use trace::{debug, info};
+use tskv::engine::EngineRef;
+use tskv::iterator::{QueryOption, TableScanMetrics};
+use tskv::VersionEdit;
+
+use super::vnode_manager::VnodeManager;
+
+/// The number of batches buffered for a query stream,
+/// the scan waits once the client falls behind.
+const QUERY_RESPONSE_BUFFER_SIZE: usize = 16;
+const FILE_CHUNK_SIZE: usize = 1024 * 1024;
+/// The size of the points read from the wal at a time.
+const WAL_BATCH_SIZE: usize = 4 * 1024 * 1024;
+/// The number of rows read from the source vnode at a time when repairing a vnode.
+const REPAIR_BATCH_SIZE: usize = 4096;
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send + 'static>>;
+
+/// Serves the requests of the other nodes of the cluster.
+pub struct CoordServiceImpl {
+    pub coord: CoordinatorRef,
+}
+
+fn check_version(version: u64) -> Result<(), Status> {
+    if version != COORDINATOR_PROTOCOL_VERSION {
+        return Err(Status::failed_precondition(format!(
+            "protocol version {} not supported, expect {}",
+            version, COORDINATOR_PROTOCOL_VERSION
+        )));
+    }
+
+    Ok(())
+}
+
+fn success_response(data: String) -> Response<StatusResponse> {
+    Response::new(StatusResponse {
+        code: SUCCESS_RESPONSE_CODE,
+        data,
+    })
+}
+
+#[tonic::async_trait]
+impl CoordinatorService for CoordServiceImpl {
+    async fn write_vnode_points(
+        &self,
+        request: Request<WriteVnodeRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let req = request.into_inner();
+        check_version(req.version)?;
+
+        let points = WritePointsRpcRequest {
+            version: 1,
+            meta: Some(Meta {
+                tenant: req.tenant,
+                user: None,
+                password: None,
+            }),
+            points: req.data,
+            consistency_level: None,
+        };
+
+        self.coord
+            .store_engine()
+            .write(req.vnode_id, points)
+            .await
+            .map_err(|err| error_to_status(err.into()))?;
+        debug!("success write data to vnode: {}", req.vnode_id);
+
+        Ok(success_response("".to_string()))
+    }
+
+    async fn exec_admin_command(
+        &self,
+        request: Request<AdminCommandRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let req = request.into_inner();
+        check_version(req.version)?;
+
+        let req = AdminStatementRequest::from_rpc_request(req).map_err(error_to_status)?;
+        let data = exec_admin_statement(req, self.coord.clone())
+            .await
+            .map_err(error_to_status)?;
+
+        Ok(success_response(data))
+    }
+
+    type QueryRecordBatchStream = ResponseStream<RecordBatchResponse>;
+
+    async fn query_record_batch(
+        &self,
+        request: Request<QueryRecordBatchRequest>,
+    ) -> Result<Response<Self::QueryRecordBatchStream>, Status> {
+        let req = request.into_inner();
+        check_version(req.version)?;
+
+        let args = bincode::deserialize::<QueryArgs>(&req.args)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let expr = QueryExpr::decode(req.expr).map_err(|e| error_to_status(e.into()))?;
+
+        // The scan stops once the client drops the stream and the receiver is closed.
+        let (sender, receiver) = mpsc::channel(QUERY_RESPONSE_BUFFER_SIZE);
+        tokio::spawn(query_record_batch(args, expr, self.coord.clone(), sender));
+
+        let stream = ReceiverStream::new(receiver).map(|item| {
+            item.and_then(|record| encode_record_batch(&record))
+                .map(|record_batch| RecordBatchResponse { record_batch })
+                .map_err(error_to_status)
+        });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn fetch_vnode_summary(
+        &self,
+        request: Request<FetchVnodeSummaryRequest>,
+    ) -> Result<Response<FetchVnodeSummaryResponse>, Status> {
+        let req = request.into_inner();
+        check_version(req.version)?;
+
+        let version_edit = self
+            .coord
+            .store_engine()
+            .get_vnode_summary(&req.tenant, &req.database, req.vnode_id)
+            .await
+            .map_err(|e| Status::internal(format!("failed to get vnode summary: {}", e)))?;
+
+        let version_edit = match version_edit {
+            Some(ve) => ve
+                .encode()
+                .map_err(|e| Status::internal(format!("failed to encode vnode summary: {}", e)))?,
+            None => vec![],
+        };
+
+        Ok(Response::new(FetchVnodeSummaryResponse { version_edit }))
+    }
+
+    async fn apply_vnode_summary(
+        &self,
+        request: Request<ApplyVnodeSummaryRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let req = request.into_inner();
+        check_version(req.version)?;
+
+        let version_edit = VersionEdit::decode(&req.version_edit).map_err(|e| {
+            Status::invalid_argument(format!("failed to decode vnode summary: {}", e))
+        })?;
+
+        self.coord
+            .store_engine()
+            .apply_vnode_summary(&req.tenant, &req.database, req.vnode_id, version_edit)
+            .await
+            .map_err(|e| Status::internal(format!("failed to apply vnode summary: {}", e)))?;
+
+        Ok(success_response("".to_string()))
+    }
+
+    type DownloadFileStream = ResponseStream<FileChunkResponse>;
+
+    async fn download_file(
+        &self,
+        request: Request<DownloadFileRequest>,
+    ) -> Result<Response<Self::DownloadFileStream>, Status> {
+        let req = request.into_inner();
+        check_version(req.version)?;
+
+        // only the files of the vnode can be downloaded
+        let filename = Path::new(&req.filename);
+        if !filename
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(Status::invalid_argument(format!(
+                "invalid file name: {}",
+                req.filename
+            )));
+        }
+
+        let engine = self.coord.store_engine();
+        let owner = models::schema::make_owner(&req.tenant, &req.database);
+        let path = engine
+            .get_storage_options()
+            .ts_family_dir(&owner, req.vnode_id)
+            .join(filename);
+        info!("download file: {}", path.display());
+
+        let mut file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| Status::not_found(format!("{}: {}", path.display(), e)))?;
+
+        let stream = async_stream::stream! {
+            let mut buffer = vec![0; FILE_CHUNK_SIZE];
+            loop {
+                match file.read(&mut buffer).await {
+                    Ok(0) => break,
+                    Ok(len) => yield Ok(FileChunkResponse {
+                        data: buffer[..len].to_vec(),
+                    }),
+                    Err(e) => {
+                        yield Err(Status::internal(e.to_string()));
+                        break;
+                    }
+                }
+            }
+        };
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+    async fn snapshot_vnode(
+        &self,
+        request: Request<SnapshotVnodeRequest>,
+    ) -> Result<Response<SnapshotVnodeResponse>, Status> {
+        let req = request.into_inner();
+        check_version(req.version)?;
+
+        let engine = self.coord.store_engine();
+        let snapshot = engine
+            .snapshot_vnode(&req.tenant, &req.database, req.vnode_id)
+            .await
+            .map_err(|e| Status::internal(format!("failed to snapshot vnode: {}", e)))?
+            .ok_or_else(|| Status::not_found(format!("vnode {} not found", req.vnode_id)))?;
+
+        let owner = models::schema::make_owner(&req.tenant, &req.database);
+        let path = engine
+            .get_storage_options()
+            .ts_family_dir(&owner, req.vnode_id);
+        let response = async {
+            let mut meta = Vec::with_capacity(snapshot.files.len());
+            for file in snapshot.files.iter() {
+                meta.push(get_file_info(&file.to_string_lossy()).await?);
+            }
+            let files_meta = PathFilesMeta {
+                path: path.to_string_lossy().to_string(),
+                meta,
+            };
+
+            Ok::<_, CoordinatorError>(SnapshotVnodeResponse {
+                seq: snapshot.seq,
+                version_edit: snapshot.version_edit.encode()?,
+                files_meta: serde_json::to_string(&files_meta)
+                    .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() })?,
+            })
+        }
+        .await;
+
+        match response {
+            Ok(response) => Ok(Response::new(response)),
+            Err(err) => {
+                engine.release_vnode_snapshot(req.vnode_id);
+                Err(error_to_status(err))
+            }
+        }
+    }
+
+    type FetchVnodeWalStream = ResponseStream<WalEntryResponse>;
+
+    async fn fetch_vnode_wal(
+        &self,
+        request: Request<FetchVnodeWalRequest>,
+    ) -> Result<Response<Self::FetchVnodeWalStream>, Status> {
+        let req = request.into_inner();
+        check_version(req.version)?;
+
+        let engine = self.coord.store_engine();
+        let stream = async_stream::stream! {
+            let mut min_seq = req.min_seq;
+            let mut cursor = req.cursor.map(|cursor| tskv::WalCursor {
+                file_id: cursor.file_id,
+                pos: cursor.pos,
+            });
+            loop {
+                let entries = match engine.get_vnode_wal(req.vnode_id, min_seq, cursor, WAL_BATCH_SIZE).await {
+                    Ok(entries) => entries,
+                    Err(e @ tskv::Error::WalDeleted { .. }) => {
+                        yield Err(Status::out_of_range(e.to_string()));
+                        break;
+                    }
+                    Err(e) => {
+                        yield Err(Status::internal(e.to_string()));
+                        break;
+                    }
+                };
+
+                let size: usize = entries.iter().map(|e| e.data.len()).sum();
+                for entry in entries {
+                    min_seq = entry.seq;
+                    cursor = Some(entry.cursor);
+                    yield Ok(WalEntryResponse {
+                        seq: entry.seq,
+                        tenant: entry.tenant,
+                        data: entry.data,
+                        typ: entry.typ as u32,
+                        cursor: Some(WalCursor {
+                            file_id: entry.cursor.file_id,
+                            pos: entry.cursor.pos,
+                        }),
+                    });
+                }
+
+                // the end of the wal is reached
+                if size < WAL_BATCH_SIZE {
+                    break;
+                }
+            }
+        };
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn release_vnode_snapshot(
+        &self,
+        request: Request<ReleaseVnodeSnapshotRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let req = request.into_inner();
+        check_version(req.version)?;
+
+        self.coord
+            .store_engine()
+            .release_vnode_snapshot(req.vnode_id);
+
+        Ok(success_response("".to_string()))
+    }
+}
+
+/// Returns the data of the response.
+async fn exec_admin_statement(
+    req: AdminStatementRequest,
+    coord: CoordinatorRef,
+) -> CoordinatorResult<String> {
+    let meta = coord.meta_manager();
+    let engine = coord.store_engine();
+    let vnode_manager = || {
+        VnodeManager::new(
+            meta.clone(),
+            engine.clone(),
+            coord.node_clients(),
+            coord.node_id(),
+        )
+    };
+
+    match req.stmt {
+        AdminStatementType::DropDB { db } => {
+            let _ = engine.drop_database(&req.tenant, &db).await;
+        }
+
+        AdminStatementType::DropTable { db, table } => {
+            let _ = engine.drop_table(&req.tenant, &db, &table).await;
+        }
+
+        AdminStatementType::DropColumn { db, table, column } => {
+            let _ = engine
+                .drop_table_column(&req.tenant, &db, &table, &column)
+                .await;
+        }
+        AdminStatementType::AddColumn { db, table, column } => {
+            let _ = engine
+                .add_table_column(&req.tenant, &db, &table, column)
+                .await;
+        }
+        AdminStatementType::AlterColumn {
+            db,
+            table,
+            column_name,
+            new_column,
+        } => {
+            let _ = engine
+                .change_table_column(&req.tenant, &db, &table, &column_name, new_column)
+                .await;
+        }
+
+        AdminStatementType::DeleteVnode { db, vnode_id } => {
+            vnode_manager().drop_vnode(&req.tenant, vnode_id).await?;
+        }
+
+        AdminStatementType::CopyVnode { vnode_id } => {
+            vnode_manager().copy_vnode(&req.tenant, vnode_id).await?;
+        }
+
+        AdminStatementType::MoveVnode { vnode_id } => {
+            vnode_manager().move_vnode(&req.tenant, vnode_id).await?;
+        }
+
+        AdminStatementType::GetVnodeFilesMeta { db, vnode_id } => {
+            let owner = models::schema::make_owner(&req.tenant, &db);
+            let storage_opt = engine.get_storage_options();
+
+            engine.flush_tsfamily(&req.tenant, &db, vnode_id).await?;
+
+            let path = storage_opt.ts_family_dir(&owner, vnode_id);
+            info!("get files meta: {:?}", path);
+            let meta = get_files_meta(&path.as_path().to_string_lossy()).await?;
+            info!("files meta: {:?}", meta);
+
+            return serde_json::to_string(&meta)
+                .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() });
+        }
+
+        AdminStatementType::DeleteFromTable {
+            db,
+            table,
+            vnode_ids,
+            expr,
+        } => {
+            let series =
+                delete_from_table(&req.tenant, &db, &table, &vnode_ids, expr, engine).await?;
+
+            return serde_json::to_string(&series)
+                .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() });
+        }
+
+        AdminStatementType::GetVnodeHashTree { db, vnode_id } => {
+            let tree = engine
+                .get_vnode_hash_tree(&req.tenant, &db, vnode_id)
+                .await?;
+
+            return serde_json::to_string(&tree)
+                .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() });
+        }
+
+        AdminStatementType::RepairVnode {
+            db,
+            vnode_id,
+            source,
+            table,
+            time_ranges,
+        } => {
+            let rows = repair_vnode(
+                &req.tenant,
+                &db,
+                vnode_id,
+                source,
+                &table,
+                &time_ranges,
+                coord.clone(),
+            )
+            .await?;
+
+            return Ok(rows.to_string());
+        }
+
+        AdminStatementType::HintedOff { node_id, cmd } => {
+            if let Some(manager) = coord.hinted_off_manager() {
+                manager.control(node_id, cmd).await?;
+            }
+        }
+
+        AdminStatementType::GetMetaVersion => {
+            let version = meta
+                .tenant_manager()
+                .tenant_meta(&req.tenant)
+                .map_or(0, |meta_client| meta_client.version());
+
+            return Ok(version.to_string());
+        }
+    }
+
+    Ok("".to_string())
+}
+
+/// Delete the data of the series matched by expr from the vnodes,
+/// returns the hash of the affected series keys.
+async fn delete_from_table(
+    tenant: &str,
+    db: &str,
+    table: &str,
+    vnode_ids: &[u32],
+    expr: Vec<u8>,
+    engine: EngineRef,
+) -> CoordinatorResult<Vec<u64>> {
+    let expr = QueryExpr::decode(expr)?;
+    let table_schema = expr.table_schema;
+    // a filter widened to all the rows would delete the whole table
+    let filter =
+        Arc::new(Predicate::default().push_down_exact_filter(&expr.filters, &table_schema)?);
+
+    let time_ranges = QueryOption::parse_time_ranges(filter.clone(), table_schema.clone());
+    let tags_filter = filter.filter().translate_column(|c| {
+        table_schema
+            .column(&c.name)
+            .filter(|e| e.column_type.is_tag())
+            .map(|e| e.name.clone())
+    });
+    let column_ids: Vec<ColumnId> = table_schema.columns().iter().map(|c| c.id).collect();
+
+    let mut series = vec![];
+    for vnode_id in vnode_ids.iter() {
+        let series_ids = engine
+            .get_series_id_by_filter(*vnode_id, tenant, db, table, &tags_filter)
+            .await
+            .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() })?;
+        if series_ids.is_empty() {
+            continue;
+        }
+
+        for time_range in time_ranges.iter() {
+            engine
+                .delete_series(tenant, db, *vnode_id, &series_ids, &column_ids, time_range)
+                .await?;
+        }
+
+        for sid in series_ids.iter() {
+            if let Some(key) = engine
+                .get_series_key(tenant, db, *vnode_id, *sid)
+                .await
+                .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() })?
+            {
+                series.push(key.hash());
+            }
+        }
+        info!(
+            "delete {} series from vnode: {}, table: {}.{}",
+            series_ids.len(),
+            vnode_id,
+            db,
+            table
+        );
+    }
+
+    Ok(series)
+}
+
+/// Copies the data of the table in the time ranges from the source vnode into the vnode,
+/// returns the number of rows copied.
+async fn repair_vnode(
+    tenant: &str,
+    db: &str,
+    vnode_id: u32,
+    source: VnodeInfo,
+    table: &str,
+    time_ranges: &[(i64, i64)],
+    coord: CoordinatorRef,
+) -> CoordinatorResult<usize> {
+    let meta = coord
+        .tenant_meta(tenant)
+        .ok_or(CoordinatorError::TenantNotFound {
+            name: tenant.to_string(),
+        })?;
+    let table_schema = meta
+        .get_tskv_table_schema(db, table)?
+        .ok_or(MetaError::TableNotFound {
+            table: table.to_string(),
+        })?;
+
+    let time_filter = time_ranges
+        .iter()
+        .map(|(min_ts, max_ts)| {
+            let time = col(TIME_FIELD_NAME);
+            time.clone()
+                .gt_eq(lit(ScalarValue::TimestampNanosecond(Some(*min_ts), None)))
+                .and(time.lt(lit(ScalarValue::TimestampNanosecond(Some(*max_ts), None))))
+        })
+        .reduce(|a, b| a.or(b));
+    let filter = Arc::new(
+        Predicate::default()
+            .push_down_filter(&time_filter.into_iter().collect::<Vec<_>>(), &table_schema),
+    );
+
+    let plan_metrics = ExecutionPlanMetricsSet::new();
+    let scan_metrics = TableScanMetrics::new(&plan_metrics, 0);
+    let option = QueryOption::new(
+        REPAIR_BATCH_SIZE,
+        tenant.to_string(),
+        filter,
+        table_schema.to_arrow_schema(),
+        table_schema.clone(),
+        scan_metrics.tskv_metrics(),
+    );
+
+    let (mut iterator, sender) = ReaderIterator::new();
+    let executor = QueryExecutor::new(
+        option,
+        coord.store_engine(),
+        coord.meta_manager(),
+        coord.node_clients(),
+        sender.clone(),
+    );
+    let source_id = source.id;
+    tokio::spawn(async move {
+        if let Err(err) = executor.execute_on_vnodes(vec![source]).await {
+            let _ = sender.send(Err(err)).await;
+        }
+    });
+
+    let engine = coord.store_engine();
+    let table_schema = Arc::new(table_schema);
+    let mut rows = 0;
+    while let Some(batch) = iterator.next().await {
+        let batch = batch?;
+        if batch.num_rows() == 0 {
+            continue;
+        }
+
+        let points = record_batch_to_points_flat_buffer(&batch, table_schema.clone())
+            .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() })?;
+        let req = WritePointsRpcRequest {
+            version: 1,
+            meta: Some(Meta {
+                tenant: tenant.to_string(),
+                user: None,
+                password: None,
+            }),
+            points,
+            consistency_level: None,
+        };
+        engine.write(vnode_id, req).await?;
+        rows += batch.num_rows();
+    }
+    info!(
+        "repair vnode {} from vnode {}, table: {}.{}, rows: {}",
+        vnode_id, source_id, db, table, rows
+    );
+
+    Ok(rows)
+}
+
+async fn query_record_batch(
+    args: QueryArgs,
+    expr: QueryExpr,
+    coord: CoordinatorRef,
+    sender: Sender<CoordinatorResult<RecordBatch>>,
+) {
+    let filter = Arc::new(
+        Predicate::default()
+            .set_limit(args.limit)
+            .push_down_filter(&expr.filters, &expr.table_schema),
+    );
+
+    let plan_metrics = ExecutionPlanMetricsSet::new();
+    let scan_metrics = TableScanMetrics::new(&plan_metrics, 0);
+    let option = QueryOption::new(
+        args.batch_size,
+        args.tenant.clone(),
+        filter,
+        expr.df_schema,
+        expr.table_schema,
+        scan_metrics.tskv_metrics(),
+    )
+    .with_aggregates(expr.aggregates)
+    .with_fragment(expr.fragment);
+
+    let meta = coord.meta_manager();
+    let node_id = meta.node_id();
+    let mut vnodes = Vec::with_capacity(args.vnode_ids.len());
+    for id in args.vnode_ids.iter() {
+        vnodes.push(VnodeInfo { id: *id, node_id })
+    }
+
+    let mut executor = QueryExecutor::new(
+        option,
+        coord.store_engine(),
+        meta,
+        coord.node_clients(),
+        sender.clone(),
+    );
+    if let Some(fragment_executor) = coord.fragment_executor() {
+        executor = executor.with_fragment_executor(fragment_executor);
+    }
+    if let Err(err) = executor.local_node_executor(vnodes).await {
+        info!("select statement execute failed: {}", err.to_string());
+        let _ = sender.send(Err(err)).await;
+    } else {
+        info!("select statement execute success");
+    }
+}
@@ -1,27 +1,30 @@
+use crate::rpc::coordinator::CoordServiceImpl;
 use crate::rpc::tskv::TskvServiceImpl;
+use crate::rpc::vnode_manager::VnodeManager;
 use crate::server::{Service, ServiceHandle};
 use crate::{info, server};
 use config::TLSConfig;
+use coordinator::service::CoordinatorRef;
+use protos::coordinator_service::coordinator_service_server::CoordinatorServiceServer;
 use protos::kv_service::tskv_service_server::TskvServiceServer;
 use spi::server::dbms::DBMSRef;
 use std::net::SocketAddr;
 use tokio::sync::oneshot;
 use tonic::transport::{Identity, Server, ServerTlsConfig};
-use tskv::engine::EngineRef;
 
 pub struct GrpcService {
     tls_config: Option<TLSConfig>,
     addr: SocketAddr,
     //todo grpc support sql query
     _dbms: DBMSRef,
-    kv_inst: EngineRef,
+    coord: CoordinatorRef,
     handle: Option<ServiceHandle<Result<(), tonic::transport::Error>>>,
 }
 
 impl GrpcService {
     pub fn new(
         dbms: DBMSRef,
-        kv_inst: EngineRef,
+        coord: CoordinatorRef,
         addr: SocketAddr,
         tls_config: Option<TLSConfig>,
     ) -> Self {
@@ -29,7 +32,7 @@ impl GrpcService {
             tls_config,
             addr,
             _dbms: dbms,
-            kv_inst,
+            coord,
             handle: None,
         }
     }
@@ -58,7 +61,7 @@ impl Service for GrpcService {
     fn start(&mut self) -> server::Result<()> {
         let (shutdown, rx) = oneshot::channel();
         let tskv_grpc_service = TskvServiceServer::new(TskvServiceImpl {
-            kv_engine: self.kv_inst.clone(),
+            coord: self.coord.clone(),
         });
         let mut grpc_builder = build_grpc_server(&self.tls_config)?;
         let grpc_router = grpc_builder.add_service(tskv_grpc_service);
@@ -82,3 +85,66 @@ impl Service for GrpcService {
         };
     }
 }
+
+/// The service between the nodes of the cluster: writes, admin statements, queries of vnodes
+/// and the vnode files to copy.
+pub struct CoordGrpcService {
+    tls_config: Option<TLSConfig>,
+    addr: SocketAddr,
+    coord: CoordinatorRef,
+    handle: Option<ServiceHandle<Result<(), tonic::transport::Error>>>,
+}
+
+impl CoordGrpcService {
+    pub fn new(coord: CoordinatorRef, addr: SocketAddr, tls_config: Option<TLSConfig>) -> Self {
+        Self {
+            tls_config,
+            addr,
+            coord,
+            handle: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Service for CoordGrpcService {
+    fn start(&mut self) -> server::Result<()> {
+        let (shutdown, rx) = oneshot::channel();
+        let coord_grpc_service = CoordinatorServiceServer::new(CoordServiceImpl {
+            coord: self.coord.clone(),
+        });
+        let mut grpc_builder = build_grpc_server(&self.tls_config)?;
+        let grpc_router = grpc_builder.add_service(coord_grpc_service);
+        let server = grpc_router.serve_with_shutdown(self.addr, async {
+            rx.await.ok();
+            info!("coordinator grpc server graceful shutdown!");
+        });
+        info!("coordinator grpc server start addr: {}", self.addr);
+        let grpc_handle = tokio::spawn(server);
+        self.handle = Some(ServiceHandle::new(
+            "coordinator grpc service".to_string(),
+            grpc_handle,
+            shutdown,
+        ));
+
+        let coord = self.coord.clone();
+        tokio::spawn(async move {
+            VnodeManager::new(
+                coord.meta_manager(),
+                coord.store_engine(),
+                coord.node_clients(),
+                coord.node_id(),
+            )
+            .resume_migrations()
+            .await
+        });
+
+        Ok(())
+    }
+
+    async fn stop(&mut self, force: bool) {
+        if let Some(stop) = self.handle.take() {
+            stop.shutdown(force).await
+        };
+    }
+}
@@ -1,3 +1,5 @@
+pub mod coordinator;
 pub mod grpc_service;
 pub mod schema;
 pub mod tskv;
+pub mod vnode_manager;
@@ -1,6 +1,9 @@
 use std::pin::Pin;
 
+use coordinator::service::CoordinatorRef;
 use futures::Stream;
+use models::consistency_level::ConsistencyLevel;
+use models::schema::DEFAULT_CATALOG;
 use protos::{
     kv_service::{
         tskv_service_server::TskvService, AddSeriesRpcRequest, AddSeriesRpcResponse,
@@ -14,11 +17,9 @@ use tokio_stream::{wrappers::ReceiverStream, StreamExt};
 use tonic::{Request, Response, Status, Streaming};
 use trace::debug;
 
-use tskv::engine::EngineRef;
-
 pub struct TskvServiceImpl {
     // pub sender: channel::Sender<tskv::Task>,
-    pub kv_engine: EngineRef,
+    pub coord: CoordinatorRef,
 }
 
 #[tonic::async_trait]
@@ -122,10 +123,21 @@ impl TskvService for TskvServiceImpl {
                     //     .await
                     //     .map_err(|err| Status::internal(err.to_string()));
 
+                    let tenant = req
+                        .meta
+                        .as_ref()
+                        .map(|meta| meta.tenant.clone())
+                        .unwrap_or_else(|| DEFAULT_CATALOG.to_string());
+                    let level = ConsistencyLevel::from(req.consistency_level());
+
                     let ret = self
-                        .kv_engine
-                        .write(0, req)
+                        .coord
+                        .write_points(tenant, level, req)
                         .await
+                        .map(|_| WritePointsRpcResponse {
+                            version: 1,
+                            points: vec![],
+                        })
                         .map_err(|err| Status::internal(err.to_string()));
                     // 2. if something wrong when sending Request
                     // if let Err(err) = ret {
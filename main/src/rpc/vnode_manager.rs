@@ -0,0 +1,630 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use coordinator::command::*;
+use coordinator::file_info::get_file_info;
+use coordinator::node_client::{status_to_error, NodeClientsRef, ADMIN_REQUEST_TIMEOUT};
+use coordinator::{
+    errors::{CoordinatorError, CoordinatorResult},
+    file_info::PathFilesMeta,
+};
+
+use models::meta_data::{VnodeAllInfo, VnodeInfo};
+use models::schema;
+use protos::coordinator_service::{
+    DownloadFileRequest, FetchVnodeWalRequest, ReleaseVnodeSnapshotRequest, SnapshotVnodeRequest,
+    WalCursor,
+};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tonic::{Code, Request};
+use trace::{error, info, warn};
+
+/// The copy has caught up with the source vnode if fewer writes are copied in a round,
+/// then it's added to the replication set.
+const CATCH_UP_LAG: usize = 128;
+const MAX_CATCH_UP_ROUNDS: usize = 32;
+/// The time for the nodes to watch the new replication set,
+/// the writes routed by the old one are copied after all the nodes have it.
+const META_SYNC_TIMEOUT: Duration = Duration::from_secs(60);
+const META_SYNC_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum MigrationPhase {
+    /// Downloading the files of the snapshot of the source vnode.
+    Download,
+    /// Copying the writes after the snapshot from the wal of the source vnode.
+    CatchUp,
+    /// Adding the copy to the replication set.
+    Switch,
+    /// Dropping the source vnode if it's moved.
+    Drop,
+}
+
+/// The progress of copying or moving a vnode to this node,
+/// it's persisted so that the migration can be resumed after a crash.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Migration {
+    tenant: String,
+    vnode_id: u32,
+    new_id: u32,
+    drop_source: bool,
+    phase: MigrationPhase,
+    /// The writes of the source vnode up to the sequence have been copied.
+    seq: u64,
+    /// The position in the WAL of the source vnode after the last write copied.
+    #[serde(default)]
+    cursor: Option<tskv::WalCursor>,
+}
+
+pub struct VnodeManager {
+    node_id: u64,
+    meta: meta::meta_client::MetaRef,
+    kv_inst: tskv::engine::EngineRef,
+    node_clients: NodeClientsRef,
+}
+
+impl VnodeManager {
+    pub fn new(
+        meta: meta::meta_client::MetaRef,
+        kv_inst: tskv::engine::EngineRef,
+        node_clients: NodeClientsRef,
+        node_id: u64,
+    ) -> Self {
+        Self {
+            node_id,
+            meta,
+            kv_inst,
+            node_clients,
+        }
+    }
+
+    pub async fn move_vnode(&self, tenant: &str, vnode_id: u32) -> CoordinatorResult<()> {
+        self.migrate_vnode(tenant, vnode_id, true).await
+    }
+
+    pub async fn copy_vnode(&self, tenant: &str, vnode_id: u32) -> CoordinatorResult<()> {
+        self.migrate_vnode(tenant, vnode_id, false).await
+    }
+
+    /// Resumes the migrations interrupted by the last shutdown of the node.
+    pub async fn resume_migrations(&self) {
+        let dir = self.kv_inst.get_storage_options().migration_dir();
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.path().extension().map_or(true, |ext| ext != "json") {
+                continue;
+            }
+
+            let migration = match tokio::fs::read(entry.path())
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|data| {
+                    serde_json::from_slice::<Migration>(&data).map_err(|e| e.to_string())
+                }) {
+                Ok(migration) => migration,
+                Err(err) => {
+                    warn!("invalid migration file {:?}: {}", entry.path(), err);
+                    continue;
+                }
+            };
+
+            info!("resume migration: {:?}", migration);
+            if let Err(err) = self.run_migration(migration.clone()).await {
+                error!("migration {:?} failed: {}", migration, err);
+            }
+        }
+    }
+
+    /// Copies the vnode to this node, the migration of the vnode interrupted before is resumed.
+    async fn migrate_vnode(
+        &self,
+        tenant: &str,
+        vnode_id: u32,
+        drop_source: bool,
+    ) -> CoordinatorResult<()> {
+        let migration = match self.load_migration(vnode_id).await? {
+            Some(mut migration) => {
+                info!("resume migration: {:?}", migration);
+                migration.drop_source |= drop_source;
+                migration
+            }
+
+            None => {
+                let all_info = self.get_vnode_all_info(tenant, vnode_id)?;
+                let new_id = self.meta.admin_meta().retain_id(1)?;
+                info!(
+                    "Copy Vnode:{} from: {} to: {}; new id: {}",
+                    vnode_id, all_info.node_id, self.node_id, new_id
+                );
+
+                Migration {
+                    tenant: tenant.to_string(),
+                    vnode_id,
+                    new_id,
+                    drop_source,
+                    phase: MigrationPhase::Download,
+                    seq: 0,
+                    cursor: None,
+                }
+            }
+        };
+        self.save_migration(&migration).await?;
+
+        self.run_migration(migration).await
+    }
+
+    async fn run_migration(&self, mut migration: Migration) -> CoordinatorResult<()> {
+        loop {
+            match migration.phase {
+                MigrationPhase::Download => {
+                    let all_info =
+                        self.get_vnode_all_info(&migration.tenant, migration.vnode_id)?;
+                    migration.seq = self.download_snapshot(&all_info, migration.new_id).await?;
+                    migration.cursor = None;
+                    migration.phase = MigrationPhase::CatchUp;
+                }
+
+                MigrationPhase::CatchUp => {
+                    let all_info =
+                        self.get_vnode_all_info(&migration.tenant, migration.vnode_id)?;
+                    let mut rounds = 0;
+                    loop {
+                        rounds += 1;
+                        match self.catch_up(&all_info, &mut migration).await? {
+                            Some(count)
+                                if count < CATCH_UP_LAG || rounds >= MAX_CATCH_UP_ROUNDS =>
+                            {
+                                migration.phase = MigrationPhase::Switch;
+                                break;
+                            }
+                            Some(_) => self.save_migration(&migration).await?,
+                            // the writes after the snapshot are lost, copy the vnode again
+                            None => {
+                                migration.phase = MigrationPhase::Download;
+                                break;
+                            }
+                        }
+                    }
+                }
+
+                MigrationPhase::Switch => {
+                    let all_info =
+                        self.get_vnode_all_info(&migration.tenant, migration.vnode_id)?;
+                    // the writes and deletes routed by the new replication set wait until the
+                    // ones routed by the old one are copied, or they would be raced by them
+                    self.kv_inst.hold_vnode(migration.new_id).await;
+                    let switched = self.switch(&all_info, &mut migration).await;
+                    self.kv_inst.release_vnode(migration.new_id);
+                    switched?;
+                    self.release_snapshot(&all_info).await?;
+
+                    if !migration.drop_source {
+                        return self.remove_migration(migration.vnode_id).await;
+                    }
+                    migration.phase = MigrationPhase::Drop;
+                }
+
+                MigrationPhase::Drop => {
+                    if self
+                        .get_vnode_all_info(&migration.tenant, migration.vnode_id)
+                        .is_ok()
+                    {
+                        self.drop_vnode(&migration.tenant, migration.vnode_id)
+                            .await?;
+                    }
+
+                    return self.remove_migration(migration.vnode_id).await;
+                }
+            }
+
+            self.save_migration(&migration).await?;
+        }
+    }
+
+    pub async fn drop_vnode(&self, tenant: &str, vnode_id: u32) -> CoordinatorResult<()> {
+        let all_info = self.get_vnode_all_info(tenant, vnode_id)?;
+
+        if all_info.node_id == self.node_id {
+            let meta_client = self.meta.tenant_manager().tenant_meta(tenant).ok_or(
+                CoordinatorError::TenantNotFound {
+                    name: tenant.to_string(),
+                },
+            )?;
+
+            self.kv_inst
+                .remove_tsfamily(tenant, &all_info.db_name, vnode_id)
+                .await?;
+
+            let del_repl = vec![VnodeInfo {
+                id: vnode_id,
+                node_id: all_info.node_id,
+            }];
+            meta_client.update_replication_set(
+                &all_info.db_name,
+                all_info.bucket_id,
+                all_info.repl_set_id,
+                &del_repl,
+                &[],
+            )?;
+        } else {
+            let req = AdminStatementRequest {
+                tenant: all_info.tenant.to_string(),
+                stmt: AdminStatementType::DeleteVnode {
+                    db: all_info.db_name.to_string(),
+                    vnode_id,
+                },
+            };
+
+            self.node_clients
+                .exec_admin_command(all_info.node_id, &req)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Downloads the files of a snapshot of the vnode as the vnode `new_id`,
+    /// returns the sequence of the snapshot.
+    async fn download_snapshot(
+        &self,
+        all_info: &VnodeAllInfo,
+        new_id: u32,
+    ) -> CoordinatorResult<u64> {
+        // the files downloaded before the crash may be from another snapshot
+        self.kv_inst
+            .remove_tsfamily(&all_info.tenant, &all_info.db_name, new_id)
+            .await?;
+        let owner = schema::make_owner(&all_info.tenant, &all_info.db_name);
+        let path = self
+            .kv_inst
+            .get_storage_options()
+            .ts_family_dir(&owner, new_id);
+        if path.exists() {
+            tokio::fs::remove_dir_all(&path).await?;
+        }
+
+        let mut client = self.node_clients.client(all_info.node_id).await?;
+        let mut request = Request::new(SnapshotVnodeRequest {
+            version: COORDINATOR_PROTOCOL_VERSION,
+            tenant: all_info.tenant.clone(),
+            database: all_info.db_name.clone(),
+            vnode_id: all_info.vnode_id,
+        });
+        request.set_timeout(ADMIN_REQUEST_TIMEOUT);
+        let snapshot = client
+            .snapshot_vnode(request)
+            .await
+            .map_err(|status| status_to_error(all_info.node_id, status))?
+            .into_inner();
+        let files_meta = serde_json::from_str::<PathFilesMeta>(&snapshot.files_meta)
+            .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() })?;
+        info!(
+            "snapshot of vnode {} at seq {}, files meta: {:?}",
+            all_info.vnode_id, snapshot.seq, files_meta
+        );
+
+        if let Err(err) = self
+            .download_vnode_files(all_info, &files_meta, &path)
+            .await
+        {
+            tokio::fs::remove_dir_all(&path).await?;
+            return Err(err);
+        }
+
+        let ve = tskv::VersionEdit::decode(&snapshot.version_edit)?;
+        self.kv_inst
+            .apply_vnode_summary(&all_info.tenant, &all_info.db_name, new_id, ve)
+            .await?;
+
+        Ok(snapshot.seq)
+    }
+
+    /// Copies the writes of the source vnode after the sequence of the migration,
+    /// returns the number of the writes copied, or `None` if the wal has been deleted.
+    async fn catch_up(
+        &self,
+        all_info: &VnodeAllInfo,
+        migration: &mut Migration,
+    ) -> CoordinatorResult<Option<usize>> {
+        let mut client = self.node_clients.client(all_info.node_id).await?;
+        let mut request = Request::new(FetchVnodeWalRequest {
+            version: COORDINATOR_PROTOCOL_VERSION,
+            vnode_id: all_info.vnode_id,
+            min_seq: migration.seq,
+            cursor: migration.cursor.map(|cursor| WalCursor {
+                file_id: cursor.file_id,
+                pos: cursor.pos,
+            }),
+        });
+        request.set_timeout(ADMIN_REQUEST_TIMEOUT);
+
+        let mut stream = match client.fetch_vnode_wal(request).await {
+            Ok(response) => response.into_inner(),
+            Err(status) if status.code() == Code::OutOfRange => return Ok(None),
+            Err(status) => return Err(status_to_error(all_info.node_id, status)),
+        };
+
+        let mut count = 0;
+        loop {
+            let entry = match stream.message().await {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(status) if status.code() == Code::OutOfRange => return Ok(None),
+                Err(status) => return Err(status_to_error(all_info.node_id, status)),
+            };
+
+            self.kv_inst
+                .copy_wal_entry(
+                    &entry.tenant,
+                    migration.new_id,
+                    tskv::WalEntryType::from(entry.typ as u8),
+                    entry.data,
+                )
+                .await?;
+            migration.seq = entry.seq;
+            migration.cursor = entry.cursor.map(|cursor| tskv::WalCursor {
+                file_id: cursor.file_id,
+                pos: cursor.pos,
+            });
+            count += 1;
+        }
+        info!(
+            "copied {} writes of vnode {}, seq: {}",
+            count, all_info.vnode_id, migration.seq
+        );
+
+        Ok(Some(count))
+    }
+
+    /// Adds the copy to the replication set, then copies the writes routed by the old
+    /// replication set.
+    async fn switch(
+        &self,
+        all_info: &VnodeAllInfo,
+        migration: &mut Migration,
+    ) -> CoordinatorResult<()> {
+        let version = self.add_to_replication_set(all_info, migration.new_id)?;
+        self.wait_for_meta_version(&all_info.tenant, version)
+            .await?;
+        if self.catch_up(all_info, migration).await?.is_none() {
+            return Err(CoordinatorError::CommonError {
+                msg: format!(
+                    "the writes of vnode {} after {} are lost",
+                    migration.vnode_id, migration.seq
+                ),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the version of the meta data with the new replication set.
+    fn add_to_replication_set(
+        &self,
+        all_info: &VnodeAllInfo,
+        new_id: u32,
+    ) -> CoordinatorResult<u64> {
+        let meta_client = self
+            .meta
+            .tenant_manager()
+            .tenant_meta(&all_info.tenant)
+            .ok_or(CoordinatorError::TenantNotFound {
+                name: all_info.tenant.to_string(),
+            })?;
+
+        // it may have been added before the crash
+        if meta_client.get_vnode_all_info(new_id).is_none() {
+            let add_repl = vec![VnodeInfo {
+                id: new_id,
+                node_id: self.node_id,
+            }];
+            meta_client.update_replication_set(
+                &all_info.db_name,
+                all_info.bucket_id,
+                all_info.repl_set_id,
+                &[],
+                &add_repl,
+            )?;
+        }
+
+        Ok(meta_client.sync_version()?)
+    }
+
+    /// Waits until the data nodes have the meta data of the version, then the writes are
+    /// routed by the new replication set.
+    async fn wait_for_meta_version(&self, tenant: &str, version: u64) -> CoordinatorResult<()> {
+        let req = AdminStatementRequest {
+            tenant: tenant.to_string(),
+            stmt: AdminStatementType::GetMetaVersion,
+        };
+        let start = Instant::now();
+        for node in self.meta.admin_meta().data_nodes() {
+            if node.id == self.node_id {
+                continue;
+            }
+
+            loop {
+                let node_version = self
+                    .node_clients
+                    .exec_admin_command(node.id, &req)
+                    .await
+                    .map(|rsp| rsp.data.parse::<u64>().unwrap_or_default());
+                match node_version {
+                    Ok(node_version) if node_version >= version => break,
+                    Ok(node_version) => {
+                        info!(
+                            "wait for node {} to sync meta version {}, current: {}",
+                            node.id, version, node_version
+                        );
+                    }
+                    Err(err) => warn!("failed to get meta version of node {}: {}", node.id, err),
+                }
+
+                if start.elapsed() > META_SYNC_TIMEOUT {
+                    return Err(CoordinatorError::CommonError {
+                        msg: format!(
+                            "node {} has not synced meta version {} in {:?}",
+                            node.id, version, META_SYNC_TIMEOUT
+                        ),
+                    });
+                }
+                tokio::time::sleep(META_SYNC_INTERVAL).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn release_snapshot(&self, all_info: &VnodeAllInfo) -> CoordinatorResult<()> {
+        let mut client = self.node_clients.client(all_info.node_id).await?;
+        let mut request = Request::new(ReleaseVnodeSnapshotRequest {
+            version: COORDINATOR_PROTOCOL_VERSION,
+            vnode_id: all_info.vnode_id,
+        });
+        request.set_timeout(ADMIN_REQUEST_TIMEOUT);
+        client
+            .release_vnode_snapshot(request)
+            .await
+            .map_err(|status| status_to_error(all_info.node_id, status))?;
+
+        Ok(())
+    }
+
+    async fn download_vnode_files(
+        &self,
+        all_info: &VnodeAllInfo,
+        files_meta: &PathFilesMeta,
+        data_path: &Path,
+    ) -> CoordinatorResult<()> {
+        for info in files_meta.meta.iter() {
+            let relative_filename = info
+                .name
+                .strip_prefix(&(files_meta.path.clone() + "/"))
+                .ok_or_else(|| CoordinatorError::CommonError {
+                    msg: format!("file {} not in {}", info.name, files_meta.path),
+                })?;
+
+            self.download_file(all_info, relative_filename, data_path)
+                .await?;
+
+            let filename = data_path.join(relative_filename);
+            let filename = filename.to_string_lossy().to_string();
+            let tmp_info = get_file_info(&filename).await?;
+            if tmp_info.md5 != info.md5 {
+                return Err(CoordinatorError::CommonError {
+                    msg: format!("file md5 of {} mismatch", filename),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn download_file(
+        &self,
+        req: &VnodeAllInfo,
+        filename: &str,
+        data_path: &Path,
+    ) -> CoordinatorResult<()> {
+        let mut client = self.node_clients.client(req.node_id).await?;
+        let mut request = Request::new(DownloadFileRequest {
+            version: COORDINATOR_PROTOCOL_VERSION,
+            tenant: req.tenant.clone(),
+            database: req.db_name.clone(),
+            vnode_id: req.vnode_id,
+            filename: filename.to_string(),
+        });
+        request.set_timeout(ADMIN_REQUEST_TIMEOUT);
+
+        let mut stream = client
+            .download_file(request)
+            .await
+            .map_err(|status| status_to_error(req.node_id, status))?
+            .into_inner();
+
+        let filename = data_path.join(filename);
+        tokio::fs::create_dir_all(filename.parent().unwrap()).await?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(&filename)
+            .await?;
+
+        let mut size = 0;
+        while let Some(chunk) = stream
+            .message()
+            .await
+            .map_err(|status| status_to_error(req.node_id, status))?
+        {
+            size += chunk.data.len();
+            file.write_all(&chunk.data).await?;
+        }
+        file.sync_all().await?;
+        info!("save file: {:?}, size; {}", filename, size);
+
+        Ok(())
+    }
+
+    fn migration_file(&self, vnode_id: u32) -> PathBuf {
+        self.kv_inst
+            .get_storage_options()
+            .migration_dir()
+            .join(format!("{}.json", vnode_id))
+    }
+
+    async fn load_migration(&self, vnode_id: u32) -> CoordinatorResult<Option<Migration>> {
+        let path = self.migration_file(vnode_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let data = tokio::fs::read(&path).await?;
+        let migration = serde_json::from_slice::<Migration>(&data)
+            .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() })?;
+
+        Ok(Some(migration))
+    }
+
+    /// Writes a temporary file then renames it, so the saved progress is never partial.
+    async fn save_migration(&self, migration: &Migration) -> CoordinatorResult<()> {
+        let path = self.migration_file(migration.vnode_id);
+        let tmp_path = path.with_extension("tmp");
+        let data = serde_json::to_vec(migration)
+            .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() })?;
+
+        tokio::fs::create_dir_all(path.parent().unwrap()).await?;
+        let mut file = tokio::fs::File::create(&tmp_path).await?;
+        file.write_all(&data).await?;
+        file.sync_all().await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+
+        Ok(())
+    }
+
+    async fn remove_migration(&self, vnode_id: u32) -> CoordinatorResult<()> {
+        tokio::fs::remove_file(self.migration_file(vnode_id)).await?;
+        info!("migration of vnode {} finished", vnode_id);
+
+        Ok(())
+    }
+
+    fn get_vnode_all_info(&self, tenant: &str, vnode_id: u32) -> CoordinatorResult<VnodeAllInfo> {
+        match self.meta.tenant_manager().tenant_meta(tenant) {
+            Some(meta_client) => match meta_client.get_vnode_all_info(vnode_id) {
+                Some(all_info) => Ok(all_info),
+                None => Err(CoordinatorError::VnodeNotFound { id: vnode_id }),
+            },
+
+            None => Err(CoordinatorError::TenantNotFound {
+                name: tenant.to_string(),
+            }),
+        }
+    }
+}
@@ -15,6 +15,12 @@ pub enum Error {
 
     #[snafu(display("Ensure the TLS configuration is correct"))]
     TLSConfigError,
+
+    #[snafu(display("Failed to bind {}: {}", addr, source))]
+    BindAddr {
+        addr: std::net::SocketAddr,
+        source: std::io::Error,
+    },
 }
 
 impl From<tonic::transport::Error> for Error {
@@ -1,2 +0,0 @@
-pub mod tcp_service;
-pub mod vnode_manager;
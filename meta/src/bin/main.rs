@@ -9,7 +9,7 @@ use meta::service::{api, raft_api};
 use meta::store::config::Opt;
 use meta::store::Store;
 use meta::{store, MetaApp, RaftStore};
-use openraft::{Config, Raft};
+use openraft::{Config, Raft, SnapshotPolicy};
 use sled::Db;
 use std::sync::Arc;
 use std::time::Duration;
@@ -35,10 +35,14 @@ pub fn get_sled_db(config: &Opt) -> Db {
 }
 
 pub async fn start_service(opt: Opt) -> std::io::Result<()> {
-    let mut config = Config::default().validate().unwrap();
-    config.heartbeat_interval = 100;
-    config.install_snapshot_timeout = 100000;
-    let config = Arc::new(config);
+    let config = Config {
+        heartbeat_interval: 100,
+        install_snapshot_timeout: 100000,
+        snapshot_policy: SnapshotPolicy::LogsSinceLast(opt.snapshot_per_events as u64),
+        max_in_snapshot_log_to_keep: opt.logs_to_keep,
+        ..Default::default()
+    };
+    let config = Arc::new(config.validate().unwrap());
     let es = get_sled_db(&opt);
     let store = Arc::new(Store::new(es));
 
@@ -70,6 +74,8 @@ pub async fn start_service(opt: Opt) -> std::io::Result<()> {
             .service(api::read)
             .service(api::debug)
             .service(api::watch_tenant)
+            .service(api::heartbeat)
+            .service(api::data_nodes_status)
     })
     .keep_alive(Duration::from_secs(5));
 
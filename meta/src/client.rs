@@ -10,6 +10,7 @@ use openraft::error::RemoteError;
 use openraft::raft::ClientWriteResponse;
 
 use crate::error::{MetaError, MetaResult};
+use models::meta_data::{NodeHeartbeat, NodeStatus};
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
@@ -72,6 +73,31 @@ impl MetaHttpClient {
         Ok(rsp)
     }
 
+    pub fn heartbeat(&self, req: &(String, NodeHeartbeat)) -> MetaResult<StatusResponse> {
+        let rsp: CommandResp = self.send_rpc_to_leader("heartbeat", Some(req))?;
+
+        let rsp = serde_json::from_str::<StatusResponse>(&rsp).map_err(|err| {
+            MetaError::MetaClientErr {
+                msg: err.to_string(),
+            }
+        })?;
+
+        Ok(rsp)
+    }
+
+    pub fn data_nodes_status(&self, cluster: &str) -> MetaResult<Vec<NodeStatus>> {
+        let rsp: CommandResp =
+            self.send_rpc_to_leader("data_nodes_status", Some(&cluster.to_string()))?;
+
+        let rsp = serde_json::from_str::<Vec<NodeStatus>>(&rsp).map_err(|err| {
+            MetaError::MetaClientErr {
+                msg: err.to_string(),
+            }
+        })?;
+
+        Ok(rsp)
+    }
+
     //////////////////////////////////////////////////
 
     fn send_rpc_to_leader<Req, Resp, Err>(
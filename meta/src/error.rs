@@ -119,6 +119,22 @@ pub enum MetaError {
         name: String,
         max: String,
     },
+
+    #[snafu(display("Stream not found: {:?}", stream))]
+    #[error_code(code = 25)]
+    StreamNotFound { stream: String },
+
+    #[snafu(display("Stream {} already exists.", stream))]
+    #[error_code(code = 26)]
+    StreamAlreadyExists { stream: String },
+
+    #[snafu(display("Not Found Meta Node: {}", id))]
+    #[error_code(code = 27)]
+    NotFoundMetaNode { id: u64 },
+
+    #[snafu(display("Meta node {} already exists", id))]
+    #[error_code(code = 28)]
+    MetaNodeAlreadyExists { id: u64 },
     // RaftRPC{
     //     source: RPCError<ClusterNodeId, ClusterNode, Err>
     // }
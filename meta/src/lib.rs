@@ -9,6 +9,7 @@ use std::sync::Arc;
 pub mod client;
 pub mod error;
 pub mod limiter;
+pub mod membership;
 pub mod meta_client;
 pub mod meta_client_mock;
 pub mod service;
@@ -0,0 +1,433 @@
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+use openraft::error::Infallible;
+use openraft::RaftMetrics;
+use serde::{Deserialize, Serialize};
+use trace::{error, info, warn};
+
+use crate::error::{MetaError, MetaResult};
+use crate::{ClusterNode, ClusterNodeId};
+
+type MetaRaftMetrics = RaftMetrics<ClusterNodeId, ClusterNode>;
+
+/// The timeout of the requests to a meta node, an unreachable node is unhealthy.
+const META_NODE_TIMEOUT: Duration = Duration::from_secs(3);
+/// The timeout of the membership changes, adding a learner blocks until it received the logs,
+/// and the learner applies them before it's promoted.
+const MEMBERSHIP_TIMEOUT: Duration = Duration::from_secs(600);
+/// How long to wait for the meta cluster to elect a new leader.
+const ELECT_LEADER_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MetaNodeStatus {
+    pub id: ClusterNodeId,
+    pub addr: String,
+    /// A voter or a learner of the raft group.
+    pub voter: bool,
+    /// The raft state reported by the node, none if the node is unreachable.
+    pub role: Option<String>,
+    pub term: Option<u64>,
+    pub last_log_index: Option<u64>,
+    pub last_applied: Option<u64>,
+    pub healthy: bool,
+}
+
+fn agent(timeout: Duration) -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(timeout).build()
+}
+
+fn client_err(err: impl ToString) -> MetaError {
+    MetaError::MetaClientErr {
+        msg: err.to_string(),
+    }
+}
+
+fn get_metrics(addr: &str) -> MetaResult<MetaRaftMetrics> {
+    let url = format!("http://{}/metrics", addr);
+    let rsp: Result<MetaRaftMetrics, Infallible> = agent(META_NODE_TIMEOUT)
+        .get(&url)
+        .call()
+        .map_err(client_err)?
+        .into_json()
+        .map_err(client_err)?;
+
+    rsp.map_err(client_err)
+}
+
+/// Posts the membership change to the raft api of the meta node,
+/// the errors of raft are returned as is.
+fn post<Req: Serialize>(addr: &str, uri: &str, req: &Req) -> MetaResult<()> {
+    let url = format!("http://{}/{}", addr, uri);
+    let rsp: Result<serde_json::Value, serde_json::Value> = agent(MEMBERSHIP_TIMEOUT)
+        .post(&url)
+        .send_json(req)
+        .map_err(client_err)?
+        .into_json()
+        .map_err(client_err)?;
+
+    rsp.map(|_| ()).map_err(client_err)
+}
+
+/// Runs the blocking requests to the meta nodes out of the async runtime.
+async fn spawn_blocking<T, F>(f: F) -> MetaResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> MetaResult<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await.map_err(client_err)?
+}
+
+/// Returns the metrics of the leader and its address, any meta node works as the seed.
+fn leader(seed: &str) -> MetaResult<(MetaRaftMetrics, String)> {
+    let metrics = get_metrics(seed)?;
+    let leader_id = metrics
+        .current_leader
+        .ok_or_else(|| client_err("the meta cluster has no leader"))?;
+    if leader_id == metrics.id {
+        return Ok((metrics, seed.to_string()));
+    }
+
+    let addr = node_addr(&metrics, leader_id)?;
+    Ok((get_metrics(&addr)?, addr))
+}
+
+fn node_addr(metrics: &MetaRaftMetrics, id: ClusterNodeId) -> MetaResult<String> {
+    metrics
+        .membership_config
+        .membership
+        .get_node(&id)
+        .map(|node| node.api_addr.clone())
+        .ok_or(MetaError::NotFoundMetaNode { id })
+}
+
+fn voters(metrics: &MetaRaftMetrics) -> BTreeSet<ClusterNodeId> {
+    metrics.membership_config.membership.voter_ids().collect()
+}
+
+/// Returns the voters and the learners of the meta cluster with the state they report.
+pub fn meta_nodes(seed: &str) -> MetaResult<Vec<MetaNodeStatus>> {
+    let (leader, _) = leader(seed)?;
+    let membership = &leader.membership_config.membership;
+
+    let mut ids: Vec<(ClusterNodeId, bool)> = membership.voter_ids().map(|id| (id, true)).collect();
+    ids.extend(membership.learner_ids().map(|id| (id, false)));
+    ids.sort_unstable();
+
+    let mut nodes = Vec::with_capacity(ids.len());
+    for (id, voter) in ids {
+        let addr = node_addr(&leader, id)?;
+        let metrics = get_metrics(&addr).ok();
+        nodes.push(MetaNodeStatus {
+            id,
+            addr,
+            voter,
+            role: metrics.as_ref().map(|m| format!("{:?}", m.state)),
+            term: metrics.as_ref().map(|m| m.current_term),
+            last_log_index: metrics.as_ref().and_then(|m| m.last_log_index),
+            last_applied: metrics
+                .as_ref()
+                .and_then(|m| m.last_applied.map(|log| log.index)),
+            healthy: metrics.is_some(),
+        });
+    }
+
+    Ok(nodes)
+}
+
+/// Adds the node as a learner, waits for it to apply the logs of the leader, then promotes it
+/// to a voter. A retry resumes a node left as a learner by a failed attempt.
+pub async fn add_meta_node(seed: &str, id: ClusterNodeId, addr: &str) -> MetaResult<()> {
+    let (seed, addr) = (seed.to_string(), addr.to_string());
+    let leader_addr = spawn_blocking(move || {
+        let (leader, leader_addr) = leader(&seed)?;
+        match node_addr(&leader, id) {
+            Ok(old_addr) if old_addr != addr => {
+                return Err(MetaError::MetaNodeAlreadyExists { id })
+            }
+            Ok(_) if voters(&leader).contains(&id) => {
+                info!("meta node {} {} is already a voter", id, addr);
+                return Ok(None);
+            }
+            Ok(_) => info!(
+                "meta node {} {} is already a learner, resume adding it",
+                id, addr
+            ),
+            Err(_) => {
+                info!("add meta node {} {} as a learner", id, addr);
+                post(&leader_addr, "add-learner", &(id, &addr))?;
+            }
+        }
+
+        Ok(Some(leader_addr))
+    })
+    .await?;
+    let leader_addr = match leader_addr {
+        Some(leader_addr) => leader_addr,
+        None => return Ok(()),
+    };
+
+    wait_for_catch_up(&leader_addr, id).await?;
+
+    spawn_blocking(move || {
+        let (leader, leader_addr) = leader(&leader_addr)?;
+        let mut members = voters(&leader);
+        members.insert(id);
+        info!("change the voters of the meta cluster to {:?}", members);
+        post(&leader_addr, "change-membership", &members)
+    })
+    .await
+}
+
+/// Removes the node from the meta cluster, the remaining voters must still form a quorum.
+pub async fn del_meta_node(seed: &str, id: ClusterNodeId) -> MetaResult<()> {
+    let seed = seed.to_string();
+    spawn_blocking(move || {
+        let (leader, leader_addr) = leader(&seed)?;
+        node_addr(&leader, id)?;
+
+        let mut members = voters(&leader);
+        if members.remove(&id) {
+            if members.is_empty() {
+                return Err(MetaError::CommonError {
+                    msg: format!("meta node {} is the last voter", id),
+                });
+            }
+            check_quorum(&leader, &members)?;
+        }
+
+        info!("change the voters of the meta cluster to {:?}", members);
+        post(&leader_addr, "change-membership", &members)
+    })
+    .await
+}
+
+/// Moves the leadership to another node. The voters elect a new leader only if the leader
+/// leaves them, then the new leader adds the old one back. If the other voters would lose
+/// their quorum without the leader, a healthy learner joins the voters first, and goes back
+/// to a learner at the end unless it was elected. If a step fails, the old leader joins the
+/// voters again.
+pub async fn transfer_meta_leader(seed: &str) -> MetaResult<()> {
+    let seed = seed.to_string();
+    let transfer = spawn_blocking(move || LeaderTransfer::plan(&seed)).await?;
+    if let Err(err) = transfer.run().await {
+        warn!("failed to transfer the meta leader, roll back: {}", err);
+        if let Err(e) = transfer.roll_back().await {
+            error!("failed to roll back the meta leader transfer: {}", e);
+        }
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+struct LeaderTransfer {
+    leader: MetaRaftMetrics,
+    leader_addr: String,
+    /// The learner joining the voters while the leader leaves them,
+    /// none if the other voters form a quorum without the leader.
+    learner: Option<(ClusterNodeId, String)>,
+}
+
+impl LeaderTransfer {
+    fn plan(seed: &str) -> MetaResult<Self> {
+        let (leader, leader_addr) = leader(seed)?;
+        let mut others = voters(&leader);
+        others.remove(&leader.id);
+        if others.is_empty() {
+            return Err(MetaError::CommonError {
+                msg: "no other voter to transfer the meta leader to".to_string(),
+            });
+        }
+        if check_quorum(&leader, &others).is_ok() {
+            return Ok(Self {
+                leader,
+                leader_addr,
+                learner: None,
+            });
+        }
+
+        let learner = leader
+            .membership_config
+            .membership
+            .learner_ids()
+            .filter_map(|id| node_addr(&leader, id).ok().map(|addr| (id, addr)))
+            .find(|(_, addr)| get_metrics(addr).is_ok())
+            .ok_or_else(|| MetaError::CommonError {
+                msg: "the other voters would lose their quorum without the meta leader, \
+                    and no healthy learner can join them"
+                    .to_string(),
+            })?;
+        others.insert(learner.0);
+        check_quorum(&leader, &others)?;
+
+        Ok(Self {
+            leader,
+            leader_addr,
+            learner: Some(learner),
+        })
+    }
+
+    async fn run(&self) -> MetaResult<()> {
+        let transfer = self.clone();
+        spawn_blocking(move || {
+            let mut members = voters(&transfer.leader);
+            if let Some((learner, _)) = &transfer.learner {
+                members.insert(*learner);
+                info!("meta node {} joins the voters {:?}", learner, members);
+                post(&transfer.leader_addr, "change-membership", &members)?;
+            }
+
+            members.remove(&transfer.leader.id);
+            info!("meta leader {} steps down", transfer.leader.id);
+            post(&transfer.leader_addr, "change-membership", &members)
+        })
+        .await?;
+
+        let new_leader_addr = self.wait_for_new_leader().await?;
+        info!("meta node {} joins the voters again", self.leader.id);
+        add_meta_node(&new_leader_addr, self.leader.id, &self.leader_addr).await?;
+
+        match self.learner.clone() {
+            Some((learner, learner_addr)) => {
+                spawn_blocking(move || demote(&new_leader_addr, learner, &learner_addr)).await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the address of the new leader elected by the voters except the old leader.
+    async fn wait_for_new_leader(&self) -> MetaResult<String> {
+        let start = Instant::now();
+        loop {
+            let transfer = self.clone();
+            let elected = spawn_blocking(move || {
+                Ok(transfer
+                    .addrs()
+                    .iter()
+                    .filter_map(|addr| leader(addr).ok())
+                    .find(|(metrics, _)| metrics.id != transfer.leader.id))
+            })
+            .await?;
+            match elected {
+                Some((_, addr)) => return Ok(addr),
+                None if start.elapsed() > ELECT_LEADER_TIMEOUT => {
+                    return Err(MetaError::CommonError {
+                        msg: format!(
+                            "no new meta leader elected after meta node {} stepped down",
+                            self.leader.id
+                        ),
+                    })
+                }
+                None => tokio::time::sleep(POLL_INTERVAL).await,
+            }
+        }
+    }
+
+    /// Makes the old leader a voter again, and the learner a learner again unless it
+    /// has been elected.
+    async fn roll_back(&self) -> MetaResult<()> {
+        let transfer = self.clone();
+        let leader_addr = spawn_blocking(move || {
+            transfer
+                .addrs()
+                .iter()
+                .find_map(|addr| leader(addr).ok())
+                .map(|(_, addr)| addr)
+                .ok_or_else(|| client_err("the meta cluster has no leader"))
+        })
+        .await?;
+
+        add_meta_node(&leader_addr, self.leader.id, &self.leader_addr).await?;
+        match self.learner.clone() {
+            Some((learner, learner_addr)) => {
+                spawn_blocking(move || demote(&leader_addr, learner, &learner_addr)).await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the addresses of the voters and the learner taking part in the transfer.
+    fn addrs(&self) -> Vec<String> {
+        voters(&self.leader)
+            .into_iter()
+            .filter_map(|id| node_addr(&self.leader, id).ok())
+            .chain(self.learner.iter().map(|(_, addr)| addr.clone()))
+            .collect()
+    }
+}
+
+/// Turns the voter back to a learner, unless it's the leader or not a voter.
+fn demote(seed: &str, id: ClusterNodeId, addr: &str) -> MetaResult<()> {
+    let (leader, leader_addr) = leader(seed)?;
+    if leader.id == id {
+        info!("meta node {} is elected, it stays a voter", id);
+        return Ok(());
+    }
+
+    let mut members = voters(&leader);
+    if !members.remove(&id) {
+        return Ok(());
+    }
+    info!("change the voters of the meta cluster to {:?}", members);
+    post(&leader_addr, "change-membership", &members)?;
+    post(&leader_addr, "add-learner", &(id, addr))
+}
+
+/// Waits until the node applied the logs the leader had when the wait started.
+async fn wait_for_catch_up(seed: &str, id: ClusterNodeId) -> MetaResult<()> {
+    let seed = seed.to_string();
+    let (target, addr) = spawn_blocking(move || {
+        let (leader, _) = leader(&seed)?;
+        Ok((
+            leader.last_log_index.unwrap_or_default(),
+            node_addr(&leader, id)?,
+        ))
+    })
+    .await?;
+
+    let start = Instant::now();
+    loop {
+        let node_addr = addr.clone();
+        let applied = spawn_blocking(move || get_metrics(&node_addr))
+            .await
+            .ok()
+            .and_then(|metrics| metrics.last_applied.map(|log| log.index))
+            .unwrap_or_default();
+        if applied >= target {
+            return Ok(());
+        }
+        if start.elapsed() > MEMBERSHIP_TIMEOUT {
+            return Err(MetaError::CommonError {
+                msg: format!(
+                    "meta node {} applied the log {} of {} only, it's left as a learner",
+                    id, applied, target
+                ),
+            });
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Fails if less than a majority of the voters are reachable.
+fn check_quorum(leader: &MetaRaftMetrics, voters: &BTreeSet<ClusterNodeId>) -> MetaResult<()> {
+    let mut healthy = 0;
+    for id in voters.iter() {
+        if get_metrics(&node_addr(leader, *id)?).is_ok() {
+            healthy += 1;
+        }
+    }
+
+    if healthy * 2 <= voters.len() {
+        return Err(MetaError::CommonError {
+            msg: format!(
+                "only {} of the voters {:?} are healthy, the meta cluster would lose its quorum",
+                healthy, voters
+            ),
+        });
+    }
+
+    Ok(())
+}
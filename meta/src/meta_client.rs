@@ -14,14 +14,13 @@ use rand::distributions::{Alphanumeric, DistString};
 use snafu::Snafu;
 
 use std::borrow::BorrowMut;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 use std::ops::DerefMut;
 use std::sync::Arc;
 use std::thread::sleep;
 use std::time::Duration;
 use std::{fmt::Debug, io};
 use store::command;
-use tokio::net::TcpStream;
 
 use trace::{debug, info, warn};
 
@@ -32,6 +31,7 @@ use models::schema::{
 };
 
 use crate::limiter::{Limiter, LimiterImpl, NoneLimiter};
+use crate::membership::{self, MetaNodeStatus};
 use crate::store::command::{
     META_REQUEST_FAILED, META_REQUEST_PRIVILEGE_EXIST, META_REQUEST_PRIVILEGE_NOT_FOUND,
     META_REQUEST_ROLE_EXIST, META_REQUEST_ROLE_NOT_FOUND, META_REQUEST_SUCCESS,
@@ -53,6 +53,8 @@ pub trait MetaManager: Send + Sync + Debug {
     fn user_manager(&self) -> UserManagerRef;
     fn tenant_manager(&self) -> TenantManagerRef;
     fn expired_bucket(&self) -> Vec<ExpiredBucketInfo>;
+    /// Returns the streams of all the tenants.
+    fn streams(&self) -> MetaResult<Vec<StreamInfo>>;
     fn user_with_privileges(&self, user_name: &str, tenant_name: Option<&str>) -> MetaResult<User>;
 }
 
@@ -72,6 +74,7 @@ pub trait TenantManager: Send + Sync + Debug {
     ) -> MetaResult<()>;
 
     fn expired_bucket(&self) -> Vec<ExpiredBucketInfo>;
+    fn streams(&self) -> MetaResult<Vec<StreamInfo>>;
 }
 
 #[async_trait::async_trait]
@@ -79,17 +82,20 @@ pub trait AdminMeta: Send + Sync + Debug {
     // *数据节点上下线管理 */
     fn data_nodes(&self) -> Vec<NodeInfo>;
     fn add_data_node(&self, node: &NodeInfo) -> MetaResult<()>;
-    // fn del_data_node(&self, id: u64) -> MetaResult<()>;
+    fn update_data_node_status(&self, id: u64, status: u64) -> MetaResult<()>;
+    fn del_data_node(&self, id: u64) -> MetaResult<()>;
 
-    // fn meta_nodes(&self);
-    // fn add_meta_node(&self, node: &NodeInfo) -> MetaResult<()>;
-    // fn del_meta_node(&self, id: u64) -> MetaResult<()>;
+    // *元数据节点管理 */
+    fn meta_nodes(&self) -> MetaResult<Vec<MetaNodeStatus>>;
+    async fn add_meta_node(&self, id: u64, addr: &str) -> MetaResult<()>;
+    async fn del_meta_node(&self, id: u64) -> MetaResult<()>;
+    async fn transfer_meta_leader(&self) -> MetaResult<()>;
 
-    fn heartbeat(&self); // update node status
+    /// Reports the load of the node, the nodes without heartbeats are suspect, then dead.
+    fn heartbeat(&self, heartbeat: &NodeHeartbeat) -> MetaResult<()>;
+    fn data_nodes_status(&self) -> MetaResult<Vec<NodeStatus>>;
 
     fn node_info_by_id(&self, id: u64) -> MetaResult<NodeInfo>;
-    async fn get_node_conn(&self, node_id: u64) -> MetaResult<TcpStream>;
-    fn put_node_conn(&self, node_id: u64, conn: TcpStream);
     fn retain_id(&self, count: u32) -> MetaResult<u32>;
 }
 
@@ -153,6 +159,12 @@ pub trait MetaClient: Send + Sync + Debug {
     fn create_bucket(&self, db: &str, ts: i64) -> MetaResult<BucketInfo>;
     fn delete_bucket(&self, db: &str, id: u32) -> MetaResult<()>;
 
+    fn create_stream(&self, stream: &StreamInfo) -> MetaResult<()>;
+    fn get_stream(&self, db: &str, name: &str) -> MetaResult<Option<StreamInfo>>;
+    fn list_streams(&self) -> Vec<StreamInfo>;
+    fn drop_stream(&self, db: &str, name: &str) -> MetaResult<()>;
+    fn update_stream_watermark(&self, db: &str, name: &str, watermark: i64) -> MetaResult<()>;
+
     fn database_min_ts(&self, db: &str) -> Option<i64>;
     fn expired_bucket(&self) -> Vec<ExpiredBucketInfo>;
 
@@ -177,6 +189,10 @@ pub trait MetaClient: Send + Sync + Debug {
     ) -> MetaResult<()>;
 
     fn print_data(&self) -> String;
+    /// Returns the version of the meta data of the tenant cached on this node.
+    fn version(&self) -> u64;
+    /// Syncs the meta data of the tenant from the meta service, returns the version of it.
+    fn sync_version(&self) -> MetaResult<u64>;
 
     fn limiter(&self) -> Arc<dyn Limiter>;
 }
@@ -247,6 +263,10 @@ impl MetaManager for RemoteMetaManager {
         self.tenant_manager.expired_bucket()
     }
 
+    fn streams(&self) -> MetaResult<Vec<StreamInfo>> {
+        self.tenant_manager.streams()
+    }
+
     fn user_with_privileges(&self, user_name: &str, tenant_name: Option<&str>) -> MetaResult<User> {
         let user_desc =
             self.user_manager
@@ -299,7 +319,6 @@ pub struct RemoteAdminMeta {
     cluster: String,
     meta_url: String,
     data_nodes: RwLock<HashMap<u64, NodeInfo>>,
-    conn_map: RwLock<HashMap<u64, VecDeque<TcpStream>>>,
 
     client: MetaHttpClient,
 }
@@ -309,7 +328,6 @@ impl RemoteAdminMeta {
         Self {
             cluster,
             meta_url: meta_url.clone(),
-            conn_map: RwLock::new(HashMap::new()),
             data_nodes: RwLock::new(HashMap::new()),
             client: MetaHttpClient::new(1, meta_url),
         }
@@ -320,6 +338,7 @@ impl RemoteAdminMeta {
 
         if let Ok(val) = sys_info::disk_info() {
             info.disk_free = val.free;
+            info.disk_total = val.total;
         }
 
         if let Ok(val) = sys_info::mem_info() {
@@ -348,11 +367,43 @@ impl AdminMeta for RemoteAdminMeta {
         Ok(())
     }
 
+    fn update_data_node_status(&self, id: u64, status: u64) -> MetaResult<()> {
+        let req = command::WriteCommand::UpdateDataNodeStatus(self.cluster.clone(), id, status);
+        let rsp = self.client.write::<command::StatusResponse>(&req)?;
+        if rsp.code != command::META_REQUEST_SUCCESS {
+            return Err(MetaError::CommonError {
+                msg: format!("update data node err: {} {}", rsp.code, rsp.msg),
+            });
+        }
+
+        if let Some(node) = self.data_nodes.write().get_mut(&id) {
+            node.status = status;
+        }
+
+        Ok(())
+    }
+
+    fn del_data_node(&self, id: u64) -> MetaResult<()> {
+        let req = command::WriteCommand::DelDataNode(self.cluster.clone(), id);
+        let rsp = self.client.write::<command::StatusResponse>(&req)?;
+        if rsp.code != command::META_REQUEST_SUCCESS {
+            return Err(MetaError::CommonError {
+                msg: format!("del data node err: {} {}", rsp.code, rsp.msg),
+            });
+        }
+
+        self.data_nodes.write().remove(&id);
+
+        Ok(())
+    }
+
     fn data_nodes(&self) -> Vec<NodeInfo> {
         let req = command::ReadCommand::DataNodes(self.cluster.clone());
         let resp = self.client.read::<Vec<NodeInfo>>(&req).unwrap();
         {
+            // the nodes removed from the cluster are dropped from the cache
             let mut nodes = self.data_nodes.write();
+            nodes.clear();
             for item in resp.iter() {
                 nodes.insert(item.id, item.clone());
             }
@@ -387,35 +438,6 @@ impl AdminMeta for RemoteAdminMeta {
         Err(MetaError::NotFoundNode { id })
     }
 
-    async fn get_node_conn(&self, node_id: u64) -> MetaResult<TcpStream> {
-        {
-            let mut write = self.conn_map.write();
-            let entry = write
-                .entry(node_id)
-                .or_insert_with(|| VecDeque::with_capacity(32));
-            if let Some(val) = entry.pop_front() {
-                return Ok(val);
-            }
-        }
-
-        let info = self.node_info_by_id(node_id)?;
-        let client = TcpStream::connect(info.tcp_addr).await?;
-
-        return Ok(client);
-    }
-
-    fn put_node_conn(&self, node_id: u64, conn: TcpStream) {
-        let mut write = self.conn_map.write();
-        let entry = write
-            .entry(node_id)
-            .or_insert_with(|| VecDeque::with_capacity(32));
-
-        // close too more idle connection
-        if entry.len() < 32 {
-            entry.push_back(conn);
-        }
-    }
-
     fn retain_id(&self, count: u32) -> MetaResult<u32> {
         let req = command::WriteCommand::RetainID(self.cluster.clone(), count);
         let rsp = self.client.write::<command::StatusResponse>(&req)?;
@@ -435,7 +457,38 @@ impl AdminMeta for RemoteAdminMeta {
         Ok(id)
     }
 
-    fn heartbeat(&self) {}
+    fn heartbeat(&self, heartbeat: &NodeHeartbeat) -> MetaResult<()> {
+        let rsp = self
+            .client
+            .heartbeat(&(self.cluster.clone(), heartbeat.clone()))?;
+        if rsp.code != command::META_REQUEST_SUCCESS {
+            return Err(MetaError::CommonError {
+                msg: format!("heartbeat err: {} {}", rsp.code, rsp.msg),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn data_nodes_status(&self) -> MetaResult<Vec<NodeStatus>> {
+        self.client.data_nodes_status(&self.cluster)
+    }
+
+    fn meta_nodes(&self) -> MetaResult<Vec<MetaNodeStatus>> {
+        membership::meta_nodes(&self.meta_url)
+    }
+
+    async fn add_meta_node(&self, id: u64, addr: &str) -> MetaResult<()> {
+        membership::add_meta_node(&self.meta_url, id, addr).await
+    }
+
+    async fn del_meta_node(&self, id: u64) -> MetaResult<()> {
+        membership::del_meta_node(&self.meta_url, id).await
+    }
+
+    async fn transfer_meta_leader(&self) -> MetaResult<()> {
+        membership::transfer_meta_leader(&self.meta_url).await
+    }
 }
 
 #[derive(Debug)]
@@ -1031,6 +1084,101 @@ impl MetaClient for RemoteMetaClient {
         }
     }
 
+    fn create_stream(&self, stream: &StreamInfo) -> MetaResult<()> {
+        let req = command::WriteCommand::CreateStream(
+            self.cluster.clone(),
+            self.tenant_name(),
+            stream.clone(),
+        );
+
+        let rsp = self.client.write::<command::TenaneMetaDataResp>(&req)?;
+        info!("create stream: {:?}; {:?}", req, rsp.status);
+        {
+            let mut data = self.data.write();
+            if rsp.data.version > data.version {
+                *data = rsp.data;
+            }
+        }
+
+        if rsp.status.code == command::META_REQUEST_SUCCESS {
+            Ok(())
+        } else if rsp.status.code == command::META_REQUEST_DB_NOT_FOUND {
+            Err(MetaError::DatabaseNotFound {
+                database: stream.db.clone(),
+            })
+        } else if rsp.status.code == command::META_REQUEST_STREAM_EXIST {
+            Err(MetaError::StreamAlreadyExists {
+                stream: stream.name.clone(),
+            })
+        } else {
+            Err(MetaError::CommonError {
+                msg: rsp.status.to_string(),
+            })
+        }
+    }
+
+    fn get_stream(&self, db: &str, name: &str) -> MetaResult<Option<StreamInfo>> {
+        Ok(self
+            .data
+            .read()
+            .dbs
+            .get(db)
+            .and_then(|info| info.streams.get(name).cloned()))
+    }
+
+    fn list_streams(&self) -> Vec<StreamInfo> {
+        self.data.read().streams()
+    }
+
+    fn drop_stream(&self, db: &str, name: &str) -> MetaResult<()> {
+        let req = command::WriteCommand::DropStream(
+            self.cluster.clone(),
+            self.tenant_name(),
+            db.to_string(),
+            name.to_string(),
+        );
+
+        let rsp = self.client.write::<command::StatusResponse>(&req)?;
+        info!("drop stream: {:?}; {:?}", req, rsp);
+
+        if rsp.code == command::META_REQUEST_SUCCESS {
+            Ok(())
+        } else if rsp.code == command::META_REQUEST_STREAM_NOT_FOUND {
+            Err(MetaError::StreamNotFound {
+                stream: name.to_string(),
+            })
+        } else {
+            Err(MetaError::CommonError {
+                msg: rsp.to_string(),
+            })
+        }
+    }
+
+    fn update_stream_watermark(&self, db: &str, name: &str, watermark: i64) -> MetaResult<()> {
+        let req = command::WriteCommand::UpdateStreamWatermark(
+            self.cluster.clone(),
+            self.tenant_name(),
+            db.to_string(),
+            name.to_string(),
+            watermark,
+        );
+
+        let rsp = self.client.write::<command::StatusResponse>(&req)?;
+        debug!("update stream watermark: {:?}; {:?}", req, rsp);
+
+        if rsp.code == command::META_REQUEST_SUCCESS {
+            Ok(())
+        } else if rsp.code == command::META_REQUEST_STREAM_NOT_FOUND {
+            Err(MetaError::StreamNotFound {
+                stream: name.to_string(),
+            })
+        } else {
+            Err(MetaError::CommonError {
+                msg: rsp.to_string(),
+            })
+        }
+    }
+
     fn update_replication_set(
         &self,
         db: &str,
@@ -1143,6 +1291,16 @@ impl MetaClient for RemoteMetaClient {
         format!("{:#?}", self.data.read())
     }
 
+    fn version(&self) -> u64 {
+        self.data.read().version
+    }
+
+    fn sync_version(&self) -> MetaResult<u64> {
+        self.sync_all_tenant_metadata()?;
+
+        Ok(self.version())
+    }
+
     fn limiter(&self) -> Arc<dyn Limiter> {
         self.limiter.clone()
     }
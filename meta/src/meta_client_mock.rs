@@ -11,7 +11,10 @@ use models::{
         role::{CustomTenantRole, SystemTenantRole, TenantRole, TenantRoleIdentifier},
         user::UserDesc,
     },
-    meta_data::{BucketInfo, DatabaseInfo, ExpiredBucketInfo, NodeInfo, ReplicationSet},
+    meta_data::{
+        BucketInfo, DatabaseInfo, ExpiredBucketInfo, NodeHeartbeat, NodeInfo, NodeStatus,
+        ReplicationSet, StreamInfo,
+    },
     oid::Oid,
     schema::{
         DatabaseSchema, ExternalTableSchema, TableSchema, Tenant, TenantOptions, TskvTableSchema,
@@ -19,10 +22,10 @@ use models::{
 };
 use models::{limiter::LimiterConfig, meta_data::VnodeInfo};
 use models::{meta_data::VnodeAllInfo, oid::Identifier};
-use tokio::net::TcpStream;
 
 use crate::error::{MetaError, MetaResult};
 use crate::limiter::{Limiter, LimiterImpl};
+use crate::membership::MetaNodeStatus;
 use crate::{
     meta_client::{
         AdminMeta, AdminMetaRef, MetaClient, MetaClientRef, MetaManager, TenantManager,
@@ -39,6 +42,14 @@ impl AdminMeta for MockAdminMeta {
         Ok(())
     }
 
+    fn update_data_node_status(&self, id: u64, status: u64) -> MetaResult<()> {
+        Ok(())
+    }
+
+    fn del_data_node(&self, id: u64) -> MetaResult<()> {
+        Ok(())
+    }
+
     fn data_nodes(&self) -> Vec<NodeInfo> {
         vec![]
     }
@@ -47,15 +58,29 @@ impl AdminMeta for MockAdminMeta {
         Ok(NodeInfo::default())
     }
 
-    async fn get_node_conn(&self, node_id: u64) -> MetaResult<TcpStream> {
-        Err(MetaError::CommonError {
-            msg: "mock not implement node conn".to_string(),
-        })
+    fn heartbeat(&self, heartbeat: &NodeHeartbeat) -> MetaResult<()> {
+        Ok(())
+    }
+
+    fn data_nodes_status(&self) -> MetaResult<Vec<NodeStatus>> {
+        Ok(vec![])
+    }
+
+    fn meta_nodes(&self) -> MetaResult<Vec<MetaNodeStatus>> {
+        Ok(vec![])
+    }
+
+    async fn add_meta_node(&self, id: u64, addr: &str) -> MetaResult<()> {
+        Ok(())
     }
 
-    fn put_node_conn(&self, node_id: u64, conn: TcpStream) {}
+    async fn del_meta_node(&self, id: u64) -> MetaResult<()> {
+        Ok(())
+    }
 
-    fn heartbeat(&self) {}
+    async fn transfer_meta_leader(&self) -> MetaResult<()> {
+        Ok(())
+    }
 
     fn retain_id(&self, count: u32) -> MetaResult<u32> {
         Ok(0)
@@ -159,6 +184,14 @@ impl MetaClient for MockMetaClient {
         "".to_string()
     }
 
+    fn version(&self) -> u64 {
+        0
+    }
+
+    fn sync_version(&self) -> MetaResult<u64> {
+        Ok(0)
+    }
+
     fn add_member_with_role(&self, user_id: Oid, role: TenantRoleIdentifier) -> MetaResult<()> {
         todo!()
     }
@@ -220,6 +253,26 @@ impl MetaClient for MockMetaClient {
         vec![]
     }
 
+    fn create_stream(&self, stream: &StreamInfo) -> MetaResult<()> {
+        Ok(())
+    }
+
+    fn get_stream(&self, db: &str, name: &str) -> MetaResult<Option<StreamInfo>> {
+        Ok(None)
+    }
+
+    fn list_streams(&self) -> Vec<StreamInfo> {
+        vec![]
+    }
+
+    fn drop_stream(&self, db: &str, name: &str) -> MetaResult<()> {
+        Ok(())
+    }
+
+    fn update_stream_watermark(&self, db: &str, name: &str, watermark: i64) -> MetaResult<()> {
+        Ok(())
+    }
+
     fn update_replication_set(
         &self,
         db: &str,
@@ -256,6 +309,10 @@ impl MetaManager for MockMetaManager {
         vec![]
     }
 
+    fn streams(&self) -> MetaResult<Vec<StreamInfo>> {
+        Ok(vec![])
+    }
+
     fn admin_meta(&self) -> AdminMetaRef {
         Arc::new(MockAdminMeta::default())
     }
@@ -305,6 +362,10 @@ impl TenantManager for TenantManagerMock {
         vec![]
     }
 
+    fn streams(&self) -> MetaResult<Vec<StreamInfo>> {
+        Ok(vec![])
+    }
+
     fn tenants(&self) -> MetaResult<Vec<Tenant>> {
         todo!()
     }
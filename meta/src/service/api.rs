@@ -3,14 +3,19 @@ use actix_web::post;
 use actix_web::web;
 use actix_web::web::Data;
 use actix_web::Responder;
+use models::meta_data::NodeHeartbeat;
+use openraft::error::ClientWriteError;
+use openraft::error::ForwardToLeader;
 use openraft::error::Infallible;
 use tokio::sync::mpsc;
+use tokio::sync::RwLockWriteGuard;
 use web::Json;
 
 use crate::store::command::*;
 use crate::store::state_machine::CommandResp;
+use crate::store::state_machine::NodeHeartbeats;
 use crate::store::state_machine::WatchTenantMetaData;
-use crate::MetaApp;
+use crate::{ClusterNode, ClusterNodeId, MetaApp};
 
 #[post("/read")]
 pub async fn read(app: Data<MetaApp>, req: Json<ReadCommand>) -> actix_web::Result<impl Responder> {
@@ -31,6 +36,57 @@ pub async fn write(
     Ok(Json(response))
 }
 
+/// The heartbeats are kept in the memory of the leader instead of the raft log,
+/// the other meta nodes forward the data nodes to the leader.
+async fn leader_heartbeats(
+    app: &MetaApp,
+) -> Result<RwLockWriteGuard<'_, NodeHeartbeats>, ClientWriteError<ClusterNodeId, ClusterNode>> {
+    let metrics = app.raft.metrics().borrow().clone();
+    if metrics.current_leader != Some(app.id) {
+        let leader_node = metrics
+            .current_leader
+            .and_then(|id| metrics.membership_config.membership.get_node(&id).cloned());
+        return Err(ClientWriteError::ForwardToLeader(ForwardToLeader {
+            leader_id: metrics.current_leader,
+            leader_node,
+        }));
+    }
+
+    let mut heartbeats = app.store.heartbeats.write().await;
+    heartbeats.renew_term(metrics.current_term);
+    Ok(heartbeats)
+}
+
+#[post("/heartbeat")]
+pub async fn heartbeat(
+    app: Data<MetaApp>,
+    req: Json<(String, NodeHeartbeat)>, // cluster, heartbeat
+) -> actix_web::Result<impl Responder> {
+    let (cluster, heartbeat) = req.0;
+    let response = leader_heartbeats(&app).await.map(|mut heartbeats| {
+        heartbeats.receive(&cluster, &heartbeat);
+        serde_json::to_string(&StatusResponse::default()).unwrap()
+    });
+
+    Ok(Json(response))
+}
+
+#[post("/data_nodes_status")]
+pub async fn data_nodes_status(
+    app: Data<MetaApp>,
+    req: Json<String>, // cluster
+) -> actix_web::Result<impl Responder> {
+    let response = match leader_heartbeats(&app).await {
+        Ok(heartbeats) => {
+            let sm = app.store.state_machine.read().await;
+            Ok(serde_json::to_string(&sm.data_nodes_status(&req.0, &heartbeats)).unwrap())
+        }
+        Err(err) => Err(err),
+    };
+
+    Ok(Json(response))
+}
+
 #[post("/watch_tenant")]
 pub async fn watch_tenant(
     app: Data<MetaApp>,
@@ -36,6 +36,10 @@ pub enum WriteCommand {
 
     // cluster, node info
     AddDataNode(String, NodeInfo),
+    // cluster, node id, status
+    UpdateDataNodeStatus(String, u64, u64),
+    // cluster, node id
+    DelDataNode(String, u64),
 
     // cluster, tenant, db schema
     CreateDB(String, String, DatabaseSchema),
@@ -58,6 +62,13 @@ pub enum WriteCommand {
     // cluster, tenant, db name, table name
     DropTable(String, String, String, String),
 
+    // cluster, tenant, stream info
+    CreateStream(String, String, StreamInfo),
+    // cluster, tenant, db name, stream name
+    DropStream(String, String, String, String),
+    // cluster, tenant, db name, stream name, watermark
+    UpdateStreamWatermark(String, String, String, String, i64),
+
     // cluster, user_name, user_options, is_admin
     CreateUser(String, String, UserOptions, bool),
     // cluster, user_id, user_options
@@ -142,6 +153,8 @@ pub const META_REQUEST_ROLE_NOT_FOUND: i32 = 8;
 pub const META_REQUEST_PRIVILEGE_EXIST: i32 = 9;
 pub const META_REQUEST_PRIVILEGE_NOT_FOUND: i32 = 10;
 pub const META_REQUEST_DB_NOT_FOUND: i32 = 11;
+pub const META_REQUEST_STREAM_EXIST: i32 = 12;
+pub const META_REQUEST_STREAM_NOT_FOUND: i32 = 13;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct StatusResponse {
@@ -297,6 +310,40 @@ impl TenantMetaDataDelta {
         );
     }
 
+    pub fn create_or_update_stream(&mut self, ver: u64, stream: &StreamInfo) {
+        self.update_version(ver);
+
+        let db_info = self
+            .update
+            .dbs
+            .entry(stream.db.clone())
+            .or_insert_with(DatabaseInfo::default);
+
+        db_info.streams.insert(stream.name.clone(), stream.clone());
+
+        if let Some(info) = self.delete.dbs.get_mut(&stream.db) {
+            info.streams.remove(&stream.name);
+        }
+    }
+
+    pub fn delete_stream(&mut self, ver: u64, db: &str, stream: &str) {
+        self.update_version(ver);
+
+        if let Some(info) = self.update.dbs.get_mut(db) {
+            info.streams.remove(stream);
+        }
+
+        let db_info = self
+            .delete
+            .dbs
+            .entry(db.to_string())
+            .or_insert_with(DatabaseInfo::default);
+
+        db_info
+            .streams
+            .insert(stream.to_string(), StreamInfo::default());
+    }
+
     pub fn create_or_update_bucket(&mut self, ver: u64, db: &str, bucket: &BucketInfo) {
         self.update_version(ver);
 
@@ -39,9 +39,15 @@ pub struct Opt {
     )]
     pub journal_path: String,
 
+    /// Builds a snapshot every this many logs applied since the last snapshot.
     #[clap(long, env = "RAFT_SNAPSHOT_PER_EVENTS", default_value = "500")]
     pub snapshot_per_events: u32,
 
+    /// The logs kept after a snapshot, a lagging node within them catches up
+    /// from the logs instead of installing the snapshot.
+    #[clap(long, env = "RAFT_LOGS_TO_KEEP", default_value = "1000")]
+    pub logs_to_keep: u64,
+
     #[clap(long, env = "META_LOGS_PATH", default_value = "/tmp/cnosdb/logs")]
     pub logs_path: String,
 
@@ -6,6 +6,7 @@ use models::oid::Oid;
 // **    /cluster_name/tenant_name/dbs/db_name -> [DatabaseInfo] db相关信息、保留策略等
 // **    /cluster_name/tenant_name/dbs/db_name/buckets/id -> [BucketInfo] bucket相关信息
 // **    /cluster_name/tenant_name/dbs/db_name/schemas/name -> [TskvTableSchema] schema相关信息
+// **    /cluster_name/tenant_name/dbs/db_name/streams/name -> [StreamInfo] 流计算定义及处理进度
 pub struct KeyPath {}
 
 impl KeyPath {
@@ -57,6 +58,14 @@ impl KeyPath {
         format!("/{}/{}/dbs/{}/schemas/{}", cluster, tenant, db, name)
     }
 
+    pub fn tenant_streams(cluster: &str, tenant: &str, db: &str) -> String {
+        format!("/{}/{}/dbs/{}/streams", cluster, tenant, db)
+    }
+
+    pub fn tenant_stream_name(cluster: &str, tenant: &str, db: &str, name: &str) -> String {
+        format!("/{}/{}/dbs/{}/streams/{}", cluster, tenant, db, name)
+    }
+
     pub fn users(cluster: &str) -> String {
         format!("/{}/users", cluster)
     }
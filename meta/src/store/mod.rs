@@ -37,9 +37,8 @@ pub mod config;
 mod key_path;
 mod sled_store;
 pub mod state_machine;
-pub mod store;
 
-use self::state_machine::{StateMachine, WatchTenantMetaData};
+use self::state_machine::{NodeHeartbeats, StateMachine, WatchTenantMetaData};
 
 #[derive(serde::Deserialize, serde::Serialize, Debug)]
 pub struct SnapshotInfo {
@@ -54,6 +53,7 @@ pub struct Store {
     /// The Raft state machine.
     pub state_machine: RwLock<StateMachine>,
     pub watch: RwLock<HashMap<String, WatchTenantMetaData>>,
+    pub heartbeats: RwLock<NodeHeartbeats>,
 }
 
 fn store(db: &sled::Db) -> sled::Tree {
@@ -62,9 +62,6 @@ fn store(db: &sled::Db) -> sled::Tree {
 fn logs(db: &sled::Db) -> sled::Tree {
     db.open_tree("logs").expect("logs open failed")
 }
-fn data(db: &sled::Db) -> sled::Tree {
-    db.open_tree("data").expect("data open failed")
-}
 // fn state_machine(db: &sled::Db) -> sled::Tree {
 //     db.open_tree("state_machine")
 //         .expect("state_machine open failed")
@@ -77,6 +74,7 @@ impl Store {
             db,
             state_machine: RwLock::new(sm),
             watch: RwLock::new(HashMap::new()),
+            heartbeats: RwLock::new(NodeHeartbeats::default()),
         }
     }
     fn get_last_purged_(&self) -> StorageIOResult<Option<LogId<u64>>> {
@@ -484,6 +482,10 @@ impl RaftStorage<TypeConfig> for Arc<Store> {
             *state_machine = StateMachine::from_serializable(content, self.db.clone()).await?;
         }
 
+        // the deltas of the watchers miss the changes in the snapshot,
+        // the watchers reload the full meta data when they come back.
+        self.watch.write().await.clear();
+
         self.set_current_snapshot_(new_snapshot).await?;
         Ok(())
     }
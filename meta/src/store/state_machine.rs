@@ -29,7 +29,6 @@ use std::sync::Arc;
 use trace::info;
 
 use crate::error::{l_r_err, s_w_err, sm_r_err, sm_w_err, StorageIOResult};
-use crate::store::data;
 use crate::store::key_path::KeyPath;
 use models::{meta_data::*, utils};
 
@@ -130,6 +129,44 @@ pub struct WatchTenantMetaData {
     pub delta: TenantMetaDataDelta,
 }
 
+/// The last heartbeats of the data nodes, kept in the memory of the leader
+/// instead of the raft log. They are dropped when the term changes,
+/// the new leader judges the nodes by the time it became the leader.
+#[derive(Debug, Default)]
+pub struct NodeHeartbeats {
+    term: u64,
+    since: i64,
+    heartbeats: HashMap<(String, u64), NodeHeartbeat>,
+}
+
+impl NodeHeartbeats {
+    /// Starts over if the leader of the term is new.
+    pub fn renew_term(&mut self, term: u64) {
+        if self.since == 0 || self.term != term {
+            self.term = term;
+            self.since = utils::now_timestamp();
+            self.heartbeats.clear();
+        }
+    }
+
+    /// The heartbeat is stamped with the time of the leader.
+    pub fn receive(&mut self, cluster: &str, heartbeat: &NodeHeartbeat) {
+        let mut heartbeat = heartbeat.clone();
+        heartbeat.time = utils::now_timestamp();
+        self.heartbeats
+            .insert((cluster.to_string(), heartbeat.node_id), heartbeat);
+    }
+
+    pub fn get(&self, cluster: &str, node_id: u64) -> Option<&NodeHeartbeat> {
+        self.heartbeats.get(&(cluster.to_string(), node_id))
+    }
+
+    /// The time the leader started to receive the heartbeats.
+    pub fn since(&self) -> i64 {
+        self.since
+    }
+}
+
 impl WatchTenantMetaData {
     pub fn interesting(&self, cluster: &str, tenant: &str) -> bool {
         if self.cluster == cluster && self.tenant == tenant {
@@ -175,8 +212,10 @@ impl StateMachine {
     pub(crate) fn new(db: Arc<sled::Db>) -> StateMachine {
         Self { db }
     }
+    /// The meta data is kept in the default tree of the db.
     fn data(&self) -> sled::Tree {
-        self.db.open_tree("data").expect("data open failed")
+        let tree: &sled::Tree = &self.db;
+        tree.clone()
     }
     fn state_machine(&self) -> sled::Tree {
         self.db
@@ -271,19 +310,25 @@ impl StateMachine {
     //         .map_err(ct_err)?;
     //     Ok(())
     // }
+    /// Replaces the meta data with the content of the snapshot.
     pub(crate) async fn from_serializable(
         sm: StateMachineContent,
         db: Arc<sled::Db>,
     ) -> StorageIOResult<Self> {
-        let data_tree = data(&db);
+        let r = Self { db };
+
+        // the keys deleted before the snapshot must not survive the installation
+        let data_tree = r.data();
         let mut batch = sled::Batch::default();
+        for key in data_tree.iter().keys() {
+            batch.remove(key.map_err(sm_r_err)?);
+        }
         for (key, value) in sm.data {
             batch.insert(key.as_bytes(), value.as_bytes())
         }
         data_tree.apply_batch(batch).map_err(sm_w_err)?;
         data_tree.flush_async().await.map_err(s_w_err)?;
 
-        let r = Self { db };
         if let Some(log_id) = sm.last_applied_log {
             r.set_last_applied_log(log_id).await?;
         }
@@ -355,8 +400,14 @@ impl StateMachine {
                 self.db.clone(),
             );
 
+            let streams = children_data::<StreamInfo>(
+                &KeyPath::tenant_streams(cluster, tenant, key),
+                self.db.clone(),
+            );
+
             let info = DatabaseInfo {
                 tables,
+                streams,
                 schema: schema.clone(),
                 buckets: buckets.into_values().collect(),
             };
@@ -491,6 +542,12 @@ impl StateMachine {
 
             WriteCommand::AddDataNode(cluster, node) => self.process_add_date_node(cluster, node),
 
+            WriteCommand::UpdateDataNodeStatus(cluster, id, status) => {
+                self.process_update_data_node_status(cluster, *id, *status)
+            }
+
+            WriteCommand::DelDataNode(cluster, id) => self.process_del_data_node(cluster, *id),
+
             WriteCommand::CreateDB(cluster, tenant, schema) => {
                 self.process_create_db(cluster, tenant, schema, watch)
             }
@@ -523,6 +580,18 @@ impl StateMachine {
                 self.process_delete_bucket(cluster, tenant, db, *id, watch)
             }
 
+            WriteCommand::CreateStream(cluster, tenant, stream) => {
+                self.process_create_stream(cluster, tenant, stream, watch)
+            }
+
+            WriteCommand::DropStream(cluster, tenant, db, name) => {
+                self.process_drop_stream(cluster, tenant, db, name, watch)
+            }
+
+            WriteCommand::UpdateStreamWatermark(cluster, tenant, db, name, watermark) => {
+                self.process_update_stream_watermark(cluster, tenant, db, name, *watermark, watch)
+            }
+
             WriteCommand::CreateUser(cluster, name, options, is_admin) => {
                 self.process_create_user(cluster, name, options, *is_admin)
             }
@@ -635,13 +704,72 @@ impl StateMachine {
 
     fn process_add_date_node(&self, cluster: &str, node: &NodeInfo) -> CommandResp {
         let key = KeyPath::data_node_id(cluster, node.id);
-        let value = serde_json::to_string(node).unwrap();
+
+        // a decommissioning node stays decommissioning after it restarts
+        let mut node = node.clone();
+        if let Some(old) = get_struct::<NodeInfo>(&key, self.db.clone()) {
+            node.status = old.status;
+        }
+
+        let value = serde_json::to_string(&node).unwrap();
         let _ = self.db.insert(key.as_bytes(), value.as_bytes());
         info!("WRITE: {} :{}", key, value);
 
         serde_json::to_string(&StatusResponse::default()).unwrap()
     }
 
+    /// The nodes that sent no heartbeat to the leader are suspect, then dead
+    /// if the leader has been receiving the heartbeats for long enough.
+    pub fn data_nodes_status(&self, cluster: &str, heartbeats: &NodeHeartbeats) -> Vec<NodeStatus> {
+        let now = utils::now_timestamp();
+        children_data::<NodeInfo>(&KeyPath::data_nodes(cluster), self.db.clone())
+            .into_values()
+            .map(|info| {
+                let heartbeat = heartbeats.get(cluster, info.id).cloned();
+                let state = match heartbeat.as_ref() {
+                    Some(heartbeat) => NodeState::from_heartbeat(heartbeat.time, now),
+                    None => match NodeState::from_heartbeat(heartbeats.since(), now) {
+                        NodeState::Alive => NodeState::Suspect,
+                        state => state,
+                    },
+                };
+
+                NodeStatus {
+                    info,
+                    heartbeat,
+                    state,
+                }
+            })
+            .collect()
+    }
+
+    fn process_update_data_node_status(&self, cluster: &str, id: u64, status: u64) -> CommandResp {
+        let key = KeyPath::data_node_id(cluster, id);
+        let mut node = match get_struct::<NodeInfo>(&key, self.db.clone()) {
+            Some(node) => node,
+            None => {
+                let status =
+                    StatusResponse::new(META_REQUEST_FAILED, format!("data node {} not found", id));
+                return serde_json::to_string(&status).unwrap();
+            }
+        };
+
+        node.status = status;
+        let value = serde_json::to_string(&node).unwrap();
+        let _ = self.db.insert(key.as_bytes(), value.as_bytes());
+        info!("WRITE: {} :{}", key, value);
+
+        serde_json::to_string(&StatusResponse::default()).unwrap()
+    }
+
+    fn process_del_data_node(&self, cluster: &str, id: u64) -> CommandResp {
+        let key = KeyPath::data_node_id(cluster, id);
+        let _ = self.db.remove(&key);
+        info!("DELETE: {}", key);
+
+        serde_json::to_string(&StatusResponse::default()).unwrap()
+    }
+
     fn process_drop_db(
         &self,
         cluster: &str,
@@ -662,6 +790,11 @@ impl StateMachine {
             let _ = self.db.remove(it);
         }
 
+        let streams_path = KeyPath::tenant_streams(cluster, tenant, db_name);
+        for it in children_fullpath(&streams_path, self.db.clone()).iter() {
+            let _ = self.db.remove(it);
+        }
+
         for (_, item) in watch.iter_mut() {
             if item.interesting(cluster, tenant) {
                 item.delta.delete_db(self.version(), db_name);
@@ -893,6 +1026,7 @@ impl StateMachine {
         let node_list: Vec<NodeInfo> =
             children_data::<NodeInfo>(&KeyPath::data_nodes(cluster), self.db.clone())
                 .into_values()
+                .filter(|node| !node.is_decommissioning())
                 .collect();
 
         let now = utils::now_timestamp();
@@ -980,6 +1114,120 @@ impl StateMachine {
         StatusResponse::new(META_REQUEST_SUCCESS, "".to_string()).to_string()
     }
 
+    fn process_create_stream(
+        &self,
+        cluster: &str,
+        tenant: &str,
+        stream: &StreamInfo,
+        watch: &mut HashMap<String, WatchTenantMetaData>,
+    ) -> CommandResp {
+        let key = KeyPath::tenant_db_name(cluster, tenant, &stream.db);
+        if !self.db.contains_key(key).unwrap() {
+            return TenaneMetaDataResp::new_from_data(
+                META_REQUEST_DB_NOT_FOUND,
+                "database not found".to_string(),
+                self.to_tenant_meta_data(cluster, tenant).unwrap(),
+            )
+            .to_string();
+        }
+        let key = KeyPath::tenant_stream_name(cluster, tenant, &stream.db, &stream.name);
+        if self.db.contains_key(&key).unwrap() {
+            return TenaneMetaDataResp::new_from_data(
+                META_REQUEST_STREAM_EXIST,
+                "stream already exist".to_string(),
+                self.to_tenant_meta_data(cluster, tenant).unwrap(),
+            )
+            .to_string();
+        }
+
+        let value = serde_json::to_string(stream).unwrap();
+        let _ = self.db.insert(key.as_bytes(), value.as_bytes());
+        info!("WRITE: {} :{}", key, value);
+
+        for (_, item) in watch.iter_mut() {
+            if item.interesting(cluster, tenant) {
+                item.delta.create_or_update_stream(self.version(), stream);
+                let _ = item.sender.try_send(true);
+            }
+        }
+
+        TenaneMetaDataResp::new_from_data(
+            META_REQUEST_SUCCESS,
+            "".to_string(),
+            self.to_tenant_meta_data(cluster, tenant).unwrap(),
+        )
+        .to_string()
+    }
+
+    fn process_drop_stream(
+        &self,
+        cluster: &str,
+        tenant: &str,
+        db: &str,
+        name: &str,
+        watch: &mut HashMap<String, WatchTenantMetaData>,
+    ) -> CommandResp {
+        let key = KeyPath::tenant_stream_name(cluster, tenant, db, name);
+        if !self.db.contains_key(&key).unwrap() {
+            return StatusResponse::new(
+                META_REQUEST_STREAM_NOT_FOUND,
+                "stream not found".to_string(),
+            )
+            .to_string();
+        }
+        let _ = self.db.remove(key);
+
+        for (_, item) in watch.iter_mut() {
+            if item.interesting(cluster, tenant) {
+                item.delta.delete_stream(self.version(), db, name);
+                let _ = item.sender.try_send(true);
+            }
+        }
+
+        StatusResponse::new(META_REQUEST_SUCCESS, "".to_string()).to_string()
+    }
+
+    fn process_update_stream_watermark(
+        &self,
+        cluster: &str,
+        tenant: &str,
+        db: &str,
+        name: &str,
+        watermark: i64,
+        watch: &mut HashMap<String, WatchTenantMetaData>,
+    ) -> CommandResp {
+        let key = KeyPath::tenant_stream_name(cluster, tenant, db, name);
+        let mut stream = match get_struct::<StreamInfo>(&key, self.db.clone()) {
+            Some(stream) => stream,
+            None => {
+                return StatusResponse::new(
+                    META_REQUEST_STREAM_NOT_FOUND,
+                    "stream not found".to_string(),
+                )
+                .to_string()
+            }
+        };
+
+        // the watermark never goes back
+        if watermark <= stream.watermark {
+            return StatusResponse::new(META_REQUEST_SUCCESS, "".to_string()).to_string();
+        }
+        stream.watermark = watermark;
+
+        let value = serde_json::to_string(&stream).unwrap();
+        let _ = self.db.insert(key.as_bytes(), value.as_bytes());
+        info!("WRITE: {} :{}", key, value);
+
+        for (_, item) in watch.iter_mut() {
+            if item.interesting(cluster, tenant) {
+                item.delta.create_or_update_stream(self.version(), &stream);
+                let _ = item.sender.try_send(true);
+            }
+        }
+
+        StatusResponse::new(META_REQUEST_SUCCESS, "".to_string()).to_string()
+    }
+
     fn process_create_user(
         &self,
         cluster: &str,
@@ -1320,6 +1568,49 @@ mod test {
     use serde::{Deserialize, Serialize};
     use std::collections::BTreeMap;
     use std::println;
+    use std::sync::Arc;
+
+    use models::meta_data::{NodeHeartbeat, NodeInfo, NodeState, NODE_DEAD_TIMEOUT};
+
+    use super::{NodeHeartbeats, StateMachine, StateMachineContent};
+    use crate::store::key_path::KeyPath;
+
+    #[tokio::test]
+    async fn test_install_snapshot() {
+        let open_db = || Arc::new(sled::Config::new().temporary(true).open().unwrap());
+
+        let source = StateMachine::new(open_db());
+        source.db.insert("/cluster/tenants/t1", "v1").unwrap();
+        source.db.insert("/cluster/tenants/t2", "v2").unwrap();
+        let content = StateMachineContent::from(&source);
+        assert_eq!(content.data.len(), 2);
+
+        let target_db = open_db();
+        target_db.insert("/cluster/tenants/t1", "old").unwrap();
+        target_db.insert("/cluster/tenants/t3", "deleted").unwrap();
+        let target = StateMachine::from_serializable(content, target_db)
+            .await
+            .unwrap();
+
+        let data: Vec<(String, String)> = target
+            .db
+            .iter()
+            .map(|res| {
+                let (k, v) = res.unwrap();
+                (
+                    String::from_utf8(k.to_vec()).unwrap(),
+                    String::from_utf8(v.to_vec()).unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            data,
+            vec![
+                ("/cluster/tenants/t1".to_string(), "v1".to_string()),
+                ("/cluster/tenants/t2".to_string(), "v2".to_string()),
+            ]
+        );
+    }
 
     #[tokio::test]
     async fn test_btree_map() {
@@ -1379,4 +1670,63 @@ mod test {
         let str = serde_json::to_string(&"xxx".to_string()).unwrap();
         print!("\n4 === {}=== \n", str);
     }
+
+    #[test]
+    fn test_data_nodes_status() {
+        let sm = StateMachine::new(Arc::new(
+            sled::Config::new().temporary(true).open().unwrap(),
+        ));
+        for id in [1, 2] {
+            let node = NodeInfo {
+                id,
+                ..Default::default()
+            };
+            sm.db
+                .insert(
+                    KeyPath::data_node_id("cluster", id),
+                    serde_json::to_vec(&node).unwrap(),
+                )
+                .unwrap();
+        }
+
+        let mut heartbeats = NodeHeartbeats::default();
+        heartbeats.renew_term(1);
+        let heartbeat = NodeHeartbeat {
+            node_id: 1,
+            ..Default::default()
+        };
+        heartbeats.receive("cluster", &heartbeat);
+        let states = |heartbeats: &NodeHeartbeats| {
+            let mut states = sm
+                .data_nodes_status("cluster", heartbeats)
+                .into_iter()
+                .map(|n| (n.info.id, n.state))
+                .collect::<Vec<_>>();
+            states.sort_by_key(|(id, _)| *id);
+            states
+        };
+        assert_eq!(
+            states(&heartbeats),
+            vec![(1, NodeState::Alive), (2, NodeState::Suspect)]
+        );
+
+        // the leader of the same term keeps the heartbeats
+        heartbeats.renew_term(1);
+        assert!(heartbeats.get("cluster", 1).is_some());
+
+        // a long time leader judges the silent nodes dead
+        heartbeats.since -= NODE_DEAD_TIMEOUT;
+        assert_eq!(
+            states(&heartbeats),
+            vec![(1, NodeState::Alive), (2, NodeState::Dead)]
+        );
+
+        heartbeats.renew_term(2);
+        assert!(heartbeats.get("cluster", 1).is_none());
+        assert!(heartbeats.since() > 0);
+        assert_eq!(
+            states(&heartbeats),
+            vec![(1, NodeState::Suspect), (2, NodeState::Suspect)]
+        );
+    }
 }
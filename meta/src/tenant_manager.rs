@@ -3,7 +3,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use models::schema::LimiterConfig;
 use models::{
-    meta_data::ExpiredBucketInfo,
+    meta_data::{ExpiredBucketInfo, StreamInfo},
     oid::{Identifier, Oid},
     schema::{Tenant, TenantOptions},
 };
@@ -153,13 +153,20 @@ impl TenantManager for RemoteTenantManager {
             return Some(client.clone());
         }
 
+        // the client watches the meta of the tenant, it's cached and not created again
         self.tenant(tenant).ok().unwrap_or_default().map(|tenant| {
-            RemoteMetaClient::new(
+            let client = RemoteMetaClient::new(
                 self.cluster_name.clone(),
                 tenant,
                 self.cluster_meta.clone(),
                 self.node_id,
-            ) as MetaClientRef
+            ) as MetaClientRef;
+
+            self.tenants
+                .write()
+                .entry(client.tenant().name().to_string())
+                .or_insert(client)
+                .clone()
         })
     }
 
@@ -218,4 +225,15 @@ impl TenantManager for RemoteTenantManager {
 
         list
     }
+
+    fn streams(&self) -> MetaResult<Vec<StreamInfo>> {
+        let mut list = vec![];
+        for tenant in self.tenants()? {
+            if let Some(client) = self.tenant_meta(tenant.name()) {
+                list.append(&mut client.list_streams());
+            }
+        }
+
+        Ok(list)
+    }
 }
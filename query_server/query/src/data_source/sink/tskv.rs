@@ -48,6 +48,7 @@ impl RecordBatchSink for TskvRecordBatchSink {
             version: 0,
             meta: None,
             points,
+            consistency_level: None,
         };
 
         self.coord
@@ -5,7 +5,7 @@ use coordinator::service::CoordinatorRef;
 use models::oid::Oid;
 
 use spi::query::dispatcher::{QueryInfo, QueryStatus};
-use spi::query::execution::Output;
+use spi::query::execution::{Output, OutputStream};
 use spi::query::scheduler::SchedulerRef;
 use spi::{
     query::{
@@ -30,7 +30,7 @@ use crate::{
     execution::factory::SqlQueryExecutionFactory, sql::logical::planner::DefaultLogicalPlanner,
 };
 
-use super::query_tracker::QueryTracker;
+use super::query_tracker::{QueryTracker, TrackedStream};
 
 #[derive(Clone)]
 pub struct SimpleQueryDispatcher {
@@ -150,10 +150,19 @@ impl SimpleQueryDispatcher {
             .create_query_execution(logical_plan, query_state_machine.clone());
 
         // TrackedQuery.drop() is called implicitly when the value goes out of scope,
-        self.query_tracker
-            .try_track_query(query_state_machine.query_id, execution)?
-            .start()
-            .await
+        let query = self
+            .query_tracker
+            .try_track_query(query_state_machine.query_id, execution)?;
+        match query.start().await? {
+            // the streaming query is tracked until its stream ends or is dropped
+            Output::Stream(stream) => match stream.take() {
+                Some(inner) => Ok(Output::Stream(OutputStream::new(Box::pin(
+                    TrackedStream::new(inner, query),
+                )))),
+                None => Ok(Output::Stream(stream)),
+            },
+            output => Ok(output),
+        }
     }
 }
 
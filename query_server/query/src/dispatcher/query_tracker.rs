@@ -1,18 +1,25 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::{collections::HashMap, ops::Deref, sync::Arc};
 
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::error::Result as ArrowResult;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::physical_plan::{RecordBatchStream, SendableRecordBatchStream};
+use futures::{Stream, StreamExt};
 use parking_lot::RwLock;
 use spi::{query::execution::QueryExecution, service::protocol::QueryId, QueryError};
-use tokio::sync::{Semaphore, SemaphorePermit, TryAcquireError};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, TryAcquireError};
 use trace::{debug, warn};
 
 pub struct QueryTracker {
     queries: RwLock<HashMap<QueryId, Arc<dyn QueryExecution>>>,
-    query_limit_semaphore: Semaphore,
+    query_limit_semaphore: Arc<Semaphore>,
 }
 
 impl QueryTracker {
     pub fn new(query_limit: usize) -> Self {
-        let query_limit_semaphore = Semaphore::new(query_limit);
+        let query_limit_semaphore = Arc::new(Semaphore::new(query_limit));
 
         Self {
             queries: RwLock::new(HashMap::new()),
@@ -28,7 +35,7 @@ impl QueryTracker {
     ///
     /// [`QueryError::Closed`] after call [`Self::close`]
     pub fn try_track_query(
-        &self,
+        self: &Arc<Self>,
         query_id: QueryId,
         query: Arc<dyn QueryExecution>,
     ) -> std::result::Result<TrackedQuery, QueryError> {
@@ -41,7 +48,7 @@ impl QueryTracker {
 
         let _ = self.queries.write().insert(query_id, query.clone());
 
-        let _permit = match self.query_limit_semaphore.try_acquire() {
+        let _permit = match self.query_limit_semaphore.clone().try_acquire_owned() {
             Ok(p) => p,
             Err(TryAcquireError::NoPermits) => {
                 warn!("simultaneous request limit exceeded - dropping request");
@@ -54,7 +61,7 @@ impl QueryTracker {
 
         Ok(TrackedQuery {
             _permit,
-            tracker: self,
+            tracker: self.clone(),
             query_id,
             query,
         })
@@ -85,14 +92,14 @@ impl QueryTracker {
     }
 }
 
-pub struct TrackedQuery<'a> {
-    _permit: SemaphorePermit<'a>,
-    tracker: &'a QueryTracker,
+pub struct TrackedQuery {
+    _permit: OwnedSemaphorePermit,
+    tracker: Arc<QueryTracker>,
     query_id: QueryId,
     query: Arc<dyn QueryExecution>,
 }
 
-impl Deref for TrackedQuery<'_> {
+impl Deref for TrackedQuery {
     type Target = dyn QueryExecution;
 
     fn deref(&self) -> &Self::Target {
@@ -100,13 +107,54 @@ impl Deref for TrackedQuery<'_> {
     }
 }
 
-impl Drop for TrackedQuery<'_> {
+impl Drop for TrackedQuery {
     fn drop(&mut self) {
         debug!("TrackedQuery drop: {:?}", &self.query_id);
         self.tracker.expire_query(&self.query_id);
     }
 }
 
+/// The result stream of a streaming query, the query is tracked until the stream ends
+/// or is dropped.
+pub struct TrackedStream {
+    schema: SchemaRef,
+    inner: Option<SendableRecordBatchStream>,
+    query: Option<TrackedQuery>,
+}
+
+impl TrackedStream {
+    pub fn new(inner: SendableRecordBatchStream, query: TrackedQuery) -> Self {
+        Self {
+            schema: inner.schema(),
+            inner: Some(inner),
+            query: Some(query),
+        }
+    }
+}
+
+impl Stream for TrackedStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = match self.inner.as_mut() {
+            Some(inner) => inner.poll_next_unpin(cx),
+            None => return Poll::Ready(None),
+        };
+        if let Poll::Ready(None) = poll {
+            self.inner = None;
+            self.query = None;
+        }
+
+        poll
+    }
+}
+
+impl RecordBatchStream for TrackedStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -154,8 +202,8 @@ mod tests {
         }
     }
 
-    fn new_query_tracker(limit: usize) -> QueryTracker {
-        QueryTracker::new(limit)
+    fn new_query_tracker(limit: usize) -> Arc<QueryTracker> {
+        Arc::new(QueryTracker::new(limit))
     }
 
     #[test]
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use spi::query::{
+    execution::{Output, QueryStateMachineRef},
+    logical_planner::AddMetaNode,
+};
+use spi::Result;
+use trace::info;
+
+use super::DDLDefinitionTask;
+
+pub struct AddMetaNodeTask {
+    stmt: AddMetaNode,
+}
+
+impl AddMetaNodeTask {
+    #[inline(always)]
+    pub fn new(stmt: AddMetaNode) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for AddMetaNodeTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let AddMetaNode { node_id, addr } = &self.stmt;
+        info!("Add meta node {} {}", node_id, addr);
+
+        query_state_machine
+            .meta
+            .admin_meta()
+            .add_meta_node(*node_id, addr)
+            .await?;
+
+        Ok(Output::Nil(()))
+    }
+}
@@ -1,15 +1,16 @@
-use async_trait::async_trait;
+use std::sync::Arc;
 
+use async_trait::async_trait;
+use datafusion::arrow::array::{Int64Builder, StringBuilder, UInt32Builder, UInt64Builder};
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::arrow::record_batch::RecordBatch;
 use spi::query::{
     execution::{Output, QueryStateMachineRef},
     logical_planner::ChecksumGroup,
 };
+use spi::Result;
 
 use super::DDLDefinitionTask;
-use meta::error::MetaError;
-
-use spi::QueryError;
-use spi::Result;
 
 pub struct ChecksumGroupTask {
     stmt: ChecksumGroup,
@@ -25,19 +26,59 @@ impl ChecksumGroupTask {
 #[async_trait]
 impl DDLDefinitionTask for ChecksumGroupTask {
     async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
-        let ChecksumGroup {
-            replication_set_id: _,
-        } = self.stmt;
+        let ChecksumGroup { replication_set_id } = self.stmt;
         let tenant = query_state_machine.session.tenant();
-        let _meta = query_state_machine
-            .meta
-            .tenant_manager()
-            .tenant_meta(tenant)
-            .ok_or_else(|| QueryError::Meta {
-                source: MetaError::TenantNotFound {
-                    tenant: tenant.to_string(),
-                },
-            })?;
-        todo!()
+
+        let trees = query_state_machine
+            .coord
+            .checksum_replication_set(tenant, replication_set_id)
+            .await?;
+
+        let mut vnode_id = UInt32Builder::new();
+        let mut node_id = UInt64Builder::new();
+        let mut table = StringBuilder::new();
+        let mut column = StringBuilder::new();
+        let mut min_time = Int64Builder::new();
+        let mut max_time = Int64Builder::new();
+        let mut checksum = StringBuilder::new();
+        for tree in trees.iter() {
+            for table_node in tree.tables.iter() {
+                for column_node in table_node.columns.iter() {
+                    for node in column_node.values.iter() {
+                        vnode_id.append_value(tree.vnode.id);
+                        node_id.append_value(tree.vnode.node_id);
+                        table.append_value(&table_node.table);
+                        column.append_value(&column_node.column);
+                        min_time.append_value(node.min_ts);
+                        max_time.append_value(node.max_ts);
+                        checksum.append_value(tskv::hash_to_string(node.hash));
+                    }
+                }
+            }
+        }
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("VNODE_ID", DataType::UInt32, false),
+            Field::new("NODE_ID", DataType::UInt64, false),
+            Field::new("TABLE", DataType::Utf8, false),
+            Field::new("COLUMN", DataType::Utf8, false),
+            Field::new("MIN_TIME", DataType::Int64, false),
+            Field::new("MAX_TIME", DataType::Int64, false),
+            Field::new("CHECKSUM", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(vnode_id.finish()),
+                Arc::new(node_id.finish()),
+                Arc::new(table.finish()),
+                Arc::new(column.finish()),
+                Arc::new(min_time.finish()),
+                Arc::new(max_time.finish()),
+                Arc::new(checksum.finish()),
+            ],
+        )?;
+
+        Ok(Output::StreamData(schema, vec![batch]))
     }
 }
@@ -0,0 +1,72 @@
+use crate::execution::ddl::DDLDefinitionTask;
+use async_trait::async_trait;
+use meta::error::MetaError;
+use models::meta_data::StreamInfo;
+use models::oid::Identifier;
+use snafu::ResultExt;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::CreateStream;
+use spi::Result;
+
+pub struct CreateStreamTask {
+    stmt: CreateStream,
+}
+
+impl CreateStreamTask {
+    pub fn new(stmt: CreateStream) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for CreateStreamTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let CreateStream {
+            ref name,
+            ref database_name,
+            ref if_not_exists,
+            ref target_table,
+            ref query,
+            ref interval,
+            ref delay,
+        } = self.stmt;
+
+        let tenant = query_state_machine.session.tenant();
+        let client = query_state_machine
+            .meta
+            .tenant_manager()
+            .tenant_meta(tenant)
+            .ok_or(MetaError::TenantNotFound {
+                tenant: tenant.to_string(),
+            })?;
+
+        match (if_not_exists, client.get_stream(database_name, name)?) {
+            // do not create if exists
+            (true, Some(_)) => Ok(Output::Nil(())),
+            // Report an error if it exists
+            (false, Some(_)) => Err(MetaError::StreamAlreadyExists {
+                stream: name.clone(),
+            })?,
+            // does not exist, create
+            (_, None) => {
+                // The stream processes the data written after it is created
+                let now = models::utils::now_timestamp();
+                let stream = StreamInfo {
+                    tenant: tenant.to_string(),
+                    db: database_name.clone(),
+                    name: name.clone(),
+                    target_table: target_table.clone(),
+                    query: query.clone(),
+                    interval: *interval,
+                    delay: *delay,
+                    owner: query_state_machine.session.user().desc().name().to_string(),
+                    node_id: query_state_machine.coord.node_id(),
+                    watermark: now - now.rem_euclid(*interval),
+                };
+
+                client.create_stream(&stream).context(spi::MetaSnafu)?;
+                Ok(Output::Nil(()))
+            }
+        }
+    }
+}
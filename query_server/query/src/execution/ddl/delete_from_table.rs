@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::array::UInt64Array;
+use datafusion::arrow::datatypes::{Field, Schema};
+use datafusion::arrow::record_batch::RecordBatch;
+use spi::query::{
+    execution::{Output, QueryStateMachineRef},
+    logical_planner::DeleteFromTable,
+    AFFECTED_ROWS,
+};
+use spi::Result;
+
+use trace::info;
+
+use super::DDLDefinitionTask;
+
+pub struct DeleteFromTableTask {
+    stmt: DeleteFromTable,
+}
+
+impl DeleteFromTableTask {
+    #[inline(always)]
+    pub fn new(stmt: DeleteFromTable) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for DeleteFromTableTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let DeleteFromTable {
+            ref database_name,
+            ref table_name,
+            ref selection,
+        } = self.stmt;
+
+        let tenant = query_state_machine.session.tenant();
+        let filters = selection.iter().cloned().collect::<Vec<_>>();
+
+        let series = query_state_machine
+            .coord
+            .delete_from_table(tenant, database_name, table_name, filters)
+            .await?;
+        info!(
+            "Delete from table {}.{}, {} series affected",
+            database_name, table_name, series
+        );
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            AFFECTED_ROWS.0,
+            AFFECTED_ROWS.1,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(UInt64Array::from(vec![series]))],
+        )?;
+
+        Ok(Output::StreamData(schema, vec![batch]))
+    }
+}
@@ -66,6 +66,21 @@ impl DDLDefinitionTask for DropDatabaseObjectTask {
 
                 client.drop_table(table.schema, table.table)
             }
+            DatabaseObjectType::Stream => {
+                info!("Drop stream {}", object_name);
+                let tenant = query_state_machine.session.tenant();
+                let client = query_state_machine
+                    .meta
+                    .tenant_manager()
+                    .tenant_meta(tenant)
+                    .ok_or(MetaError::TenantNotFound {
+                        tenant: tenant.to_string(),
+                    })?;
+                let stream = TableReference::from(object_name.as_str())
+                    .resolve(tenant, query_state_machine.session.default_database());
+
+                client.drop_stream(stream.schema, stream.table)
+            }
         };
 
         if *if_exist {
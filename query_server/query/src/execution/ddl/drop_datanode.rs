@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use meta::error::MetaError;
+use models::meta_data::NODE_STATUS_DECOMMISSIONING;
+use spi::query::{
+    execution::{Output, QueryStateMachineRef},
+    logical_planner::DropDatanode,
+};
+use spi::{QueryError, Result};
+use trace::info;
+
+use super::DDLDefinitionTask;
+
+pub struct DropDatanodeTask {
+    stmt: DropDatanode,
+}
+
+impl DropDatanodeTask {
+    #[inline(always)]
+    pub fn new(stmt: DropDatanode) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for DropDatanodeTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let node_id = self.stmt.node_id;
+        let admin_meta = query_state_machine.meta.admin_meta();
+
+        let nodes = admin_meta.data_nodes();
+        let node = nodes
+            .iter()
+            .find(|n| n.id == node_id)
+            .ok_or(QueryError::Meta {
+                source: MetaError::NotFoundNode { id: node_id },
+            })?;
+        if node.is_decommissioning() {
+            return Ok(Output::Nil(()));
+        }
+
+        // the vnodes of the node need another node to move to
+        if nodes
+            .iter()
+            .all(|n| n.id == node_id || n.is_decommissioning())
+        {
+            return Err(QueryError::Meta {
+                source: MetaError::CommonError {
+                    msg: format!("data node {} is the last online data node", node_id),
+                },
+            });
+        }
+
+        info!("Decommission data node {}", node_id);
+        admin_meta.update_data_node_status(node_id, NODE_STATUS_DECOMMISSIONING)?;
+
+        Ok(Output::Nil(()))
+    }
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use spi::query::{
+    execution::{Output, QueryStateMachineRef},
+    logical_planner::DropMetaNode,
+};
+use spi::Result;
+use trace::info;
+
+use super::DDLDefinitionTask;
+
+pub struct DropMetaNodeTask {
+    stmt: DropMetaNode,
+}
+
+impl DropMetaNodeTask {
+    #[inline(always)]
+    pub fn new(stmt: DropMetaNode) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for DropMetaNodeTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let node_id = self.stmt.node_id;
+        info!("Drop meta node {}", node_id);
+
+        query_state_machine
+            .meta
+            .admin_meta()
+            .del_meta_node(node_id)
+            .await?;
+
+        Ok(Output::Nil(()))
+    }
+}
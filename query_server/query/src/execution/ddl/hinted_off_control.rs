@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use spi::query::{
+    execution::{Output, QueryStateMachineRef},
+    logical_planner::HintedOffControl,
+};
+use spi::Result;
+use trace::info;
+
+use super::DDLDefinitionTask;
+
+pub struct HintedOffControlTask {
+    stmt: HintedOffControl,
+}
+
+impl HintedOffControlTask {
+    #[inline(always)]
+    pub fn new(stmt: HintedOffControl) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for HintedOffControlTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let HintedOffControl { node_id, cmd } = self.stmt;
+        info!("Hinted off queues of data node {}: {:?}", node_id, cmd);
+
+        query_state_machine
+            .coord
+            .hinted_off_control(node_id, cmd)
+            .await?;
+
+        Ok(Output::Nil(()))
+    }
+}
@@ -8,28 +8,37 @@ use spi::Result;
 use self::alter_tenant::AlterTenantTask;
 use self::alter_user::AlterUserTask;
 use self::create_role::CreateRoleTask;
+use self::create_stream::CreateStreamTask;
 use self::create_table::CreateTableTask;
 use self::create_tenant::CreateTenantTask;
 use self::create_user::CreateUserTask;
 use self::drop_global_object::DropGlobalObjectTask;
 use self::drop_tenant_object::DropTenantObjectTask;
 use self::grant_revoke::GrantRevokeTask;
+use crate::execution::ddl::add_meta_node::AddMetaNodeTask;
 use crate::execution::ddl::alter_database::AlterDatabaseTask;
 use crate::execution::ddl::alter_table::AlterTableTask;
 use crate::execution::ddl::checksum_group::ChecksumGroupTask;
 use crate::execution::ddl::compact_vnode::CompactVnodeTask;
 use crate::execution::ddl::copy_vnode::CopyVnodeTask;
 use crate::execution::ddl::create_database::CreateDatabaseTask;
+use crate::execution::ddl::delete_from_table::DeleteFromTableTask;
 use crate::execution::ddl::describe_database::DescribeDatabaseTask;
 use crate::execution::ddl::describe_table::DescribeTableTask;
+use crate::execution::ddl::drop_datanode::DropDatanodeTask;
+use crate::execution::ddl::drop_meta_node::DropMetaNodeTask;
 use crate::execution::ddl::drop_vnode::DropVnodeTask;
+use crate::execution::ddl::hinted_off_control::HintedOffControlTask;
 use crate::execution::ddl::move_node::MoveVnodeTask;
+use crate::execution::ddl::repair_group::RepairGroupTask;
 use crate::execution::ddl::show_database::ShowDatabasesTask;
 use crate::execution::ddl::show_table::ShowTablesTask;
+use crate::execution::ddl::transfer_meta_leader::TransferMetaLeaderTask;
 
 use self::create_external_table::CreateExternalTableTask;
 use self::drop_database_object::DropDatabaseObjectTask;
 
+mod add_meta_node;
 mod alter_database;
 mod alter_table;
 mod alter_tenant;
@@ -40,19 +49,26 @@ mod copy_vnode;
 mod create_database;
 mod create_external_table;
 mod create_role;
+mod create_stream;
 mod create_table;
 mod create_tenant;
 mod create_user;
+mod delete_from_table;
 mod describe_database;
 mod describe_table;
 mod drop_database_object;
+mod drop_datanode;
 mod drop_global_object;
+mod drop_meta_node;
 mod drop_tenant_object;
 mod drop_vnode;
 mod grant_revoke;
+mod hinted_off_control;
 mod move_node;
+mod repair_group;
 mod show_database;
 mod show_table;
+mod transfer_meta_leader;
 
 /// Traits that DDL tasks should implement
 #[async_trait]
@@ -146,6 +162,7 @@ impl DDLDefinitionTaskFactory {
             DDLPlan::CreateTenant(sub_plan) => Box::new(CreateTenantTask::new(*sub_plan.clone())),
             DDLPlan::CreateUser(sub_plan) => Box::new(CreateUserTask::new(sub_plan.clone())),
             DDLPlan::CreateRole(sub_plan) => Box::new(CreateRoleTask::new(sub_plan.clone())),
+            DDLPlan::CreateStream(sub_plan) => Box::new(CreateStreamTask::new(sub_plan.clone())),
             DDLPlan::DescribeDatabase(sub_plan) => {
                 Box::new(DescribeDatabaseTask::new(sub_plan.clone()))
             }
@@ -162,6 +179,17 @@ impl DDLDefinitionTaskFactory {
             DDLPlan::MoveVnode(sub_plan) => Box::new(MoveVnodeTask::new(sub_plan.clone())),
             DDLPlan::CompactVnode(sub_plan) => Box::new(CompactVnodeTask::new(sub_plan.clone())),
             DDLPlan::ChecksumGroup(sub_plan) => Box::new(ChecksumGroupTask::new(sub_plan.clone())),
+            DDLPlan::RepairGroup(sub_plan) => Box::new(RepairGroupTask::new(sub_plan.clone())),
+            DDLPlan::DropDatanode(sub_plan) => Box::new(DropDatanodeTask::new(sub_plan.clone())),
+            DDLPlan::HintedOffControl(sub_plan) => {
+                Box::new(HintedOffControlTask::new(sub_plan.clone()))
+            }
+            DDLPlan::AddMetaNode(sub_plan) => Box::new(AddMetaNodeTask::new(sub_plan.clone())),
+            DDLPlan::DropMetaNode(sub_plan) => Box::new(DropMetaNodeTask::new(sub_plan.clone())),
+            DDLPlan::TransferMetaLeader => Box::new(TransferMetaLeaderTask),
+            DDLPlan::DeleteFromTable(sub_plan) => {
+                Box::new(DeleteFromTableTask::new(sub_plan.clone()))
+            }
         }
     }
 }
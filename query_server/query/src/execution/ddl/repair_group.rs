@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::array::{Int64Builder, StringBuilder, UInt32Builder};
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::arrow::record_batch::RecordBatch;
+use spi::query::{
+    execution::{Output, QueryStateMachineRef},
+    logical_planner::RepairGroup,
+};
+use spi::Result;
+use trace::info;
+
+use super::DDLDefinitionTask;
+
+pub struct RepairGroupTask {
+    stmt: RepairGroup,
+}
+
+impl RepairGroupTask {
+    #[inline(always)]
+    pub fn new(stmt: RepairGroup) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for RepairGroupTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let RepairGroup { replication_set_id } = self.stmt;
+        let tenant = query_state_machine.session.tenant();
+
+        let tasks = query_state_machine
+            .coord
+            .repair_replication_set(tenant, replication_set_id)
+            .await?;
+        info!(
+            "Repair replication set {}, {} repairs executed",
+            replication_set_id,
+            tasks.len()
+        );
+
+        // one row for every repaired time range
+        let mut table = StringBuilder::new();
+        let mut source = UInt32Builder::new();
+        let mut target = UInt32Builder::new();
+        let mut min_time = Int64Builder::new();
+        let mut max_time = Int64Builder::new();
+        for task in tasks.iter() {
+            for (min_ts, max_ts) in task.time_ranges.iter() {
+                table.append_value(&task.table);
+                source.append_value(task.source.id);
+                target.append_value(task.target.id);
+                min_time.append_value(*min_ts);
+                max_time.append_value(*max_ts);
+            }
+        }
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("TABLE", DataType::Utf8, false),
+            Field::new("SOURCE_VNODE_ID", DataType::UInt32, false),
+            Field::new("TARGET_VNODE_ID", DataType::UInt32, false),
+            Field::new("MIN_TIME", DataType::Int64, false),
+            Field::new("MAX_TIME", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(table.finish()),
+                Arc::new(source.finish()),
+                Arc::new(target.finish()),
+                Arc::new(min_time.finish()),
+                Arc::new(max_time.finish()),
+            ],
+        )?;
+
+        Ok(Output::StreamData(schema, vec![batch]))
+    }
+}
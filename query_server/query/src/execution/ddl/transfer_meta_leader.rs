@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::Result;
+use trace::info;
+
+use super::DDLDefinitionTask;
+
+pub struct TransferMetaLeaderTask;
+
+#[async_trait]
+impl DDLDefinitionTask for TransferMetaLeaderTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        info!("Transfer the leader of the meta cluster");
+
+        query_state_machine
+            .meta
+            .admin_meta()
+            .transfer_meta_leader()
+            .await?;
+
+        Ok(Output::Nil(()))
+    }
+}
@@ -1,11 +1,17 @@
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use async_trait::async_trait;
-use futures::stream::AbortHandle;
-use futures::TryStreamExt;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::error::{ArrowError, Result as ArrowResult};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::physical_plan::{RecordBatchStream, SendableRecordBatchStream};
+use futures::stream::{AbortHandle, Abortable};
+use futures::{Stream, StreamExt, TryStreamExt};
 use parking_lot::Mutex;
 use spi::query::dispatcher::{QueryInfo, QueryStatus};
-use spi::query::execution::Output;
+use spi::query::execution::{Output, OutputStream, QueryState, DONE};
 use spi::query::scheduler::SchedulerRef;
 use spi::query::{
     execution::{QueryExecution, QueryStateMachineRef},
@@ -62,6 +68,22 @@ impl SqlQueryExecution {
             )?
             .stream();
         debug!("Success build result stream.");
+        if self.query_state_machine.query.context().is_streaming() {
+            // the query is cancelled by aborting the stream once it's returned
+            let (abort_handle, abort_registration) = AbortHandle::new_pair();
+            *self.abort_handle.lock() = Some(abort_handle);
+            self.query_state_machine.end_schedule();
+
+            return Ok(Output::Stream(OutputStream::new(Box::pin(
+                CancellableStream {
+                    schema: stream.schema(),
+                    inner: Abortable::new(stream, abort_registration),
+                    query_state_machine: self.query_state_machine.clone(),
+                    done: false,
+                },
+            ))));
+        }
+
         let schema_ref = stream.schema();
         let execution_result = stream.try_collect::<Vec<_>>().await?;
         self.query_state_machine.end_schedule();
@@ -70,6 +92,47 @@ impl SqlQueryExecution {
     }
 }
 
+/// The result stream of a streaming query, it fails with [`QueryError::Cancel`] once the
+/// query is cancelled.
+struct CancellableStream {
+    schema: SchemaRef,
+    inner: Abortable<SendableRecordBatchStream>,
+    query_state_machine: QueryStateMachineRef,
+    done: bool,
+}
+
+impl Stream for CancellableStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        match self.inner.poll_next_unpin(cx) {
+            Poll::Ready(None) => {
+                self.done = true;
+                if matches!(
+                    self.query_state_machine.state(),
+                    QueryState::DONE(DONE::CANCELLED)
+                ) {
+                    return Poll::Ready(Some(Err(ArrowError::ExternalError(Box::new(
+                        QueryError::Cancel,
+                    )))));
+                }
+                Poll::Ready(None)
+            }
+            poll => poll,
+        }
+    }
+}
+
+impl RecordBatchStream for CancellableStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
 #[async_trait]
 impl QueryExecution for SqlQueryExecution {
     async fn start(&self) -> Result<Output> {
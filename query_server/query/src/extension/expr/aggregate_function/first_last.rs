@@ -0,0 +1,298 @@
+use std::sync::Arc;
+
+use datafusion::{
+    arrow::{
+        array::{as_primitive_array, Array, ArrayRef},
+        datatypes::{DataType, TimeUnit, TimestampNanosecondType},
+    },
+    error::Result as DFResult,
+    logical_expr::{
+        type_coercion::aggregates::{NUMERICS, STRINGS, TIMESTAMPS},
+        Accumulator, AccumulatorFunctionImplementation, AggregateState, AggregateUDF,
+        ReturnTypeFunction, Signature, StateTypeFunction, TypeSignature, Volatility,
+    },
+    scalar::ScalarValue,
+};
+
+use spi::query::function::FunctionMetadataManager;
+use spi::Result;
+
+use super::{FIRST, FIRST_TIME, FIRST_VALUE, LAST, LAST_TIME, LAST_VALUE};
+
+pub fn register_udafs(func_manager: &mut dyn FunctionMetadataManager) -> Result<()> {
+    func_manager.register_udaf(new(FIRST, false))?;
+    func_manager.register_udaf(new(LAST, true))?;
+    func_manager.register_udaf(new(FIRST_VALUE, false))?;
+    func_manager.register_udaf(new(LAST_VALUE, true))?;
+    func_manager.register_udaf(new_time(FIRST_TIME, false))?;
+    func_manager.register_udaf(new_time(LAST_TIME, true))?;
+    Ok(())
+}
+
+/// Returns Some(true) for `last`, `last_value` and `last_time`,
+/// Some(false) for `first`, `first_value` and `first_time`.
+pub fn is_last(name: &str) -> Option<bool> {
+    if [FIRST, FIRST_VALUE, FIRST_TIME]
+        .iter()
+        .any(|n| name.eq_ignore_ascii_case(n))
+    {
+        Some(false)
+    } else if [LAST, LAST_VALUE, LAST_TIME]
+        .iter()
+        .any(|n| name.eq_ignore_ascii_case(n))
+    {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Returns true for `first_time` and `last_time`, which return the time of the value.
+pub fn is_time(name: &str) -> bool {
+    name.eq_ignore_ascii_case(FIRST_TIME) || name.eq_ignore_ascii_case(LAST_TIME)
+}
+
+/// first(value, time) returns the value with the minimum time,
+/// last(value, time) returns the value with the maximum time, null values are ignored.
+pub fn new(name: &str, last: bool) -> AggregateUDF {
+    new_udaf(name, last, false)
+}
+
+/// first_time(value, time) and last_time(value, time) return the time of the value
+/// returned by first(value, time) and last(value, time).
+pub fn new_time(name: &str, last: bool) -> AggregateUDF {
+    new_udaf(name, last, true)
+}
+
+fn new_udaf(name: &str, last: bool, return_time: bool) -> AggregateUDF {
+    let time = DataType::Timestamp(TimeUnit::Nanosecond, None);
+    let type_signatures = STRINGS
+        .iter()
+        .chain(NUMERICS.iter())
+        .chain(TIMESTAMPS.iter())
+        .chain([DataType::Boolean].iter())
+        .map(|t| TypeSignature::Exact(vec![t.clone(), time.clone()]))
+        .collect();
+    let signature = Signature::one_of(type_signatures, Volatility::Immutable);
+
+    let return_type: ReturnTypeFunction = Arc::new(move |input_expr_types| {
+        if return_time {
+            Ok(Arc::new(DataType::Timestamp(TimeUnit::Nanosecond, None)))
+        } else {
+            Ok(Arc::new(input_expr_types[0].clone()))
+        }
+    });
+
+    // the data type is the return type
+    let accumulator: AccumulatorFunctionImplementation = Arc::new(move |data_type| {
+        Ok(Box::new(FirstLastAccumulator::try_new(
+            data_type,
+            last,
+            return_time,
+        )?))
+    });
+
+    // the returned value and the time
+    let state_type: StateTypeFunction = Arc::new(move |data_type| {
+        Ok(Arc::new(vec![
+            data_type.clone(),
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+        ]))
+    });
+
+    AggregateUDF::new(name, &signature, &return_type, &accumulator, &state_type)
+}
+
+#[derive(Debug)]
+struct FirstLastAccumulator {
+    last: bool,
+    return_time: bool,
+    time: Option<i64>,
+    /// The value to return, the time if `return_time`.
+    value: ScalarValue,
+}
+
+impl FirstLastAccumulator {
+    fn try_new(data_type: &DataType, last: bool, return_time: bool) -> DFResult<Self> {
+        Ok(Self {
+            last,
+            return_time,
+            time: None,
+            value: ScalarValue::try_from(data_type)?,
+        })
+    }
+
+    fn is_better(&self, time: i64) -> bool {
+        match self.time {
+            None => true,
+            Some(t) if self.last => time > t,
+            Some(t) => time < t,
+        }
+    }
+}
+
+impl Accumulator for FirstLastAccumulator {
+    fn state(&self) -> DFResult<Vec<AggregateState>> {
+        Ok(vec![
+            AggregateState::Scalar(self.value.clone()),
+            AggregateState::Scalar(ScalarValue::TimestampNanosecond(self.time, None)),
+        ])
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> DFResult<()> {
+        let (vals, times) = (&values[0], &values[1]);
+        let times = as_primitive_array::<TimestampNanosecondType>(times.as_ref());
+
+        let mut best = None;
+        for i in 0..times.len() {
+            if times.is_null(i) || vals.is_null(i) {
+                continue;
+            }
+            let time = times.value(i);
+            if self.is_better(time) {
+                self.time = Some(time);
+                best = Some(i);
+            }
+        }
+
+        if let Some(i) = best {
+            self.value = if self.return_time {
+                ScalarValue::TimestampNanosecond(self.time, None)
+            } else {
+                ScalarValue::try_from_array(vals, i)?
+            };
+        }
+
+        Ok(())
+    }
+
+    /// The states are the returned values and the times
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> DFResult<()> {
+        self.update_batch(states)
+    }
+
+    fn evaluate(&self) -> DFResult<ScalarValue> {
+        Ok(self.value.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use datafusion::{
+        arrow::{
+            array::{ArrayRef, Float64Array, TimestampNanosecondArray},
+            datatypes::{DataType, TimeUnit},
+        },
+        logical_expr::{Accumulator, AggregateState},
+        scalar::ScalarValue,
+    };
+
+    use super::{is_last, is_time, new, new_time, FirstLastAccumulator};
+    use crate::extension::expr::aggregate_function::{LAST, LAST_TIME};
+
+    #[test]
+    fn test_first_last() {
+        let values: ArrayRef = Arc::new(Float64Array::from(vec![
+            Some(1.0),
+            Some(2.0),
+            None,
+            Some(4.0),
+        ]));
+        let times: ArrayRef = Arc::new(TimestampNanosecondArray::from(vec![
+            Some(20),
+            Some(10),
+            Some(40),
+            Some(30),
+        ]));
+
+        let mut first = FirstLastAccumulator::try_new(&DataType::Float64, false, false).unwrap();
+        first
+            .update_batch(&[values.clone(), times.clone()])
+            .unwrap();
+        assert_eq!(first.evaluate().unwrap(), ScalarValue::Float64(Some(2.0)));
+
+        let mut last = FirstLastAccumulator::try_new(&DataType::Float64, true, false).unwrap();
+        last.update_batch(&[values, times]).unwrap();
+        assert_eq!(last.evaluate().unwrap(), ScalarValue::Float64(Some(4.0)));
+
+        // merge the partial results
+        let values: ArrayRef = Arc::new(Float64Array::from(vec![Some(5.0), None]));
+        let times: ArrayRef = Arc::new(TimestampNanosecondArray::from(vec![Some(35), None]));
+        last.merge_batch(&[values, times]).unwrap();
+        assert_eq!(last.evaluate().unwrap(), ScalarValue::Float64(Some(5.0)));
+    }
+
+    #[test]
+    fn test_first_last_time() {
+        let time_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let values: ArrayRef = Arc::new(Float64Array::from(vec![Some(1.0), Some(2.0), None]));
+        let times: ArrayRef = Arc::new(TimestampNanosecondArray::from(vec![
+            Some(20),
+            Some(10),
+            Some(5),
+        ]));
+
+        let mut first_time = FirstLastAccumulator::try_new(&time_type, false, true).unwrap();
+        assert_eq!(
+            first_time.evaluate().unwrap(),
+            ScalarValue::TimestampNanosecond(None, None)
+        );
+        first_time
+            .update_batch(&[values.clone(), times.clone()])
+            .unwrap();
+        // the time of a null value is ignored
+        assert_eq!(
+            first_time.evaluate().unwrap(),
+            ScalarValue::TimestampNanosecond(Some(10), None)
+        );
+
+        let mut last_time = FirstLastAccumulator::try_new(&time_type, true, true).unwrap();
+        last_time.update_batch(&[values, times]).unwrap();
+        assert_eq!(
+            last_time.evaluate().unwrap(),
+            ScalarValue::TimestampNanosecond(Some(20), None)
+        );
+
+        // the states are the times
+        let states = last_time
+            .state()
+            .unwrap()
+            .into_iter()
+            .map(|state| match state {
+                AggregateState::Scalar(v) => v.to_array(),
+                AggregateState::Array(v) => v,
+            })
+            .collect::<Vec<_>>();
+        let mut merged = FirstLastAccumulator::try_new(&time_type, true, true).unwrap();
+        merged.merge_batch(&states).unwrap();
+        assert_eq!(
+            merged.evaluate().unwrap(),
+            ScalarValue::TimestampNanosecond(Some(20), None)
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(is_last("last"), Some(true));
+        assert_eq!(is_last("LAST_TIME"), Some(true));
+        assert_eq!(is_last("first_value"), Some(false));
+        assert_eq!(is_last("first_time"), Some(false));
+        assert_eq!(is_last("max"), None);
+
+        assert!(is_time("last_time"));
+        assert!(!is_time("last"));
+
+        let udaf = new_time(LAST_TIME, true);
+        assert_eq!(
+            (udaf.return_type)(&[DataType::Float64]).unwrap().as_ref(),
+            &DataType::Timestamp(TimeUnit::Nanosecond, None)
+        );
+        let udaf = new(LAST, true);
+        assert_eq!(
+            (udaf.return_type)(&[DataType::Float64]).unwrap().as_ref(),
+            &DataType::Float64
+        );
+    }
+}
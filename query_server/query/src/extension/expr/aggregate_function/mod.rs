@@ -1,16 +1,25 @@
 #[cfg(test)]
 mod example;
+pub mod first_last;
 
 use spi::query::function::FunctionMetadataManager;
 use spi::Result;
 
-pub fn register_udafs(_func_manager: &mut dyn FunctionMetadataManager) -> Result<()> {
+pub fn register_udafs(func_manager: &mut dyn FunctionMetadataManager) -> Result<()> {
     // extend function...
     // eg.
     //   example::register_udaf(func_manager)?;
+    first_last::register_udafs(func_manager)?;
     Ok(())
 }
 
+pub const FIRST: &str = "FIRST";
+pub const LAST: &str = "LAST";
+pub const FIRST_VALUE: &str = "FIRST_VALUE";
+pub const LAST_VALUE: &str = "LAST_VALUE";
+pub const FIRST_TIME: &str = "FIRST_TIME";
+pub const LAST_TIME: &str = "LAST_TIME";
+
 #[cfg(test)]
 mod tests {
     use crate::function::simple_func_manager::SimpleFunctionMetadataManager;
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use datafusion::{
+    arrow::{array::ArrayRef, datatypes::DataType},
+    error::DataFusionError,
+    logical_expr::{
+        type_coercion::aggregates::{NUMERICS, STRINGS, TIMESTAMPS},
+        ReturnTypeFunction, ScalarUDF, Signature, TypeSignature, Volatility,
+    },
+    physical_expr::functions::make_scalar_function,
+};
+
+use spi::query::function::FunctionMetadataManager;
+use spi::Result;
+
+use super::{time_bucket, INTERPOLATE, LOCF, TIME_BUCKET_GAPFILL};
+
+pub fn register_udfs(func_manager: &mut dyn FunctionMetadataManager) -> Result<()> {
+    func_manager.register_udf(new_time_bucket_gapfill())?;
+    func_manager.register_udf(new_fill_function(LOCF, locf_signature()))?;
+    func_manager.register_udf(new_fill_function(INTERPOLATE, interpolate_signature()))?;
+    Ok(())
+}
+
+fn unimplemented_func(
+    name: &'static str,
+) -> impl Fn(&[ArrayRef]) -> datafusion::error::Result<ArrayRef> + Send + Sync + 'static {
+    move |_: &[ArrayRef]| {
+        Err(DataFusionError::Execution(format!(
+            "{} has no specific implementation, should be converted to gap fill operator.",
+            name
+        )))
+    }
+}
+
+/// time_bucket_gapfill(interval, time [, origin])
+fn new_time_bucket_gapfill() -> ScalarUDF {
+    let func = make_scalar_function(unimplemented_func(TIME_BUCKET_GAPFILL));
+
+    let return_type: ReturnTypeFunction =
+        Arc::new(move |input_expr_types| Ok(Arc::new(input_expr_types[1].clone())));
+
+    ScalarUDF::new(
+        TIME_BUCKET_GAPFILL,
+        &time_bucket::signature(),
+        &return_type,
+        &func,
+    )
+}
+
+/// locf(value), fills the missing buckets with the last observation.
+fn locf_signature() -> Signature {
+    let type_signatures = STRINGS
+        .iter()
+        .chain(NUMERICS.iter())
+        .chain(TIMESTAMPS.iter())
+        .chain([DataType::Boolean].iter())
+        .map(|t| TypeSignature::Exact(vec![t.clone()]))
+        .collect();
+
+    Signature::one_of(type_signatures, Volatility::Immutable)
+}
+
+/// interpolate(value), fills the missing buckets by linear interpolation.
+fn interpolate_signature() -> Signature {
+    let type_signatures = NUMERICS
+        .iter()
+        .map(|t| TypeSignature::Exact(vec![t.clone()]))
+        .collect();
+
+    Signature::one_of(type_signatures, Volatility::Immutable)
+}
+
+fn new_fill_function(name: &'static str, signature: Signature) -> ScalarUDF {
+    let func = make_scalar_function(unimplemented_func(name));
+
+    let return_type: ReturnTypeFunction =
+        Arc::new(move |input_expr_types| Ok(Arc::new(input_expr_types[0].clone())));
+
+    ScalarUDF::new(name, &signature, &return_type, &func)
+}
@@ -1,16 +1,25 @@
 #[cfg(test)]
 mod example;
+mod gapfill;
+pub mod time_bucket;
 
 use spi::query::function::FunctionMetadataManager;
 use spi::Result;
 
-pub fn register_udfs(_func_manager: &mut dyn FunctionMetadataManager) -> Result<()> {
+pub fn register_udfs(func_manager: &mut dyn FunctionMetadataManager) -> Result<()> {
     // extend function...
     // eg.
     //   example::register_udf(func_manager)?;
+    time_bucket::register_udf(func_manager)?;
+    gapfill::register_udfs(func_manager)?;
     Ok(())
 }
 
+pub const TIME_BUCKET: &str = "TIME_BUCKET";
+pub const TIME_BUCKET_GAPFILL: &str = "TIME_BUCKET_GAPFILL";
+pub const LOCF: &str = "LOCF";
+pub const INTERPOLATE: &str = "INTERPOLATE";
+
 #[cfg(test)]
 mod tests {
     use super::example;
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use datafusion::{
+    arrow::{
+        array::{as_primitive_array, Array, ArrayRef, TimestampNanosecondArray},
+        datatypes::{DataType, IntervalUnit, TimeUnit, TimestampNanosecondType},
+    },
+    error::{DataFusionError, Result as DFResult},
+    logical_expr::{ReturnTypeFunction, ScalarUDF, Signature, TypeSignature, Volatility},
+    physical_expr::functions::make_scalar_function,
+    scalar::ScalarValue,
+};
+
+use spi::query::function::FunctionMetadataManager;
+use spi::Result;
+
+use super::TIME_BUCKET;
+
+const NANOS_PER_MILLI: i64 = 1_000_000;
+const NANOS_PER_DAY: i64 = 86_400 * 1_000 * NANOS_PER_MILLI;
+
+pub fn register_udf(func_manager: &mut dyn FunctionMetadataManager) -> Result<ScalarUDF> {
+    let udf = new();
+    func_manager.register_udf(udf.clone())?;
+    Ok(udf)
+}
+
+pub fn new() -> ScalarUDF {
+    let func = make_scalar_function(time_bucket);
+
+    let return_type: ReturnTypeFunction =
+        Arc::new(move |_| Ok(Arc::new(DataType::Timestamp(TimeUnit::Nanosecond, None))));
+
+    ScalarUDF::new(TIME_BUCKET, &signature(), &return_type, &func)
+}
+
+/// time_bucket(interval, time [, origin])
+///
+/// The interval must not contain months, the default origin is 1970-01-01T00:00:00Z.
+pub fn signature() -> Signature {
+    let time = DataType::Timestamp(TimeUnit::Nanosecond, None);
+    let type_signatures = [IntervalUnit::DayTime, IntervalUnit::MonthDayNano]
+        .into_iter()
+        .flat_map(|unit| {
+            let interval = DataType::Interval(unit);
+            [
+                TypeSignature::Exact(vec![interval.clone(), time.clone()]),
+                TypeSignature::Exact(vec![interval, time.clone(), time.clone()]),
+            ]
+        })
+        .collect();
+
+    Signature::one_of(type_signatures, Volatility::Immutable)
+}
+
+fn time_bucket(args: &[ArrayRef]) -> DFResult<ArrayRef> {
+    let times = as_primitive_array::<TimestampNanosecondType>(args[1].as_ref());
+    let origins = args
+        .get(2)
+        .map(|a| as_primitive_array::<TimestampNanosecondType>(a.as_ref()));
+
+    let buckets = (0..times.len())
+        .map(|i| {
+            if times.is_null(i) || args[0].is_null(i) {
+                return Ok(None);
+            }
+            let origin = match origins {
+                Some(origins) if origins.is_null(i) => return Ok(None),
+                Some(origins) => origins.value(i),
+                None => 0,
+            };
+            let stride = stride_nanos(&ScalarValue::try_from_array(&args[0], i)?)?;
+
+            Ok(Some(bucket_start(times.value(i), stride, origin)))
+        })
+        .collect::<DFResult<TimestampNanosecondArray>>()?;
+
+    Ok(Arc::new(buckets))
+}
+
+/// Returns the start of the bucket containing the timestamp.
+pub fn bucket_start(ts: i64, stride: i64, origin: i64) -> i64 {
+    origin + (ts - origin).div_euclid(stride) * stride
+}
+
+/// Converts the interval to nanoseconds, the interval must be positive and without months.
+pub fn stride_nanos(interval: &ScalarValue) -> DFResult<i64> {
+    let nanos = match interval {
+        ScalarValue::IntervalDayTime(Some(v)) => {
+            let (days, millis) = ((v >> 32) as i32, *v as i32);
+            days as i64 * NANOS_PER_DAY + millis as i64 * NANOS_PER_MILLI
+        }
+        ScalarValue::IntervalMonthDayNano(Some(v)) => {
+            let (months, days, nanos) = ((v >> 96) as i32, (v >> 64) as i32, *v as i64);
+            if months != 0 {
+                return Err(DataFusionError::Execution(format!(
+                    "{} does not support intervals with months, found: {}",
+                    TIME_BUCKET, interval
+                )));
+            }
+            days as i64 * NANOS_PER_DAY + nanos
+        }
+        _ => {
+            return Err(DataFusionError::Execution(format!(
+                "{} expects an interval, found: {}",
+                TIME_BUCKET, interval
+            )))
+        }
+    };
+
+    if nanos <= 0 {
+        return Err(DataFusionError::Execution(format!(
+            "{} expects a positive interval, found: {}",
+            TIME_BUCKET, interval
+        )));
+    }
+
+    Ok(nanos)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use datafusion::{
+        arrow::array::{ArrayRef, IntervalDayTimeArray, TimestampNanosecondArray},
+        scalar::ScalarValue,
+    };
+
+    use super::{bucket_start, stride_nanos, time_bucket};
+
+    #[test]
+    fn test_stride_nanos() {
+        // 1 day 1 second
+        let interval = ScalarValue::IntervalDayTime(Some((1_i64 << 32) + 1_000));
+        assert_eq!(stride_nanos(&interval).unwrap(), 86_401_000_000_000);
+
+        // 1 month
+        let interval = ScalarValue::IntervalMonthDayNano(Some(1_i128 << 96));
+        assert!(stride_nanos(&interval).is_err());
+
+        assert!(stride_nanos(&ScalarValue::IntervalDayTime(Some(0))).is_err());
+    }
+
+    #[test]
+    fn test_time_bucket() {
+        assert_eq!(bucket_start(25, 10, 0), 20);
+        assert_eq!(bucket_start(25, 10, 3), 23);
+        assert_eq!(bucket_start(-5, 10, 0), -10);
+
+        // 1 second
+        let intervals: ArrayRef = Arc::new(IntervalDayTimeArray::from(vec![1_000; 3]));
+        let times: ArrayRef = Arc::new(TimestampNanosecondArray::from(vec![
+            Some(1_500_000_000),
+            None,
+            Some(2_000_000_000),
+        ]));
+
+        let buckets = time_bucket(&[intervals, times]).unwrap();
+        let expected: ArrayRef = Arc::new(TimestampNanosecondArray::from(vec![
+            Some(1_000_000_000),
+            None,
+            Some(2_000_000_000),
+        ]));
+        assert_eq!(&buckets, &expected);
+    }
+}
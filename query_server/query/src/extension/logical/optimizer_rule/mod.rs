@@ -1,7 +1,10 @@
 pub mod implicit_type_conversion;
 pub mod merge_limit_with_sort;
 pub mod projection_push_down;
+pub mod push_down_aggregation;
+pub mod push_down_plan_fragment;
 pub mod reject_cross_join;
+pub mod rewrite_gap_fill;
 pub mod rewrite_tag_scan;
 pub mod transform_bottom_func_to_topk_node;
 pub mod transform_topk_func_to_topk_node;
@@ -0,0 +1,468 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use datafusion::{
+    common::{Column, DFField, DFSchema},
+    datasource::source_as_provider,
+    logical_expr::{
+        aggregate_function::AggregateFunction, coalesce, col, lit, Aggregate, BinaryExpr,
+        Extension, LogicalPlan, LogicalPlanBuilder, Operator, TableScan,
+    },
+    optimizer::{OptimizerConfig, OptimizerRule},
+    prelude::Expr,
+};
+use models::{
+    predicate::aggregate::PushedAggregateFunction,
+    schema::{ColumnType, TskvTableSchema},
+};
+
+use crate::{
+    extension::{
+        expr::aggregate_function::{first_last, FIRST, FIRST_TIME, LAST, LAST_TIME},
+        logical::plan_node::aggregate_scan::AggregateScanPlanNode,
+    },
+    table::ClusterTable,
+};
+
+use datafusion::error::Result;
+
+/// Push the aggregate functions down into the tskv scan
+///
+/// Triggering conditions:
+/// 1. The aggregation has no group by, or only groups by tags
+/// 2. The aggregate functions are count/min/max/sum/first/last/first_time/last_time
+///    without distinct and filter
+/// 3. The input is a scan of ClusterTable, the filters only restrict the time and tags
+///
+/// The aggregation is rewritten to merge the partial results of all vnodes,
+/// if grouped by tags, every vnode answers the partial results per series.
+/// So `last(value, time)` grouped by tags reads only the latest block of each series.
+pub struct PushDownAggregation {}
+
+impl OptimizerRule for PushDownAggregation {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        optimizer_config: &mut OptimizerConfig,
+    ) -> Result<LogicalPlan> {
+        if let LogicalPlan::Aggregate(Aggregate {
+            input,
+            group_expr,
+            aggr_expr,
+            ..
+        }) = plan
+        {
+            if let Some(new_plan) = push_down_aggregation(input, group_expr, aggr_expr)? {
+                return Ok(new_plan);
+            }
+        }
+
+        // If we didn't find the match pattern, recurse as
+        // normal and build the result.
+        datafusion::optimizer::utils::optimize_children(self, plan, optimizer_config)
+    }
+
+    fn name(&self) -> &str {
+        "push_down_aggregation"
+    }
+}
+
+fn push_down_aggregation(
+    input: &LogicalPlan,
+    group_expr: &[Expr],
+    aggr_expr: &[Expr],
+) -> Result<Option<LogicalPlan>> {
+    let (predicate, scan) = match input {
+        LogicalPlan::Filter(filter) => (Some(filter.predicate()), filter.input().as_ref()),
+        _ => (None, input),
+    };
+
+    let (table_name, source, filters) = match scan {
+        LogicalPlan::TableScan(TableScan {
+            table_name,
+            source,
+            filters,
+            fetch: None,
+            ..
+        }) => (table_name, source, filters),
+        _ => return Ok(None),
+    };
+
+    let cluster_table = match source_as_provider(source)?
+        .as_any()
+        .downcast_ref::<ClusterTable>()
+    {
+        Some(table) => table.clone(),
+        None => return Ok(None),
+    };
+    let table_schema = cluster_table.table_schema();
+
+    // The scan must return exactly the rows the aggregate functions read
+    let exact = predicate
+        .into_iter()
+        .chain(filters.iter())
+        .all(|expr| is_exact_filter(expr, &table_schema));
+    if !exact {
+        return Ok(None);
+    }
+
+    // the tags grouped by, answered per series
+    let mut group_columns = Vec::with_capacity(group_expr.len());
+    for expr in group_expr {
+        match expr {
+            Expr::Column(c)
+                if table_schema
+                    .column(&c.name)
+                    .map_or(false, |c| c.column_type == ColumnType::Tag) =>
+            {
+                group_columns.push(c.clone())
+            }
+            _ => return Ok(None),
+        }
+    }
+
+    let mut aggregates = Vec::with_capacity(group_expr.len() + aggr_expr.len());
+    for c in group_columns.iter() {
+        aggregates.push(PushedAggregateFunction::Tag(c.name.clone()));
+    }
+    for expr in aggr_expr {
+        match to_pushed_aggregate(expr, &table_schema) {
+            Some(func) => aggregates.push(func),
+            None => return Ok(None),
+        }
+    }
+
+    let mut fields = vec![];
+    for (idx, func) in aggregates.iter().enumerate() {
+        let partial_fields = match func.partial_fields(&table_schema, idx) {
+            Some(partial_fields) => partial_fields,
+            None => return Ok(None),
+        };
+        // keep the qualified names of the tags grouped by
+        match group_columns.get(idx) {
+            Some(Column {
+                relation: Some(relation),
+                ..
+            }) => fields.extend(
+                partial_fields
+                    .into_iter()
+                    .map(|f| DFField::from_qualified(relation, f)),
+            ),
+            _ => fields.extend(partial_fields.into_iter().map(DFField::from)),
+        }
+    }
+    let partial_schema = DFSchema::new_with_metadata(fields, HashMap::new())?;
+
+    let scan = LogicalPlan::Extension(Extension {
+        node: Arc::new(AggregateScanPlanNode {
+            table_name: table_name.clone(),
+            source: Arc::new(cluster_table),
+            aggregates: aggregates.clone(),
+            projected_schema: Arc::new(partial_schema),
+            filters: filters.clone(),
+        }),
+    });
+
+    // Merge the partial results, keep the names of the origin aggregate functions
+    let group_exprs = group_columns
+        .into_iter()
+        .map(Expr::Column)
+        .collect::<Vec<_>>();
+    let mut merge_exprs = Vec::with_capacity(aggr_expr.len());
+    let mut project_exprs = group_exprs.clone();
+    for (idx, (func, expr)) in aggregates
+        .iter()
+        .zip(group_expr.iter().chain(aggr_expr))
+        .enumerate()
+        .skip(group_expr.len())
+    {
+        let partial = col(&format!("_partial_{}", idx));
+        let merge_fun = match func {
+            PushedAggregateFunction::Count(_) | PushedAggregateFunction::Sum(_) => {
+                AggregateFunction::Sum
+            }
+            PushedAggregateFunction::Min(_) => AggregateFunction::Min,
+            PushedAggregateFunction::Max(_) => AggregateFunction::Max,
+            PushedAggregateFunction::First(_) | PushedAggregateFunction::Last(_) => {
+                let last = matches!(func, PushedAggregateFunction::Last(_));
+                let time = col(&format!("_partial_{}_time", idx));
+                let merge_expr = match expr {
+                    Expr::AggregateUDF { fun, .. } if first_last::is_time(&fun.name) => {
+                        let name = if last { LAST_TIME } else { FIRST_TIME };
+                        first_last::new_time(name, last).call(vec![partial, time])
+                    }
+                    _ => {
+                        let name = if last { LAST } else { FIRST };
+                        first_last::new(name, last).call(vec![partial, time])
+                    }
+                };
+
+                project_exprs.push(col(&merge_expr.display_name()?).alias(&expr.display_name()?));
+                merge_exprs.push(merge_expr);
+                continue;
+            }
+            // not produced by to_pushed_aggregate
+            PushedAggregateFunction::Tag(_) => return Ok(None),
+        };
+        let merge_expr = Expr::AggregateFunction {
+            fun: merge_fun,
+            args: vec![partial],
+            distinct: false,
+            filter: None,
+        };
+        let merged = col(&merge_expr.display_name()?);
+        let project_expr = match func {
+            // the count of no rows is 0
+            PushedAggregateFunction::Count(_) => coalesce(vec![merged, lit(0_i64)]),
+            _ => merged,
+        };
+
+        merge_exprs.push(merge_expr);
+        project_exprs.push(project_expr.alias(&expr.display_name()?));
+    }
+
+    let plan = LogicalPlanBuilder::from(scan)
+        .aggregate(group_exprs, merge_exprs)?
+        .project(project_exprs)?
+        .build()?;
+
+    Ok(Some(plan))
+}
+
+/// Converts the aggregate function to the one answered by the tskv scan
+fn to_pushed_aggregate(
+    expr: &Expr,
+    table_schema: &TskvTableSchema,
+) -> Option<PushedAggregateFunction> {
+    let (fun, arg) = match expr {
+        Expr::AggregateFunction {
+            fun,
+            args,
+            distinct: false,
+            filter: None,
+        } if args.len() == 1 => (fun, &args[0]),
+        Expr::AggregateUDF {
+            fun,
+            args,
+            filter: None,
+        } if args.len() == 2 => return to_pushed_selector(&fun.name, args, table_schema),
+        _ => return None,
+    };
+
+    let column = match arg {
+        // count(*) is count(UInt8(1))
+        Expr::Literal(v) if !v.is_null() => None,
+        Expr::Column(c) => Some(table_schema.column(&c.name)?),
+        _ => return None,
+    };
+
+    match (fun, column) {
+        (AggregateFunction::Count, None) => Some(PushedAggregateFunction::Count(None)),
+        (AggregateFunction::Count, Some(c)) => match c.column_type {
+            // The time column is not null
+            ColumnType::Time => Some(PushedAggregateFunction::Count(None)),
+            ColumnType::Field(_) => Some(PushedAggregateFunction::Count(Some(c.name.clone()))),
+            ColumnType::Tag => None,
+        },
+        (AggregateFunction::Min, Some(c)) if c.column_type.is_field() => {
+            Some(PushedAggregateFunction::Min(c.name.clone()))
+        }
+        (AggregateFunction::Max, Some(c)) if c.column_type.is_field() => {
+            Some(PushedAggregateFunction::Max(c.name.clone()))
+        }
+        (AggregateFunction::Sum, Some(c)) if c.column_type.is_field() => {
+            Some(PushedAggregateFunction::Sum(c.name.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Converts first(field, time) and last(field, time),
+/// first_time and last_time are answered by the same partial results
+fn to_pushed_selector(
+    name: &str,
+    args: &[Expr],
+    table_schema: &TskvTableSchema,
+) -> Option<PushedAggregateFunction> {
+    let last = first_last::is_last(name)?;
+    let (value, time) = match (&args[0], &args[1]) {
+        (Expr::Column(value), Expr::Column(time)) => (
+            table_schema.column(&value.name)?,
+            table_schema.column(&time.name)?,
+        ),
+        _ => return None,
+    };
+
+    match (&value.column_type, &time.column_type) {
+        (ColumnType::Field(_), ColumnType::Time) if last => {
+            Some(PushedAggregateFunction::Last(value.name.clone()))
+        }
+        (ColumnType::Field(_), ColumnType::Time) => {
+            Some(PushedAggregateFunction::First(value.name.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the filter is answered exactly by the tskv scan:
+/// comparisons of the time column with literals, equations of tags with literals,
+/// joined by AND.
+fn is_exact_filter(expr: &Expr, table_schema: &TskvTableSchema) -> bool {
+    let column_type = |expr: &Expr| match expr {
+        Expr::Column(c) => table_schema.column(&c.name).map(|c| c.column_type),
+        _ => None,
+    };
+    let is_literal = |expr: &Expr| matches!(expr, Expr::Literal(v) if !v.is_null());
+
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        }) => is_exact_filter(left, table_schema) && is_exact_filter(right, table_schema),
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            let column = if is_literal(right) {
+                column_type(left)
+            } else if is_literal(left) {
+                column_type(right)
+            } else {
+                None
+            };
+
+            match column {
+                Some(ColumnType::Time) => matches!(
+                    op,
+                    Operator::Eq | Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq
+                ),
+                Some(ColumnType::Tag) => matches!(op, Operator::Eq),
+                _ => false,
+            }
+        }
+        // between, in list, not and is null are widened to all by the tskv scan
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::logical_expr::{aggregate_function::AggregateFunction, col, lit, Between};
+    use datafusion::prelude::Expr;
+    use models::codec::Encoding;
+    use models::predicate::aggregate::PushedAggregateFunction;
+    use models::schema::{ColumnType, TableColumn, TskvTableSchema};
+    use models::ValueType;
+
+    use super::{is_exact_filter, to_pushed_aggregate};
+    use crate::extension::expr::aggregate_function::{first_last, FIRST, LAST, LAST_TIME};
+
+    fn table_schema() -> TskvTableSchema {
+        TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "air".to_string(),
+            vec![
+                TableColumn::new(0, "time".to_string(), ColumnType::Time, Encoding::Default),
+                TableColumn::new(1, "station".to_string(), ColumnType::Tag, Encoding::Default),
+                TableColumn::new(
+                    2,
+                    "pressure".to_string(),
+                    ColumnType::Field(ValueType::Float),
+                    Encoding::Default,
+                ),
+            ],
+        )
+    }
+
+    fn aggregate(fun: AggregateFunction, arg: Expr) -> Expr {
+        Expr::AggregateFunction {
+            fun,
+            args: vec![arg],
+            distinct: false,
+            filter: None,
+        }
+    }
+
+    #[test]
+    fn test_to_pushed_aggregate() {
+        let schema = table_schema();
+
+        assert_eq!(
+            to_pushed_aggregate(&aggregate(AggregateFunction::Count, lit(1_u8)), &schema),
+            Some(PushedAggregateFunction::Count(None))
+        );
+        assert_eq!(
+            to_pushed_aggregate(&aggregate(AggregateFunction::Max, col("pressure")), &schema),
+            Some(PushedAggregateFunction::Max("pressure".to_string()))
+        );
+        assert_eq!(
+            to_pushed_aggregate(
+                &aggregate(AggregateFunction::Count, col("station")),
+                &schema
+            ),
+            None
+        );
+        assert_eq!(
+            to_pushed_aggregate(&aggregate(AggregateFunction::Avg, col("pressure")), &schema),
+            None
+        );
+
+        let last = first_last::new(LAST, true).call(vec![col("pressure"), col("time")]);
+        assert_eq!(
+            to_pushed_aggregate(&last, &schema),
+            Some(PushedAggregateFunction::Last("pressure".to_string()))
+        );
+        let first = first_last::new(FIRST, false).call(vec![col("pressure"), col("time")]);
+        assert_eq!(
+            to_pushed_aggregate(&first, &schema),
+            Some(PushedAggregateFunction::First("pressure".to_string()))
+        );
+        let last_time =
+            first_last::new_time(LAST_TIME, true).call(vec![col("pressure"), col("time")]);
+        assert_eq!(
+            to_pushed_aggregate(&last_time, &schema),
+            Some(PushedAggregateFunction::Last("pressure".to_string()))
+        );
+        let last = first_last::new(LAST, true).call(vec![col("station"), col("time")]);
+        assert_eq!(to_pushed_aggregate(&last, &schema), None);
+    }
+
+    #[test]
+    fn test_is_exact_filter() {
+        let schema = table_schema();
+
+        assert!(is_exact_filter(&col("time").gt(lit(10_i64)), &schema));
+        assert!(is_exact_filter(
+            &lit("XiaoMaiDao").eq(col("station")),
+            &schema
+        ));
+        assert!(!is_exact_filter(&col("station").gt(lit("a")), &schema));
+        assert!(!is_exact_filter(&col("pressure").gt(lit(1.0)), &schema));
+        assert!(is_exact_filter(
+            &col("time").gt(lit(10_i64)).and(col("station").eq(lit("a"))),
+            &schema
+        ));
+        assert!(!is_exact_filter(
+            &col("time").gt(lit(10_i64)).or(col("station").eq(lit("a"))),
+            &schema
+        ));
+        assert!(!is_exact_filter(
+            &col("station").in_list(vec![lit("a"), lit("b")], false),
+            &schema
+        ));
+        assert!(!is_exact_filter(
+            &col("time")
+                .gt(lit(10_i64))
+                .and(col("station").in_list(vec![lit("a")], false)),
+            &schema
+        ));
+        assert!(!is_exact_filter(
+            &Expr::Between(Between {
+                expr: Box::new(col("time")),
+                negated: false,
+                low: Box::new(lit(1_i64)),
+                high: Box::new(lit(10_i64)),
+            }),
+            &schema
+        ));
+    }
+}
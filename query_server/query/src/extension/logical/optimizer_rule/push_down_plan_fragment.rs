@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use datafusion::{
+    datasource::source_as_provider,
+    logical_expr::{
+        Aggregate, Extension, Limit, LogicalPlan, LogicalPlanBuilder, Projection, Sort, TableScan,
+    },
+    optimizer::{OptimizerConfig, OptimizerRule},
+};
+use models::predicate::fragment::{FragmentStep, PlanFragment};
+
+use crate::{
+    extension::logical::plan_node::plan_fragment::PlanFragmentPlanNode, table::ClusterTable,
+};
+
+use datafusion::error::Result;
+
+/// Push the filters, projections, partial aggregations and the top k rows down to the data nodes
+///
+/// Triggering conditions:
+/// 1. The input is a scan of ClusterTable without limit, under filters and projections
+/// 2. The plan is an aggregation, a limit of a sort, or has filters
+/// 3. All expressions can be serialized
+///
+/// The data nodes execute the fragment over their vnodes, the query node merges the
+/// partial states of the aggregation, or sorts and limits the top k rows of every node again.
+pub struct PushDownPlanFragment {}
+
+impl OptimizerRule for PushDownPlanFragment {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        optimizer_config: &mut OptimizerConfig,
+    ) -> Result<LogicalPlan> {
+        if let Some(new_plan) = push_down_plan_fragment(plan)? {
+            return Ok(new_plan);
+        }
+
+        // If we didn't find the match pattern, recurse as
+        // normal and build the result.
+        datafusion::optimizer::utils::optimize_children(self, plan, optimizer_config)
+    }
+
+    fn name(&self) -> &str {
+        "push_down_plan_fragment"
+    }
+}
+
+fn push_down_plan_fragment(plan: &LogicalPlan) -> Result<Option<LogicalPlan>> {
+    match plan {
+        LogicalPlan::Aggregate(Aggregate {
+            input,
+            group_expr,
+            aggr_expr,
+            ..
+        }) => {
+            let step = FragmentStep::Aggregate {
+                group_expr: group_expr.clone(),
+                aggr_expr: aggr_expr.clone(),
+            };
+            fragment_node(plan.clone(), input, vec![step])
+        }
+        LogicalPlan::Limit(Limit {
+            skip,
+            fetch: Some(fetch),
+            input,
+        }) => {
+            let (expr, input) = match input.as_ref() {
+                LogicalPlan::Sort(Sort { expr, input, .. }) => (expr, input),
+                _ => return Ok(None),
+            };
+
+            // every data node returns its top k rows
+            let k = skip + fetch;
+            let fragment_plan = LogicalPlanBuilder::from(input.as_ref().clone())
+                .sort(expr.clone())?
+                .limit(0, Some(k))?
+                .build()?;
+            let steps = vec![FragmentStep::Sort(expr.clone()), FragmentStep::Limit(k)];
+
+            match fragment_node(fragment_plan, input, steps)? {
+                Some(node) => Ok(Some(
+                    LogicalPlanBuilder::from(node)
+                        .sort(expr.clone())?
+                        .limit(*skip, Some(*fetch))?
+                        .build()?,
+                )),
+                None => Ok(None),
+            }
+        }
+        LogicalPlan::Filter(_) | LogicalPlan::Projection(_) => {
+            // only worth it if the rows are filtered by the data nodes
+            let has_filter = steps_over_scan(plan).map_or(false, |(steps, _)| {
+                steps.iter().any(|s| matches!(s, FragmentStep::Filter(_)))
+            });
+            if !has_filter {
+                return Ok(None);
+            }
+
+            fragment_node(plan.clone(), plan, vec![])
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Returns the filters and projections over the scan, in the order they are applied.
+fn steps_over_scan(plan: &LogicalPlan) -> Option<(Vec<FragmentStep>, &TableScan)> {
+    let mut steps = vec![];
+    let mut plan = plan;
+    let scan = loop {
+        match plan {
+            LogicalPlan::Filter(filter) => {
+                steps.push(FragmentStep::Filter(filter.predicate().clone()));
+                plan = filter.input().as_ref();
+            }
+            LogicalPlan::Projection(Projection {
+                expr,
+                input,
+                alias: None,
+                ..
+            }) => {
+                steps.push(FragmentStep::Projection(expr.clone()));
+                plan = input.as_ref();
+            }
+            LogicalPlan::TableScan(scan) if scan.fetch.is_none() => break scan,
+            _ => return None,
+        }
+    };
+    steps.reverse();
+
+    Some((steps, scan))
+}
+
+/// Replaces `plan` with the fragment of the steps over the scan under `input`
+/// and the `extra_steps`.
+fn fragment_node(
+    plan: LogicalPlan,
+    input: &LogicalPlan,
+    extra_steps: Vec<FragmentStep>,
+) -> Result<Option<LogicalPlan>> {
+    let (mut steps, scan) = match steps_over_scan(input) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let cluster_table = match source_as_provider(&scan.source)?
+        .as_any()
+        .downcast_ref::<ClusterTable>()
+    {
+        Some(table) => table.clone(),
+        None => return Ok(None),
+    };
+
+    steps.extend(extra_steps);
+    let fragment = PlanFragment::new(scan.table_name.clone(), steps);
+    // the expressions not supported by the serialization are evaluated by the query node
+    let encoded_fragment = match fragment.encode() {
+        Ok(encoded) => encoded,
+        Err(_) => return Ok(None),
+    };
+
+    Ok(Some(LogicalPlan::Extension(Extension {
+        node: Arc::new(PlanFragmentPlanNode {
+            plan: Arc::new(plan),
+            source: Arc::new(cluster_table),
+            fragment,
+            encoded_fragment,
+            scan_schema: scan.projected_schema.clone(),
+            filters: scan.filters.clone(),
+        }),
+    })))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use coordinator::service::MockCoordinator;
+    use datafusion::{
+        datasource::provider_as_source,
+        logical_expr::{avg, col, lit, Extension, LogicalPlan, LogicalPlanBuilder},
+    };
+    use models::codec::Encoding;
+    use models::predicate::fragment::FragmentStep;
+    use models::schema::{ColumnType, TableColumn, TskvTableSchema};
+    use models::ValueType;
+
+    use super::push_down_plan_fragment;
+    use crate::{
+        extension::logical::plan_node::plan_fragment::PlanFragmentPlanNode, table::ClusterTable,
+    };
+
+    fn scan() -> LogicalPlanBuilder {
+        let schema = TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "air".to_string(),
+            vec![
+                TableColumn::new(0, "time".to_string(), ColumnType::Time, Encoding::Default),
+                TableColumn::new(1, "station".to_string(), ColumnType::Tag, Encoding::Default),
+                TableColumn::new(
+                    2,
+                    "pressure".to_string(),
+                    ColumnType::Field(ValueType::Float),
+                    Encoding::Default,
+                ),
+            ],
+        );
+        let table = ClusterTable::new(Arc::new(MockCoordinator::default()), schema);
+
+        LogicalPlanBuilder::scan("air", provider_as_source(Arc::new(table)), None).unwrap()
+    }
+
+    fn fragment_steps(plan: &LogicalPlan) -> Vec<FragmentStep> {
+        match plan {
+            LogicalPlan::Extension(Extension { node }) => node
+                .as_any()
+                .downcast_ref::<PlanFragmentPlanNode>()
+                .unwrap()
+                .fragment
+                .steps
+                .clone(),
+            _ => panic!("expect a plan fragment, found: {:?}", plan),
+        }
+    }
+
+    #[test]
+    fn test_push_down_aggregation() {
+        let filter = col("air.pressure").gt(lit(10.0));
+        let plan = scan()
+            .filter(filter.clone())
+            .unwrap()
+            .aggregate(vec![col("air.station")], vec![avg(col("air.pressure"))])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let new_plan = push_down_plan_fragment(&plan).unwrap().unwrap();
+        assert_eq!(new_plan.schema(), plan.schema());
+        assert_eq!(
+            fragment_steps(&new_plan),
+            vec![
+                FragmentStep::Filter(filter),
+                FragmentStep::Aggregate {
+                    group_expr: vec![col("air.station")],
+                    aggr_expr: vec![avg(col("air.pressure"))],
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_push_down_top_k() {
+        let sort = vec![col("air.pressure").sort(false, false)];
+        let plan = scan()
+            .sort(sort.clone())
+            .unwrap()
+            .limit(5, Some(10))
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let new_plan = push_down_plan_fragment(&plan).unwrap().unwrap();
+        let fragment = match &new_plan {
+            LogicalPlan::Limit(limit) => match limit.input.as_ref() {
+                LogicalPlan::Sort(sort) => sort.input.clone(),
+                _ => panic!("expect sort: {:?}", new_plan),
+            },
+            _ => panic!("expect limit: {:?}", new_plan),
+        };
+        assert_eq!(
+            fragment_steps(&fragment),
+            vec![FragmentStep::Sort(sort), FragmentStep::Limit(15)]
+        );
+
+        // nothing filtered
+        let plan = scan()
+            .project(vec![col("air.pressure")])
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(push_down_plan_fragment(&plan).unwrap().is_none());
+    }
+}
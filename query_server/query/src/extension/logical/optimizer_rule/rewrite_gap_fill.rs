@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use datafusion::{
+    common::{Column, DFField, DFSchema},
+    error::DataFusionError,
+    logical_expr::{
+        expr_rewriter::{ExprRewritable, ExprRewriter},
+        Aggregate, Between, BinaryExpr, Extension, LogicalPlan, LogicalPlanBuilder, Operator,
+        Projection, TableScan,
+    },
+    optimizer::{OptimizerConfig, OptimizerRule},
+    prelude::Expr,
+    scalar::ScalarValue,
+};
+
+use crate::extension::{
+    expr::{
+        expr_utils,
+        scalar_function::{
+            time_bucket::{self, stride_nanos},
+            INTERPOLATE, LOCF, TIME_BUCKET_GAPFILL,
+        },
+    },
+    logical::plan_node::gap_fill::{FillStrategy, GapFillOptions, GapFillPlanNode},
+};
+
+use datafusion::error::Result;
+
+const INVALID_ARGUMENTS: &str =
+    "Routine not match. Maybe (interval, time [, origin]). interval is an interval literal without months, time is the time column, origin is a timestamp literal.";
+
+/// Rewrite the aggregation grouped by time_bucket_gapfill to a GapFill node
+///
+/// Projection: locf(AVG(pressure)) ...
+///   Aggregate: groupBy=[[time_bucket_gapfill(interval, time), station]], aggr=[[AVG(pressure)]]
+///
+/// is rewritten to
+///
+/// Projection: AVG(pressure) AS locf(AVG(pressure)) ...
+///   GapFill
+///     Aggregate: groupBy=[[time_bucket(interval, time), station]], aggr=[[AVG(pressure)]]
+///
+/// The buckets are filled in the time range of the filters, or between the first and
+/// the last bucket of the group if the time is not bounded.
+pub struct RewriteGapFill {}
+
+impl OptimizerRule for RewriteGapFill {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        optimizer_config: &mut OptimizerConfig,
+    ) -> Result<LogicalPlan> {
+        if let LogicalPlan::Projection(projection) = plan {
+            if let LogicalPlan::Aggregate(aggregate) = projection.input.as_ref() {
+                if let Some(new_plan) =
+                    self.do_transform(projection, aggregate, optimizer_config)?
+                {
+                    return Ok(new_plan);
+                }
+            }
+        }
+
+        // If we didn't find the match pattern, recurse as
+        // normal and build the result.
+        datafusion::optimizer::utils::optimize_children(self, plan, optimizer_config)
+    }
+
+    fn name(&self) -> &str {
+        "rewrite_gap_fill"
+    }
+}
+
+impl RewriteGapFill {
+    fn do_transform(
+        &self,
+        projection: &Projection,
+        aggregate: &Aggregate,
+        optimizer_config: &mut OptimizerConfig,
+    ) -> Result<Option<LogicalPlan>> {
+        let Aggregate {
+            input,
+            group_expr,
+            aggr_expr,
+            schema: aggr_schema,
+            ..
+        } = aggregate;
+
+        let gapfill_columns = group_expr
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| is_function(e, TIME_BUCKET_GAPFILL))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let time_column = match gapfill_columns.as_slice() {
+            [] => return Ok(None),
+            [i] => *i,
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "There cannot be multiple {} functions in group by, found: {:?}",
+                    TIME_BUCKET_GAPFILL, group_expr
+                )))
+            }
+        };
+
+        let (stride, time, origin) = extract_args(&group_expr[time_column])?;
+        let (start, end) = time_range(input, &time);
+
+        // group by time_bucket instead
+        let new_group_expr = group_expr
+            .iter()
+            .enumerate()
+            .map(|(i, e)| match e {
+                Expr::ScalarUDF { args, .. } if i == time_column => Expr::ScalarUDF {
+                    fun: Arc::new(time_bucket::new()),
+                    args: args.clone(),
+                },
+                _ => e.clone(),
+            })
+            .collect::<Vec<_>>();
+        let new_input = self.optimize(input, optimizer_config)?;
+        let new_aggregate = LogicalPlanBuilder::from(new_input)
+            .aggregate(new_group_expr, aggr_expr.clone())?
+            .build()?;
+
+        let fill_strategies = fill_strategies(&projection.expr, aggr_schema, group_expr.len())?;
+
+        // the values of the missing buckets are null
+        let fields = aggr_schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                if i < group_expr.len() {
+                    f.clone()
+                } else {
+                    DFField::new(
+                        f.qualifier().map(|q| q.as_str()),
+                        f.name(),
+                        f.data_type().clone(),
+                        true,
+                    )
+                }
+            })
+            .collect();
+        let gap_fill_schema = DFSchema::new_with_metadata(fields, HashMap::new())?;
+
+        let options = GapFillOptions {
+            time_column,
+            group_columns: (0..group_expr.len())
+                .filter(|i| *i != time_column)
+                .collect(),
+            stride,
+            origin,
+            start,
+            end,
+            fill_strategies,
+        };
+        let gap_fill = LogicalPlan::Extension(Extension {
+            node: Arc::new(GapFillPlanNode::new(
+                Arc::new(new_aggregate),
+                Arc::new(gap_fill_schema),
+                options,
+            )),
+        });
+
+        // replace the fill functions with their arguments, keep the names of the origin expressions
+        let mut new_exprs = Vec::with_capacity(projection.expr.len());
+        for expr in &projection.expr {
+            let new_expr = expr.clone().rewrite(&mut FillFunctionRewriter {})?;
+            let new_expr = match (expr, new_expr) {
+                (Expr::Alias(..), new_expr) => new_expr,
+                (expr, new_expr) if &new_expr != expr => new_expr.alias(&expr.display_name()?),
+                (_, new_expr) => new_expr,
+            };
+            new_exprs.push(new_expr);
+        }
+
+        Ok(Some(LogicalPlan::Projection(Projection {
+            expr: new_exprs,
+            input: Arc::new(gap_fill),
+            schema: projection.schema.clone(),
+            alias: projection.alias.clone(),
+        })))
+    }
+}
+
+fn is_function(expr: &Expr, name: &str) -> bool {
+    matches!(
+        expr,
+        Expr::ScalarUDF {
+            fun,
+            ..
+        } if fun.name.eq_ignore_ascii_case(name)
+    )
+}
+
+/// Extract the stride, the time column and the origin
+fn extract_args(expr: &Expr) -> Result<(i64, Column, i64)> {
+    let args = match expr {
+        Expr::ScalarUDF { args, .. } if args.len() == 2 || args.len() == 3 => args,
+        _ => return Err(DataFusionError::Plan(INVALID_ARGUMENTS.to_string())),
+    };
+
+    let stride = match &args[0] {
+        Expr::Literal(interval) => stride_nanos(interval)
+            .map_err(|e| DataFusionError::Plan(format!("{}, {}", INVALID_ARGUMENTS, e)))?,
+        _ => return Err(DataFusionError::Plan(INVALID_ARGUMENTS.to_string())),
+    };
+
+    let time = match &args[1] {
+        Expr::Column(c) => c.clone(),
+        _ => return Err(DataFusionError::Plan(INVALID_ARGUMENTS.to_string())),
+    };
+
+    let origin = match args.get(2) {
+        None => 0,
+        Some(Expr::Literal(ScalarValue::TimestampNanosecond(Some(v), _))) => *v,
+        _ => return Err(DataFusionError::Plan(INVALID_ARGUMENTS.to_string())),
+    };
+
+    Ok((stride, time, origin))
+}
+
+/// Returns the fill strategies of the value columns referenced by locf and interpolate
+fn fill_strategies(
+    exprs: &[Expr],
+    aggr_schema: &DFSchema,
+    num_group_columns: usize,
+) -> Result<Vec<(usize, FillStrategy)>> {
+    let fill_functions = expr_utils::find_exprs_in_exprs_deeply_nested(exprs, &|e| {
+        is_function(e, LOCF) || is_function(e, INTERPOLATE)
+    });
+
+    let mut fill_strategies: Vec<(usize, FillStrategy)> = vec![];
+    for expr in fill_functions {
+        let (strategy, arg) = match &expr {
+            Expr::ScalarUDF { fun, args } if args.len() == 1 => {
+                if fun.name.eq_ignore_ascii_case(LOCF) {
+                    (FillStrategy::Locf, &args[0])
+                } else {
+                    (FillStrategy::Interpolate, &args[0])
+                }
+            }
+            _ => continue,
+        };
+
+        let column = match arg {
+            Expr::Column(c) => aggr_schema.index_of_column(c).ok(),
+            _ => None,
+        }
+        .filter(|i| *i >= num_group_columns)
+        .ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "The argument of {} must be an aggregate function, found: {}",
+                expr, arg
+            ))
+        })?;
+
+        match fill_strategies.iter().find(|(c, _)| *c == column) {
+            Some((_, s)) if *s != strategy => {
+                return Err(DataFusionError::Plan(format!(
+                    "{} cannot be filled by both {} and {}",
+                    arg, LOCF, INTERPOLATE
+                )))
+            }
+            Some(_) => {}
+            None => fill_strategies.push((column, strategy)),
+        }
+    }
+
+    Ok(fill_strategies)
+}
+
+/// Returns the time range [start, end) of the filters on the time column
+fn time_range(plan: &LogicalPlan, time: &Column) -> (Option<i64>, Option<i64>) {
+    let mut predicates = vec![];
+    collect_predicates(plan, &mut predicates);
+
+    let mut conjunctions = vec![];
+    for predicate in predicates {
+        split_conjunction(predicate, &mut conjunctions);
+    }
+
+    let (mut start, mut end): (Option<i64>, Option<i64>) = (None, None);
+    let mut restrict = |lower: Option<i64>, upper: Option<i64>| {
+        if let Some(lower) = lower {
+            start = Some(start.map_or(lower, |s| s.max(lower)));
+        }
+        if let Some(upper) = upper {
+            end = Some(end.map_or(upper, |e| e.min(upper)));
+        }
+    };
+
+    for expr in conjunctions {
+        match expr {
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                let (v, op) = match (left.as_ref(), right.as_ref()) {
+                    (Expr::Column(c), Expr::Literal(v)) if c.name == time.name => (v, *op),
+                    (Expr::Literal(v), Expr::Column(c)) if c.name == time.name => (v, swap(*op)),
+                    _ => continue,
+                };
+                let v = match timestamp_value(v) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                match op {
+                    Operator::Eq => restrict(Some(v), Some(v + 1)),
+                    Operator::Gt => restrict(Some(v + 1), None),
+                    Operator::GtEq => restrict(Some(v), None),
+                    Operator::Lt => restrict(None, Some(v)),
+                    Operator::LtEq => restrict(None, Some(v + 1)),
+                    _ => {}
+                }
+            }
+            Expr::Between(Between {
+                expr,
+                negated: false,
+                low,
+                high,
+            }) => {
+                if let (Expr::Column(c), Expr::Literal(low), Expr::Literal(high)) =
+                    (expr.as_ref(), low.as_ref(), high.as_ref())
+                {
+                    if c.name == time.name {
+                        restrict(timestamp_value(low), timestamp_value(high).map(|v| v + 1));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (start, end)
+}
+
+fn collect_predicates<'a>(plan: &'a LogicalPlan, predicates: &mut Vec<&'a Expr>) {
+    match plan {
+        LogicalPlan::Filter(filter) => {
+            predicates.push(filter.predicate());
+            collect_predicates(filter.input(), predicates);
+        }
+        LogicalPlan::TableScan(TableScan { filters, .. }) => predicates.extend(filters.iter()),
+        _ => {}
+    }
+}
+
+fn split_conjunction<'a>(expr: &'a Expr, conjunctions: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        }) => {
+            split_conjunction(left, conjunctions);
+            split_conjunction(right, conjunctions);
+        }
+        Expr::Alias(expr, _) => split_conjunction(expr, conjunctions),
+        other => conjunctions.push(other),
+    }
+}
+
+/// Swap the operands of the comparison
+fn swap(op: Operator) -> Operator {
+    match op {
+        Operator::Gt => Operator::Lt,
+        Operator::GtEq => Operator::LtEq,
+        Operator::Lt => Operator::Gt,
+        Operator::LtEq => Operator::GtEq,
+        op => op,
+    }
+}
+
+fn timestamp_value(v: &ScalarValue) -> Option<i64> {
+    match v {
+        ScalarValue::TimestampNanosecond(Some(v), _) | ScalarValue::Int64(Some(v)) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Replace locf(x) and interpolate(x) with x
+struct FillFunctionRewriter {}
+
+impl ExprRewriter for FillFunctionRewriter {
+    fn mutate(&mut self, expr: Expr) -> Result<Expr> {
+        match expr {
+            Expr::ScalarUDF { fun, mut args }
+                if args.len() == 1
+                    && (fun.name.eq_ignore_ascii_case(LOCF)
+                        || fun.name.eq_ignore_ascii_case(INTERPOLATE)) =>
+            {
+                Ok(args.remove(0))
+            }
+            _ => Ok(expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::{
+        arrow::datatypes::{DataType, Field, Schema, TimeUnit},
+        common::Column,
+        logical_expr::{col, lit, logical_plan::builder::table_scan},
+        scalar::ScalarValue,
+    };
+
+    use super::time_range;
+
+    #[test]
+    fn test_time_range() {
+        let time = Column::from_name("time");
+        let ts = |v: i64| lit(ScalarValue::TimestampNanosecond(Some(v), None));
+        let schema = Schema::new(vec![Field::new(
+            "time",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            false,
+        )]);
+
+        let plan = table_scan(Some("air"), &schema, None)
+            .unwrap()
+            .filter(
+                col("time")
+                    .gt_eq(ts(10))
+                    .and(ts(100).gt(col("time")))
+                    .and(col("time").lt_eq(ts(50))),
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(time_range(&plan, &time), (Some(10), Some(51)));
+
+        let plan = table_scan(Some("air"), &schema, None)
+            .unwrap()
+            .filter(col("time").gt(ts(10)).or(col("time").lt(ts(5))))
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(time_range(&plan, &time), (None, None));
+    }
+}
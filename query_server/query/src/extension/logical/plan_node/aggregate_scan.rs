@@ -0,0 +1,72 @@
+use std::{
+    any::Any,
+    fmt::{self, Debug},
+    sync::Arc,
+};
+
+use datafusion::{
+    common::DFSchemaRef,
+    logical_expr::{LogicalPlan, UserDefinedLogicalNode},
+    prelude::Expr,
+};
+use models::predicate::aggregate::PushedAggregateFunction;
+
+use crate::table::ClusterTable;
+
+/// Scan the table and answer the aggregate functions on every vnode,
+/// outputs one row of partial results per vnode.
+#[derive(Clone)]
+pub struct AggregateScanPlanNode {
+    /// The name of the table
+    pub table_name: String,
+    /// The source of the table
+    pub source: Arc<ClusterTable>,
+    /// The aggregate functions pushed down into the scan
+    pub aggregates: Vec<PushedAggregateFunction>,
+    /// The schema description of the partial results
+    pub projected_schema: DFSchemaRef,
+    /// Expressions to be used as filters by the table provider
+    pub filters: Vec<Expr>,
+}
+
+impl Debug for AggregateScanPlanNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_for_explain(f)
+    }
+}
+
+impl UserDefinedLogicalNode for AggregateScanPlanNode {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.projected_schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "AggregateScan: {}, aggregates={:?}, filters={:?}",
+            self.table_name, self.aggregates, self.filters
+        )
+    }
+
+    fn from_template(
+        &self,
+        exprs: &[Expr],
+        inputs: &[LogicalPlan],
+    ) -> Arc<dyn UserDefinedLogicalNode> {
+        assert_eq!(inputs.len(), 0, "input size inconsistent");
+        assert_eq!(exprs.len(), 0, "expr size inconsistent");
+        Arc::new(self.clone())
+    }
+}
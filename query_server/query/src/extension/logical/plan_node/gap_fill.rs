@@ -0,0 +1,130 @@
+use std::{
+    any::Any,
+    fmt::{self, Debug, Display},
+    sync::Arc,
+};
+
+use datafusion::{
+    common::DFSchemaRef,
+    logical_expr::{LogicalPlan, UserDefinedLogicalNode},
+    prelude::Expr,
+};
+
+/// How to fill the values of the aggregate functions in the missing buckets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillStrategy {
+    /// Last observation carried forward
+    Locf,
+    /// Linear interpolation between the previous and the next observation
+    Interpolate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GapFillOptions {
+    /// The index of the bucket column in the input
+    pub time_column: usize,
+    /// The indices of the other group columns in the input
+    pub group_columns: Vec<usize>,
+    /// The width of the buckets in nanoseconds
+    pub stride: i64,
+    /// The origin of the buckets in nanoseconds
+    pub origin: i64,
+    /// The inclusive lower bound of the time, the first bucket of the group if None
+    pub start: Option<i64>,
+    /// The exclusive upper bound of the time, the last bucket of the group if None
+    pub end: Option<i64>,
+    /// The value columns to be filled, the other value columns are null in the missing buckets
+    pub fill_strategies: Vec<(usize, FillStrategy)>,
+}
+
+impl Display for GapFillOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GapFillOptions: time_column={}, group_columns={:?}, stride={}, origin={}, start={}, end={}, fill={:?}",
+            self.time_column,
+            self.group_columns,
+            self.stride,
+            self.origin,
+            self.start.map_or("None".to_string(), |x| x.to_string()),
+            self.end.map_or("None".to_string(), |x| x.to_string()),
+            self.fill_strategies,
+        )
+    }
+}
+
+/// Emits the missing buckets of every group of the time_bucket_gapfill aggregation.
+pub struct GapFillPlanNode {
+    /// The aggregation grouped by the buckets
+    input: Arc<LogicalPlan>,
+    /// The schema of the origin aggregation
+    schema: DFSchemaRef,
+
+    options: GapFillOptions,
+}
+
+impl GapFillPlanNode {
+    pub fn new(input: Arc<LogicalPlan>, schema: DFSchemaRef, options: GapFillOptions) -> Self {
+        Self {
+            input,
+            schema,
+            options,
+        }
+    }
+
+    pub fn input(&self) -> &Arc<LogicalPlan> {
+        &self.input
+    }
+
+    pub fn options(&self) -> &GapFillOptions {
+        &self.options
+    }
+}
+
+impl Debug for GapFillPlanNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_for_explain(f)
+    }
+}
+
+impl UserDefinedLogicalNode for GapFillPlanNode {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    /// The columns of the input are renamed to the ones of the origin aggregation
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    /// The columns are referenced by index, so all the columns of the input are required
+    fn expressions(&self) -> Vec<Expr> {
+        self.input
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| Expr::Column(f.qualified_column()))
+            .collect()
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "GapFill: {}", self.options)
+    }
+
+    fn from_template(
+        &self,
+        _exprs: &[Expr],
+        inputs: &[LogicalPlan],
+    ) -> Arc<dyn UserDefinedLogicalNode> {
+        assert_eq!(inputs.len(), 1, "input size inconsistent");
+        Arc::new(GapFillPlanNode {
+            input: Arc::new(inputs[0].clone()),
+            schema: self.schema.clone(),
+            options: self.options.clone(),
+        })
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    any::Any,
+    fmt::{self, Debug},
+    sync::Arc,
+};
+
+use datafusion::{
+    common::DFSchemaRef,
+    logical_expr::{LogicalPlan, UserDefinedLogicalNode},
+    prelude::Expr,
+};
+use models::predicate::fragment::PlanFragment;
+
+use crate::table::ClusterTable;
+
+/// A part of the plan over the scan of a table, executed by the data nodes over their vnodes.
+/// If the fragment ends with an aggregation, the data nodes only output the partial states
+/// and the query node merges them.
+#[derive(Clone)]
+pub struct PlanFragmentPlanNode {
+    /// The plan replaced, planned to find the partial aggregation
+    pub plan: Arc<LogicalPlan>,
+    /// The source of the table
+    pub source: Arc<ClusterTable>,
+    pub fragment: PlanFragment,
+    pub encoded_fragment: Vec<u8>,
+    /// The schema description of the columns scanned
+    pub scan_schema: DFSchemaRef,
+    /// Expressions to be used as filters by the table provider
+    pub filters: Vec<Expr>,
+}
+
+impl Debug for PlanFragmentPlanNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_for_explain(f)
+    }
+}
+
+impl UserDefinedLogicalNode for PlanFragmentPlanNode {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        self.plan.schema()
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PlanFragment: {}, steps={:?}, filters={:?}",
+            self.fragment.table_name, self.fragment.steps, self.filters
+        )
+    }
+
+    fn from_template(
+        &self,
+        exprs: &[Expr],
+        inputs: &[LogicalPlan],
+    ) -> Arc<dyn UserDefinedLogicalNode> {
+        assert_eq!(inputs.len(), 0, "input size inconsistent");
+        assert_eq!(exprs.len(), 0, "expr size inconsistent");
+        Arc::new(self.clone())
+    }
+}
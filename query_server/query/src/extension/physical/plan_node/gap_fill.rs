@@ -0,0 +1,438 @@
+use std::{any::Any, fmt::Debug, ops::Range, sync::Arc};
+
+use async_trait::async_trait;
+use datafusion::{
+    arrow::{
+        array::{
+            as_primitive_array, Array, ArrayRef, Float64Array, TimestampNanosecondArray,
+            UInt32Array,
+        },
+        compute::{
+            cast, concat_batches, lexicographical_partition_ranges, lexsort_to_indices, take,
+            SortColumn,
+        },
+        datatypes::{DataType, Float64Type, SchemaRef, TimestampNanosecondType},
+        error::ArrowError,
+        record_batch::RecordBatch,
+    },
+    error::DataFusionError,
+    execution::context::TaskContext,
+    physical_expr::PhysicalSortExpr,
+    physical_plan::{
+        common,
+        metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet},
+        stream::RecordBatchStreamAdapter,
+        DisplayFormatType, Distribution, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+        Statistics,
+    },
+};
+
+use datafusion::error::Result;
+use futures::TryStreamExt;
+use trace::debug;
+
+use crate::extension::{
+    expr::scalar_function::time_bucket::bucket_start,
+    logical::plan_node::gap_fill::{FillStrategy, GapFillOptions},
+};
+
+/// The maximum number of rows output by the gap fill
+const MAX_GAP_FILL_ROWS: usize = 10_000_000;
+
+pub struct GapFillExec {
+    input: Arc<dyn ExecutionPlan>,
+    schema: SchemaRef,
+    options: GapFillOptions,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl GapFillExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>, schema: SchemaRef, options: GapFillOptions) -> Self {
+        Self {
+            input,
+            schema,
+            options,
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
+}
+
+impl Debug for GapFillExec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GapFillExec")
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for GapFillExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        Distribution::SinglePartition
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(GapFillExec {
+            input: children[0].clone(),
+            schema: self.schema.clone(),
+            options: self.options.clone(),
+            metrics: self.metrics.clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        debug!(
+            "Start GapFillExec::execute for partition {} of context session_id {} and task_id {:?}",
+            partition,
+            context.session_id(),
+            context.task_id()
+        );
+
+        let input = self.input.execute(partition, context)?;
+        let baseline_metrics = BaselineMetrics::new(&self.metrics, partition);
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            futures::stream::once(do_gap_fill(
+                input,
+                self.schema(),
+                self.options.clone(),
+                baseline_metrics,
+            ))
+            .map_err(ArrowError::from),
+        )))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "GapFillExec: {}", self.options)
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+async fn do_gap_fill(
+    input: SendableRecordBatchStream,
+    schema: SchemaRef,
+    options: GapFillOptions,
+    baseline_metrics: BaselineMetrics,
+) -> Result<RecordBatch> {
+    let input_schema = input.schema();
+    let batches = common::collect(input).await?;
+    let batch = concat_batches(&input_schema, &batches)?;
+
+    let timer = baseline_metrics.elapsed_compute().timer();
+    let result = gap_fill(&batch, &options, schema)?;
+    timer.done();
+
+    baseline_metrics.record_output(result.num_rows());
+    Ok(result)
+}
+
+/// Sorts the rows by the groups and the buckets, inserts the missing buckets of every group.
+fn gap_fill(
+    batch: &RecordBatch,
+    options: &GapFillOptions,
+    schema: SchemaRef,
+) -> Result<RecordBatch> {
+    let GapFillOptions {
+        time_column,
+        group_columns,
+        stride,
+        origin,
+        start,
+        end,
+        fill_strategies,
+    } = options;
+
+    let sort_columns = group_columns
+        .iter()
+        .chain([time_column])
+        .map(|&i| SortColumn {
+            values: batch.column(i).clone(),
+            options: None,
+        })
+        .collect::<Vec<_>>();
+    let indices = lexsort_to_indices(&sort_columns, None)?;
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|c| take(c.as_ref(), &indices, None))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let group_ranges: Vec<Range<usize>> = if batch.num_rows() == 0 {
+        vec![]
+    } else if group_columns.is_empty() {
+        vec![0..batch.num_rows()]
+    } else {
+        let sort_columns = group_columns
+            .iter()
+            .map(|&i| SortColumn {
+                values: columns[i].clone(),
+                options: None,
+            })
+            .collect::<Vec<_>>();
+        lexicographical_partition_ranges(&sort_columns)?.collect()
+    };
+
+    let times = as_primitive_array::<TimestampNanosecondType>(columns[*time_column].as_ref());
+    // the row of the input for every output row, None if the bucket is missing
+    let mut sources: Vec<Option<u32>> = vec![];
+    // the first row of the group for every output row
+    let mut group_rows: Vec<u32> = vec![];
+    let mut out_times: Vec<i64> = vec![];
+    let mut out_ranges: Vec<Range<usize>> = Vec::with_capacity(group_ranges.len());
+
+    for range in group_ranges {
+        let out_start = out_times.len();
+        let first = times.value(range.start);
+        let last = times.value(range.end - 1);
+        let mut bucket = start.map_or(first, |s| bucket_start(s, *stride, *origin));
+        let group_end = end.unwrap_or(last + 1);
+
+        let mut row = range.start;
+        while row < range.end || bucket < group_end {
+            if out_times.len() >= MAX_GAP_FILL_ROWS {
+                return Err(DataFusionError::ResourcesExhausted(format!(
+                    "gap fill produced more than {} rows, try a larger interval or a smaller time range",
+                    MAX_GAP_FILL_ROWS
+                )));
+            }
+
+            let ts = (row < range.end).then(|| times.value(row));
+            match ts {
+                Some(ts) if bucket >= group_end || ts <= bucket => {
+                    if ts == bucket {
+                        bucket += stride;
+                    }
+                    sources.push(Some(row as u32));
+                    out_times.push(ts);
+                    row += 1;
+                }
+                _ => {
+                    sources.push(None);
+                    out_times.push(bucket);
+                    bucket += stride;
+                }
+            }
+            group_rows.push(range.start as u32);
+        }
+
+        out_ranges.push(out_start..out_times.len());
+    }
+
+    let source_indices = UInt32Array::from(sources.clone());
+    let group_indices = UInt32Array::from(group_rows);
+    let strategy = |i: usize| {
+        fill_strategies
+            .iter()
+            .find(|(column, _)| *column == i)
+            .map(|(_, s)| *s)
+    };
+
+    let mut arrays = Vec::with_capacity(columns.len());
+    for (i, column) in columns.iter().enumerate() {
+        let array = if i == *time_column {
+            Arc::new(TimestampNanosecondArray::from(out_times.clone())) as ArrayRef
+        } else if group_columns.contains(&i) {
+            take(column.as_ref(), &group_indices, None)?
+        } else {
+            match strategy(i) {
+                None => take(column.as_ref(), &source_indices, None)?,
+                Some(FillStrategy::Locf) => {
+                    let indices = locf_indices(column, &sources, &out_ranges);
+                    take(column.as_ref(), &indices, None)?
+                }
+                Some(FillStrategy::Interpolate) => {
+                    let values = take(column.as_ref(), &source_indices, None)?;
+                    interpolate(&values, &out_times, &out_ranges)?
+                }
+            }
+        };
+        arrays.push(array);
+    }
+
+    Ok(RecordBatch::try_new(schema, arrays)?)
+}
+
+/// Returns the last non-null row of the group for every output row
+fn locf_indices(
+    column: &ArrayRef,
+    sources: &[Option<u32>],
+    out_ranges: &[Range<usize>],
+) -> UInt32Array {
+    let mut indices = Vec::with_capacity(sources.len());
+    for range in out_ranges {
+        let mut last = None;
+        for source in &sources[range.clone()] {
+            if let Some(row) = source {
+                if column.is_valid(*row as usize) {
+                    last = Some(*row);
+                }
+            }
+            indices.push(last);
+        }
+    }
+
+    UInt32Array::from(indices)
+}
+
+/// Fills the nulls between two values of the group by linear interpolation
+fn interpolate(values: &ArrayRef, times: &[i64], out_ranges: &[Range<usize>]) -> Result<ArrayRef> {
+    let data_type = values.data_type().clone();
+    let float_values = cast(values, &DataType::Float64)?;
+    let mut filled: Vec<Option<f64>> = as_primitive_array::<Float64Type>(float_values.as_ref())
+        .iter()
+        .collect();
+
+    for range in out_ranges {
+        let mut prev: Option<usize> = None;
+        for k in range.clone() {
+            let value = match filled[k] {
+                Some(v) => v,
+                None => continue,
+            };
+            if let Some(p) = prev {
+                let prev_value = filled[p].unwrap_or_default();
+                let span = (times[k] - times[p]) as f64;
+                for m in p + 1..k {
+                    let ratio = (times[m] - times[p]) as f64 / span;
+                    filled[m] = Some(prev_value + (value - prev_value) * ratio);
+                }
+            }
+            prev = Some(k);
+        }
+    }
+
+    let filled: ArrayRef = Arc::new(Float64Array::from(filled));
+    Ok(cast(&filled, &data_type)?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use datafusion::arrow::{
+        array::{ArrayRef, Float64Array, Int64Array, StringArray, TimestampNanosecondArray},
+        datatypes::{DataType, Field, Schema, TimeUnit},
+        record_batch::RecordBatch,
+    };
+
+    use super::gap_fill;
+    use crate::extension::logical::plan_node::gap_fill::{FillStrategy, GapFillOptions};
+
+    #[test]
+    fn test_gap_fill() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("station", DataType::Utf8, true),
+            Field::new("avg", DataType::Float64, true),
+            Field::new("max", DataType::Int64, true),
+            Field::new("count", DataType::Int64, true),
+        ]));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(TimestampNanosecondArray::from(vec![40, 10, 0, 30])),
+            Arc::new(StringArray::from(vec!["a", "a", "a", "b"])),
+            Arc::new(Float64Array::from(vec![4.0, 1.0, 0.0, 3.0])),
+            Arc::new(Int64Array::from(vec![40, 10, 0, 30])),
+            Arc::new(Int64Array::from(vec![1, 1, 1, 1])),
+        ];
+        let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+
+        let options = GapFillOptions {
+            time_column: 0,
+            group_columns: vec![1],
+            stride: 10,
+            origin: 0,
+            start: Some(5),
+            end: Some(50),
+            fill_strategies: vec![(2, FillStrategy::Interpolate), (3, FillStrategy::Locf)],
+        };
+        let result = gap_fill(&batch, &options, schema).unwrap();
+
+        let expected: Vec<ArrayRef> = vec![
+            Arc::new(TimestampNanosecondArray::from(vec![
+                0, 10, 20, 30, 40, 0, 10, 20, 30, 40,
+            ])),
+            Arc::new(StringArray::from(vec![
+                "a", "a", "a", "a", "a", "b", "b", "b", "b", "b",
+            ])),
+            Arc::new(Float64Array::from(vec![
+                Some(0.0),
+                Some(1.0),
+                Some(2.0),
+                Some(3.0),
+                Some(4.0),
+                None,
+                None,
+                None,
+                Some(3.0),
+                None,
+            ])),
+            Arc::new(Int64Array::from(vec![
+                Some(0),
+                Some(10),
+                Some(10),
+                Some(10),
+                Some(40),
+                None,
+                None,
+                None,
+                Some(30),
+                Some(30),
+            ])),
+            Arc::new(Int64Array::from(vec![
+                Some(1),
+                Some(1),
+                None,
+                None,
+                Some(1),
+                None,
+                None,
+                None,
+                Some(1),
+                None,
+            ])),
+        ];
+        assert_eq!(result.columns(), expected.as_slice());
+    }
+}
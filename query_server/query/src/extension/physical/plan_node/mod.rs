@@ -1,3 +1,4 @@
+pub mod gap_fill;
 pub mod table_writer;
 pub mod tag_scan;
 pub mod topk;
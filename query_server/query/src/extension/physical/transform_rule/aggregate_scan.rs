@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::{
+    execution::context::SessionState,
+    logical_expr::{LogicalPlan, UserDefinedLogicalNode},
+    physical_plan::{planner::ExtensionPlanner, ExecutionPlan, PhysicalPlanner},
+};
+
+use crate::extension::logical::plan_node::aggregate_scan::AggregateScanPlanNode;
+
+use datafusion::error::Result;
+
+/// Physical planner for AggregateScan nodes
+pub struct AggregateScanPlanner {}
+
+#[async_trait]
+impl ExtensionPlanner for AggregateScanPlanner {
+    /// Create a physical plan for an extension node
+    async fn plan_extension(
+        &self,
+        _planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        _logical_inputs: &[&LogicalPlan],
+        _physical_inputs: &[Arc<dyn ExecutionPlan>],
+        session_state: &SessionState,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        Ok(
+            if let Some(AggregateScanPlanNode {
+                table_name: _,
+                source,
+                aggregates,
+                projected_schema,
+                filters,
+            }) = as_aggregate_scan_plan_node(node)
+            {
+                let aggregate_scan = source
+                    .aggregate_scan(session_state, projected_schema, filters, aggregates)
+                    .await?;
+
+                Some(aggregate_scan)
+            } else {
+                None
+            },
+        )
+    }
+}
+
+fn as_aggregate_scan_plan_node(
+    node: &dyn UserDefinedLogicalNode,
+) -> Option<&AggregateScanPlanNode> {
+    node.as_any().downcast_ref::<AggregateScanPlanNode>()
+}
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::{
+    arrow::datatypes::Schema,
+    execution::context::SessionState,
+    logical_expr::{LogicalPlan, UserDefinedLogicalNode},
+    physical_plan::{planner::ExtensionPlanner, ExecutionPlan, PhysicalPlanner},
+};
+
+use crate::extension::{
+    logical::plan_node::gap_fill::GapFillPlanNode, physical::plan_node::gap_fill::GapFillExec,
+};
+
+use datafusion::error::Result;
+
+/// Physical planner for GapFill nodes
+pub struct GapFillPlanner {}
+
+#[async_trait]
+impl ExtensionPlanner for GapFillPlanner {
+    /// Create a physical plan for an extension node
+    async fn plan_extension(
+        &self,
+        _planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        _logical_inputs: &[&LogicalPlan],
+        physical_inputs: &[Arc<dyn ExecutionPlan>],
+        _session_state: &SessionState,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        Ok(
+            if let Some(gap_fill_node) = node.as_any().downcast_ref::<GapFillPlanNode>() {
+                let schema: Schema = gap_fill_node.schema().as_ref().into();
+
+                Some(Arc::new(GapFillExec::new(
+                    physical_inputs[0].clone(),
+                    Arc::new(schema),
+                    gap_fill_node.options().clone(),
+                )))
+            } else {
+                None
+            },
+        )
+    }
+}
@@ -1,4 +1,7 @@
 //! logical paln to physical plan transform rule
+pub mod aggregate_scan;
+pub mod gap_fill;
+pub mod plan_fragment;
 pub mod table_writer;
 pub mod tag_scan;
 pub mod topk;
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::{
+    arrow::datatypes::Schema,
+    execution::context::SessionState,
+    logical_expr::{LogicalPlan, UserDefinedLogicalNode},
+    physical_plan::{
+        aggregates::{AggregateExec, AggregateMode},
+        planner::ExtensionPlanner,
+        ExecutionPlan, PhysicalPlanner,
+    },
+};
+
+use crate::{
+    extension::logical::plan_node::plan_fragment::PlanFragmentPlanNode, tskv_exec::ScanFragment,
+};
+
+use datafusion::error::{DataFusionError, Result};
+
+/// Physical planner for PlanFragment nodes
+pub struct PlanFragmentPlanner {}
+
+#[async_trait]
+impl ExtensionPlanner for PlanFragmentPlanner {
+    /// Create a physical plan for an extension node
+    async fn plan_extension(
+        &self,
+        planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        _logical_inputs: &[&LogicalPlan],
+        _physical_inputs: &[Arc<dyn ExecutionPlan>],
+        session_state: &SessionState,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        let node = match node.as_any().downcast_ref::<PlanFragmentPlanNode>() {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+
+        let scan_fragment = |schema| ScanFragment {
+            fragment: node.fragment.clone(),
+            encoded: node.encoded_fragment.clone(),
+            schema,
+        };
+
+        if !node.fragment.is_partial_aggregate() {
+            let schema: Schema = node.plan.schema().as_ref().into();
+            let scan = node
+                .source
+                .fragment_scan(
+                    session_state,
+                    &node.scan_schema,
+                    &node.filters,
+                    scan_fragment(Arc::new(schema)),
+                )
+                .await?;
+            return Ok(Some(scan));
+        }
+
+        // The partial aggregation is replaced with the scan of the partial states,
+        // the merge of the states is planned as usual.
+        let plan = planner
+            .create_physical_plan(&node.plan, session_state)
+            .await?;
+        let partial = find_partial_aggregate(&plan).ok_or_else(|| {
+            DataFusionError::Internal(format!(
+                "partial aggregation not found in the plan: {:?}",
+                node.plan
+            ))
+        })?;
+        let scan = node
+            .source
+            .fragment_scan(
+                session_state,
+                &node.scan_schema,
+                &node.filters,
+                scan_fragment(partial.schema()),
+            )
+            .await?;
+
+        Ok(Some(replace_partial_aggregate(plan, &scan)?))
+    }
+}
+
+fn is_partial_aggregate(plan: &Arc<dyn ExecutionPlan>) -> bool {
+    plan.as_any()
+        .downcast_ref::<AggregateExec>()
+        .map_or(false, |aggr| aggr.mode() == &AggregateMode::Partial)
+}
+
+/// Returns the first partial aggregation in the plan.
+pub fn find_partial_aggregate(plan: &Arc<dyn ExecutionPlan>) -> Option<Arc<dyn ExecutionPlan>> {
+    if is_partial_aggregate(plan) {
+        return Some(plan.clone());
+    }
+
+    plan.children().iter().find_map(find_partial_aggregate)
+}
+
+fn replace_partial_aggregate(
+    plan: Arc<dyn ExecutionPlan>,
+    replacement: &Arc<dyn ExecutionPlan>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    if is_partial_aggregate(&plan) {
+        return Ok(replacement.clone());
+    }
+
+    let children = plan.children();
+    if children.is_empty() {
+        return Ok(plan);
+    }
+
+    let children = children
+        .into_iter()
+        .map(|child| replace_partial_aggregate(child, replacement))
+        .collect::<Result<Vec<_>>>()?;
+
+    plan.with_new_children(children)
+}
@@ -0,0 +1,239 @@
+use std::{
+    any::Any,
+    fmt::{self, Debug},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use coordinator::errors::{CoordinatorError, CoordinatorResult};
+use coordinator::service::FragmentExecutor;
+use datafusion::{
+    arrow::datatypes::SchemaRef,
+    datasource::{provider_as_source, TableProvider, TableType},
+    error::{DataFusionError, Result as DFResult},
+    execution::context::{SessionState, TaskContext},
+    logical_expr::{Expr, LogicalPlan, LogicalPlanBuilder},
+    physical_expr::PhysicalSortExpr,
+    physical_plan::{
+        coalesce_partitions::CoalescePartitionsExec, DisplayFormatType, ExecutionPlan,
+        Partitioning, SendableRecordBatchStream, Statistics,
+    },
+    prelude::SessionContext,
+};
+use models::predicate::fragment::{FragmentStep, PlanFragment};
+use parking_lot::Mutex;
+use spi::Result;
+use trace::debug;
+
+use crate::extension::expr::load_all_functions;
+use crate::extension::physical::transform_rule::plan_fragment::find_partial_aggregate;
+use crate::function::simple_func_manager::SimpleFunctionMetadataManager;
+
+/// Executes the plan fragments pushed down to the data nodes with datafusion.
+pub struct FragmentExecutorImpl {
+    ctx: SessionContext,
+}
+
+impl FragmentExecutorImpl {
+    pub fn new() -> Result<Self> {
+        let mut func_manager = SimpleFunctionMetadataManager::default();
+        load_all_functions(&mut func_manager)?;
+
+        // the functions in the fragments are resolved by the session
+        let mut ctx = SessionContext::new();
+        for udf in func_manager.scalar_functions.into_values() {
+            ctx.register_udf(udf.as_ref().clone());
+        }
+        for udaf in func_manager.aggregate_functions.into_values() {
+            ctx.register_udaf(udaf.as_ref().clone());
+        }
+
+        Ok(Self { ctx })
+    }
+
+    async fn execute_fragment(
+        &self,
+        fragment: &[u8],
+        input: SendableRecordBatchStream,
+    ) -> DFResult<SendableRecordBatchStream> {
+        // The plan must be built as the query node planned it, so the outputs are the same
+        let state = self.ctx.state().with_optimizer_rules(vec![]);
+        let fragment = PlanFragment::decode(fragment, &state)
+            .map_err(|err| DataFusionError::External(Box::new(err)))?;
+        debug!("execute plan fragment: {:?}", fragment);
+
+        let plan = fragment_plan(&fragment, input)?;
+        let mut physical_plan = state.create_physical_plan(&plan).await?;
+
+        // only the partial aggregation is executed, the query node merges the states
+        if fragment.is_partial_aggregate() {
+            physical_plan = find_partial_aggregate(&physical_plan).ok_or_else(|| {
+                DataFusionError::Internal(format!(
+                    "partial aggregation not found in the plan of fragment: {:?}",
+                    fragment
+                ))
+            })?;
+        }
+        if physical_plan.output_partitioning().partition_count() > 1 {
+            physical_plan = Arc::new(CoalescePartitionsExec::new(physical_plan));
+        }
+
+        physical_plan.execute(0, Arc::new(TaskContext::from(&state)))
+    }
+}
+
+impl Debug for FragmentExecutorImpl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FragmentExecutorImpl").finish()
+    }
+}
+
+#[async_trait]
+impl FragmentExecutor for FragmentExecutorImpl {
+    async fn execute(
+        &self,
+        fragment: &[u8],
+        input: SendableRecordBatchStream,
+    ) -> CoordinatorResult<SendableRecordBatchStream> {
+        self.execute_fragment(fragment, input)
+            .await
+            .map_err(|err| CoordinatorError::CommonError {
+                msg: err.to_string(),
+            })
+    }
+}
+
+/// Applies the steps of the fragment to the scanned batches.
+fn fragment_plan(
+    fragment: &PlanFragment,
+    input: SendableRecordBatchStream,
+) -> DFResult<LogicalPlan> {
+    let source = provider_as_source(Arc::new(FragmentSource::new(input)));
+    let mut builder = LogicalPlanBuilder::scan(&fragment.table_name, source, None)?;
+
+    for step in fragment.steps.iter() {
+        builder = match step {
+            FragmentStep::Filter(expr) => builder.filter(expr.clone())?,
+            FragmentStep::Projection(exprs) => builder.project(exprs.clone())?,
+            FragmentStep::Aggregate {
+                group_expr,
+                aggr_expr,
+            } => builder.aggregate(group_expr.clone(), aggr_expr.clone())?,
+            FragmentStep::Sort(exprs) => builder.sort(exprs.clone())?,
+            FragmentStep::Limit(fetch) => builder.limit(0, Some(*fetch))?,
+        };
+    }
+
+    builder.build()
+}
+
+/// The batches scanned from the vnodes, can only be scanned once.
+struct FragmentSource {
+    schema: SchemaRef,
+    input: Arc<Mutex<Option<SendableRecordBatchStream>>>,
+}
+
+impl FragmentSource {
+    fn new(input: SendableRecordBatchStream) -> Self {
+        Self {
+            schema: input.schema(),
+            input: Arc::new(Mutex::new(Some(input))),
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for FragmentSource {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Temporary
+    }
+
+    async fn scan(
+        &self,
+        _ctx: &SessionState,
+        projection: &Option<Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        if projection.is_some() {
+            return Err(DataFusionError::Internal(
+                "the scan of plan fragment does not support projection".to_string(),
+            ));
+        }
+
+        Ok(Arc::new(FragmentSourceExec {
+            schema: self.schema.clone(),
+            input: self.input.clone(),
+        }))
+    }
+}
+
+struct FragmentSourceExec {
+    schema: SchemaRef,
+    input: Arc<Mutex<Option<SendableRecordBatchStream>>>,
+}
+
+impl Debug for FragmentSourceExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FragmentSourceExec")
+            .field("schema", &self.schema)
+            .finish()
+    }
+}
+
+impl ExecutionPlan for FragmentSourceExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        Ok(self)
+    }
+
+    fn execute(
+        &self,
+        _partition: usize,
+        _context: Arc<TaskContext>,
+    ) -> DFResult<SendableRecordBatchStream> {
+        self.input.lock().take().ok_or_else(|| {
+            DataFusionError::Internal("the scan of plan fragment is executed twice".to_string())
+        })
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => write!(f, "FragmentSourceExec"),
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
@@ -6,12 +6,14 @@ pub mod dispatcher;
 pub mod error;
 mod execution;
 pub mod extension;
+pub mod fragment_executor;
 pub mod function;
 pub mod instance;
 pub mod metadata;
 pub mod prom;
 pub mod sql;
 mod stream;
+pub mod stream_executor;
 mod table;
 mod tskv_exec;
-mod utils;
+pub mod utils;
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use datafusion::{
+    arrow::{
+        array::{BooleanBuilder, Float64Builder, TimestampNanosecondBuilder, UInt64Builder},
+        datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+        record_batch::RecordBatch,
+    },
+    datasource::MemTable,
+    error::DataFusionError,
+};
+
+use lazy_static::lazy_static;
+use models::meta_data::{HintedOffQueueStatus, NodeId};
+
+lazy_static! {
+    static ref SCHEMA: SchemaRef = Arc::new(Schema::new(vec![
+        Field::new("node_id", DataType::UInt64, false),
+        Field::new("target_node_id", DataType::UInt64, false),
+        Field::new("size", DataType::UInt64, false),
+        Field::new(
+            "oldest",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            true
+        ),
+        Field::new("paused", DataType::Boolean, false),
+        Field::new("replayed_bytes", DataType::UInt64, false),
+        Field::new("replay_rate", DataType::Float64, false),
+    ]));
+}
+
+/// Builds the `cluster_schema.HINTED_OFF` table row by row
+pub struct ClusterSchemaHintedOffBuilder {
+    node_ids: UInt64Builder,
+    target_node_ids: UInt64Builder,
+    sizes: UInt64Builder,
+    oldests: TimestampNanosecondBuilder,
+    pauseds: BooleanBuilder,
+    replayed_bytes: UInt64Builder,
+    replay_rates: Float64Builder,
+}
+
+impl Default for ClusterSchemaHintedOffBuilder {
+    fn default() -> Self {
+        Self {
+            node_ids: UInt64Builder::new(),
+            target_node_ids: UInt64Builder::new(),
+            sizes: UInt64Builder::new(),
+            oldests: TimestampNanosecondBuilder::new(),
+            pauseds: BooleanBuilder::new(),
+            replayed_bytes: UInt64Builder::new(),
+            replay_rates: Float64Builder::new(),
+        }
+    }
+}
+
+impl ClusterSchemaHintedOffBuilder {
+    pub fn append_row(&mut self, node_id: NodeId, queue: &HintedOffQueueStatus) {
+        // Note: append_value is actually infallable.
+        self.node_ids.append_value(node_id);
+        self.target_node_ids.append_value(queue.target_node);
+        self.sizes.append_value(queue.size);
+        self.oldests.append_option(queue.oldest_ts);
+        self.pauseds.append_value(queue.paused);
+        self.replayed_bytes.append_value(queue.replayed_bytes);
+        self.replay_rates.append_value(queue.replay_rate);
+    }
+}
+
+impl TryFrom<ClusterSchemaHintedOffBuilder> for MemTable {
+    type Error = DataFusionError;
+
+    fn try_from(value: ClusterSchemaHintedOffBuilder) -> Result<Self, Self::Error> {
+        let ClusterSchemaHintedOffBuilder {
+            mut node_ids,
+            mut target_node_ids,
+            mut sizes,
+            mut oldests,
+            mut pauseds,
+            mut replayed_bytes,
+            mut replay_rates,
+        } = value;
+
+        let batch = RecordBatch::try_new(
+            SCHEMA.clone(),
+            vec![
+                Arc::new(node_ids.finish()),
+                Arc::new(target_node_ids.finish()),
+                Arc::new(sizes.finish()),
+                Arc::new(oldests.finish()),
+                Arc::new(pauseds.finish()),
+                Arc::new(replayed_bytes.finish()),
+                Arc::new(replay_rates.finish()),
+            ],
+        )?;
+
+        MemTable::try_new(SCHEMA.clone(), vec![vec![batch]])
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use datafusion::{
+    arrow::{
+        array::{BooleanBuilder, StringBuilder, UInt64Builder},
+        datatypes::{DataType, Field, Schema, SchemaRef},
+        record_batch::RecordBatch,
+    },
+    datasource::MemTable,
+    error::DataFusionError,
+};
+
+use lazy_static::lazy_static;
+use meta::membership::MetaNodeStatus;
+
+lazy_static! {
+    static ref SCHEMA: SchemaRef = Arc::new(Schema::new(vec![
+        Field::new("node_id", DataType::UInt64, false),
+        Field::new("addr", DataType::Utf8, false),
+        Field::new("voter", DataType::Boolean, false),
+        Field::new("role", DataType::Utf8, true),
+        Field::new("term", DataType::UInt64, true),
+        Field::new("last_log_index", DataType::UInt64, true),
+        Field::new("last_applied", DataType::UInt64, true),
+        Field::new("healthy", DataType::Boolean, false),
+    ]));
+}
+
+/// Builds the `cluster_schema.META_NODES` table row by row
+pub struct ClusterSchemaMetaNodesBuilder {
+    node_ids: UInt64Builder,
+    addrs: StringBuilder,
+    voters: BooleanBuilder,
+    roles: StringBuilder,
+    terms: UInt64Builder,
+    last_log_indexes: UInt64Builder,
+    last_applieds: UInt64Builder,
+    healthies: BooleanBuilder,
+}
+
+impl Default for ClusterSchemaMetaNodesBuilder {
+    fn default() -> Self {
+        Self {
+            node_ids: UInt64Builder::new(),
+            addrs: StringBuilder::new(),
+            voters: BooleanBuilder::new(),
+            roles: StringBuilder::new(),
+            terms: UInt64Builder::new(),
+            last_log_indexes: UInt64Builder::new(),
+            last_applieds: UInt64Builder::new(),
+            healthies: BooleanBuilder::new(),
+        }
+    }
+}
+
+impl ClusterSchemaMetaNodesBuilder {
+    pub fn append_row(&mut self, node: &MetaNodeStatus) {
+        // Note: append_value is actually infallable.
+        self.node_ids.append_value(node.id);
+        self.addrs.append_value(&node.addr);
+        self.voters.append_value(node.voter);
+        self.roles.append_option(node.role.as_ref());
+        self.terms.append_option(node.term);
+        self.last_log_indexes.append_option(node.last_log_index);
+        self.last_applieds.append_option(node.last_applied);
+        self.healthies.append_value(node.healthy);
+    }
+}
+
+impl TryFrom<ClusterSchemaMetaNodesBuilder> for MemTable {
+    type Error = DataFusionError;
+
+    fn try_from(value: ClusterSchemaMetaNodesBuilder) -> Result<Self, Self::Error> {
+        let ClusterSchemaMetaNodesBuilder {
+            mut node_ids,
+            mut addrs,
+            mut voters,
+            mut roles,
+            mut terms,
+            mut last_log_indexes,
+            mut last_applieds,
+            mut healthies,
+        } = value;
+
+        let batch = RecordBatch::try_new(
+            SCHEMA.clone(),
+            vec![
+                Arc::new(node_ids.finish()),
+                Arc::new(addrs.finish()),
+                Arc::new(voters.finish()),
+                Arc::new(roles.finish()),
+                Arc::new(terms.finish()),
+                Arc::new(last_log_indexes.finish()),
+                Arc::new(last_applieds.finish()),
+                Arc::new(healthies.finish()),
+            ],
+        )?;
+
+        MemTable::try_new(SCHEMA.clone(), vec![vec![batch]])
+    }
+}
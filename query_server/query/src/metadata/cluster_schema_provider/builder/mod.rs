@@ -1,2 +1,6 @@
+pub mod hinted_off;
+pub mod meta_nodes;
+pub mod nodes;
+pub mod rebalance;
 pub mod tenants;
 pub mod users;
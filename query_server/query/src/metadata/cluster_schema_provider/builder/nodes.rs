@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use datafusion::{
+    arrow::{
+        array::{Float64Builder, StringBuilder, TimestampNanosecondBuilder, UInt64Builder},
+        datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+        record_batch::RecordBatch,
+    },
+    datasource::MemTable,
+    error::DataFusionError,
+};
+
+use lazy_static::lazy_static;
+use models::meta_data::NodeStatus;
+
+lazy_static! {
+    static ref SCHEMA: SchemaRef = Arc::new(Schema::new(vec![
+        Field::new("node_id", DataType::UInt64, false),
+        Field::new("tcp_addr", DataType::Utf8, false),
+        Field::new("http_addr", DataType::Utf8, false),
+        Field::new("status", DataType::Utf8, false),
+        Field::new("state", DataType::Utf8, false),
+        Field::new("version", DataType::Utf8, true),
+        Field::new("cpu_load", DataType::Float64, true),
+        Field::new("mem_free", DataType::UInt64, true),
+        Field::new("disk_free", DataType::UInt64, true),
+        Field::new("disk_total", DataType::UInt64, true),
+        Field::new(
+            "last_heartbeat",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            true
+        ),
+    ]));
+}
+
+/// Builds the `cluster_schema.NODES` table row by row
+pub struct ClusterSchemaNodesBuilder {
+    node_ids: UInt64Builder,
+    tcp_addrs: StringBuilder,
+    http_addrs: StringBuilder,
+    statuses: StringBuilder,
+    states: StringBuilder,
+    versions: StringBuilder,
+    cpu_loads: Float64Builder,
+    mem_frees: UInt64Builder,
+    disk_frees: UInt64Builder,
+    disk_totals: UInt64Builder,
+    last_heartbeats: TimestampNanosecondBuilder,
+}
+
+impl Default for ClusterSchemaNodesBuilder {
+    fn default() -> Self {
+        Self {
+            node_ids: UInt64Builder::new(),
+            tcp_addrs: StringBuilder::new(),
+            http_addrs: StringBuilder::new(),
+            statuses: StringBuilder::new(),
+            states: StringBuilder::new(),
+            versions: StringBuilder::new(),
+            cpu_loads: Float64Builder::new(),
+            mem_frees: UInt64Builder::new(),
+            disk_frees: UInt64Builder::new(),
+            disk_totals: UInt64Builder::new(),
+            last_heartbeats: TimestampNanosecondBuilder::new(),
+        }
+    }
+}
+
+impl ClusterSchemaNodesBuilder {
+    pub fn append_row(&mut self, node: &NodeStatus) {
+        // Note: append_value is actually infallable.
+        self.node_ids.append_value(node.info.id);
+        self.tcp_addrs.append_value(&node.info.tcp_addr);
+        self.http_addrs.append_value(&node.info.http_addr);
+        self.statuses.append_value(node.info.status_name());
+        self.states.append_value(node.state.as_str());
+
+        // the heartbeat of the registration carries no load
+        match node.heartbeat.as_ref() {
+            Some(heartbeat) if !heartbeat.version.is_empty() => {
+                self.versions.append_value(&heartbeat.version);
+                self.cpu_loads.append_value(heartbeat.sys_info.cpu_load);
+                self.mem_frees.append_value(heartbeat.sys_info.mem_free);
+                self.disk_frees.append_value(heartbeat.sys_info.disk_free);
+                self.disk_totals.append_value(heartbeat.sys_info.disk_total);
+            }
+            _ => {
+                self.versions.append_null();
+                self.cpu_loads.append_null();
+                self.mem_frees.append_null();
+                self.disk_frees.append_null();
+                self.disk_totals.append_null();
+            }
+        }
+        self.last_heartbeats
+            .append_option(node.heartbeat.as_ref().map(|h| h.time));
+    }
+}
+
+impl TryFrom<ClusterSchemaNodesBuilder> for MemTable {
+    type Error = DataFusionError;
+
+    fn try_from(value: ClusterSchemaNodesBuilder) -> Result<Self, Self::Error> {
+        let ClusterSchemaNodesBuilder {
+            mut node_ids,
+            mut tcp_addrs,
+            mut http_addrs,
+            mut statuses,
+            mut states,
+            mut versions,
+            mut cpu_loads,
+            mut mem_frees,
+            mut disk_frees,
+            mut disk_totals,
+            mut last_heartbeats,
+        } = value;
+
+        let batch = RecordBatch::try_new(
+            SCHEMA.clone(),
+            vec![
+                Arc::new(node_ids.finish()),
+                Arc::new(tcp_addrs.finish()),
+                Arc::new(http_addrs.finish()),
+                Arc::new(statuses.finish()),
+                Arc::new(states.finish()),
+                Arc::new(versions.finish()),
+                Arc::new(cpu_loads.finish()),
+                Arc::new(mem_frees.finish()),
+                Arc::new(disk_frees.finish()),
+                Arc::new(disk_totals.finish()),
+                Arc::new(last_heartbeats.finish()),
+            ],
+        )?;
+
+        MemTable::try_new(SCHEMA.clone(), vec![vec![batch]])
+    }
+}
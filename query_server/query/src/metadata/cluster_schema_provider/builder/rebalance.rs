@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use datafusion::{
+    arrow::{
+        array::{StringBuilder, UInt64Builder},
+        datatypes::{DataType, Field, Schema, SchemaRef},
+        record_batch::RecordBatch,
+    },
+    datasource::MemTable,
+    error::DataFusionError,
+};
+
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref SCHEMA: SchemaRef = Arc::new(Schema::new(vec![
+        Field::new("node_id", DataType::UInt64, false),
+        Field::new("tcp_addr", DataType::Utf8, false),
+        Field::new("status", DataType::Utf8, false),
+        Field::new("vnodes", DataType::UInt64, false),
+        Field::new("pending_moves_out", DataType::UInt64, false),
+        Field::new("pending_moves_in", DataType::UInt64, false),
+    ]));
+}
+
+/// Builds the `cluster_schema.REBALANCE` table row by row
+pub struct ClusterSchemaRebalanceBuilder {
+    node_ids: UInt64Builder,
+    tcp_addrs: StringBuilder,
+    statuses: StringBuilder,
+    vnodes: UInt64Builder,
+    pending_moves_out: UInt64Builder,
+    pending_moves_in: UInt64Builder,
+}
+
+impl Default for ClusterSchemaRebalanceBuilder {
+    fn default() -> Self {
+        Self {
+            node_ids: UInt64Builder::new(),
+            tcp_addrs: StringBuilder::new(),
+            statuses: StringBuilder::new(),
+            vnodes: UInt64Builder::new(),
+            pending_moves_out: UInt64Builder::new(),
+            pending_moves_in: UInt64Builder::new(),
+        }
+    }
+}
+
+impl ClusterSchemaRebalanceBuilder {
+    pub fn append_row(
+        &mut self,
+        node_id: u64,
+        tcp_addr: impl AsRef<str>,
+        status: impl AsRef<str>,
+        vnodes: u64,
+        pending_moves_out: u64,
+        pending_moves_in: u64,
+    ) {
+        // Note: append_value is actually infallable.
+        self.node_ids.append_value(node_id);
+        self.tcp_addrs.append_value(tcp_addr.as_ref());
+        self.statuses.append_value(status.as_ref());
+        self.vnodes.append_value(vnodes);
+        self.pending_moves_out.append_value(pending_moves_out);
+        self.pending_moves_in.append_value(pending_moves_in);
+    }
+}
+
+impl TryFrom<ClusterSchemaRebalanceBuilder> for MemTable {
+    type Error = DataFusionError;
+
+    fn try_from(value: ClusterSchemaRebalanceBuilder) -> Result<Self, Self::Error> {
+        let ClusterSchemaRebalanceBuilder {
+            mut node_ids,
+            mut tcp_addrs,
+            mut statuses,
+            mut vnodes,
+            mut pending_moves_out,
+            mut pending_moves_in,
+        } = value;
+
+        let batch = RecordBatch::try_new(
+            SCHEMA.clone(),
+            vec![
+                Arc::new(node_ids.finish()),
+                Arc::new(tcp_addrs.finish()),
+                Arc::new(statuses.finish()),
+                Arc::new(vnodes.finish()),
+                Arc::new(pending_moves_out.finish()),
+                Arc::new(pending_moves_in.finish()),
+            ],
+        )?;
+
+        MemTable::try_new(SCHEMA.clone(), vec![vec![batch]])
+    }
+}
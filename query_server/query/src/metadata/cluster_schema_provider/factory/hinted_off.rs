@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use datafusion::datasource::MemTable;
+use meta::{error::MetaError, meta_client::MetaRef};
+use models::auth::user::User;
+
+use crate::metadata::cluster_schema_provider::{
+    builder::hinted_off::ClusterSchemaHintedOffBuilder, ClusterSchemaTableFactory,
+};
+
+const CLUSTER_SCHEMA_HINTED_OFF: &str = "HINTED_OFF";
+
+/// The hinted off queues of the data nodes, as of their last heartbeats.
+pub struct ClusterSchemaHintedOffFactory {}
+
+impl ClusterSchemaTableFactory for ClusterSchemaHintedOffFactory {
+    fn table_name(&self) -> &str {
+        CLUSTER_SCHEMA_HINTED_OFF
+    }
+
+    fn create(
+        &self,
+        user: &User,
+        metadata: MetaRef,
+    ) -> std::result::Result<Arc<MemTable>, MetaError> {
+        let mut builder = ClusterSchemaHintedOffBuilder::default();
+
+        // Only visible to admin
+        if user.desc().is_admin() {
+            let mut nodes = metadata.admin_meta().data_nodes_status()?;
+            nodes.sort_by_key(|n| n.info.id);
+            for heartbeat in nodes.iter().filter_map(|n| n.heartbeat.as_ref()) {
+                for queue in heartbeat.hinted_off.iter() {
+                    builder.append_row(heartbeat.node_id, queue);
+                }
+            }
+        }
+
+        let mem_table = MemTable::try_from(builder)
+            .map_err(|e| MetaError::CommonError { msg: e.to_string() })?;
+        Ok(Arc::new(mem_table))
+    }
+}
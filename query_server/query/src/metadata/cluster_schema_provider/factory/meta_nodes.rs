@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use datafusion::datasource::MemTable;
+use meta::{error::MetaError, meta_client::MetaRef};
+use models::auth::user::User;
+
+use crate::metadata::cluster_schema_provider::{
+    builder::meta_nodes::ClusterSchemaMetaNodesBuilder, ClusterSchemaTableFactory,
+};
+
+const CLUSTER_SCHEMA_META_NODES: &str = "META_NODES";
+
+/// The voters and the learners of the meta cluster with their raft state.
+pub struct ClusterSchemaMetaNodesFactory {}
+
+impl ClusterSchemaTableFactory for ClusterSchemaMetaNodesFactory {
+    fn table_name(&self) -> &str {
+        CLUSTER_SCHEMA_META_NODES
+    }
+
+    fn create(
+        &self,
+        user: &User,
+        metadata: MetaRef,
+    ) -> std::result::Result<Arc<MemTable>, MetaError> {
+        let mut builder = ClusterSchemaMetaNodesBuilder::default();
+
+        // Only visible to admin
+        if user.desc().is_admin() {
+            for node in metadata.admin_meta().meta_nodes()?.iter() {
+                builder.append_row(node);
+            }
+        }
+
+        let mem_table = MemTable::try_from(builder)
+            .map_err(|e| MetaError::CommonError { msg: e.to_string() })?;
+        Ok(Arc::new(mem_table))
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use datafusion::datasource::MemTable;
+use meta::{error::MetaError, meta_client::MetaRef};
+use models::auth::user::User;
+
+use crate::metadata::cluster_schema_provider::{
+    builder::nodes::ClusterSchemaNodesBuilder, ClusterSchemaTableFactory,
+};
+
+const CLUSTER_SCHEMA_NODES: &str = "NODES";
+
+/// The data nodes with their liveness and the load of their last heartbeats.
+pub struct ClusterSchemaNodesFactory {}
+
+impl ClusterSchemaTableFactory for ClusterSchemaNodesFactory {
+    fn table_name(&self) -> &str {
+        CLUSTER_SCHEMA_NODES
+    }
+
+    fn create(
+        &self,
+        user: &User,
+        metadata: MetaRef,
+    ) -> std::result::Result<Arc<MemTable>, MetaError> {
+        let mut builder = ClusterSchemaNodesBuilder::default();
+
+        // Only visible to admin
+        if user.desc().is_admin() {
+            let mut nodes = metadata.admin_meta().data_nodes_status()?;
+            nodes.sort_by_key(|n| n.info.id);
+            for node in nodes.iter() {
+                builder.append_row(node);
+            }
+        }
+
+        let mem_table = MemTable::try_from(builder)
+            .map_err(|e| MetaError::CommonError { msg: e.to_string() })?;
+        Ok(Arc::new(mem_table))
+    }
+}
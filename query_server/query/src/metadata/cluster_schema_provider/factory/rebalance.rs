@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use coordinator::rebalance::{collect_placements, plan_rebalance};
+use datafusion::datasource::MemTable;
+use meta::{error::MetaError, meta_client::MetaRef};
+use models::auth::user::User;
+
+use crate::metadata::cluster_schema_provider::{
+    builder::rebalance::ClusterSchemaRebalanceBuilder, ClusterSchemaTableFactory,
+};
+
+const CLUSTER_SCHEMA_REBALANCE: &str = "REBALANCE";
+
+/// The vnodes of the data nodes and the moves left to rebalance them.
+pub struct ClusterSchemaRebalanceFactory {}
+
+impl ClusterSchemaTableFactory for ClusterSchemaRebalanceFactory {
+    fn table_name(&self) -> &str {
+        CLUSTER_SCHEMA_REBALANCE
+    }
+
+    fn create(
+        &self,
+        user: &User,
+        metadata: MetaRef,
+    ) -> std::result::Result<Arc<MemTable>, MetaError> {
+        let mut builder = ClusterSchemaRebalanceBuilder::default();
+
+        // Only visible to admin
+        if user.desc().is_admin() {
+            let mut nodes = metadata.admin_meta().data_nodes();
+            nodes.sort_by_key(|n| n.id);
+            let placements = collect_placements(&metadata)?;
+            let moves = plan_rebalance(&nodes, &placements, usize::MAX);
+
+            for node in nodes.iter() {
+                let vnodes = placements.iter().filter(|p| p.node_id == node.id).count();
+                let moves_out = moves.iter().filter(|m| m.source == node.id).count();
+                let moves_in = moves.iter().filter(|m| m.target == node.id).count();
+
+                builder.append_row(
+                    node.id,
+                    &node.tcp_addr,
+                    node.status_name(),
+                    vnodes as u64,
+                    moves_out as u64,
+                    moves_in as u64,
+                );
+            }
+        }
+
+        let mem_table = MemTable::try_from(builder)
+            .map_err(|e| MetaError::CommonError { msg: e.to_string() })?;
+        Ok(Arc::new(mem_table))
+    }
+}
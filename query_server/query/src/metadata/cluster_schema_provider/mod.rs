@@ -7,7 +7,11 @@ use datafusion::datasource::MemTable;
 use meta::{error::MetaError, meta_client::MetaRef};
 use models::auth::user::User;
 
-use self::factory::{tenants::ClusterSchemaTenantsFactory, users::ClusterSchemaUsersFactory};
+use self::factory::{
+    hinted_off::ClusterSchemaHintedOffFactory, meta_nodes::ClusterSchemaMetaNodesFactory,
+    nodes::ClusterSchemaNodesFactory, rebalance::ClusterSchemaRebalanceFactory,
+    tenants::ClusterSchemaTenantsFactory, users::ClusterSchemaUsersFactory,
+};
 
 use super::CLUSTER_SCHEMA;
 
@@ -23,6 +27,10 @@ impl ClusterSchemaProvider {
 
         provider.register_table_factory(Box::new(ClusterSchemaTenantsFactory {}));
         provider.register_table_factory(Box::new(ClusterSchemaUsersFactory {}));
+        provider.register_table_factory(Box::new(ClusterSchemaRebalanceFactory {}));
+        provider.register_table_factory(Box::new(ClusterSchemaNodesFactory {}));
+        provider.register_table_factory(Box::new(ClusterSchemaHintedOffFactory {}));
+        provider.register_table_factory(Box::new(ClusterSchemaMetaNodesFactory {}));
 
         provider
     }
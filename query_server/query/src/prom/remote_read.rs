@@ -2,12 +2,19 @@ use std::collections::HashMap;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use coordinator::service::CoordinatorRef;
 use datafusion::arrow::datatypes::ToByteSlice;
+use flatbuffers::FlatBufferBuilder;
 use meta::error::MetaError;
 use meta::meta_client::{MetaClientRef, MetaRef};
+use models::consistency_level::ConsistencyLevel;
 use models::schema::{TskvTableSchema, TIME_FIELD_NAME};
+use protos::kv_service::WritePointsRpcRequest;
+use protos::models::{FieldBuilder, FieldType, Point, PointArgs, Points, PointsArgs, TagBuilder};
 use protos::models_helper::{parse_proto_bytes, to_proto_bytes};
-use protos::prompb::remote::{Query as PromQuery, QueryResult, ReadRequest, ReadResponse};
+use protos::prompb::remote::{
+    Query as PromQuery, QueryResult, ReadRequest, ReadResponse, WriteRequest,
+};
 
 use protos::prompb::types::label_matcher::Type;
 use protos::prompb::types::TimeSeries;
@@ -52,10 +59,28 @@ impl PromRemoteServer for PromRemoteSqlServer {
         self.serialize_read_response(read_response).await
     }
 
-    fn remote_write(&self, _ctx: &Context, _req: Bytes) -> Result<()> {
-        Err(QueryError::NotImplemented {
-            err: "prom remote write".to_string(),
-        })
+    async fn remote_write(&self, ctx: &Context, coord: CoordinatorRef, req: Bytes) -> Result<()> {
+        let write_request = self.deserialize_write_request(req).await?;
+
+        debug!(
+            "Received remote write request, timeseries num: {}",
+            write_request.timeseries.len()
+        );
+
+        let points = transform_write_request_to_points(ctx.database(), write_request)?;
+
+        let req = WritePointsRpcRequest {
+            version: 1,
+            meta: None,
+            points,
+            consistency_level: None,
+        };
+
+        coord
+            .write_points(ctx.tenant().to_string(), ConsistencyLevel::Any, req)
+            .await?;
+
+        Ok(())
     }
 }
 
@@ -83,6 +108,22 @@ impl PromRemoteSqlServer {
         })
     }
 
+    async fn deserialize_write_request(&self, req: Bytes) -> Result<WriteRequest> {
+        let mut decompressed = Vec::new();
+        let compressed = req.to_byte_slice();
+
+        self.codec
+            .lock()
+            .await
+            .decompress(compressed, &mut decompressed, None)?;
+
+        parse_proto_bytes::<WriteRequest>(&decompressed).map_err(|source| {
+            QueryError::InvalidRemoteWriteReq {
+                source: Box::new(source),
+            }
+        })
+    }
+
     async fn process_read_request(
         &self,
         ctx: &Context,
@@ -281,6 +322,70 @@ fn transform_time_series(
     Ok(timeseries.into_values().collect())
 }
 
+/// Convert the timeseries of prometheus remote write request to flatbuffers points
+///
+/// The metric name is used as the table name, the other labels are used as tags,
+/// and the sample value is written to the float field `value`.
+fn transform_write_request_to_points(db: &str, write_request: WriteRequest) -> Result<Vec<u8>> {
+    let mut fbb = FlatBufferBuilder::new();
+    let mut point_offsets = Vec::new();
+
+    for ts in write_request.timeseries {
+        let table_name = ts
+            .labels
+            .iter()
+            .find(|l| l.name == METRIC_NAME_LABEL)
+            .map(|l| l.value.as_str())
+            .ok_or_else(|| QueryError::InvalidRemoteWriteReq {
+                source: format!("Timeseries missing label {}", METRIC_NAME_LABEL).into(),
+            })?;
+
+        for sample in ts.samples.iter() {
+            let mut tags = Vec::with_capacity(ts.labels.len());
+            for label in ts.labels.iter().filter(|l| l.name != METRIC_NAME_LABEL) {
+                let fbk = fbb.create_vector(label.name.as_bytes());
+                let fbv = fbb.create_vector(label.value.as_bytes());
+                let mut tag_builder = TagBuilder::new(&mut fbb);
+                tag_builder.add_key(fbk);
+                tag_builder.add_value(fbv);
+                tags.push(tag_builder.finish());
+            }
+
+            let fbk = fbb.create_vector(METRIC_SAMPLE_COLUMN_NAME.as_bytes());
+            let fbv = fbb.create_vector(&sample.value.to_be_bytes());
+            let mut field_builder = FieldBuilder::new(&mut fbb);
+            field_builder.add_name(fbk);
+            field_builder.add_type_(FieldType::Float);
+            field_builder.add_value(fbv);
+            let fields = vec![field_builder.finish()];
+
+            let point_args = PointArgs {
+                db: Some(fbb.create_vector(db.as_bytes())),
+                tab: Some(fbb.create_vector(table_name.as_bytes())),
+                tags: Some(fbb.create_vector(&tags)),
+                fields: Some(fbb.create_vector(&fields)),
+                // Convert to ns timestamp
+                timestamp: sample.timestamp * 1_000_000,
+            };
+
+            point_offsets.push(Point::create(&mut fbb, &point_args));
+        }
+    }
+
+    let fbb_db = fbb.create_vector(db.as_bytes());
+    let points_raw = fbb.create_vector(&point_offsets);
+    let points = Points::create(
+        &mut fbb,
+        &PointsArgs {
+            db: Some(fbb_db),
+            points: Some(points_raw),
+        },
+    );
+    fbb.finish(points, None);
+
+    Ok(fbb.finished_data().to_vec())
+}
+
 #[derive(Debug)]
 struct SqlWithTable {
     pub sql: String,
@@ -355,13 +460,15 @@ mod test {
         from_slice::FromSlice,
     };
     use models::auth::user::{User, UserDesc, UserOptions};
+    use protos::models::Points;
+    use protos::prompb::remote::WriteRequest;
     use protos::prompb::types::{Label, Sample, TimeSeries};
     use spi::{
         query::execution::Output,
         service::protocol::{ContextBuilder, Query, QueryHandle, QueryId},
     };
 
-    use crate::prom::remote_read::transform_time_series;
+    use crate::prom::remote_read::{transform_time_series, transform_write_request_to_points};
 
     #[test]
     fn test_transform_time_series() {
@@ -429,4 +536,77 @@ mod test {
 
         assert_eq!(vec![expect], time_series);
     }
+
+    #[test]
+    fn test_transform_write_request_to_points() {
+        let write_request = WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: vec![
+                    Label {
+                        name: "__name__".to_string(),
+                        value: "cpu".to_string(),
+                        ..Default::default()
+                    },
+                    Label {
+                        name: "host".to_string(),
+                        value: "h1".to_string(),
+                        ..Default::default()
+                    },
+                ],
+                samples: vec![
+                    Sample {
+                        value: 1.1_f64,
+                        timestamp: 1673069176267_i64,
+                        ..Default::default()
+                    },
+                    Sample {
+                        value: 2.2_f64,
+                        timestamp: 1673069176268_i64,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let data = transform_write_request_to_points("public", write_request).unwrap();
+        let points = flatbuffers::root::<Points>(&data).unwrap();
+        let points = points.points().unwrap();
+        assert_eq!(points.len(), 2);
+
+        let point = points.get(0);
+        assert_eq!(point.tab().unwrap().bytes(), b"cpu");
+        assert_eq!(point.timestamp(), 1673069176267000000);
+
+        let tags = point.tags().unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags.get(0).key().unwrap().bytes(), b"host");
+        assert_eq!(tags.get(0).value().unwrap().bytes(), b"h1");
+
+        let fields = point.fields().unwrap();
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields.get(0).name().unwrap().bytes(), b"value");
+        assert_eq!(
+            fields.get(0).value().unwrap().bytes(),
+            &1.1_f64.to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn test_transform_write_request_without_metric_name() {
+        let write_request = WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: vec![Label {
+                    name: "host".to_string(),
+                    value: "h1".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert!(transform_write_request_to_points("public", write_request).is_err());
+    }
 }
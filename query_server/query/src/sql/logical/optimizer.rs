@@ -22,8 +22,9 @@ use spi::Result;
 
 use crate::extension::logical::optimizer_rule::{
     implicit_type_conversion::ImplicitTypeConversion,
-    projection_push_down::ProjectionPushDownAdapter, reject_cross_join::RejectCrossJoin,
-    rewrite_tag_scan::RewriteTagScan,
+    projection_push_down::ProjectionPushDownAdapter, push_down_aggregation::PushDownAggregation,
+    push_down_plan_fragment::PushDownPlanFragment, reject_cross_join::RejectCrossJoin,
+    rewrite_gap_fill::RewriteGapFill, rewrite_tag_scan::RewriteTagScan,
     transform_bottom_func_to_topk_node::TransformBottomFuncToTopkNodeRule,
     transform_topk_func_to_topk_node::TransformTopkFuncToTopkNodeRule,
 };
@@ -78,8 +79,12 @@ impl Default for DefaultLogicalOptimizer {
             Arc::new(SingleDistinctToGroupBy::new()),
             // df default rules end
             // cnosdb rules
+            Arc::new(PushDownAggregation {}),
+            Arc::new(RewriteGapFill {}),
             Arc::new(TransformBottomFuncToTopkNodeRule {}),
             Arc::new(TransformTopkFuncToTopkNodeRule {}),
+            // must be the last, the plans pushed down are not optimized any more
+            Arc::new(PushDownPlanFragment {}),
         ];
 
         Self { rules }
@@ -3,6 +3,7 @@ use std::ops::Not;
 
 use std::str::FromStr;
 
+use coordinator::command::HintedOffCmd;
 use datafusion::sql::parser::CreateExternalTable;
 use datafusion::sql::sqlparser::ast::Expr;
 use datafusion::sql::sqlparser::ast::Ident;
@@ -25,12 +26,13 @@ use spi::query::ast::CopyIntoTable;
 use spi::query::ast::CopyTarget;
 use spi::query::ast::UriLocation;
 use spi::query::ast::{
-    parse_string_value, Action, AlterDatabase, AlterTable, AlterTableAction, AlterTenant,
-    AlterTenantOperation, AlterUser, AlterUserOperation, ChecksumGroup, ColumnOption, CompactVnode,
-    CopyVnode, CreateDatabase, CreateRole, CreateTable, CreateTenant, CreateUser, DatabaseOptions,
-    DescribeDatabase, DescribeTable, DropDatabaseObject, DropGlobalObject, DropTenantObject,
-    DropVnode, Explain, ExtStatement, GrantRevoke, MoveVnode, Privilege, ShowSeries, ShowTagBody,
-    ShowTagValues, With,
+    parse_string_value, Action, AddMetaNode, AlterDatabase, AlterTable, AlterTableAction,
+    AlterTenant, AlterTenantOperation, AlterUser, AlterUserOperation, ChecksumGroup, ColumnOption,
+    CompactVnode, CopyVnode, CreateDatabase, CreateRole, CreateStream, CreateTable, CreateTenant,
+    CreateUser, DatabaseOptions, DeleteFromTable, DescribeDatabase, DescribeTable,
+    DropDatabaseObject, DropDatanode, DropGlobalObject, DropMetaNode, DropTenantObject, DropVnode,
+    Explain, ExtStatement, GrantRevoke, HintedOffControl, MoveVnode, Privilege, RepairGroup,
+    ShowSeries, ShowTagBody, ShowTagValues, With,
 };
 use spi::query::logical_planner::{DatabaseObjectType, GlobalObjectType, TenantObjectType};
 use spi::query::parser::Parser as CnosdbParser;
@@ -96,13 +98,38 @@ enum CnosKeyWord {
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     NODE,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    DATANODE,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     MOVE,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     COMPACT,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     CHECKSUM,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    REPAIR,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     GROUP,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    PAUSE,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    RESUME,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    PURGE,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    HANDOFF,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    META,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    TRANSFER,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    LEADER,
+
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    STREAM,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    INTERVAL,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    DELAY,
 }
 
 impl FromStr for CnosKeyWord {
@@ -133,10 +160,22 @@ impl FromStr for CnosKeyWord {
             "COPY_OPTIONS" => Ok(CnosKeyWord::COPY_OPTIONS),
             "VNODE" => Ok(CnosKeyWord::VNODE),
             "NODE" => Ok(CnosKeyWord::NODE),
+            "DATANODE" => Ok(CnosKeyWord::DATANODE),
             "MOVE" => Ok(CnosKeyWord::MOVE),
             "COMPACT" => Ok(CnosKeyWord::COMPACT),
             "CHECKSUM" => Ok(CnosKeyWord::CHECKSUM),
+            "REPAIR" => Ok(CnosKeyWord::REPAIR),
             "GROUP" => Ok(CnosKeyWord::GROUP),
+            "PAUSE" => Ok(CnosKeyWord::PAUSE),
+            "RESUME" => Ok(CnosKeyWord::RESUME),
+            "PURGE" => Ok(CnosKeyWord::PURGE),
+            "HANDOFF" => Ok(CnosKeyWord::HANDOFF),
+            "META" => Ok(CnosKeyWord::META),
+            "TRANSFER" => Ok(CnosKeyWord::TRANSFER),
+            "LEADER" => Ok(CnosKeyWord::LEADER),
+            "STREAM" => Ok(CnosKeyWord::STREAM),
+            "INTERVAL" => Ok(CnosKeyWord::INTERVAL),
+            "DELAY" => Ok(CnosKeyWord::DELAY),
             _ => Err(ParserError::ParserError(format!(
                 "fail parse {} to CnosKeyWord",
                 s
@@ -233,6 +272,10 @@ impl<'a> ExtParser<'a> {
                     self.parser.next_token();
                     self.parse_drop()
                 }
+                Keyword::DELETE => {
+                    self.parser.next_token();
+                    self.parse_delete()
+                }
                 Keyword::DESCRIBE | Keyword::DESC => {
                     self.parser.next_token();
                     self.parse_describe()
@@ -261,6 +304,10 @@ impl<'a> ExtParser<'a> {
                     self.parser.next_token();
                     self.parse_explain()
                 }
+                Keyword::ADD => {
+                    self.parser.next_token();
+                    self.parse_add()
+                }
                 _ => {
                     if let Ok(word) = CnosKeyWord::from_str(&w.to_string()) {
                         return match word {
@@ -276,6 +323,26 @@ impl<'a> ExtParser<'a> {
                                 self.parser.next_token();
                                 self.parse_checksum()
                             }
+                            CnosKeyWord::REPAIR => {
+                                self.parser.next_token();
+                                self.parse_repair()
+                            }
+                            CnosKeyWord::PAUSE => {
+                                self.parser.next_token();
+                                self.parse_handoff(HintedOffCmd::Pause)
+                            }
+                            CnosKeyWord::RESUME => {
+                                self.parser.next_token();
+                                self.parse_handoff(HintedOffCmd::Resume)
+                            }
+                            CnosKeyWord::PURGE => {
+                                self.parser.next_token();
+                                self.parse_handoff(HintedOffCmd::Purge)
+                            }
+                            CnosKeyWord::TRANSFER => {
+                                self.parser.next_token();
+                                self.parse_transfer()
+                            }
                             _ => Ok(ExtStatement::SqlStatement(Box::new(
                                 self.parser.parse_statement()?,
                             ))),
@@ -300,6 +367,20 @@ impl<'a> ExtParser<'a> {
     //     parser_err!(format!("Expected {}, found: {:?}", expected, found))
     // }
 
+    /// Parse a SQL DELETE statement
+    ///
+    /// DELETE FROM table_name [WHERE expr]
+    fn parse_delete(&mut self) -> Result<ExtStatement> {
+        self.parser.expect_keyword(Keyword::FROM)?;
+        let table_name = self.parser.parse_object_name()?;
+        let selection = self.parse_where()?;
+
+        Ok(ExtStatement::DeleteFromTable(DeleteFromTable {
+            table_name,
+            selection,
+        }))
+    }
+
     /// Parse a SQL SHOW statement
     fn parse_show(&mut self) -> Result<ExtStatement> {
         if self.parser.parse_keyword(Keyword::TABLES) {
@@ -858,6 +939,44 @@ impl<'a> ExtParser<'a> {
         }))
     }
 
+    /// Parse
+    /// CREATE STREAM [IF NOT EXISTS] <stream_name>
+    /// [WITH [INTERVAL <duration>] [DELAY <duration>]]
+    /// AS <select> INTO <target_table>
+    fn parse_create_stream(&mut self) -> Result<ExtStatement> {
+        let if_not_exists =
+            self.parser
+                .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+        let name = self.parser.parse_identifier()?;
+
+        let (mut interval, mut delay) = (None, None);
+        if self.parser.parse_keyword(Keyword::WITH) {
+            loop {
+                if self.parse_cnos_keyword(CnosKeyWord::INTERVAL) {
+                    interval = Some(self.parse_string_value()?);
+                } else if self.parse_cnos_keyword(CnosKeyWord::DELAY) {
+                    delay = Some(self.parse_string_value()?);
+                } else {
+                    break;
+                }
+            }
+        }
+
+        self.parser.expect_keyword(Keyword::AS)?;
+        let query = Box::new(self.parser.parse_query()?);
+        self.parser.expect_keyword(Keyword::INTO)?;
+        let target_table = self.parser.parse_object_name()?;
+
+        Ok(ExtStatement::CreateStream(CreateStream {
+            name,
+            if_not_exists,
+            interval,
+            delay,
+            query,
+            target_table,
+        }))
+    }
+
     fn parse_grant_permission(&mut self) -> Result<Action, ParserError> {
         if self.parse_cnos_keyword(CnosKeyWord::READ) {
             Ok(Action::Read)
@@ -994,6 +1113,8 @@ impl<'a> ExtParser<'a> {
             self.parse_create_user()
         } else if self.parser.parse_keyword(Keyword::ROLE) {
             self.parse_create_role()
+        } else if self.parse_cnos_keyword(CnosKeyWord::STREAM) {
+            self.parse_create_stream()
         } else {
             self.expected("an object type after CREATE", self.parser.peek_token())
         }
@@ -1219,9 +1340,26 @@ impl<'a> ExtParser<'a> {
         } else if self.parse_cnos_keyword(CnosKeyWord::VNODE) {
             let vnode_id = self.parse_number::<VnodeId>()?;
             ExtStatement::DropVnode(DropVnode { vnode_id })
+        } else if self.parse_cnos_keyword(CnosKeyWord::DATANODE) {
+            let node_id = self.parse_number::<NodeId>()?;
+            ExtStatement::DropDatanode(DropDatanode { node_id })
+        } else if self.parse_cnos_keyword(CnosKeyWord::STREAM) {
+            let if_exist = self.parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+            let object_name = self.parser.parse_object_name()?;
+            ExtStatement::DropDatabaseObject(DropDatabaseObject {
+                object_name,
+                if_exist,
+                obj_type: DatabaseObjectType::Stream,
+            })
+        } else if self.parse_cnos_keyword(CnosKeyWord::META) {
+            if self.parse_cnos_keyword(CnosKeyWord::NODE).not() {
+                return parser_err!("expected NODE, after META");
+            }
+            let node_id = self.parse_number::<NodeId>()?;
+            ExtStatement::DropMetaNode(DropMetaNode { node_id })
         } else {
             return self.expected(
-                "TABLE,DATABASE,TENANT,USER,ROLE,VNODE after DROP",
+                "TABLE,DATABASE,TENANT,USER,ROLE,VNODE,DATANODE,STREAM,META NODE after DROP",
                 self.parser.peek_token(),
             );
         };
@@ -1269,6 +1407,55 @@ impl<'a> ExtParser<'a> {
         }
     }
 
+    fn parse_repair(&mut self) -> Result<ExtStatement> {
+        if self.parse_cnos_keyword(CnosKeyWord::GROUP) {
+            let replication_set_id = self.parse_number::<ReplicationSetId>()?;
+            Ok(ExtStatement::RepairGroup(RepairGroup {
+                replication_set_id,
+            }))
+        } else {
+            parser_err!("Expected GROUP. after REPAIR")
+        }
+    }
+
+    /// Parses `PAUSE | RESUME | PURGE HANDOFF <node_id>`.
+    fn parse_handoff(&mut self, cmd: HintedOffCmd) -> Result<ExtStatement> {
+        if self.parse_cnos_keyword(CnosKeyWord::HANDOFF) {
+            let node_id = self.parse_number::<NodeId>()?;
+            Ok(ExtStatement::HintedOffControl(HintedOffControl {
+                node_id,
+                cmd,
+            }))
+        } else {
+            parser_err!("Expected HANDOFF. after PAUSE, RESUME or PURGE")
+        }
+    }
+
+    /// Parses `ADD META NODE <node_id> '<addr>'`.
+    fn parse_add(&mut self) -> Result<ExtStatement> {
+        if self.parse_cnos_keyword(CnosKeyWord::META) {
+            if self.parse_cnos_keyword(CnosKeyWord::NODE).not() {
+                return parser_err!("expected NODE, after META");
+            }
+            let node_id = self.parse_number::<NodeId>()?;
+            let addr = self.parser.parse_literal_string()?;
+            Ok(ExtStatement::AddMetaNode(AddMetaNode { node_id, addr }))
+        } else {
+            parser_err!("expected META, after ADD")
+        }
+    }
+
+    /// Parses `TRANSFER META LEADER`.
+    fn parse_transfer(&mut self) -> Result<ExtStatement> {
+        if self.parse_cnos_keyword(CnosKeyWord::META)
+            && self.parse_cnos_keyword(CnosKeyWord::LEADER)
+        {
+            Ok(ExtStatement::TransferMetaLeader)
+        } else {
+            parser_err!("expected META LEADER, after TRANSFER")
+        }
+    }
+
     fn consume_token(&mut self, expected: &Token) -> bool {
         if self.parser.peek_token() == *expected {
             self.parser.next_token();
@@ -1921,6 +2108,42 @@ mod tests {
             statement[0],
             ExtStatement::DropVnode(DropVnode { vnode_id: 5 })
         );
+        let sql = "drop datanode 3;";
+        let statement = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::DropDatanode(DropDatanode { node_id: 3 })
+        );
+        let sql = "add meta node 4 '127.0.0.1:8904'; drop meta node 4; transfer meta leader;";
+        let statement = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::AddMetaNode(AddMetaNode {
+                node_id: 4,
+                addr: "127.0.0.1:8904".to_string()
+            })
+        );
+        assert_eq!(
+            statement[1],
+            ExtStatement::DropMetaNode(DropMetaNode { node_id: 4 })
+        );
+        assert_eq!(statement[2], ExtStatement::TransferMetaLeader);
+        let sql = "pause handoff 3; purge handoff 4;";
+        let statement = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::HintedOffControl(HintedOffControl {
+                node_id: 3,
+                cmd: HintedOffCmd::Pause
+            })
+        );
+        assert_eq!(
+            statement[1],
+            ExtStatement::HintedOffControl(HintedOffControl {
+                node_id: 4,
+                cmd: HintedOffCmd::Purge
+            })
+        );
         let sql4 = "compact vnode 6 7 8 9;";
         let statement = ExtParser::parse_sql(sql4).unwrap();
         assert_eq!(
@@ -1937,6 +2160,85 @@ mod tests {
                 replication_set_id: 10
             })
         );
+        let sql6 = "repair group 11";
+        let statement = ExtParser::parse_sql(sql6).unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::RepairGroup(RepairGroup {
+                replication_set_id: 11
+            })
+        );
+    }
+
+    #[test]
+    fn test_delete_from_table() {
+        let sql = "delete from test where ta = 'a' and time between 1 and 10;";
+        let statement = ExtParser::parse_sql(sql).unwrap();
+        match &statement[0] {
+            ExtStatement::DeleteFromTable(DeleteFromTable {
+                table_name,
+                selection,
+            }) => {
+                assert_eq!(table_name.to_string(), "test");
+                assert_eq!(
+                    selection.as_ref().unwrap().to_string(),
+                    "ta = 'a' AND time BETWEEN 1 AND 10"
+                );
+            }
+            _ => panic!("failed to parse delete statement"),
+        }
+
+        let sql = "delete from db.test;";
+        let statement = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::DeleteFromTable(DeleteFromTable {
+                table_name: ObjectName(vec![Ident::new("db"), Ident::new("test")]),
+                selection: None,
+            })
+        );
+
+        assert!(ExtParser::parse_sql("delete test;").is_err());
+    }
+
+    #[test]
+    fn test_create_stream() {
+        let sql = "create stream if not exists air_1m with interval '1m' delay '5m' as \
+            select date_bin(interval '1 minute', time, timestamp '1970-01-01T00:00:00Z') as time, \
+            station, avg(pressure) as pressure from air group by 1, station \
+            into air_rollup;";
+        let statement = ExtParser::parse_sql(sql).unwrap();
+        match &statement[0] {
+            ExtStatement::CreateStream(CreateStream {
+                name,
+                if_not_exists,
+                interval,
+                delay,
+                query,
+                target_table,
+            }) => {
+                assert_eq!(name.to_string(), "air_1m");
+                assert!(*if_not_exists);
+                assert_eq!(interval.as_deref(), Some("1m"));
+                assert_eq!(delay.as_deref(), Some("5m"));
+                assert!(query.to_string().ends_with("GROUP BY 1, station"));
+                assert_eq!(target_table.to_string(), "air_rollup");
+            }
+            _ => panic!("failed to parse create stream statement"),
+        }
+
+        let sql = "drop stream if exists air_1m;";
+        let statement = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::DropDatabaseObject(DropDatabaseObject {
+                object_name: ObjectName(vec![Ident::new("air_1m")]),
+                if_exist: true,
+                obj_type: DatabaseObjectType::Stream,
+            })
+        );
+
+        assert!(ExtParser::parse_sql("create stream s as select * from air;").is_err());
     }
 
     #[test]
@@ -18,7 +18,9 @@ use spi::query::session::IsiphoSessionCtx;
 use spi::Result;
 
 use crate::extension::physical::transform_rule::{
-    table_writer::TableWriterPlanner, tag_scan::TagScanPlanner, topk::TopKPlanner,
+    aggregate_scan::AggregateScanPlanner, gap_fill::GapFillPlanner,
+    plan_fragment::PlanFragmentPlanner, table_writer::TableWriterPlanner, tag_scan::TagScanPlanner,
+    topk::TopKPlanner,
 };
 
 use super::optimizer::PhysicalOptimizer;
@@ -57,6 +59,9 @@ impl Default for DefaultPhysicalPlanner {
             Arc::new(TableWriterPlanner {}),
             Arc::new(TopKPlanner {}),
             Arc::new(TagScanPlanner {}),
+            Arc::new(AggregateScanPlanner {}),
+            Arc::new(GapFillPlanner {}),
+            Arc::new(PlanFragmentPlanner {}),
         ];
 
         let ext_physical_optimizer_rules: Vec<Arc<dyn PhysicalOptimizerRule + Send + Sync>> = vec![
@@ -24,14 +24,16 @@ use std::option::Option;
 use std::sync::Arc;
 use url::Url;
 
+use datafusion::arrow::compute::cast;
 use datafusion::common::{Column, DFField, DFSchema, Result as DFResult, ToDFSchema};
 use datafusion::datasource::{provider_as_source, source_as_provider, TableProvider};
 use datafusion::error::DataFusionError;
 use datafusion::logical_expr::logical_plan::Analyze;
 use datafusion::logical_expr::utils::expr_to_columns;
 use datafusion::logical_expr::{
-    lit, BinaryExpr, BuiltinScalarFunction, Case, EmptyRelation, Explain, Expr, LogicalPlan,
-    LogicalPlanBuilder, Operator, PlanType, TableSource, ToStringifiedPlan, Union,
+    and, binary_expr, lit, or, Between, BinaryExpr, BuiltinScalarFunction, Case, EmptyRelation,
+    Explain, Expr, LogicalPlan, LogicalPlanBuilder, Operator, PlanType, TableSource,
+    ToStringifiedPlan, Union,
 };
 use datafusion::scalar::ScalarValue;
 use datafusion::sql::parser::CreateExternalTable as AstCreateExternalTable;
@@ -55,24 +57,28 @@ use models::schema::{
 use models::utils::SeqIdGenerator;
 use models::{ColumnId, ValueType};
 use spi::query::ast::{
-    AlterDatabase as ASTAlterDatabase, AlterTable as ASTAlterTable,
+    AddMetaNode as ASTAddMetaNode, AlterDatabase as ASTAlterDatabase, AlterTable as ASTAlterTable,
     AlterTableAction as ASTAlterTableAction, AlterTenantOperation, AlterUserOperation,
     ChecksumGroup as ASTChecksumGroup, ColumnOption, CompactVnode as ASTCompactVnode,
     CopyIntoTable, CopyTarget, CopyVnode as ASTCopyVnode, CreateDatabase as ASTCreateDatabase,
-    CreateTable as ASTCreateTable, DatabaseOptions as ASTDatabaseOptions,
+    CreateStream as ASTCreateStream, CreateTable as ASTCreateTable,
+    DatabaseOptions as ASTDatabaseOptions, DeleteFromTable as ASTDeleteFromTable,
     DescribeDatabase as DescribeDatabaseOptions, DescribeTable as DescribeTableOptions,
-    DropVnode as ASTDropVnode, ExtStatement, MoveVnode as ASTMoveVnode,
-    ShowSeries as ASTShowSeries, ShowTagBody, ShowTagValues as ASTShowTagValues, UriLocation, With,
+    DropDatanode as ASTDropDatanode, DropMetaNode as ASTDropMetaNode, DropVnode as ASTDropVnode,
+    ExtStatement, HintedOffControl as ASTHintedOffControl, MoveVnode as ASTMoveVnode,
+    RepairGroup as ASTRepairGroup, ShowSeries as ASTShowSeries, ShowTagBody,
+    ShowTagValues as ASTShowTagValues, UriLocation, With,
 };
 use spi::query::logical_planner::{
     parse_connection_options, sql_options_to_tenant_options, sql_options_to_user_options,
-    AlterDatabase, AlterTable, AlterTableAction, AlterTenant, AlterTenantAction,
+    AddMetaNode, AlterDatabase, AlterTable, AlterTableAction, AlterTenant, AlterTenantAction,
     AlterTenantAddUser, AlterTenantSetUser, AlterUser, AlterUserAction, ChecksumGroup,
     CompactVnode, CopyOptions, CopyOptionsBuilder, CopyVnode, CreateDatabase, CreateRole,
-    CreateTable, CreateTenant, CreateUser, DDLPlan, DatabaseObjectType, DescribeDatabase,
-    DescribeTable, DropDatabaseObject, DropGlobalObject, DropTenantObject, DropVnode,
-    FileFormatOptions, FileFormatOptionsBuilder, GlobalObjectType, GrantRevoke, LogicalPlanner,
-    MoveVnode, Plan, PlanWithPrivileges, QueryPlan, SYSPlan, TenantObjectType,
+    CreateStream, CreateTable, CreateTenant, CreateUser, DDLPlan, DatabaseObjectType,
+    DeleteFromTable, DescribeDatabase, DescribeTable, DropDatabaseObject, DropDatanode,
+    DropGlobalObject, DropMetaNode, DropTenantObject, DropVnode, FileFormatOptions,
+    FileFormatOptionsBuilder, GlobalObjectType, GrantRevoke, HintedOffControl, LogicalPlanner,
+    MoveVnode, Plan, PlanWithPrivileges, QueryPlan, RepairGroup, SYSPlan, TenantObjectType,
 };
 use spi::query::session::IsiphoSessionCtx;
 use spi::QueryError;
@@ -85,8 +91,12 @@ use trace::{debug, warn};
 use crate::metadata::{ContextProviderExtension, DatabaseSet, CLUSTER_SCHEMA, INFORMATION_SCHEMA};
 use crate::sql::logical::planner::TableWriteExt;
 use crate::sql::parser::{merge_object_name, normalize_ident, normalize_sql_object_name};
+use crate::stream_executor::stream_select;
 use crate::table::ClusterTable;
 
+/// The default length of the time window of streams, 1 minute
+const DEFAULT_STREAM_INTERVAL: i64 = 60 * 1_000_000_000;
+
 /// CnosDB SQL query planner
 pub struct SqlPlaner<'a, S: ContextProviderExtension> {
     schema_provider: &'a S,
@@ -117,6 +127,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlaner<'a, S> {
             ExtStatement::CreateTenant(stmt) => self.create_tenant_to_plan(stmt),
             ExtStatement::CreateUser(stmt) => self.create_user_to_plan(stmt),
             ExtStatement::CreateRole(stmt) => self.create_role_to_plan(stmt, session),
+            ExtStatement::CreateStream(stmt) => self.create_stream_to_plan(stmt, session),
             ExtStatement::DropDatabaseObject(s) => self.drop_database_object_to_plan(s, session),
             ExtStatement::DropTenantObject(s) => self.drop_tenant_object_to_plan(s, session),
             ExtStatement::DropGlobalObject(s) => self.drop_global_object_to_plan(s),
@@ -150,12 +161,22 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlaner<'a, S> {
                 })
             }
             ExtStatement::Copy(stmt) => self.copy_to_plan(stmt, session).await,
+            ExtStatement::DeleteFromTable(stmt) => self.delete_from_table_to_plan(stmt, session),
             // vnode statement
             ExtStatement::DropVnode(stmt) => self.drop_vnode_to_plan(stmt),
             ExtStatement::CopyVnode(stmt) => self.copy_vnode_to_plan(stmt),
             ExtStatement::MoveVnode(stmt) => self.move_vnode_to_plan(stmt),
             ExtStatement::CompactVnode(stmt) => self.compact_vnode_to_plan(stmt),
             ExtStatement::ChecksumGroup(stmt) => self.checksum_group_to_plan(stmt),
+            ExtStatement::RepairGroup(stmt) => self.repair_group_to_plan(stmt),
+            ExtStatement::DropDatanode(stmt) => self.drop_datanode_to_plan(stmt),
+            ExtStatement::HintedOffControl(stmt) => self.hinted_off_control_to_plan(stmt),
+            ExtStatement::AddMetaNode(stmt) => self.add_meta_node_to_plan(stmt),
+            ExtStatement::DropMetaNode(stmt) => self.drop_meta_node_to_plan(stmt),
+            ExtStatement::TransferMetaLeader => Ok(PlanWithPrivileges {
+                plan: Plan::DDL(DDLPlan::TransferMetaLeader),
+                privileges: vec![Privilege::Global(GlobalPrivilege::System)],
+            }),
         }
     }
 
@@ -330,6 +351,26 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlaner<'a, S> {
                     ),
                 )
             }
+            DatabaseObjectType::Stream => {
+                let stream = normalize_sql_object_name(object_name);
+                let object_ref = ObjectReference::from(stream.as_str());
+                let resolved_object_ref = object_ref.resolve(session.default_database());
+                let database_name = resolved_object_ref.parent;
+                (
+                    DDLPlan::DropDatabaseObject(DropDatabaseObject {
+                        if_exist,
+                        object_name: stream.clone(),
+                        obj_type: DatabaseObjectType::Stream,
+                    }),
+                    Privilege::TenantObject(
+                        TenantObjectPrivilege::Database(
+                            DatabasePrivilege::Write,
+                            Some(database_name.to_string()),
+                        ),
+                        Some(tenant_id),
+                    ),
+                )
+            }
         };
 
         Ok(PlanWithPrivileges {
@@ -1033,6 +1074,64 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlaner<'a, S> {
         Ok(source_as_provider(&table_source)?)
     }
 
+    fn create_stream_to_plan(
+        &self,
+        stmt: ASTCreateStream,
+        session: &IsiphoSessionCtx,
+    ) -> Result<PlanWithPrivileges> {
+        let ASTCreateStream {
+            name,
+            if_not_exists,
+            interval,
+            delay,
+            mut query,
+            target_table,
+        } = stmt;
+
+        stream_select(&mut query)?;
+
+        let interval = match interval {
+            Some(text) => self.str_to_stream_duration(&text)?,
+            None => DEFAULT_STREAM_INTERVAL,
+        };
+        if interval <= 0 {
+            return Err(QueryError::InvalidStream {
+                reason: "the interval must be greater than 0".to_string(),
+            });
+        }
+        let delay = match delay {
+            Some(text) => self.str_to_stream_duration(&text)?,
+            None => 0,
+        };
+
+        // Check that the results of the query can be written into the target table
+        let PlanWithPrivileges { privileges, .. } =
+            self.insert_to_plan(&target_table, &[], query.clone(), session)?;
+
+        let plan = Plan::DDL(DDLPlan::CreateStream(CreateStream {
+            name: normalize_ident(&name),
+            database_name: session.default_database().to_string(),
+            if_not_exists,
+            target_table: target_table.to_string(),
+            query: query.to_string(),
+            interval,
+            delay,
+        }));
+
+        Ok(PlanWithPrivileges { plan, privileges })
+    }
+
+    fn str_to_stream_duration(&self, text: &str) -> Result<i64> {
+        Duration::new(text)
+            .map(|d| d.to_nanoseconds())
+            .ok_or_else(|| QueryError::InvalidStream {
+                reason: format!(
+                    "{} is not a valid duration, use like '1m', '1h', '1d'",
+                    text
+                ),
+            })
+    }
+
     fn create_tenant_to_plan(&self, stmt: ast::CreateTenant) -> Result<PlanWithPrivileges> {
         let ast::CreateTenant {
             name,
@@ -1368,6 +1467,97 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlaner<'a, S> {
         })
     }
 
+    fn repair_group_to_plan(&self, stmt: ASTRepairGroup) -> Result<PlanWithPrivileges> {
+        let ASTRepairGroup { replication_set_id } = stmt;
+
+        let plan = Plan::DDL(DDLPlan::RepairGroup(RepairGroup { replication_set_id }));
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::Global(GlobalPrivilege::System)],
+        })
+    }
+
+    fn drop_datanode_to_plan(&self, stmt: ASTDropDatanode) -> Result<PlanWithPrivileges> {
+        let ASTDropDatanode { node_id } = stmt;
+
+        let plan = Plan::DDL(DDLPlan::DropDatanode(DropDatanode { node_id }));
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::Global(GlobalPrivilege::System)],
+        })
+    }
+
+    fn hinted_off_control_to_plan(&self, stmt: ASTHintedOffControl) -> Result<PlanWithPrivileges> {
+        let ASTHintedOffControl { node_id, cmd } = stmt;
+
+        let plan = Plan::DDL(DDLPlan::HintedOffControl(HintedOffControl { node_id, cmd }));
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::Global(GlobalPrivilege::System)],
+        })
+    }
+
+    fn add_meta_node_to_plan(&self, stmt: ASTAddMetaNode) -> Result<PlanWithPrivileges> {
+        let ASTAddMetaNode { node_id, addr } = stmt;
+
+        let plan = Plan::DDL(DDLPlan::AddMetaNode(AddMetaNode { node_id, addr }));
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::Global(GlobalPrivilege::System)],
+        })
+    }
+
+    fn drop_meta_node_to_plan(&self, stmt: ASTDropMetaNode) -> Result<PlanWithPrivileges> {
+        let ASTDropMetaNode { node_id } = stmt;
+
+        let plan = Plan::DDL(DDLPlan::DropMetaNode(DropMetaNode { node_id }));
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::Global(GlobalPrivilege::System)],
+        })
+    }
+
+    fn delete_from_table_to_plan(
+        &self,
+        stmt: ASTDeleteFromTable,
+        session: &IsiphoSessionCtx,
+    ) -> Result<PlanWithPrivileges> {
+        let ASTDeleteFromTable {
+            table_name,
+            selection,
+        } = stmt;
+
+        let table_name = normalize_sql_object_name(&table_name);
+        let table_source = self.get_table_source(&table_name)?;
+        let table_schema = self.get_tskv_schema(&table_name)?;
+        let table_df_schema = table_source.schema().to_dfschema_ref()?;
+
+        let selection = match selection {
+            Some(expr) => {
+                let expr =
+                    self.df_planner
+                        .sql_to_rex(expr, &table_df_schema, &mut HashMap::new())?;
+                Some(normalize_delete_predicate(expr, &table_schema)?)
+            }
+            None => None,
+        };
+
+        let database_name = table_schema.db.clone();
+        let plan = Plan::DDL(DDLPlan::DeleteFromTable(DeleteFromTable {
+            database_name: database_name.clone(),
+            table_name: table_schema.name.clone(),
+            selection,
+        }));
+
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::TenantObject(
+                TenantObjectPrivilege::Database(DatabasePrivilege::Write, Some(database_name)),
+                Some(*session.tenant_id()),
+            )],
+        })
+    }
+
     fn get_tskv_schema(&self, table_name: &str) -> Result<TskvTableSchemaRef> {
         Ok(self
             .get_table_provider(table_name)?
@@ -1829,6 +2019,127 @@ fn check_show_series_expr(columns: &HashSet<Column>, table_schema: &TskvTableSch
     Ok(())
 }
 
+/// Rewrite the where clause of DELETE into a conjunction of `column op literal` comparisons,
+/// which is converted to column domains by data nodes without losing precision.
+///
+/// BETWEEN and IN list are expanded, literals are cast to the type of the column. Only the
+/// comparisons converted to exact range or point domains are accepted, others like `!=` would
+/// delete all the rows.
+fn normalize_delete_predicate(expr: Expr, table_schema: &TskvTableSchema) -> Result<Expr> {
+    let unsupported = |expr: &Expr| QueryError::UnsupportedDeletePredicate {
+        expr: expr.to_string(),
+    };
+
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        }) => Ok(and(
+            normalize_delete_predicate(*left, table_schema)?,
+            normalize_delete_predicate(*right, table_schema)?,
+        )),
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            let (column, op, value) = match (*left, *right) {
+                (Expr::Column(column), Expr::Literal(value)) => (column, op, value),
+                (Expr::Literal(value), Expr::Column(column)) => {
+                    let op = match op {
+                        Operator::Lt => Operator::Gt,
+                        Operator::LtEq => Operator::GtEq,
+                        Operator::Gt => Operator::Lt,
+                        Operator::GtEq => Operator::LtEq,
+                        op => op,
+                    };
+                    (column, op, value)
+                }
+                (left, right) => return Err(unsupported(&binary_expr(left, op, right))),
+            };
+            // `!=` and the null literal are widened to all the rows by the data nodes
+            if value.is_null()
+                || !matches!(
+                    op,
+                    Operator::Eq | Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq
+                )
+            {
+                return Err(unsupported(&binary_expr(
+                    Expr::Column(column),
+                    op,
+                    Expr::Literal(value),
+                )));
+            }
+
+            let value = cast_delete_literal(&column, value, table_schema)?;
+            Ok(binary_expr(Expr::Column(column), op, lit(value)))
+        }
+        Expr::Between(Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        }) => {
+            let low = binary_expr(*expr.clone(), Operator::GtEq, *low);
+            let high = binary_expr(*expr, Operator::LtEq, *high);
+            normalize_delete_predicate(and(low, high), table_schema)
+        }
+        Expr::InList {
+            expr,
+            list,
+            negated: false,
+        } if !list.is_empty() => {
+            // IN list of the same column is converted to an exact equtable domain.
+            let mut disjunction = None;
+            for item in list {
+                let eq = normalize_delete_predicate(
+                    binary_expr(*expr.clone(), Operator::Eq, item),
+                    table_schema,
+                )?;
+                disjunction = Some(match disjunction {
+                    Some(d) => or(d, eq),
+                    None => eq,
+                });
+            }
+            Ok(disjunction.expect("list is not empty"))
+        }
+        expr => Err(unsupported(&expr)),
+    }
+}
+
+/// Cast the literal compared with the column to the type of the column.
+fn cast_delete_literal(
+    column: &Column,
+    value: ScalarValue,
+    table_schema: &TskvTableSchema,
+) -> Result<ScalarValue> {
+    let table_column =
+        table_schema
+            .column(&column.name)
+            .ok_or_else(|| QueryError::ColumnNotExists {
+                column: column.name.clone(),
+                table: table_schema.name.to_string(),
+            })?;
+
+    if table_column.column_type.is_field() {
+        return Err(QueryError::DeleteWhereContainsField {
+            column: column.name.clone(),
+        });
+    }
+
+    let data_type = DataType::from(table_column.column_type);
+    if value.get_datatype() == data_type {
+        return Ok(value);
+    }
+
+    let array = cast(&value.to_array(), &data_type)?;
+    let casted = ScalarValue::try_from_array(&array, 0)?;
+    if casted.is_null() {
+        return Err(QueryError::UnsupportedDeletePredicate {
+            expr: format!("{} = {}", column.name, value),
+        });
+    }
+
+    Ok(casted)
+}
+
 fn show_series_projection(
     table_schema: &TskvTableSchema,
     mut plan_builder: LogicalPlanBuilder,
@@ -2352,4 +2663,80 @@ mod tests {
             _ => panic!(),
         }
     }
+
+    #[test]
+    fn test_normalize_delete_predicate() {
+        let table_schema = TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "test".to_string(),
+            vec![
+                TableColumn::new_time_column(0),
+                TableColumn::new_tag_column(1, "ta".to_string()),
+                TableColumn::new(
+                    2,
+                    "fa".to_string(),
+                    ColumnType::Field(ValueType::Float),
+                    Encoding::Default,
+                ),
+            ],
+        );
+
+        let expr = and(
+            col("ta").in_list(vec![lit("a"), lit("b")], false),
+            Expr::Between(Between {
+                expr: Box::new(col("time")),
+                negated: false,
+                low: Box::new(lit(1_i64)),
+                high: Box::new(lit(10_i64)),
+            }),
+        );
+        let expr = normalize_delete_predicate(expr, &table_schema).unwrap();
+        let expected = and(
+            or(col("ta").eq(lit("a")), col("ta").eq(lit("b"))),
+            and(
+                col("time").gt_eq(lit(ScalarValue::TimestampNanosecond(Some(1), None))),
+                col("time").lt_eq(lit(ScalarValue::TimestampNanosecond(Some(10), None))),
+            ),
+        );
+        assert_eq!(expr, expected);
+
+        let expr = lit(1_i64).lt(col("time"));
+        let expr = normalize_delete_predicate(expr, &table_schema).unwrap();
+        let expected = col("time").gt(lit(ScalarValue::TimestampNanosecond(Some(1), None)));
+        assert_eq!(expr, expected);
+
+        let error = normalize_delete_predicate(col("fa").eq(lit(1.0)), &table_schema)
+            .err()
+            .unwrap();
+        assert!(
+            matches!(error, QueryError::DeleteWhereContainsField { column } if column.eq("fa"))
+        );
+
+        let error = normalize_delete_predicate(
+            or(col("ta").eq(lit("a")), col("time").eq(lit(1_i64))),
+            &table_schema,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(
+            error,
+            QueryError::UnsupportedDeletePredicate { .. }
+        ));
+
+        for expr in [
+            col("ta").not_eq(lit("a")),
+            lit(1_i64).not_eq(col("time")),
+            col("ta").eq(lit(ScalarValue::Utf8(None))),
+            col("ta").in_list(vec![lit("a")], true),
+        ] {
+            let error = normalize_delete_predicate(expr, &table_schema)
+                .err()
+                .unwrap();
+            assert!(matches!(
+                error,
+                QueryError::UnsupportedDeletePredicate { .. }
+            ));
+        }
+    }
 }
@@ -10,13 +10,15 @@ use futures::{executor::block_on, FutureExt, Stream};
 use models::codec::Encoding;
 use models::schema::TskvTableSchemaRef;
 use models::{
-    predicate::domain::PredicateRef,
+    predicate::{aggregate::PushedAggregateFunction, domain::PredicateRef},
     schema::{ColumnType, TableColumn, TskvTableSchema, TIME_FIELD},
 };
 
 use spi::{QueryError, Result};
 use tskv::iterator::{QueryOption, TableScanMetrics};
 
+use crate::tskv_exec::ScanFragment;
+
 #[allow(dead_code)]
 pub struct TableScanStream {
     proj_schema: SchemaRef,
@@ -34,9 +36,35 @@ impl TableScanStream {
         proj_schema: SchemaRef,
         coord: CoordinatorRef,
         filter: PredicateRef,
+        aggregates: Option<Vec<PushedAggregateFunction>>,
+        fragment: Option<ScanFragment>,
         batch_size: usize,
         metrics: TableScanMetrics,
     ) -> Result<Self> {
+        if aggregates.is_some() {
+            // The pushed aggregate functions read the columns of the table,
+            // the projected schema is the schema of the partial results.
+            let option = QueryOption::new(
+                batch_size,
+                table_schema.tenant.clone(),
+                filter,
+                proj_schema.clone(),
+                table_schema.as_ref().clone(),
+                metrics.tskv_metrics(),
+            )
+            .with_aggregates(aggregates);
+
+            let iterator = block_on(coord.read_record(option))?;
+
+            return Ok(Self {
+                proj_schema,
+                batch_size,
+                coord,
+                iterator,
+                metrics,
+            });
+        }
+
         let mut proj_fileds = Vec::with_capacity(proj_schema.fields().len());
         for item in proj_schema.fields().iter() {
             let field_name = item.name();
@@ -82,6 +110,15 @@ impl TableScanStream {
             metrics.tskv_metrics(),
         );
 
+        // the data nodes execute the fragment over the columns scanned
+        let (option, proj_schema) = match fragment {
+            Some(fragment) => (
+                option.with_fragment(Some(fragment.encoded)),
+                fragment.schema,
+            ),
+            None => (option, proj_schema),
+        };
+
         let iterator = block_on(coord.read_record(option))?;
 
         Ok(Self {
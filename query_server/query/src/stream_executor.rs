@@ -0,0 +1,180 @@
+use std::fmt::{self, Debug};
+
+use async_trait::async_trait;
+use coordinator::errors::{CoordinatorError, CoordinatorResult};
+use coordinator::service::StreamExecutor;
+use datafusion::sql::sqlparser::{
+    ast::{BinaryOperator, Expr, Query, Select, SetExpr, Statement, TableFactor},
+    dialect::GenericDialect,
+    parser::{Parser, ParserError},
+    tokenizer::Tokenizer,
+};
+use meta::meta_client::MetaRef;
+use models::meta_data::StreamInfo;
+use models::oid::Identifier;
+use models::schema::TIME_FIELD;
+use snafu::ResultExt;
+use spi::server::dbms::DBMSRef;
+use spi::service::protocol::{ContextBuilder, Query as QueryRequest};
+use spi::{ParserSnafu, QueryError, Result};
+use trace::debug;
+
+/// Executes the queries of the streams with the query engine.
+pub struct StreamExecutorImpl {
+    dbms: DBMSRef,
+    meta: MetaRef,
+}
+
+impl StreamExecutorImpl {
+    pub fn new(dbms: DBMSRef, meta: MetaRef) -> Self {
+        Self { dbms, meta }
+    }
+}
+
+impl Debug for StreamExecutorImpl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamExecutorImpl").finish()
+    }
+}
+
+#[async_trait]
+impl StreamExecutor for StreamExecutorImpl {
+    async fn execute(&self, stream: &StreamInfo, start: i64, end: i64) -> CoordinatorResult<()> {
+        let to_coord_err = |err: QueryError| CoordinatorError::CommonError {
+            msg: err.to_string(),
+        };
+
+        let sql = stream_window_sql(stream, start, end).map_err(to_coord_err)?;
+        let user = self
+            .meta
+            .user_with_privileges(&stream.owner, Some(&stream.tenant))?;
+        debug!(
+            "execute stream {}.{} as {}: {}",
+            stream.db,
+            stream.name,
+            user.desc().name(),
+            sql
+        );
+
+        let context = ContextBuilder::new(user)
+            .with_tenant(Some(stream.tenant.clone()))
+            .with_database(Some(stream.db.clone()))
+            .build();
+        let result = self
+            .dbms
+            .execute(&QueryRequest::new(context, sql))
+            .await
+            .map_err(to_coord_err)?;
+
+        debug!(
+            "stream {}.{} processed [{}, {}), affected rows: {}",
+            stream.db,
+            stream.name,
+            start,
+            end,
+            result.result().affected_rows()
+        );
+
+        Ok(())
+    }
+}
+
+/// Returns the select of the stream query,
+/// the query must be a select from a single table without limit.
+pub(crate) fn stream_select(query: &mut Query) -> Result<&mut Select> {
+    let invalid = |reason: &str| QueryError::InvalidStream {
+        reason: reason.to_string(),
+    };
+
+    if query.with.is_some() {
+        return Err(invalid("WITH is not supported"));
+    }
+    if query.limit.is_some() || query.offset.is_some() || query.fetch.is_some() {
+        return Err(invalid("LIMIT, OFFSET and FETCH are not supported"));
+    }
+
+    let select = match query.body.as_mut() {
+        SetExpr::Select(select) => select,
+        _ => return Err(invalid("the query must be a SELECT statement")),
+    };
+
+    match select.from.as_slice() {
+        [from] if from.joins.is_empty() && matches!(from.relation, TableFactor::Table { .. }) => {
+            Ok(select)
+        }
+        _ => Err(invalid("the query must select from a single table")),
+    }
+}
+
+/// Returns the statement writing the results of the stream query
+/// on the data in [start, end) into the target table.
+pub fn stream_window_sql(stream: &StreamInfo, start: i64, end: i64) -> Result<String> {
+    let dialect = GenericDialect {};
+    let mut statements = Parser::parse_sql(&dialect, &stream.query).context(ParserSnafu)?;
+    let mut query = match (statements.pop(), statements.is_empty()) {
+        (Some(Statement::Query(query)), true) => query,
+        _ => {
+            return Err(QueryError::InvalidStream {
+                reason: format!("the query must be a SELECT statement: {}", stream.query),
+            })
+        }
+    };
+
+    let window = time_window_expr(start, end).context(ParserSnafu)?;
+    let select = stream_select(&mut query)?;
+    select.selection = Some(match select.selection.take() {
+        Some(expr) => Expr::BinaryOp {
+            left: Box::new(Expr::Nested(Box::new(expr))),
+            op: BinaryOperator::And,
+            right: Box::new(window),
+        },
+        None => window,
+    });
+
+    Ok(format!("INSERT INTO {} {}", stream.target_table, query))
+}
+
+fn time_window_expr(start: i64, end: i64) -> Result<Expr, ParserError> {
+    let sql = format!(
+        "{} >= to_timestamp({}) AND {} < to_timestamp({})",
+        TIME_FIELD, start, TIME_FIELD, end
+    );
+    let dialect = GenericDialect {};
+    let tokens = Tokenizer::new(&dialect, &sql).tokenize()?;
+
+    Parser::new(tokens, &dialect).parse_expr()
+}
+
+#[cfg(test)]
+mod test {
+    use models::meta_data::StreamInfo;
+
+    use super::stream_window_sql;
+
+    #[test]
+    fn test_stream_window_sql() {
+        let mut stream = StreamInfo {
+            target_table: "air_rollup".to_string(),
+            query: "SELECT date_bin(INTERVAL '1 minute', time, TIMESTAMP '1970-01-01T00:00:00Z') AS time, station, avg(pressure) AS pressure FROM air WHERE station = 'XiaoMaiDao' GROUP BY 1, station".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            stream_window_sql(&stream, 60, 120).unwrap(),
+            "INSERT INTO air_rollup SELECT date_bin(INTERVAL '1 minute', time, TIMESTAMP '1970-01-01T00:00:00Z') AS time, station, avg(pressure) AS pressure FROM air \
+            WHERE (station = 'XiaoMaiDao') AND time >= to_timestamp(60) AND time < to_timestamp(120) GROUP BY 1, station"
+        );
+
+        stream.query = "SELECT max(pressure) FROM air".to_string();
+        assert_eq!(
+            stream_window_sql(&stream, 60, 120).unwrap(),
+            "INSERT INTO air_rollup SELECT max(pressure) FROM air WHERE time >= to_timestamp(60) AND time < to_timestamp(120)"
+        );
+
+        stream.query = "SELECT * FROM air a JOIN wind w ON a.station = w.station".to_string();
+        assert!(stream_window_sql(&stream, 60, 120).is_err());
+
+        stream.query = "SELECT * FROM air LIMIT 10".to_string();
+        assert!(stream_window_sql(&stream, 60, 120).is_err());
+    }
+}
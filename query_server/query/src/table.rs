@@ -12,13 +12,16 @@ use datafusion::{
     physical_plan::{project_schema, ExecutionPlan},
 };
 use meta::error::MetaError;
-use models::predicate::domain::{Predicate, PredicateRef};
+use models::predicate::{
+    aggregate::PushedAggregateFunction,
+    domain::{Predicate, PredicateRef},
+};
 use models::schema::{TskvTableSchema, TskvTableSchemaRef};
 
 use crate::{
     data_source::{sink::tskv::TskvRecordBatchSinkProvider, WriteExecExt},
     extension::physical::plan_node::{table_writer::TableWriterExec, tag_scan::TagScanExec},
-    tskv_exec::TskvExec,
+    tskv_exec::{ScanFragment, TskvExec},
 };
 
 #[derive(Clone)]
@@ -71,6 +74,46 @@ impl ClusterTable {
         )))
     }
 
+    pub async fn aggregate_scan(
+        &self,
+        _ctx: &SessionState,
+        projected_schema: &DFSchemaRef,
+        filters: &[Expr],
+        aggregates: &[PushedAggregateFunction],
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let filter = Arc::new(Predicate::default().push_down_filter(filters, &self.schema));
+
+        Ok(Arc::new(
+            TskvExec::new(
+                self.schema.clone(),
+                Arc::new(projected_schema.as_ref().into()),
+                filter,
+                self.coord.clone(),
+            )
+            .with_aggregates(aggregates.to_vec()),
+        ))
+    }
+
+    pub async fn fragment_scan(
+        &self,
+        _ctx: &SessionState,
+        scan_schema: &DFSchemaRef,
+        filters: &[Expr],
+        fragment: ScanFragment,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let filter = Arc::new(Predicate::default().push_down_filter(filters, &self.schema));
+
+        Ok(Arc::new(
+            TskvExec::new(
+                self.schema.clone(),
+                Arc::new(scan_schema.as_ref().into()),
+                filter,
+                self.coord.clone(),
+            )
+            .with_fragment(fragment),
+        ))
+    }
+
     pub fn table_schema(&self) -> TskvTableSchemaRef {
         self.schema.clone()
     }
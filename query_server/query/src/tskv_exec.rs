@@ -15,13 +15,24 @@ use datafusion::{
         SendableRecordBatchStream, Statistics,
     },
 };
-use models::predicate::domain::PredicateRef;
+use models::predicate::{
+    aggregate::PushedAggregateFunction, domain::PredicateRef, fragment::PlanFragment,
+};
 use models::schema::TskvTableSchemaRef;
 use trace::debug;
 
 use crate::stream::TableScanStream;
 use tskv::iterator::TableScanMetrics;
 
+/// The plan fragment executed over the scan by the data nodes.
+#[derive(Debug, Clone)]
+pub struct ScanFragment {
+    pub fragment: PlanFragment,
+    pub encoded: Vec<u8>,
+    /// The schema of the fragment outputs
+    pub schema: SchemaRef,
+}
+
 #[derive(Debug, Clone)]
 pub struct TskvExec {
     // connection
@@ -30,6 +41,12 @@ pub struct TskvExec {
     proj_schema: SchemaRef,
     filter: PredicateRef,
     coord: CoordinatorRef,
+    /// The aggregate functions pushed down into the scan,
+    /// `proj_schema` is the schema of the partial results if it's not None.
+    aggregates: Option<Vec<PushedAggregateFunction>>,
+    /// The plan fragment executed over the scan,
+    /// `proj_schema` is the schema of the columns scanned if it's not None.
+    fragment: Option<ScanFragment>,
 
     /// Execution metrics
     metrics: ExecutionPlanMetricsSet,
@@ -49,9 +66,22 @@ impl TskvExec {
             proj_schema,
             filter,
             coord,
+            aggregates: None,
+            fragment: None,
             metrics,
         }
     }
+
+    pub(crate) fn with_aggregates(mut self, aggregates: Vec<PushedAggregateFunction>) -> Self {
+        self.aggregates = Some(aggregates);
+        self
+    }
+
+    pub(crate) fn with_fragment(mut self, fragment: ScanFragment) -> Self {
+        self.fragment = Some(fragment);
+        self
+    }
+
     pub fn filter(&self) -> PredicateRef {
         self.filter.clone()
     }
@@ -63,7 +93,10 @@ impl ExecutionPlan for TskvExec {
     }
 
     fn schema(&self) -> SchemaRef {
-        self.proj_schema.clone()
+        match self.fragment.as_ref() {
+            Some(fragment) => fragment.schema.clone(),
+            None => self.proj_schema.clone(),
+        }
     }
 
     fn output_partitioning(&self) -> Partitioning {
@@ -87,6 +120,8 @@ impl ExecutionPlan for TskvExec {
             proj_schema: self.proj_schema.clone(),
             filter: self.filter.clone(),
             coord: self.coord.clone(),
+            aggregates: self.aggregates.clone(),
+            fragment: self.fragment.clone(),
             metrics: self.metrics.clone(),
         }))
     }
@@ -109,9 +144,11 @@ impl ExecutionPlan for TskvExec {
 
         let table_stream = TableScanStream::new(
             self.table_schema.clone(),
-            self.schema(),
+            self.proj_schema.clone(),
             self.coord.clone(),
             self.filter(),
+            self.aggregates.clone(),
+            self.fragment.clone(),
             batch_size,
             metrics,
         )
@@ -135,7 +172,14 @@ impl ExecutionPlan for TskvExec {
                     "TskvExec: {}, projection=[{}]",
                     PredicateDisplay(&filter),
                     fields.join(","),
-                )
+                )?;
+                if let Some(aggregates) = self.aggregates.as_ref() {
+                    write!(f, ", aggregates={:?}", aggregates)?;
+                }
+                if let Some(fragment) = self.fragment.as_ref() {
+                    write!(f, ", fragment={:?}", fragment.fragment.steps)?;
+                }
+                Ok(())
             }
         }
     }
@@ -376,6 +376,36 @@ pub enum QueryError {
     InvalidRemoteReadReq {
         source: GenericError,
     },
+
+    #[error_code(code = 57)]
+    #[snafu(display("Invalid prom remote write request, error: {}", source))]
+    InvalidRemoteWriteReq {
+        source: GenericError,
+    },
+
+    #[snafu(display(
+        "Semantic error: DELETE does not support where clause contains field {}",
+        column
+    ))]
+    #[error_code(code = 58)]
+    DeleteWhereContainsField {
+        column: String,
+    },
+
+    #[snafu(display(
+        "Semantic error: DELETE only supports comparisons between tag/time column and constant combined with AND, found: {}",
+        expr
+    ))]
+    #[error_code(code = 59)]
+    UnsupportedDeletePredicate {
+        expr: String,
+    },
+
+    #[snafu(display("Semantic error: invalid stream, {}", reason))]
+    #[error_code(code = 60)]
+    InvalidStream {
+        reason: String,
+    },
 }
 
 impl From<ParserError> for QueryError {
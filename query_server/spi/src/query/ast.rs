@@ -1,9 +1,10 @@
 use std::fmt;
 
+use coordinator::command::HintedOffCmd;
 use datafusion::sql::sqlparser::ast::{
     AnalyzeFormat, DataType, Expr, Ident, ObjectName, Offset, OrderByExpr, Value,
 };
-use datafusion::sql::sqlparser::ast::{SqlOption, TableFactor};
+use datafusion::sql::sqlparser::ast::{Query, SqlOption, TableFactor};
 use datafusion::sql::sqlparser::parser::ParserError;
 use datafusion::sql::{parser::CreateExternalTable, sqlparser::ast::Statement};
 use models::codec::Encoding;
@@ -20,12 +21,15 @@ pub enum ExtStatement {
     // bulk load/unload
     Copy(Copy),
 
+    DeleteFromTable(DeleteFromTable),
+
     CreateExternalTable(CreateExternalTable),
     CreateTable(CreateTable),
     CreateDatabase(CreateDatabase),
     CreateTenant(CreateTenant),
     CreateUser(CreateUser),
     CreateRole(CreateRole),
+    CreateStream(CreateStream),
 
     DropDatabaseObject(DropDatabaseObject),
     DropTenantObject(DropTenantObject),
@@ -54,6 +58,22 @@ pub enum ExtStatement {
     MoveVnode(MoveVnode),
     CompactVnode(CompactVnode),
     ChecksumGroup(ChecksumGroup),
+    RepairGroup(RepairGroup),
+
+    // data node cmd
+    DropDatanode(DropDatanode),
+    HintedOffControl(HintedOffControl),
+
+    // meta node cmd
+    AddMetaNode(AddMetaNode),
+    DropMetaNode(DropMetaNode),
+    TransferMetaLeader,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeleteFromTable {
+    pub table_name: ObjectName,
+    pub selection: Option<Expr>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -61,6 +81,11 @@ pub struct ChecksumGroup {
     pub replication_set_id: ReplicationSetId,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairGroup {
+    pub replication_set_id: ReplicationSetId,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CompactVnode {
     pub vnode_ids: Vec<VnodeId>,
@@ -83,6 +108,28 @@ pub struct DropVnode {
     pub vnode_id: VnodeId,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropDatanode {
+    pub node_id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintedOffControl {
+    pub node_id: NodeId,
+    pub cmd: HintedOffCmd,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddMetaNode {
+    pub node_id: NodeId,
+    pub addr: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropMetaNode {
+    pub node_id: NodeId,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Copy {
     pub copy_target: CopyTarget,
@@ -255,6 +302,19 @@ pub struct CreateDatabase {
     pub if_not_exists: bool,
     pub options: DatabaseOptions,
 }
+/// CREATE STREAM [IF NOT EXISTS] <stream_name>
+/// [WITH [INTERVAL <duration>] [DELAY <duration>]]
+/// AS <select> INTO <target_table>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateStream {
+    pub name: Ident,
+    pub if_not_exists: bool,
+    pub interval: Option<String>,
+    pub delay: Option<String>,
+    pub query: Box<Query>,
+    pub target_table: ObjectName,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CreateTable {
     pub name: ObjectName,
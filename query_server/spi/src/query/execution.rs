@@ -1,11 +1,12 @@
 use std::sync::atomic::{AtomicPtr, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use coordinator::service::CoordinatorRef;
 use datafusion::arrow::datatypes::{Schema, SchemaRef};
 use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::physical_plan::SendableRecordBatchStream;
 
 use crate::service::protocol::Query;
 use crate::service::protocol::QueryId;
@@ -37,6 +38,10 @@ pub trait QueryExecution: Send + Sync {
 #[derive(Clone)]
 pub enum Output {
     StreamData(SchemaRef, Vec<RecordBatch>),
+    /// The record batches are read as the query executes, see [`Context::is_streaming`].
+    ///
+    /// [`Context::is_streaming`]: crate::service::protocol::Context::is_streaming
+    Stream(OutputStream),
     Nil(()),
 }
 
@@ -44,14 +49,16 @@ impl Output {
     pub fn schema(&self) -> SchemaRef {
         match self {
             Self::StreamData(schema, _) => schema.clone(),
+            Self::Stream(stream) => stream.schema(),
             Self::Nil(_) => Arc::new(Schema::empty()),
         }
     }
 
+    /// The record batches of a [`Output::Stream`] are not read yet, so they are not included
     pub fn chunk_result(&self) -> &[RecordBatch] {
         match self {
             Self::StreamData(_, result) => result,
-            Self::Nil(_) => &[],
+            Self::Stream(_) | Self::Nil(_) => &[],
         }
     }
 
@@ -77,11 +84,37 @@ impl Output {
                 .map(|e| e.num_rows())
                 .reduce(|p, c| p + c)
                 .unwrap_or(0) as i64,
+            Self::Stream(_) => -1,
             Self::Nil(_) => 0,
         }
     }
 }
 
+/// The result stream of a query, it can only be taken once.
+#[derive(Clone)]
+pub struct OutputStream {
+    schema: SchemaRef,
+    stream: Arc<Mutex<Option<SendableRecordBatchStream>>>,
+}
+
+impl OutputStream {
+    pub fn new(stream: SendableRecordBatchStream) -> Self {
+        Self {
+            schema: stream.schema(),
+            stream: Arc::new(Mutex::new(Some(stream))),
+        }
+    }
+
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Returns none if the stream has been taken.
+    pub fn take(&self) -> Option<SendableRecordBatchStream> {
+        self.stream.lock().ok().and_then(|mut stream| stream.take())
+    }
+}
+
 pub trait QueryExecutionFactory {
     fn create_query_execution(
         &self,
@@ -26,6 +26,7 @@ use datafusion::{
     sql::sqlparser::ast::{Ident, ObjectName, SqlOption},
 };
 
+use coordinator::command::HintedOffCmd;
 use models::meta_data::{NodeId, ReplicationSetId, VnodeId};
 use models::schema::TableColumn;
 use models::{
@@ -83,6 +84,8 @@ pub enum DDLPlan {
 
     CreateRole(CreateRole),
 
+    CreateStream(CreateStream),
+
     DescribeTable(DescribeTable),
 
     DescribeDatabase(DescribeDatabase),
@@ -110,6 +113,28 @@ pub enum DDLPlan {
     CompactVnode(CompactVnode),
 
     ChecksumGroup(ChecksumGroup),
+
+    RepairGroup(RepairGroup),
+
+    DropDatanode(DropDatanode),
+
+    HintedOffControl(HintedOffControl),
+
+    AddMetaNode(AddMetaNode),
+
+    DropMetaNode(DropMetaNode),
+
+    TransferMetaLeader,
+
+    DeleteFromTable(DeleteFromTable),
+}
+
+#[derive(Debug, Clone)]
+pub struct DeleteFromTable {
+    pub database_name: String,
+    pub table_name: String,
+    /// Conjunction of `column op literal` comparisons on tag and time columns
+    pub selection: Option<Expr>,
 }
 
 #[derive(Debug, Clone)]
@@ -117,6 +142,11 @@ pub struct ChecksumGroup {
     pub replication_set_id: ReplicationSetId,
 }
 
+#[derive(Debug, Clone)]
+pub struct RepairGroup {
+    pub replication_set_id: ReplicationSetId,
+}
+
 #[derive(Debug, Clone)]
 pub struct CompactVnode {
     pub vnode_ids: Vec<VnodeId>,
@@ -139,6 +169,32 @@ pub struct DropVnode {
     pub vnode_id: VnodeId,
 }
 
+/// Decommissions the data node, its vnodes are moved away by the rebalancer.
+#[derive(Debug, Clone)]
+pub struct DropDatanode {
+    pub node_id: NodeId,
+}
+
+/// Pauses, resumes or purges the replay of the writes queued for the data node.
+#[derive(Debug, Clone)]
+pub struct HintedOffControl {
+    pub node_id: NodeId,
+    pub cmd: HintedOffCmd,
+}
+
+/// Joins the meta node to the raft group of the meta cluster as a voter.
+#[derive(Debug, Clone)]
+pub struct AddMetaNode {
+    pub node_id: NodeId,
+    pub addr: String,
+}
+
+/// Removes the meta node from the raft group of the meta cluster.
+#[derive(Debug, Clone)]
+pub struct DropMetaNode {
+    pub node_id: NodeId,
+}
+
 #[derive(Debug, Clone)]
 pub enum SYSPlan {
     ShowQueries,
@@ -159,6 +215,7 @@ pub struct DropDatabaseObject {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DatabaseObjectType {
     Table,
+    Stream,
 }
 
 #[derive(Debug, Clone)]
@@ -234,6 +291,21 @@ pub struct CreateTable {
     pub if_not_exists: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateStream {
+    pub name: String,
+    pub database_name: String,
+    pub if_not_exists: bool,
+    /// The table the results are written into
+    pub target_table: String,
+    /// The select statement aggregating the source data
+    pub query: String,
+    /// The length of the time window, in nanoseconds
+    pub interval: i64,
+    /// How long to wait for the late data, in nanoseconds
+    pub delay: i64,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CreateDatabase {
     pub name: String,
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use coordinator::service::CoordinatorRef;
 use meta::meta_client::MetaRef;
 
 use crate::{service::protocol::Context, Result};
@@ -12,5 +13,5 @@ pub type PromRemoteServerRef = Arc<dyn PromRemoteServer + Send + Sync>;
 pub trait PromRemoteServer {
     async fn remote_read(&self, ctx: &Context, meta: MetaRef, req: Bytes) -> Result<Vec<u8>>;
 
-    fn remote_write(&self, ctx: &Context, req: Bytes) -> Result<()>;
+    async fn remote_write(&self, ctx: &Context, coord: CoordinatorRef, req: Bytes) -> Result<()>;
 }
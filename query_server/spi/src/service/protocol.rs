@@ -59,6 +59,7 @@ pub struct Context {
     tenant: String,
     database: String,
     session_config: IsiphoSessionConfig,
+    is_streaming: bool,
 }
 
 impl Context {
@@ -77,6 +78,12 @@ impl Context {
     pub fn session_config(&self) -> &IsiphoSessionConfig {
         &self.session_config
     }
+
+    /// The result of the query is returned as a stream instead of being collected,
+    /// see [`Output::Stream`].
+    pub fn is_streaming(&self) -> bool {
+        self.is_streaming
+    }
 }
 
 pub struct ContextBuilder {
@@ -84,6 +91,7 @@ pub struct ContextBuilder {
     tenant: String,
     database: String,
     session_config: IsiphoSessionConfig,
+    is_streaming: bool,
 }
 
 impl ContextBuilder {
@@ -93,6 +101,7 @@ impl ContextBuilder {
             tenant: DEFAULT_CATALOG.to_string(),
             database: DEFAULT_DATABASE.to_string(),
             session_config: Default::default(),
+            is_streaming: false,
         }
     }
 
@@ -119,12 +128,18 @@ impl ContextBuilder {
         self
     }
 
+    pub fn with_streaming(mut self, is_streaming: bool) -> Self {
+        self.is_streaming = is_streaming;
+        self
+    }
+
     pub fn build(self) -> Context {
         Context {
             user_info: self.user_info,
             tenant: self.tenant,
             database: self.database,
             session_config: self.session_config,
+            is_streaming: self.is_streaming,
         }
     }
 }
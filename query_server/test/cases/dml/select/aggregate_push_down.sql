@@ -0,0 +1,17 @@
+--#SORT=true
+--#SLEEP=100
+DROP DATABASE IF EXISTS public;
+CREATE DATABASE public;
+
+--#LP_BEGIN
+agg,ta=a1 fa=1 1667456411000000000
+agg,ta=a2 fa=2 1667456411000000001
+agg,ta=a3 fa=4 1667456411000000002
+--#LP_END
+
+-- the in list is not answered exactly by the scan, the aggregation is not pushed down
+select count(*) as cnt, sum(fa) as total, min(fa) as low, max(fa) as high from public.agg where ta in ('a1', 'a2');
+
+select count(*) as cnt, sum(fa) as total from public.agg where ta not in ('a1');
+
+select count(*) as cnt, sum(fa) as total from public.agg where ta = 'a3';
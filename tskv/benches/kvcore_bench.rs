@@ -61,6 +61,7 @@ fn big_write(c: &mut Criterion) {
                     version: 1,
                     meta: None,
                     points,
+                    consistency_level: None,
                 };
                 rt.block_on(tskv.write(0, request)).unwrap();
             }
@@ -82,6 +83,7 @@ fn run(c: &mut Criterion) {
         version: 1,
         meta: None,
         points,
+        consistency_level: None,
     };
 
     // maybe 500 us
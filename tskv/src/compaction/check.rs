@@ -1,13 +1,13 @@
 use std::{
     collections::{BTreeMap, HashMap},
     fmt::{Display, Write},
-    rc::Rc,
     sync::Arc,
 };
 
 use blake3::Hasher;
 use chrono::{Duration, DurationRound, NaiveDateTime};
 use models::{schema::ColumnType, utils, ColumnId, FieldId, Timestamp};
+use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 use trace::warn;
 
@@ -24,17 +24,17 @@ use crate::{
 pub type Hash = [u8; 32];
 
 pub fn hash_to_string(hash: Hash) -> String {
-    let mut s = String::with_capacity(32);
+    let mut s = String::with_capacity(64);
     for v in hash {
-        s.push_str(format!("{:x}", v).as_str());
+        s.push_str(format!("{:02x}", v).as_str());
     }
     s
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TableHashTreeNode {
-    table: String,
-    columns: Vec<ColumnHashTreeNode>,
+    pub table: String,
+    pub columns: Vec<ColumnHashTreeNode>,
 }
 
 impl TableHashTreeNode {
@@ -62,10 +62,10 @@ impl Display for TableHashTreeNode {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ColumnHashTreeNode {
-    column: String,
-    values: Vec<TimeRangeHashTreeNode>,
+    pub column: String,
+    pub values: Vec<TimeRangeHashTreeNode>,
 }
 
 impl ColumnHashTreeNode {
@@ -93,11 +93,11 @@ impl Display for ColumnHashTreeNode {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TimeRangeHashTreeNode {
-    min_ts: Timestamp,
-    max_ts: Timestamp,
-    hash: Hash,
+    pub min_ts: Timestamp,
+    pub max_ts: Timestamp,
+    pub hash: Hash,
 }
 
 impl TimeRangeHashTreeNode {
@@ -117,7 +117,7 @@ impl Display for TimeRangeHashTreeNode {
             self.min_ts, self.max_ts
         ))?;
         for v in self.hash {
-            f.write_fmt(format_args!("{:x}", v))?;
+            f.write_fmt(format_args!("{:02x}", v))?;
         }
         f.write_char('}')?;
         Ok(())
@@ -140,12 +140,12 @@ pub(crate) async fn get_ts_family_hash_tree(
     };
 
     let schemas = database.get_schemas();
-    let mut cid_table_name_map: HashMap<ColumnId, Rc<String>> = HashMap::new();
+    let mut cid_table_name_map: HashMap<ColumnId, Arc<String>> = HashMap::new();
     let mut cid_col_name_map: HashMap<ColumnId, String> = HashMap::new();
     for tab in schemas.list_tables()? {
         match schemas.get_table_schema(&tab)? {
             Some(sch) => {
-                let shared_tab = Rc::new(tab);
+                let shared_tab = Arc::new(tab);
                 for col in sch.columns() {
                     if col.column_type != ColumnType::Time && col.column_type != ColumnType::Tag {
                         cid_table_name_map.insert(col.id, shared_tab.clone());
@@ -180,58 +180,69 @@ pub(crate) async fn get_ts_family_hash_tree(
 
     // Build a compact iterator, read data, slite by time range and then calculate hash.
     let iter = CompactIterator::new(readers, MAX_DATA_BLOCK_SIZE, true);
-    let (fid_tr_hash_val_map, mut cid_fid_count_map) =
+    let (fid_tr_hash_val_map, _) =
         read_from_compact_iterator(iter, ts_family_id, time_range_nanosec, &cid_col_name_map)
             .await?;
 
     // Transform hashed data into TableHashTreeNode list.
-    let mut cid_tr_hasher_map: HashMap<ColumnId, BTreeMap<TimeRange, Hasher>> = HashMap::new();
-    let mut hash_tree_builder: HashMap<String, TableHashTreeNode> = HashMap::new();
-    for (fid, tr_hashes) in fid_tr_hash_val_map.into_iter() {
-        if tr_hashes.is_empty() {
-            continue;
+    // The hashes of the series are combined in the order of the series keys and the
+    // tables and columns are sorted by name, so the trees of the replicas are comparable.
+    let mut cid_fids_map: HashMap<ColumnId, Vec<(String, FieldId)>> = HashMap::new();
+    for fid in fid_tr_hash_val_map.keys() {
+        let (column_id, series_id) = utils::split_id(*fid);
+        let series_key = match database.get_series_key(ts_family_id, series_id).await? {
+            Some(key) => key.string(),
+            None => series_id.to_string(),
+        };
+        cid_fids_map
+            .entry(column_id)
+            .or_default()
+            .push((series_key, *fid));
+    }
+
+    let mut hash_tree_builder: BTreeMap<String, BTreeMap<String, ColumnHashTreeNode>> =
+        BTreeMap::new();
+    for (column_id, mut fids) in cid_fids_map.into_iter() {
+        let (table_name, column_name) = match (
+            cid_table_name_map.get(&column_id),
+            cid_col_name_map.get(&column_id),
+        ) {
+            (Some(t), Some(c)) => (t, c),
+            _ => continue,
+        };
+        fids.sort();
+        let mut tr_hasher_map: BTreeMap<TimeRange, Hasher> = BTreeMap::new();
+        for (_, fid) in fids {
+            if let Some(tr_hashes) = fid_tr_hash_val_map.get(&fid) {
+                for (tr, hash) in tr_hashes.iter() {
+                    tr_hasher_map.entry(*tr).or_default().update(hash);
+                }
+            }
         }
-        let (column_id, _) = utils::split_id(fid);
-        if !cid_col_name_map.contains_key(&column_id) {
+        if tr_hasher_map.is_empty() {
             continue;
         }
-        let tr_hasher_map = cid_tr_hasher_map.entry(column_id).or_default();
-        for (tr, hash) in tr_hashes.into_iter() {
-            tr_hasher_map.entry(tr).or_default().update(&hash);
-        }
-        if let Some(fid_count) = cid_fid_count_map.get_mut(&column_id) {
-            *fid_count -= 1;
-            if *fid_count == 0 {
-                let table_name = cid_table_name_map.get(&column_id).unwrap();
-                let table_node = match hash_tree_builder.get_mut(table_name.as_ref()) {
-                    Some(t) => t,
-                    None => hash_tree_builder
-                        .entry((**table_name).clone())
-                        .or_insert_with(|| {
-                            TableHashTreeNode::with_capacity(
-                                (**table_name).clone(),
-                                cid_fid_count_map.len(),
-                            )
-                        }),
-                };
 
-                // cid_col_name_map must contains key column_id
-                let column_name = cid_col_name_map.remove(&column_id).unwrap();
-                // tr_hasher_map must contains key column_id
-                let tr_hasher_map = cid_tr_hasher_map.remove(&column_id).unwrap();
-                let mut column_node =
-                    ColumnHashTreeNode::with_capacity(column_name, tr_hasher_map.len());
-                for (tr, hasher) in tr_hasher_map.into_iter() {
-                    column_node.push(TimeRangeHashTreeNode::new(tr, hasher.finalize().into()));
-                }
-                table_node.push(column_node);
-            }
-        };
+        let mut column_node =
+            ColumnHashTreeNode::with_capacity(column_name.clone(), tr_hasher_map.len());
+        for (tr, hasher) in tr_hasher_map.into_iter() {
+            column_node.push(TimeRangeHashTreeNode::new(tr, hasher.finalize().into()));
+        }
+        hash_tree_builder
+            .entry((**table_name).clone())
+            .or_default()
+            .insert(column_name.clone(), column_node);
     }
 
     Ok(hash_tree_builder
         .into_iter()
-        .map(|(k, v)| v)
+        .map(|(table, columns)| {
+            let mut table_node = TableHashTreeNode::with_capacity(table, columns.len());
+            for column in columns.into_values() {
+                table_node.push(column);
+            }
+            table_node
+        })
         .collect::<Vec<TableHashTreeNode>>())
 }
 
@@ -293,35 +304,35 @@ fn hash_partial_datablock(
     match data_block {
         DataBlock::U64 { ts, val, .. } => {
             let limit = min_idx + find_timestamp(&ts[min_idx..], max_timestamp);
-            for (i, v) in val.iter().enumerate().skip(min_idx).take(limit) {
+            for (i, v) in val.iter().enumerate().skip(min_idx).take(limit - min_idx) {
                 hasher.update(v.to_be_bytes().as_slice());
             }
             limit
         }
         DataBlock::I64 { ts, val, .. } => {
             let limit = min_idx + find_timestamp(&ts[min_idx..], max_timestamp);
-            for (i, v) in val.iter().enumerate().skip(min_idx).take(limit) {
+            for (i, v) in val.iter().enumerate().skip(min_idx).take(limit - min_idx) {
                 hasher.update(v.to_be_bytes().as_slice());
             }
             limit
         }
         DataBlock::F64 { ts, val, .. } => {
             let limit = min_idx + find_timestamp(&ts[min_idx..], max_timestamp);
-            for (i, v) in val.iter().enumerate().skip(min_idx).take(limit) {
+            for (i, v) in val.iter().enumerate().skip(min_idx).take(limit - min_idx) {
                 hasher.update(v.to_be_bytes().as_slice());
             }
             limit
         }
         DataBlock::Str { ts, val, .. } => {
             let limit = min_idx + find_timestamp(&ts[min_idx..], max_timestamp);
-            for (i, v) in val.iter().enumerate().skip(min_idx).take(limit) {
+            for (i, v) in val.iter().enumerate().skip(min_idx).take(limit - min_idx) {
                 hasher.update(v.as_slice());
             }
             limit
         }
         DataBlock::Bool { ts, val, .. } => {
             let limit = min_idx + find_timestamp(&ts[min_idx..], max_timestamp);
-            for (i, v) in val.iter().enumerate().skip(min_idx).take(limit) {
+            for (i, v) in val.iter().enumerate().skip(min_idx).take(limit - min_idx) {
                 hasher.update(if *v { &[1_u8] } else { &[0_u8] });
             }
             limit
@@ -359,52 +370,30 @@ async fn read_from_compact_iterator(
                     }
                     *cid_fid_count_map.entry(column_id).or_default() += 1;
 
-                    // Check if there is last hash value that not stored.
-                    if let Some((time_range, last_fid)) = last_hashed_tr_fid {
-                        if last_fid != field_id {
-                            fid_tr_hash_val_map
-                                .entry(last_fid)
-                                .or_default()
-                                .push((time_range, hasher.finalize().into()));
-                            hasher.reset();
-                        }
-                    }
-                    if let Some(blk_time_range) = data_block.time_range() {
-                        // Get trunced time range by DataBlock.time[0]
-                        // TODO: Data block may be split into multi time ranges.
-                        let (min_ts, max_ts) = match calc_block_partial_time_range(
-                            blk_time_range.0,
+                    // Split the data block by the time ranges aligned to time_range, the data
+                    // of a field in a time range may be in several continuous data blocks.
+                    let timestamps = data_block.ts();
+                    let mut min_idx = 0;
+                    while min_idx < timestamps.len() {
+                        let (min_ts, max_ts) = calc_block_partial_time_range(
+                            timestamps[min_idx],
                             time_range,
                             time_range_nanosec,
-                        ) {
-                            Ok(tr) => tr,
-                            Err(e) => return Err(e),
-                        };
-
-                        // Calculate and store the hash value of data in time range
-                        let hash_vec = fid_tr_hash_val_map.entry(field_id).or_default();
-                        if blk_time_range.1 > max_ts {
-                            // Time range of data block need split.
-                            let min_idx =
-                                hash_partial_datablock(&mut hasher, &data_block, 0, max_ts);
-                            hash_vec
-                                .push((TimeRange::new(min_ts, max_ts), hasher.finalize().into()));
-                            hasher.reset();
-                            hash_partial_datablock(
-                                &mut hasher,
-                                &data_block,
-                                min_idx,
-                                Timestamp::MIN,
-                            );
-                            last_hashed_tr_fid =
-                                Some((TimeRange::new(max_ts, blk_time_range.1), field_id));
-                        } else {
-                            hash_partial_datablock(&mut hasher, &data_block, 0, Timestamp::MIN);
-                            last_hashed_tr_fid = Some((TimeRange::new(min_ts, max_ts), field_id));
+                        )?;
+                        let tr = TimeRange::new(min_ts, max_ts);
+
+                        // Check if there is last hash value that not stored.
+                        if let Some((last_tr, last_fid)) = last_hashed_tr_fid {
+                            if last_fid != field_id || last_tr != tr {
+                                fid_tr_hash_val_map
+                                    .entry(last_fid)
+                                    .or_default()
+                                    .push((last_tr, hasher.finalize().into()));
+                                hasher.reset();
+                            }
                         }
-                    } else {
-                        // Ignore: Case argument decode_non_overlap_blocks in CompactIterator::new()
-                        // is set to true, we may ignore it.
+                        min_idx = hash_partial_datablock(&mut hasher, &data_block, min_idx, max_ts);
+                        last_hashed_tr_fid = Some((tr, field_id));
                     }
                 };
             }
@@ -728,6 +717,7 @@ mod test {
                 password: None,
             }),
             points,
+            consistency_level: None,
         }
     }
 
@@ -737,29 +727,17 @@ mod test {
     ) -> Vec<(TimeRange, Hash)> {
         let time_range = Duration::nanoseconds(time_range_nanosec);
         let mut tr_hashes: Vec<(TimeRange, Hash)> = Vec::new();
-        let mut hasher = Hasher::new();
 
-        if let Some(blk_time_range) = data_block.time_range() {
-            // Get trunced time range by DataBlock.time[0]
+        // Split the data block by the aligned time ranges.
+        let timestamps = data_block.ts();
+        let mut min_idx = 0;
+        while min_idx < timestamps.len() {
             let (min_ts, max_ts) =
-                calc_block_partial_time_range(blk_time_range.0, time_range, time_range_nanosec)
+                calc_block_partial_time_range(timestamps[min_idx], time_range, time_range_nanosec)
                     .unwrap();
-
-            // Calculate and store the hash value of data in time range
-            if blk_time_range.1 > max_ts {
-                // Time range of data block need to split.
-                let min_idx = hash_partial_datablock(&mut hasher, data_block, 0, max_ts);
-                tr_hashes.push((TimeRange::new(min_ts, max_ts), hasher.finalize().into()));
-                hasher.reset();
-                hash_partial_datablock(&mut hasher, data_block, min_idx, Timestamp::MIN);
-                tr_hashes.push((
-                    TimeRange::new(max_ts, blk_time_range.1),
-                    hasher.finalize().into(),
-                ));
-            } else {
-                hash_partial_datablock(&mut hasher, data_block, 0, Timestamp::MIN);
-                tr_hashes.push((TimeRange::new(min_ts, max_ts), hasher.finalize().into()));
-            }
+            let mut hasher = Hasher::new();
+            min_idx = hash_partial_datablock(&mut hasher, data_block, min_idx, max_ts);
+            tr_hashes.push((TimeRange::new(min_ts, max_ts), hasher.finalize().into()));
         }
 
         tr_hashes
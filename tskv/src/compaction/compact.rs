@@ -275,7 +275,7 @@ impl CompactIterator {
                 }
                 if cbm.has_tombstone || self.decode_non_overlap_blocks {
                     let data_block = self.tsm_readers[cbm.readers_idx]
-                        .get_data_block(&cbm.block_meta)
+                        .get_data_block_owned(&cbm.block_meta)
                         .await
                         .context(error::ReadTsmSnafu)?;
                     merging_blks.push(CompactingBlock::DataBlock {
@@ -300,7 +300,7 @@ impl CompactIterator {
             ) {
                 // 2.1
                 let data_block = self.tsm_readers[cbm.readers_idx]
-                    .get_data_block(&cbm.block_meta)
+                    .get_data_block_owned(&cbm.block_meta)
                     .await
                     .context(error::ReadTsmSnafu)?;
                 merging_blks.push(CompactingBlock::DataBlock {
@@ -357,7 +357,7 @@ impl CompactIterator {
                             merged_blk_time_range.0.max(cbm.block_meta.max_ts());
                         if cbm.has_tombstone || self.decode_non_overlap_blocks {
                             let data_block = self.tsm_readers[cbm.readers_idx]
-                                .get_data_block(&cbm.block_meta)
+                                .get_data_block_owned(&cbm.block_meta)
                                 .await
                                 .context(error::ReadTsmSnafu)?;
                             merging_blks.push(CompactingBlock::DataBlock {
@@ -379,7 +379,7 @@ impl CompactIterator {
                     } else {
                         // cbm.block_meta.count is less than max_datablock_values
                         let data_block = self.tsm_readers[cbm.readers_idx]
-                            .get_data_block(&cbm.block_meta)
+                            .get_data_block_owned(&cbm.block_meta)
                             .await
                             .context(error::ReadTsmSnafu)?;
                         merging_blks.push(CompactingBlock::DataBlock {
@@ -667,7 +667,7 @@ pub mod test {
         for idx in tsm_reader.index_iterator() {
             let field_id = idx.field_id();
             for blk_meta in idx.block_iterator() {
-                let blk = tsm_reader.get_data_block(&blk_meta).await.unwrap();
+                let blk = tsm_reader.get_data_block_owned(&blk_meta).await.unwrap();
                 data.entry(field_id).or_default().push(blk);
             }
         }
@@ -374,12 +374,15 @@ pub async fn run_flush_memtable_job(
     }
 
     let mut edits: Vec<VersionEdit> = vec![];
+    let mut delete_fences = Vec::with_capacity(tsf_caches.len());
     for (tsf_id, caches) in tsf_caches.into_iter() {
         if caches.is_empty() {
             continue;
         }
         let tsf_warp = version_set.read().await.get_tsfamily_by_tf_id(tsf_id).await;
         if let Some(tsf) = tsf_warp {
+            let delete_fence = tsf.read().delete_fence();
+            delete_fences.push(delete_fence.read_owned().await);
             // todo: build path by vnode data
             let (storage_opt, version, database) = {
                 let tsf_rlock = tsf.read();
@@ -410,7 +413,13 @@ pub async fn run_flush_memtable_job(
 
     if let Err(e) = summary_task_sender.send(task) {
         warn!("failed to send Summary task, {}", e);
+        return Ok(());
     }
+    // Deletes wait until the flushed files are in the version.
+    tokio::spawn(async move {
+        let _ = task_state_receiver.await;
+        drop(delete_fences);
+    });
     Ok(())
 }
 
@@ -4,6 +4,7 @@ use std::{
         atomic::{AtomicU32, AtomicU64, Ordering},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
 use parking_lot::RwLock;
@@ -106,6 +107,7 @@ impl GlobalSequenceContext {
             inner: Arc::new(RwLock::new(GlobalSequenceContextInner {
                 min_seq,
                 tsf_seq_map,
+                wal_holds: HashMap::new(),
             })),
         }
     }
@@ -138,12 +140,59 @@ impl GlobalSequenceContext {
             }
             inner.reset_min_seq();
         }
-        self.min_seq.store(inner.min_seq, Ordering::Release);
+        self.min_seq
+            .store(inner.retained_min_seq(), Ordering::Release);
     }
 
+    /// Minimum sequence number of the WAL that must be retained.
     pub fn min_seq(&self) -> u64 {
         self.min_seq.load(Ordering::Acquire)
     }
+
+    /// Retains the WAL of `TseriesFamily` after `seq` even if it has been flushed,
+    /// until the hold is released or it's not renewed within `lease`.
+    /// A hold of the same `TseriesFamily` is replaced.
+    pub fn hold_wal(&self, tsf_id: TseriesFamilyId, seq: u64, lease: Duration) {
+        let mut inner = self.inner.write();
+        inner.wal_holds.insert(
+            tsf_id,
+            WalHold {
+                seq,
+                deadline: Instant::now() + lease,
+            },
+        );
+        self.min_seq
+            .store(inner.retained_min_seq(), Ordering::Release);
+    }
+
+    pub fn release_wal(&self, tsf_id: TseriesFamilyId) {
+        let mut inner = self.inner.write();
+        if inner.wal_holds.remove(&tsf_id).is_some() {
+            self.min_seq
+                .store(inner.retained_min_seq(), Ordering::Release);
+        }
+    }
+
+    /// Releases the holds whose lease has expired, returns the `TseriesFamily`s of them.
+    pub fn expire_wal_holds(&self) -> Vec<TseriesFamilyId> {
+        let now = Instant::now();
+        let mut inner = self.inner.write();
+        let expired: Vec<TseriesFamilyId> = inner
+            .wal_holds
+            .iter()
+            .filter(|(_, hold)| hold.deadline <= now)
+            .map(|(tsf_id, _)| *tsf_id)
+            .collect();
+        if !expired.is_empty() {
+            for tsf_id in expired.iter() {
+                inner.wal_holds.remove(tsf_id);
+            }
+            self.min_seq
+                .store(inner.retained_min_seq(), Ordering::Release);
+        }
+
+        expired
+    }
 }
 
 #[cfg(test)]
@@ -154,6 +203,7 @@ impl GlobalSequenceContext {
             inner: Arc::new(RwLock::new(GlobalSequenceContextInner {
                 min_seq: 0,
                 tsf_seq_map: HashMap::new(),
+                wal_holds: HashMap::new(),
             })),
         })
     }
@@ -171,6 +221,15 @@ struct GlobalSequenceContextInner {
     min_seq: u64,
     /// Maps `TseriesFamily`-ID to it's last sequence number flushed to disk.
     tsf_seq_map: HashMap<TseriesFamilyId, u64>,
+    /// Maps `TseriesFamily`-ID to the sequence number after which the WAL is retained,
+    /// e.g. the WAL of a vnode being copied to another node.
+    wal_holds: HashMap<TseriesFamilyId, WalHold>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WalHold {
+    seq: u64,
+    deadline: Instant,
 }
 
 impl GlobalSequenceContextInner {
@@ -186,6 +245,12 @@ impl GlobalSequenceContextInner {
             self.min_seq = min_seq;
         }
     }
+
+    fn retained_min_seq(&self) -> u64 {
+        self.wal_holds
+            .values()
+            .fold(self.min_seq, |min_seq, hold| min_seq.min(hold.seq))
+    }
 }
 
 pub struct GlobalSequenceTask {
@@ -209,6 +274,7 @@ pub(crate) fn run_global_context_job(
 #[cfg(test)]
 mod test_context {
     use std::collections::{HashMap, HashSet};
+    use std::time::Duration;
 
     use super::GlobalSequenceContext;
 
@@ -228,5 +294,21 @@ mod test_context {
         // Delete tsfamily and add tsfamily
         ctx.next_stage(HashSet::from([3]), HashMap::from([(4, 6), (5, 8), (6, 10)]));
         assert_eq!(ctx.min_seq(), 6);
+
+        // The WAL of a held tsfamily is retained after it's flushed
+        ctx.hold_wal(5, 3, Duration::from_secs(60));
+        assert_eq!(ctx.min_seq(), 3);
+        ctx.next_stage(HashSet::from([4]), HashMap::from([(5, 12), (6, 12)]));
+        assert_eq!(ctx.min_seq(), 3);
+        ctx.release_wal(5);
+        assert_eq!(ctx.min_seq(), 12);
+
+        // The hold is released once its lease expires
+        ctx.hold_wal(6, 4, Duration::from_secs(60));
+        ctx.hold_wal(5, 3, Duration::ZERO);
+        assert_eq!(ctx.min_seq(), 3);
+        assert_eq!(ctx.expire_wal_holds(), vec![5]);
+        assert_eq!(ctx.min_seq(), 4);
+        assert!(ctx.expire_wal_holds().is_empty());
     }
 }
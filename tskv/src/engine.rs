@@ -5,7 +5,8 @@ use crate::kv_option::StorageOptions;
 use crate::summary::VersionEdit;
 use crate::tseries_family::SuperVersion;
 use crate::tsm::DataBlock;
-use crate::{Options, TimeRange, TsKv, TseriesFamilyId};
+use crate::wal::{VnodeWalEntry, WalCursor, WalDeleteSeries, WalEntryType};
+use crate::{Options, TableHashTreeNode, TimeRange, TsKv, TseriesFamilyId};
 use async_trait::async_trait;
 use datafusion::prelude::Column;
 use models::codec::Encoding;
@@ -19,11 +20,22 @@ use protos::{
 };
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
+use std::path::PathBuf;
 use std::sync::Arc;
 use trace::{debug, info};
 
 pub type EngineRef = Arc<dyn Engine>;
 
+/// A flushed vnode, its files and the WAL written after it are retained
+/// until the snapshot is released, so that the vnode can be copied to other nodes.
+#[derive(Debug)]
+pub struct VnodeSnapshot {
+    /// The writes after the sequence may be not in the files.
+    pub seq: u64,
+    pub version_edit: VersionEdit,
+    pub files: Vec<PathBuf>,
+}
+
 #[async_trait]
 pub trait Engine: Send + Sync + Debug {
     async fn write(
@@ -76,11 +88,20 @@ pub trait Engine: Send + Sync + Debug {
         &self,
         tenant: &str,
         database: &str,
+        vnode_id: TseriesFamilyId,
         series_ids: &[SeriesId],
         field_ids: &[ColumnId],
         time_range: &TimeRange,
     ) -> Result<()>;
 
+    /// Replays a delete of the WAL, the WAL is not written again.
+    async fn delete_series_from_wal(
+        &self,
+        tenant: &str,
+        vnode_id: TseriesFamilyId,
+        delete: &WalDeleteSeries,
+    ) -> Result<()>;
+
     async fn get_table_schema(
         &self,
         tenant: &str,
@@ -130,6 +151,52 @@ pub trait Engine: Send + Sync + Debug {
 
     async fn drop_vnode(&self, id: TseriesFamilyId) -> Result<()>;
 
+    /// Flushes the vnode, the files and the WAL of the snapshot are retained until it's
+    /// released, or until its WAL is not read for a while.
+    async fn snapshot_vnode(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: TseriesFamilyId,
+    ) -> Result<Option<VnodeSnapshot>>;
+
+    fn release_vnode_snapshot(&self, vnode_id: TseriesFamilyId);
+
+    /// Holds the writes and the deletes of the vnode until it's released, except the entries
+    /// copied by `copy_wal_entry`, so that the entries copied from another vnode are not
+    /// raced by the newer writes and deletes.
+    async fn hold_vnode(&self, vnode_id: TseriesFamilyId);
+
+    fn release_vnode(&self, vnode_id: TseriesFamilyId);
+
+    /// Writes the write or the delete copied from the WAL of another vnode to the vnode.
+    async fn copy_wal_entry(
+        &self,
+        tenant: &str,
+        vnode_id: TseriesFamilyId,
+        typ: WalEntryType,
+        data: Vec<u8>,
+    ) -> Result<()>;
+
+    /// Returns the writes and the deletes of the vnode after `min_seq` in the WAL, read from
+    /// `cursor` if it's given, at most about `max_size` bytes. The WAL of the vnode after
+    /// `min_seq` is retained until the snapshot is released or it's not read for a while.
+    async fn get_vnode_wal(
+        &self,
+        vnode_id: TseriesFamilyId,
+        min_seq: u64,
+        cursor: Option<WalCursor>,
+        max_size: usize,
+    ) -> Result<Vec<VnodeWalEntry>>;
+
+    /// Flushes the vnode and returns the hashes of the data by (table, column, time range).
+    async fn get_vnode_hash_tree(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: TseriesFamilyId,
+    ) -> Result<Vec<TableHashTreeNode>>;
+
     async fn compact(&self, tenant: &str, database: &str);
 }
 
@@ -210,6 +277,7 @@ impl Engine for MockEngine {
         &self,
         tenant: &str,
         database: &str,
+        vnode_id: TseriesFamilyId,
         series_ids: &[SeriesId],
         field_ids: &[ColumnId],
         time_range: &TimeRange,
@@ -217,6 +285,15 @@ impl Engine for MockEngine {
         todo!()
     }
 
+    async fn delete_series_from_wal(
+        &self,
+        tenant: &str,
+        vnode_id: TseriesFamilyId,
+        delete: &WalDeleteSeries,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     async fn get_table_schema(
         &self,
         tenant: &str,
@@ -324,6 +401,50 @@ impl Engine for MockEngine {
         todo!()
     }
 
+    async fn snapshot_vnode(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: TseriesFamilyId,
+    ) -> Result<Option<VnodeSnapshot>> {
+        Ok(None)
+    }
+
+    fn release_vnode_snapshot(&self, vnode_id: TseriesFamilyId) {}
+
+    async fn hold_vnode(&self, vnode_id: TseriesFamilyId) {}
+
+    fn release_vnode(&self, vnode_id: TseriesFamilyId) {}
+
+    async fn copy_wal_entry(
+        &self,
+        tenant: &str,
+        vnode_id: TseriesFamilyId,
+        typ: WalEntryType,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_vnode_wal(
+        &self,
+        vnode_id: TseriesFamilyId,
+        min_seq: u64,
+        cursor: Option<WalCursor>,
+        max_size: usize,
+    ) -> Result<Vec<VnodeWalEntry>> {
+        Ok(vec![])
+    }
+
+    async fn get_vnode_hash_tree(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: TseriesFamilyId,
+    ) -> Result<Vec<TableHashTreeNode>> {
+        Ok(vec![])
+    }
+
     async fn compact(&self, tenant: &str, database: &str) {
         todo!()
     }
@@ -96,6 +96,11 @@ pub enum Error {
     #[snafu(display("wal truncated"))]
     WalTruncated,
 
+    #[snafu(display("wal after sequence {} has been deleted", seq))]
+    WalDeleted {
+        seq: u64,
+    },
+
     #[snafu(display("read/write record file block: {}", reason))]
     RecordFileIo {
         reason: String,
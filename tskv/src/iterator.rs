@@ -1,3 +1,4 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::ops::{Bound, RangeBounds};
 use std::task::Poll;
@@ -8,11 +9,9 @@ use datafusion::physical_plan::metrics::{
 };
 
 use datafusion::scalar::ScalarValue;
-use minivec::MiniVec;
 use std::sync::Arc;
 use tokio::time::Instant;
 
-use datafusion::arrow::array::{ArrayBuilder, TimestampNanosecondBuilder};
 use datafusion::arrow::datatypes::DataType as ArrowDataType;
 use models::utils::{min_num, unite_id};
 use models::{FieldId, SeriesId, ValueType};
@@ -25,22 +24,24 @@ use super::{
     error::IndexErrSnafu,
     memcache::DataType,
     tseries_family::{ColumnFile, SuperVersion, TimeRange},
-    tsm::{BlockMetaIterator, DataBlock, TsmReader},
+    tsm::{BlockMeta, BlockStatistics, DataBlock, TsmReader},
     ColumnFileId, Error,
 };
 
 use datafusion::arrow::{
-    array::{BooleanBuilder, Float64Builder, Int64Builder, StringBuilder, UInt64Builder},
+    array::{
+        new_null_array, ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray,
+        TimestampNanosecondArray, UInt64Array,
+    },
+    compute::concat_batches,
     datatypes::SchemaRef,
     record_batch::RecordBatch,
 };
 
 use crate::schema::error::SchemaError;
+use models::predicate::aggregate::PushedAggregateFunction;
 use models::predicate::domain::{ColumnDomains, Domain, PredicateRef, Range, ValueEntry};
-use models::schema::{ColumnType, TskvTableSchema, TIME_FIELD, TIME_FIELD_NAME};
-
-pub type CursorPtr = Box<dyn Cursor>;
-pub type ArrayBuilderPtr = Box<dyn ArrayBuilder>;
+use models::schema::{ColumnType, TableColumn, TskvTableSchema, TIME_FIELD_NAME};
 
 /// Stores metrics about the table writer execution.
 #[derive(Debug)]
@@ -128,19 +129,15 @@ impl TskvSourceMetrics {
     }
 }
 
-// 1. Tsm文件遍历： KeyCursor
-//  功能：根据输入参数遍历Tsm文件
-//  输入参数： SeriesKey、FieldName、StartTime、EndTime、Ascending
-//  功能函数：调用Peek()—>(value, timestamp)得到一个值；调用Next()方法游标移到下一个值。
-// 2. Field遍历： FiledCursor
-//  功能：一个Field特定SeriesKey的遍历
-//  输入输出参数同KeyCursor，区别是需要读取缓存数据，并按照特定顺序返回
-// 3. Fields->行  转换器
-//  一行数据是由同一个时间点的多个Field得到。借助上面的FieldCursor按照时间点对齐多个Field-Value拼接成一行数据。其过程类似于多路归并排序。
-// 4. Iterator接口抽象层
-//  调用Next接口返回一行数据，并且屏蔽查询是本机节点数据还是其他节点数据
-// 5. 行数据到DataFusion的RecordBatch转换器
-//  调用Iterator.Next得到行数据，然后转换行数据为RecordBatch结构
+// 1. Field读取： read_field_blocks
+//  功能：按时间范围列出一个Field特定SeriesKey的数据块
+//  以DataBlock为单位读取Tsm文件，整块落在时间范围外的Block直接跳过，部分重叠的Block按时间范围裁剪；
+//  缓存数据排序去重后作为最新的DataBlock。
+// 2. Series到列  转换器： SeriesCursor
+//  按最小时间戳顺序逐块解码，每次只合并不超过batch_size行（新数据覆盖旧数据），
+//  合并所有Field的时间戳作为时间列，按时间戳对齐各Field生成Arrow数组，时间戳一致时整块拷贝。
+// 3. Iterator接口抽象层
+//  调用Next接口返回batch_size行数据，并且屏蔽查询是本机节点数据还是其他节点数据
 
 #[derive(Debug, Clone)]
 pub struct QueryOption {
@@ -154,6 +151,12 @@ pub struct QueryOption {
     pub time_filter: ColumnDomains<String>,
     pub tags_filter: ColumnDomains<String>,
     pub fields_filter: ColumnDomains<String>,
+
+    /// The aggregate functions pushed down into the scan.
+    pub aggregates: Option<Vec<PushedAggregateFunction>>,
+    /// The encoded plan fragment executed over the scan,
+    /// the `df_schema` is the schema of the columns scanned.
+    pub fragment: Option<Vec<u8>>,
 }
 
 impl QueryOption {
@@ -196,9 +199,24 @@ impl QueryOption {
             time_filter,
             tags_filter,
             fields_filter,
+
+            aggregates: None,
+            fragment: None,
         }
     }
 
+    /// Answers the aggregate functions instead of returning rows,
+    /// the `df_schema` must be the schema of the partial results.
+    pub fn with_aggregates(mut self, aggregates: Option<Vec<PushedAggregateFunction>>) -> Self {
+        self.aggregates = aggregates;
+        self
+    }
+
+    pub fn with_fragment(mut self, fragment: Option<Vec<u8>>) -> Self {
+        self.fragment = fragment;
+        self
+    }
+
     pub fn parse_time_ranges(
         filter: PredicateRef,
         table_schema: TskvTableSchema,
@@ -218,302 +236,6 @@ impl QueryOption {
     }
 }
 
-pub struct FieldFileLocation {
-    reader: TsmReader,
-    block_it: BlockMetaIterator,
-
-    read_index: usize,
-    data_block: DataBlock,
-}
-
-impl FieldFileLocation {
-    pub fn new(reader: TsmReader, block_it: BlockMetaIterator, vtype: ValueType) -> Self {
-        Self {
-            reader,
-            block_it,
-            read_index: 0,
-            data_block: DataBlock::new(0, vtype),
-        }
-    }
-
-    pub async fn peek(&mut self) -> Result<Option<DataType>, Error> {
-        if self.read_index >= self.data_block.len() {
-            if let Some(meta) = self.block_it.next() {
-                self.read_index = 0;
-                self.data_block = self.reader.get_data_block(&meta).await?;
-            } else {
-                return Ok(None);
-            }
-        }
-
-        Ok(self.data_block.get(self.read_index))
-    }
-
-    pub fn next(&mut self) {
-        self.read_index += 1;
-    }
-}
-
-//-----------trait Cursor----------------
-#[async_trait::async_trait]
-pub trait Cursor: Send + Sync {
-    fn name(&self) -> &String;
-    fn is_field(&self) -> bool;
-    fn val_type(&self) -> ValueType;
-
-    async fn next(&mut self, ts: i64);
-    async fn peek(&mut self) -> Result<Option<DataType>, Error>;
-}
-
-//-----------Time Cursor----------------
-pub struct TimeCursor {
-    ts: i64,
-    name: String,
-}
-
-impl TimeCursor {
-    pub fn new(ts: i64, name: String) -> Self {
-        Self { ts, name }
-    }
-}
-
-#[async_trait::async_trait]
-impl Cursor for TimeCursor {
-    fn name(&self) -> &String {
-        &self.name
-    }
-
-    async fn peek(&mut self) -> Result<Option<DataType>, Error> {
-        let data = DataType::I64(self.ts, self.ts);
-
-        Ok(Some(data))
-    }
-
-    async fn next(&mut self, _ts: i64) {}
-
-    fn val_type(&self) -> ValueType {
-        ValueType::Integer
-    }
-
-    fn is_field(&self) -> bool {
-        false
-    }
-}
-//-----------Tag Cursor----------------
-pub struct TagCursor {
-    name: String,
-    value: Option<String>,
-}
-
-impl TagCursor {
-    pub fn new(name: String, value: Option<String>) -> Self {
-        Self { name, value }
-    }
-}
-
-#[async_trait::async_trait]
-impl Cursor for TagCursor {
-    fn name(&self) -> &String {
-        &self.name
-    }
-
-    fn is_field(&self) -> bool {
-        false
-    }
-
-    fn val_type(&self) -> ValueType {
-        ValueType::String
-    }
-
-    async fn next(&mut self, _ts: i64) {}
-
-    async fn peek(&mut self) -> Result<Option<DataType>, Error> {
-        match &self.value {
-            Some(value) => Ok(Some(DataType::Str(0, MiniVec::from(value.as_bytes())))),
-            None => Ok(None),
-        }
-    }
-}
-
-//-----------Field Cursor----------------
-pub struct FieldCursor {
-    name: String,
-    value_type: ValueType,
-
-    cache_index: usize,
-    cache_data: Vec<DataType>,
-    locations: Vec<FieldFileLocation>,
-}
-
-impl FieldCursor {
-    pub fn empty(value_type: ValueType, name: String) -> Self {
-        Self {
-            name,
-            value_type,
-            cache_index: 0,
-            cache_data: Vec::new(),
-            locations: Vec::new(),
-        }
-    }
-
-    pub async fn new(
-        field_id: FieldId,
-        name: String,
-        vtype: ValueType,
-        iterator: &mut RowIterator,
-    ) -> Result<Self, Error> {
-        let version = match iterator.version.clone() {
-            Some(v) => v,
-            None => return Ok(Self::empty(vtype, name)),
-        };
-
-        let time_ranges: Vec<TimeRange> = filter_to_time_ranges(&iterator.option.time_filter);
-
-        debug!("Pushed time range filter: {:?}", time_ranges);
-
-        // get data from im_memcache and memcache
-        let mut mem_data: Vec<DataType> = Vec::new();
-
-        let time_predicate = |ts| {
-            time_ranges
-                .iter()
-                .any(|time_range| time_range.is_boundless() || time_range.contains(ts))
-        };
-
-        version
-            .caches
-            .immut_cache
-            .iter()
-            .filter(|m| !m.read().flushed)
-            .for_each(|m| {
-                mem_data.append(&mut m.read().get_data(field_id, time_predicate, |_| true))
-            });
-
-        mem_data.append(&mut version.caches.mut_cache.read().get_data(
-            field_id,
-            time_predicate,
-            |_| true,
-        ));
-
-        mem_data.sort_by_key(|data| data.timestamp());
-
-        debug!(
-            "build memcache data id: {:02X}, len: {}",
-            field_id,
-            mem_data.len()
-        );
-
-        // get data from levelinfo
-        let mut locations = vec![];
-        for level in version.version.levels_info.iter().rev() {
-            for file in level.files.iter() {
-                if file.is_deleted() {
-                    continue;
-                }
-
-                for time_range in time_ranges.iter() {
-                    if !file.overlap(time_range) {
-                        continue;
-                    }
-
-                    debug!(
-                        "build file data block id: {:02X}, len: {}",
-                        field_id,
-                        file.file_path().display()
-                    );
-
-                    let tsm_reader = iterator.get_tsm_reader(file.clone()).await?;
-                    for idx in tsm_reader.index_iterator_opt(field_id) {
-                        let block_it = idx.block_iterator_opt(time_range);
-                        let location = FieldFileLocation::new(tsm_reader.clone(), block_it, vtype);
-                        locations.push(location);
-                    }
-                }
-            }
-        }
-
-        Ok(Self {
-            name,
-            value_type: vtype,
-            cache_index: 0,
-            cache_data: mem_data,
-            locations,
-        })
-    }
-
-    fn peek_cache(&mut self) -> Option<&DataType> {
-        let mut opt_top = self.cache_data.get(self.cache_index);
-        let mut opt_next = self.cache_data.get(self.cache_index + 1);
-
-        while let (Some(top), Some(next)) = (opt_top, opt_next) {
-            // if timestamp is same, select next data
-            // deduplication
-            if top.timestamp() == next.timestamp() {
-                self.cache_index += 1;
-                opt_top = Some(next);
-                opt_next = self.cache_data.get(self.cache_index + 1);
-            } else {
-                break;
-            }
-        }
-
-        opt_top
-    }
-}
-
-#[async_trait::async_trait]
-impl Cursor for FieldCursor {
-    fn name(&self) -> &String {
-        &self.name
-    }
-
-    async fn peek(&mut self) -> Result<Option<DataType>, Error> {
-        let mut data = DataType::new(self.value_type, i64::MAX);
-        for loc in self.locations.iter_mut() {
-            if let Some(val) = loc.peek().await? {
-                if data.timestamp() >= val.timestamp() {
-                    data = val;
-                }
-            }
-        }
-
-        if let Some(val) = self.peek_cache() {
-            if data.timestamp() >= val.timestamp() {
-                data = val.clone();
-            }
-        }
-
-        if data.timestamp() == i64::MAX {
-            return Ok(None);
-        }
-        Ok(Some(data))
-    }
-
-    async fn next(&mut self, ts: i64) {
-        if let Some(val) = self.peek_cache() {
-            if val.timestamp() == ts {
-                self.cache_index += 1;
-            }
-        }
-
-        for loc in self.locations.iter_mut() {
-            if let Some(val) = loc.peek().await.unwrap() {
-                if val.timestamp() == ts {
-                    loc.next();
-                }
-            }
-        }
-    }
-
-    fn val_type(&self) -> ValueType {
-        self.value_type
-    }
-
-    fn is_field(&self) -> bool {
-        true
-    }
-}
-
 pub fn filter_to_time_ranges(time_domain: &ColumnDomains<String>) -> Vec<TimeRange> {
     if time_domain.is_none() {
         // Does not contain any data, and returns an empty array directly
@@ -586,10 +308,14 @@ pub struct RowIterator {
     series: Vec<u32>,
     engine: EngineRef,
     option: QueryOption,
-    columns: Vec<CursorPtr>,
+    time_ranges: Vec<TimeRange>,
     version: Option<Arc<SuperVersion>>,
 
     open_files: HashMap<ColumnFileId, TsmReader>,
+    /// The series being read, its rows are returned batch by batch.
+    series_cursor: Option<SeriesCursor>,
+    /// The record batch of the current series and the offset of rows not yet returned.
+    series_batch: Option<(RecordBatch, usize)>,
 
     batch_size: usize,
     vnode_id: u32,
@@ -615,18 +341,23 @@ impl RowIterator {
 
         info!("vnode_id: {}, series number: {}", vnode_id, series.len());
 
+        let time_ranges = filter_to_time_ranges(&option.time_filter);
+        debug!("Pushed time range filter: {:?}", time_ranges);
+
         let metrics = option.metrics.clone();
         let batch_size = option.batch_size;
         Ok(Self {
             series,
             engine,
             option,
+            time_ranges,
             version,
             vnode_id,
 
-            columns: vec![],
             series_index: usize::MAX,
             open_files: HashMap::new(),
+            series_cursor: None,
+            series_batch: None,
 
             batch_size,
             metrics,
@@ -644,10 +375,130 @@ impl RowIterator {
         Ok(tsm_reader)
     }
 
-    async fn build_series_columns(&mut self, id: SeriesId) -> Result<(), Error> {
+    /// Reads the blocks of the field in the pushed time ranges, from the oldest to the newest.
+    ///
+    /// The tsm blocks entirely in the time ranges and without tombstones are not decoded,
+    /// the others are decoded and trimmed to the time ranges.
+    ///
+    /// If the domain of the field is given and the blocks do not overlap each other,
+    /// the tsm blocks whose statistics do not satisfy the domain are pruned.
+    async fn read_field_blocks(
+        &mut self,
+        field_id: FieldId,
+        vtype: ValueType,
+        domain: Option<&Domain>,
+    ) -> Result<Vec<FieldBlock>, Error> {
+        let version = match self.version.clone() {
+            Some(v) => v,
+            None => return Ok(vec![]),
+        };
+        let time_ranges = self.time_ranges.clone();
+
+        // get data blocks from levelinfo, from the oldest to the newest
+        let mut blocks = vec![];
+        for level in version.version.levels_info.iter().rev() {
+            for file in level.files.iter() {
+                if file.is_deleted() {
+                    continue;
+                }
+
+                for time_range in time_ranges.iter() {
+                    if !file.overlap(time_range) {
+                        continue;
+                    }
+
+                    debug!(
+                        "read file data block id: {:02X}, file: {}",
+                        field_id,
+                        file.file_path().display()
+                    );
+
+                    let tsm_reader = self.get_tsm_reader(file.clone()).await?;
+                    for idx in tsm_reader.index_iterator_opt(field_id) {
+                        for meta in idx.block_iterator_opt(time_range) {
+                            let block_range = TimeRange::new(meta.min_ts(), meta.max_ts());
+                            if time_range.includes(&block_range)
+                                && tsm_reader.get_block_tombstone_time_ranges(&meta).is_none()
+                            {
+                                blocks.push(FieldBlock::Tsm {
+                                    reader: tsm_reader.clone(),
+                                    meta,
+                                });
+                                continue;
+                            }
+
+                            let mut block = tsm_reader.get_data_block_owned(&meta).await?;
+                            block.retain_time_range(time_range);
+                            if !block.is_empty() {
+                                blocks.push(FieldBlock::Data(block));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // get data from im_memcache and memcache
+        let time_predicate = |ts| {
+            time_ranges
+                .iter()
+                .any(|time_range| time_range.is_boundless() || time_range.contains(ts))
+        };
+
+        let mut mem_data: Vec<DataType> = Vec::new();
+        version
+            .caches
+            .immut_cache
+            .iter()
+            .filter(|m| !m.read().flushed)
+            .for_each(|m| {
+                mem_data.append(&mut m.read().get_data(field_id, time_predicate, |_| true))
+            });
+
+        mem_data.append(&mut version.caches.mut_cache.read().get_data(
+            field_id,
+            time_predicate,
+            |_| true,
+        ));
+
+        debug!(
+            "read memcache data id: {:02X}, len: {}",
+            field_id,
+            mem_data.len()
+        );
+
+        if !mem_data.is_empty() {
+            // stable sort, the later data of the same timestamp is newer
+            mem_data.sort_by_key(|data| data.timestamp());
+            let mut block = DataBlock::new(mem_data.len(), vtype);
+            let mut iter = mem_data.into_iter().peekable();
+            while let Some(data) = iter.next() {
+                if let Some(next) = iter.peek() {
+                    if next.timestamp() == data.timestamp() {
+                        continue;
+                    }
+                }
+                block.insert(data);
+            }
+            blocks.push(FieldBlock::Data(block));
+        }
+
+        // a pruned block may override the data of the same timestamp in other blocks
+        if let Some(domain) = domain {
+            if !is_overlapped(blocks.iter().filter_map(|b| b.time_range())) {
+                blocks.retain(|b| b.may_match(domain));
+            }
+        }
+
+        Ok(blocks)
+    }
+
+    /// Lists the blocks of all fields of the series, returns `None` if the series
+    /// does not exist. The blocks are decoded while the rows are returned.
+    async fn open_series(&mut self, id: SeriesId) -> Result<Option<SeriesCursor>, Error> {
         let start = Instant::now();
 
-        if let Some(key) = self
+        let key = match self
             .engine
             .get_series_key(
                 &self.option.tenant,
@@ -658,240 +509,374 @@ impl RowIterator {
             .await
             .context(IndexErrSnafu)?
         {
-            self.columns.clear();
-            let fields = self.option.table_schema.columns().to_vec();
-            for item in fields {
-                let field_name = item.name.clone();
-                debug!("build series columns id:{:02X}, {:?}", id, item);
-                let column: CursorPtr = match item.column_type {
-                    ColumnType::Time => Box::new(TimeCursor::new(0, field_name)),
-
-                    ColumnType::Tag => {
-                        let tag_val = match key.tag_val(&item.name) {
-                            Some(val) => {
-                                Some(String::from_utf8(val).map_err(|_| Error::ErrCharacterSet)?)
-                            }
-                            None => None,
-                        };
-
-                        Box::new(TagCursor::new(field_name, tag_val))
-                    }
+            Some(key) => key,
+            None => return Ok(None),
+        };
 
-                    ColumnType::Field(vtype) => match vtype {
-                        ValueType::Unknown => todo!(),
-                        _ => {
-                            let cursor =
-                                FieldCursor::new(unite_id(item.id, id), field_name, vtype, self)
-                                    .await?;
-                            Box::new(cursor)
-                        }
-                    },
-                };
+        let columns = self.option.table_schema.columns().to_vec();
+        let mut tags = Vec::with_capacity(columns.len());
+        let mut fields = Vec::with_capacity(columns.len());
+        for item in columns.iter() {
+            let tag_val = match item.column_type {
+                ColumnType::Tag => match key.tag_val(&item.name) {
+                    Some(val) => Some(String::from_utf8(val).map_err(|_| Error::ErrCharacterSet)?),
+                    None => None,
+                },
+                _ => None,
+            };
+            tags.push(tag_val);
 
-                self.columns.push(column);
-            }
+            let field = match item.column_type {
+                ColumnType::Field(ValueType::Unknown) => {
+                    return Err(Error::CommonError {
+                        reason: format!("unknown type of {}", item.name),
+                    });
+                }
+                ColumnType::Field(vtype) => {
+                    debug!("read series field id:{:02X}, {:?}", id, item);
+                    let domain = self
+                        .option
+                        .fields_filter
+                        .domains()
+                        .and_then(|domains| domains.get(&item.name))
+                        .cloned();
+                    let blocks = self
+                        .read_field_blocks(unite_id(item.id, id), vtype, domain.as_ref())
+                        .await?;
+                    Some(FieldCursor::new(blocks))
+                }
+                _ => None,
+            };
+            fields.push(field);
         }
 
         self.metrics
             .elapsed_series_scan()
             .add_duration(Instant::now() - start);
 
-        Ok(())
+        Ok(Some(SeriesCursor { tags, fields }))
     }
 
-    async fn next_series(&mut self) -> Result<Option<()>, Error> {
-        if self.series_index == usize::MAX {
-            self.series_index = 0;
-        } else {
-            self.series_index += 1;
-        }
+    /// Converts the next rows of the series into a `RecordBatch` of at most `batch_size`
+    /// rows column by column, returns `None` if all rows of the series are returned.
+    async fn next_series_batch(
+        &self,
+        series: &mut SeriesCursor,
+    ) -> Result<Option<RecordBatch>, Error> {
+        let timer = self.metrics.elapsed_field_scan().timer();
+        let field_blocks = series.next_blocks(self.batch_size).await?;
+        let time_index = union_timestamps(&field_blocks);
+        timer.done();
 
-        if self.series_index >= self.series.len() {
+        if time_index.is_empty() {
             return Ok(None);
         }
 
-        self.build_series_columns(self.series[self.series_index])
-            .await?;
+        let _timer = self.metrics.elapsed_point_to_record_batch().timer();
+        let columns = self.option.table_schema.columns();
+        let mut arrays: Vec<ArrayRef> = Vec::with_capacity(columns.len());
+        for ((item, block), tag_val) in columns
+            .iter()
+            .zip(field_blocks.iter())
+            .zip(series.tags.iter())
+        {
+            let array: ArrayRef = match item.column_type {
+                ColumnType::Time => Arc::new(TimestampNanosecondArray::from(time_index.clone())),
+                ColumnType::Tag => Arc::new(StringArray::from(vec![
+                    tag_val.as_deref();
+                    time_index.len()
+                ])),
+                ColumnType::Field(vtype) => field_array(block.as_ref(), vtype, &time_index)?,
+            };
+            arrays.push(array);
+        }
 
-        Ok(Some(()))
+        RecordBatch::try_new(self.option.df_schema.clone(), arrays)
+            .map(Some)
+            .map_err(|err| Error::CommonError {
+                reason: format!("iterator fail, {}", err),
+            })
     }
 
-    async fn collect_row_data(
+    /// Answers the pushed down aggregate functions of all series with one row of
+    /// partial results, or with one row per series if the tags are pushed down.
+    ///
+    /// If the blocks of a field do not overlap each other, count, min, max and sum are
+    /// answered by the meta of the tsm blocks entirely in the time ranges and without
+    /// tombstones, these blocks are not decoded.
+    async fn aggregate(
         &mut self,
-        builder: &mut [ArrayBuilderPtr],
-    ) -> Result<Option<()>, Error> {
-        debug!("======collect_row_data=========");
-        let timer = self.metrics.elapsed_field_scan().timer();
+        aggregates: &[PushedAggregateFunction],
+    ) -> Result<RecordBatch, Error> {
+        let _timer = self.metrics.elapsed_field_scan().timer();
 
-        let mut min_time = i64::MAX;
-        let mut values = Vec::with_capacity(self.columns.len());
-        for column in self.columns.iter_mut() {
-            let val = column.peek().await?;
-            if let Some(ref data) = val {
-                if column.is_field() {
-                    min_time = min_num(min_time, data.timestamp());
+        let table_schema = self.option.table_schema.clone();
+        let fields = table_schema
+            .columns()
+            .iter()
+            .filter(|c| c.column_type.is_field())
+            .cloned()
+            .collect::<Vec<_>>();
+        // count the rows by the only field if possible
+        let aggregates = aggregates
+            .iter()
+            .map(|func| match func {
+                PushedAggregateFunction::Count(None) if fields.len() == 1 => {
+                    PushedAggregateFunction::Count(Some(fields[0].name.clone()))
+                }
+                _ => func.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut columns = vec![];
+        for func in aggregates.iter() {
+            let column =
+                match func.column() {
+                    Some(name) => Some(table_schema.column(name).cloned().ok_or_else(|| {
+                        Error::CommonError {
+                            reason: format!("column {} not found", name),
+                        }
+                    })?),
+                    None => None,
+                };
+            if let Some(column) = column.as_ref() {
+                if !columns.contains(column) {
+                    columns.push(column.clone());
                 }
             }
-            values.push(val)
         }
 
-        for (column, value) in self.columns.iter_mut().zip(values.iter_mut()) {
-            debug!("field: {} value {:?}", column.name(), value);
-            if !column.is_field() {
-                continue;
+        let per_series = aggregates
+            .iter()
+            .any(|func| matches!(func, PushedAggregateFunction::Tag(_)));
+        let new_states = || {
+            aggregates
+                .iter()
+                .map(AggregateState::new)
+                .collect::<Vec<_>>()
+        };
+
+        let mut rows = vec![];
+        let mut states = new_states();
+        for sid in self.series.clone() {
+            if per_series {
+                let key = match self
+                    .engine
+                    .get_series_key(
+                        &self.option.tenant,
+                        &self.option.table_schema.db,
+                        self.vnode_id,
+                        sid,
+                    )
+                    .await
+                    .context(IndexErrSnafu)?
+                {
+                    Some(key) => key,
+                    None => continue,
+                };
+                for (func, state) in aggregates.iter().zip(states.iter_mut()) {
+                    if let PushedAggregateFunction::Tag(tag) = func {
+                        let tag_val = key
+                            .tag_val(tag)
+                            .map(|v| String::from_utf8(v).map_err(|_| Error::ErrCharacterSet))
+                            .transpose()?;
+                        *state = AggregateState::Tag(tag_val);
+                    }
+                }
             }
 
-            if let Some(data) = value {
-                let ts = data.timestamp();
-                if ts == min_time {
-                    column.next(ts).await;
-                } else {
-                    *value = None;
+            self.aggregate_series(sid, &aggregates, &fields, &columns, &mut states)
+                .await?;
+
+            if per_series {
+                let series_states = std::mem::replace(&mut states, new_states());
+                // skip the series without data
+                if series_states.iter().any(|s| s.has_value()) {
+                    rows.push(series_states);
                 }
             }
         }
+        if !per_series {
+            rows.push(states);
+        }
 
-        timer.done();
+        let schema = self.option.df_schema.clone();
+        if rows.is_empty() {
+            return Ok(RecordBatch::new_empty(schema));
+        }
 
-        debug!("min time {}", min_time);
-        if min_time == i64::MAX {
-            self.columns.clear();
-            return Ok(None);
+        let mut columns: Vec<Vec<ScalarValue>> =
+            vec![Vec::with_capacity(rows.len()); schema.fields().len()];
+        for states in rows {
+            let values = states
+                .into_iter()
+                .flat_map(|s| s.evaluate())
+                .collect::<Vec<_>>();
+            if values.len() != columns.len() {
+                return Err(Error::CommonError {
+                    reason: "schema of pushed aggregate functions mismatch".to_string(),
+                });
+            }
+            for ((value, column), field) in values
+                .into_iter()
+                .zip(columns.iter_mut())
+                .zip(schema.fields())
+            {
+                let value = match value {
+                    Some(value) => value,
+                    None => ScalarValue::try_from(field.data_type()).map_err(|err| {
+                        Error::CommonError {
+                            reason: err.to_string(),
+                        }
+                    })?,
+                };
+                column.push(value);
+            }
         }
 
-        let timer = self.metrics.elapsed_point_to_record_batch().timer();
+        let arrays = columns
+            .into_iter()
+            .map(ScalarValue::iter_to_array)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| Error::CommonError {
+                reason: err.to_string(),
+            })?;
 
-        for (i, value) in values.into_iter().enumerate() {
-            if self.columns[i].name() == TIME_FIELD {
-                let field_builder = builder[i]
-                    .as_any_mut()
-                    .downcast_mut::<TimestampNanosecondBuilder>()
-                    .unwrap();
-                field_builder.append_value(min_time);
+        RecordBatch::try_new(schema, arrays).map_err(|err| Error::CommonError {
+            reason: format!("iterator fail, {}", err),
+        })
+    }
 
-                continue;
+    /// Updates the states of the aggregate functions with the data of the series.
+    async fn aggregate_series(
+        &mut self,
+        sid: SeriesId,
+        aggregates: &[PushedAggregateFunction],
+        fields: &[TableColumn],
+        columns: &[TableColumn],
+        states: &mut [AggregateState],
+    ) -> Result<(), Error> {
+        if aggregates.contains(&PushedAggregateFunction::Count(None)) {
+            let mut field_cursors = Vec::with_capacity(fields.len());
+            for field in fields.iter() {
+                let vtype = field_value_type(field);
+                let blocks = self
+                    .read_field_blocks(unite_id(field.id, sid), vtype, None)
+                    .await?;
+                field_cursors.push(Some(FieldCursor::new(blocks)));
             }
-
-            match self.columns[i].val_type() {
-                ValueType::Unknown => {
-                    return Err(Error::CommonError {
-                        reason: format!("unknown type of {}", self.columns[i].name()),
-                    });
-                }
-                ValueType::Float => {
-                    let field_builder = builder[i]
-                        .as_any_mut()
-                        .downcast_mut::<Float64Builder>()
-                        .unwrap();
-                    if let Some(DataType::F64(_, val)) = value {
-                        field_builder.append_value(val);
-                    } else {
-                        field_builder.append_null();
-                    }
+            let mut series = SeriesCursor {
+                tags: vec![],
+                fields: field_cursors,
+            };
+            let mut rows = 0;
+            loop {
+                let num_rows = union_timestamps(&series.next_blocks(self.batch_size).await?).len();
+                if num_rows == 0 {
+                    break;
                 }
-                ValueType::Integer => {
-                    let field_builder = builder[i]
-                        .as_any_mut()
-                        .downcast_mut::<Int64Builder>()
-                        .unwrap();
-                    if let Some(DataType::I64(_, val)) = value {
-                        field_builder.append_value(val);
-                    } else {
-                        field_builder.append_null();
-                    }
+                rows += num_rows as i64;
+            }
+            for (func, state) in aggregates.iter().zip(states.iter_mut()) {
+                if func == &PushedAggregateFunction::Count(None) {
+                    state.update_count(rows);
                 }
-                ValueType::Unsigned => {
-                    let field_builder = builder[i]
-                        .as_any_mut()
-                        .downcast_mut::<UInt64Builder>()
-                        .unwrap();
-                    if let Some(DataType::U64(_, val)) = value {
-                        field_builder.append_value(val);
-                    } else {
-                        field_builder.append_null();
+            }
+        }
+
+        for column in columns.iter() {
+            let vtype = field_value_type(column);
+            let mut blocks = self
+                .read_field_blocks(unite_id(column.id, sid), vtype, None)
+                .await?;
+
+            let column_states = aggregates
+                .iter()
+                .zip(states.iter_mut())
+                .filter(|(func, _)| func.column() == Some(column.name.as_str()))
+                .map(|(_, state)| state)
+                .collect::<Vec<_>>();
+
+            // first and last only read the blocks with the minimum or the maximum timestamp
+            let selectors_only = column_states
+                .iter()
+                .all(|state| matches!(state, AggregateState::First(_) | AggregateState::Last(_)));
+            if selectors_only {
+                let has_first = column_states
+                    .iter()
+                    .any(|state| matches!(state, AggregateState::First(_)));
+                let has_last = column_states
+                    .iter()
+                    .any(|state| matches!(state, AggregateState::Last(_)));
+                blocks = select_edge_blocks(blocks, has_first, has_last);
+            }
+
+            if is_overlapped(blocks.iter().filter_map(|b| b.time_range())) {
+                let mut series = SeriesCursor {
+                    tags: vec![],
+                    fields: vec![Some(FieldCursor::new(blocks))],
+                };
+                while let Some(block) = series.next_blocks(self.batch_size).await?.pop().flatten() {
+                    for state in column_states.iter_mut() {
+                        state.update(&block)?;
                     }
                 }
-                ValueType::Boolean => {
-                    let field_builder = builder[i]
-                        .as_any_mut()
-                        .downcast_mut::<BooleanBuilder>()
-                        .unwrap();
-                    if let Some(DataType::Bool(_, val)) = value {
-                        field_builder.append_value(val);
-                    } else {
-                        field_builder.append_null();
+                continue;
+            }
+
+            // no block is overridden by others, the tsm blocks are answered by the meta
+            // if possible and decoded only if any state needs the data
+            for block in blocks {
+                match block {
+                    FieldBlock::Tsm { reader, meta } => {
+                        let mut data = None;
+                        for state in column_states.iter_mut() {
+                            if state.update_by_meta(&meta) {
+                                continue;
+                            }
+                            if data.is_none() {
+                                data = Some(reader.get_data_block(&meta).await?);
+                            }
+                            if let Some(data) = data.as_ref() {
+                                state.update(data)?;
+                            }
+                        }
                     }
-                }
-                ValueType::String => {
-                    let field_builder = builder[i]
-                        .as_any_mut()
-                        .downcast_mut::<StringBuilder>()
-                        .unwrap();
-                    if let Some(DataType::Str(_, val)) = value {
-                        field_builder.append_value(
-                            String::from_utf8(val.to_vec()).map_err(|_| Error::ErrCharacterSet)?,
-                        );
-                    } else {
-                        field_builder.append_null();
+                    FieldBlock::Data(data) => {
+                        for state in column_states.iter_mut() {
+                            state.update(&data)?;
+                        }
                     }
                 }
             }
         }
 
-        timer.done();
-
-        Ok(Some(()))
+        Ok(())
     }
 
-    async fn next_row(&mut self, builder: &mut [ArrayBuilderPtr]) -> Result<Option<()>, Error> {
+    /// Returns the next rows of the current series, moves to the next series if all
+    /// rows of the current one are returned.
+    async fn next_series(&mut self) -> Result<Option<RecordBatch>, Error> {
         loop {
-            if self.columns.is_empty() && self.next_series().await?.is_none() {
-                return Ok(None);
+            if let Some(mut series) = self.series_cursor.take() {
+                if let Some(batch) = self.next_series_batch(&mut series).await? {
+                    self.series_cursor = Some(series);
+                    return Ok(Some(batch));
+                }
             }
 
-            if self.collect_row_data(builder).await?.is_some() {
-                return Ok(Some(()));
+            if self.series_index == usize::MAX {
+                self.series_index = 0;
+            } else {
+                self.series_index += 1;
             }
-        }
-    }
-
-    fn record_builder(&self) -> Vec<ArrayBuilderPtr> {
-        let mut builders: Vec<ArrayBuilderPtr> =
-            Vec::with_capacity(self.option.table_schema.columns().len());
-        for item in self.option.table_schema.columns().iter() {
-            debug!("schema info {:02X} {}", item.id, item.name);
 
-            match item.column_type {
-                ColumnType::Tag => builders.push(Box::new(StringBuilder::with_capacity(
-                    self.batch_size,
-                    self.batch_size * 32,
-                ))),
-                ColumnType::Time => builders.push(Box::new(
-                    TimestampNanosecondBuilder::with_capacity(self.batch_size),
-                )),
-                ColumnType::Field(t) => match t {
-                    ValueType::Unknown => todo!(),
-                    ValueType::Float => {
-                        builders.push(Box::new(Float64Builder::with_capacity(self.batch_size)))
-                    }
-                    ValueType::Integer => {
-                        builders.push(Box::new(Int64Builder::with_capacity(self.batch_size)))
-                    }
-                    ValueType::Unsigned => {
-                        builders.push(Box::new(UInt64Builder::with_capacity(self.batch_size)))
-                    }
-                    ValueType::Boolean => {
-                        builders.push(Box::new(BooleanBuilder::with_capacity(self.batch_size)))
-                    }
-                    ValueType::String => builders.push(Box::new(StringBuilder::with_capacity(
-                        self.batch_size,
-                        self.batch_size * 32,
-                    ))),
-                },
+            if self.series_index >= self.series.len() {
+                return Ok(None);
             }
-        }
 
-        builders
+            self.series_cursor = self.open_series(self.series[self.series_index]).await?;
+        }
     }
 
     fn is_finish(&self) -> bool {
@@ -899,7 +884,7 @@ impl RowIterator {
             return false;
         }
 
-        self.series_index >= self.series.len()
+        self.series_batch.is_none() && self.series_index >= self.series.len()
     }
 }
 
@@ -909,34 +894,858 @@ impl RowIterator {
             return None;
         }
 
-        let timer = self.metrics.elapsed_point_to_record_batch().timer();
-        let mut builder = self.record_builder();
-        timer.done();
+        if let Some(aggregates) = self.option.aggregates.clone() {
+            // all series are answered in one row
+            self.series_index = self.series.len();
+            return Some(self.aggregate(&aggregates).await);
+        }
 
-        for _ in 0..self.batch_size {
-            match self.next_row(&mut builder).await {
-                Ok(Some(_)) => {}
-                Ok(None) => break,
-                Err(err) => return Some(Err(err)),
+        let mut batches = vec![];
+        let mut num_rows = 0;
+        while num_rows < self.batch_size {
+            let (batch, offset) = match self.series_batch.take() {
+                Some(val) => val,
+                None => match self.next_series().await {
+                    Ok(Some(batch)) => (batch, 0),
+                    Ok(None) => break,
+                    Err(err) => return Some(Err(err)),
+                },
             };
+
+            let len = min_num(self.batch_size - num_rows, batch.num_rows() - offset);
+            batches.push(batch.slice(offset, len));
+            num_rows += len;
+            if offset + len < batch.num_rows() {
+                self.series_batch = Some((batch, offset + len));
+            }
+        }
+
+        if batches.is_empty() {
+            return None;
+        }
+
+        if batches.len() == 1 {
+            return batches.pop().map(Ok);
+        }
+
+        let _timer = self.metrics.elapsed_point_to_record_batch().timer();
+        match concat_batches(&self.option.df_schema, &batches) {
+            Ok(batch) => Some(Ok(batch)),
+            Err(err) => Some(Err(Error::CommonError {
+                reason: format!("iterator fail, {}", err),
+            })),
+        }
+    }
+}
+
+/// Merges `DataBlock`s sorted by timestamp into one, if many (timestamp, value) conflict
+/// with the same timestamp, use the value of the last `DataBlock`.
+fn merge_data_blocks(mut blocks: Vec<DataBlock>) -> Option<DataBlock> {
+    if blocks.len() <= 1 {
+        return blocks.pop();
+    }
+
+    // If no blocks overlap, they can be simply concatenated.
+    if is_overlapped(blocks.iter().filter_map(|b| b.time_range())) {
+        return DataBlock::merge_blocks(blocks, 0).pop();
+    }
+
+    blocks.sort_by_key(|b| b.time_range());
+    let mut iter = blocks.into_iter();
+    let mut merged = iter.next()?;
+    for block in iter {
+        merged.append(block);
+    }
+
+    Some(merged)
+}
+
+/// Returns true if any two of the time ranges overlap.
+fn is_overlapped(ranges: impl Iterator<Item = (i64, i64)>) -> bool {
+    let mut ranges = ranges.collect::<Vec<_>>();
+    ranges.sort_unstable();
+    ranges.windows(2).any(|pair| pair[0].1 >= pair[1].0)
+}
+
+fn field_value_type(column: &TableColumn) -> ValueType {
+    match column.column_type {
+        ColumnType::Field(vtype) => vtype,
+        _ => ValueType::Unknown,
+    }
+}
+
+/// A block of field data, which may not be decoded yet.
+enum FieldBlock {
+    /// A tsm block entirely in the pushed time ranges and without tombstones.
+    Tsm {
+        reader: TsmReader,
+        meta: BlockMeta,
+    },
+    Data(DataBlock),
+}
+
+impl FieldBlock {
+    fn time_range(&self) -> Option<(i64, i64)> {
+        match self {
+            FieldBlock::Tsm { meta, .. } => Some((meta.min_ts(), meta.max_ts())),
+            FieldBlock::Data(block) => block.time_range(),
+        }
+    }
+
+    /// Returns false if no value of the block satisfies the domain.
+    fn may_match(&self, domain: &Domain) -> bool {
+        match self {
+            FieldBlock::Tsm { meta, .. } => meta
+                .statistics()
+                .map_or(true, |stats| statistics_may_match(&stats, domain)),
+            FieldBlock::Data(_) => true,
+        }
+    }
+
+    async fn decode(self) -> Result<DataBlock, Error> {
+        match self {
+            FieldBlock::Tsm { reader, meta } => Ok(reader.get_data_block_owned(&meta).await?),
+            FieldBlock::Data(block) => Ok(block),
+        }
+    }
+}
+
+/// The blocks of a field of the series being read, they are decoded in the order of the
+/// minimum timestamp and only the decoded rows not yet returned are kept.
+struct FieldCursor {
+    /// The blocks not yet decoded and their order in the blocks of the field, sorted by
+    /// the minimum timestamp in descending order.
+    pending: Vec<(usize, FieldBlock)>,
+    /// The decoded blocks and their order in the blocks of the field.
+    decoded: Vec<(usize, DataBlock)>,
+}
+
+impl FieldCursor {
+    /// The blocks are from the oldest to the newest, like `read_field_blocks` returns.
+    fn new(blocks: Vec<FieldBlock>) -> Self {
+        let mut pending = blocks
+            .into_iter()
+            .enumerate()
+            .filter(|(_, b)| b.time_range().is_some())
+            .collect::<Vec<_>>();
+        pending.sort_by_key(|(_, b)| Reverse(b.time_range()));
+
+        Self {
+            pending,
+            decoded: vec![],
+        }
+    }
+
+    /// Returns the minimum timestamp of the blocks not yet decoded.
+    fn pending_min_ts(&self) -> Option<i64> {
+        self.pending
+            .last()
+            .and_then(|(_, b)| b.time_range())
+            .map(|(min_ts, _)| min_ts)
+    }
+
+    async fn decode_next(&mut self) -> Result<(), Error> {
+        if let Some((order, block)) = self.pending.pop() {
+            self.decoded.push((order, block.decode().await?));
+        }
+        Ok(())
+    }
+
+    /// Returns the decoded timestamps before `end`, or all of them if `end` is none.
+    fn decoded_ts(&self, end: Option<i64>) -> impl Iterator<Item = &[i64]> {
+        self.decoded.iter().map(move |(_, b)| match end {
+            Some(end) => &b.ts()[..b.ts().partition_point(|ts| *ts < end)],
+            None => b.ts(),
+        })
+    }
+
+    /// Takes the decoded rows not after `last` and merges them into one block, the
+    /// data of the newer block overrides the older one.
+    fn take_until(&mut self, last: i64) -> Option<DataBlock> {
+        let mut heads = vec![];
+        for (order, block) in self.decoded.iter_mut() {
+            let at = block.ts().partition_point(|ts| *ts <= last);
+            if at > 0 {
+                let tail = block.split_off(at);
+                heads.push((*order, std::mem::replace(block, tail)));
+            }
+        }
+        self.decoded.retain(|(_, b)| !b.is_empty());
+
+        heads.sort_by_key(|(order, _)| *order);
+        merge_data_blocks(heads.into_iter().map(|(_, b)| b).collect())
+    }
+}
+
+/// The series being read, its rows are returned in batches so that a series with
+/// many blocks is not decoded into memory at once.
+struct SeriesCursor {
+    /// The values of the tag columns, none for the other columns.
+    tags: Vec<Option<String>>,
+    /// The blocks of the field columns, none for the other columns.
+    fields: Vec<Option<FieldCursor>>,
+}
+
+impl SeriesCursor {
+    fn pending_min_ts(&self) -> Option<i64> {
+        self.fields
+            .iter()
+            .flatten()
+            .filter_map(|f| f.pending_min_ts())
+            .min()
+    }
+
+    /// Returns the blocks of the fields with the next at most `batch_size` timestamps,
+    /// all blocks are none if all rows are returned.
+    ///
+    /// The blocks are decoded in the order of the minimum timestamp until `batch_size`
+    /// rows before the minimum timestamp of the pending blocks are decoded, these rows
+    /// can not be overridden by the pending blocks.
+    async fn next_blocks(&mut self, batch_size: usize) -> Result<Vec<Option<DataBlock>>, Error> {
+        let mut end = self.pending_min_ts();
+        while let Some(min_ts) = end {
+            let rows: usize = self
+                .fields
+                .iter()
+                .flatten()
+                .flat_map(|f| f.decoded_ts(Some(min_ts)))
+                .map(|ts| ts.len())
+                .sum();
+            if rows >= batch_size {
+                break;
+            }
+
+            if let Some(field) = self
+                .fields
+                .iter_mut()
+                .flatten()
+                .find(|f| f.pending_min_ts() == Some(min_ts))
+            {
+                field.decode_next().await?;
+            }
+            end = self.pending_min_ts();
+        }
+
+        let mut time_index = self
+            .fields
+            .iter()
+            .flatten()
+            .flat_map(|f| f.decoded_ts(end))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        time_index.sort_unstable();
+        time_index.dedup();
+        time_index.truncate(batch_size);
+
+        let last = match time_index.last() {
+            Some(ts) => *ts,
+            None => return Ok(vec![None; self.fields.len()]),
+        };
+        Ok(self
+            .fields
+            .iter_mut()
+            .map(|f| f.as_mut().and_then(|f| f.take_until(last)))
+            .collect())
+    }
+}
+
+/// The partial result of a pushed down aggregate function.
+enum AggregateState {
+    Count(i64),
+    Min(Option<ScalarValue>),
+    Max(Option<ScalarValue>),
+    Sum(Option<ScalarValue>),
+    First(Option<(i64, ScalarValue)>),
+    Last(Option<(i64, ScalarValue)>),
+    Tag(Option<String>),
+}
+
+impl AggregateState {
+    fn new(func: &PushedAggregateFunction) -> Self {
+        match func {
+            PushedAggregateFunction::Count(_) => Self::Count(0),
+            PushedAggregateFunction::Min(_) => Self::Min(None),
+            PushedAggregateFunction::Max(_) => Self::Max(None),
+            PushedAggregateFunction::Sum(_) => Self::Sum(None),
+            PushedAggregateFunction::First(_) => Self::First(None),
+            PushedAggregateFunction::Last(_) => Self::Last(None),
+            PushedAggregateFunction::Tag(_) => Self::Tag(None),
+        }
+    }
+
+    /// Returns true if the state is updated by any data.
+    fn has_value(&self) -> bool {
+        match self {
+            Self::Count(val) => *val > 0,
+            Self::Min(val) | Self::Max(val) | Self::Sum(val) => val.is_some(),
+            Self::First(val) | Self::Last(val) => val.is_some(),
+            Self::Tag(_) => false,
+        }
+    }
+
+    fn update_count(&mut self, count: i64) {
+        if let Self::Count(val) = self {
+            *val += count;
+        }
+    }
+
+    /// Updates the state with the merged data of a field of one series.
+    fn update(&mut self, block: &DataBlock) -> Result<(), Error> {
+        if block.is_empty() {
+            return Ok(());
+        }
+
+        match self {
+            Self::Count(val) => *val += block.len() as i64,
+            Self::Min(_) | Self::Max(_) | Self::Sum(_) => {
+                if let Some(stats) = block.statistics() {
+                    self.update_statistics(&stats);
+                }
+            }
+            Self::First(val) => {
+                let ts = block.ts()[0];
+                if val.as_ref().map_or(true, |(t, _)| ts < *t) {
+                    *val = Some((ts, data_to_scalar(block.get(0))?));
+                }
+            }
+            Self::Last(val) => {
+                let idx = block.len() - 1;
+                let ts = block.ts()[idx];
+                if val.as_ref().map_or(true, |(t, _)| ts > *t) {
+                    *val = Some((ts, data_to_scalar(block.get(idx))?));
+                }
+            }
+            Self::Tag(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// Updates the state with the meta of a tsm block which is not overridden by other
+    /// blocks, returns false if the state needs the decoded data of the block.
+    fn update_by_meta(&mut self, meta: &BlockMeta) -> bool {
+        match self {
+            Self::Count(val) => *val += meta.count() as i64,
+            Self::Min(_) | Self::Max(_) | Self::Sum(_) => match meta.statistics() {
+                Some(stats) => self.update_statistics(&stats),
+                None => return false,
+            },
+            Self::First(_) | Self::Last(_) => return false,
+            Self::Tag(_) => {}
+        }
+
+        true
+    }
+
+    fn update_statistics(&mut self, stats: &BlockStatistics) {
+        let (min, max, sum) = statistics_to_scalars(stats);
+        match self {
+            Self::Min(val) => {
+                if val.as_ref().map_or(true, |v| min < *v) {
+                    *val = Some(min);
+                }
+            }
+            Self::Max(val) => {
+                if val.as_ref().map_or(true, |v| max > *v) {
+                    *val = Some(max);
+                }
+            }
+            Self::Sum(val) => {
+                *val = Some(match (val.take(), sum) {
+                    (Some(ScalarValue::Float64(Some(a))), ScalarValue::Float64(Some(b))) => {
+                        ScalarValue::Float64(Some(a + b))
+                    }
+                    (Some(ScalarValue::Int64(Some(a))), ScalarValue::Int64(Some(b))) => {
+                        ScalarValue::Int64(Some(a.wrapping_add(b)))
+                    }
+                    (Some(ScalarValue::UInt64(Some(a))), ScalarValue::UInt64(Some(b))) => {
+                        ScalarValue::UInt64(Some(a.wrapping_add(b)))
+                    }
+                    (_, sum) => sum,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the values of the partial result, None is null.
+    fn evaluate(self) -> Vec<Option<ScalarValue>> {
+        match self {
+            Self::Count(val) => vec![Some(ScalarValue::Int64(Some(val)))],
+            Self::Min(val) | Self::Max(val) | Self::Sum(val) => vec![val],
+            Self::First(val) | Self::Last(val) => match val {
+                Some((ts, val)) => vec![
+                    Some(ScalarValue::TimestampNanosecond(Some(ts), None)),
+                    Some(val),
+                ],
+                None => vec![None, None],
+            },
+            Self::Tag(val) => vec![val.map(|v| ScalarValue::Utf8(Some(v)))],
+        }
+    }
+}
+
+/// Returns the blocks may contain the minimum timestamp if `first`,
+/// and the blocks may contain the maximum timestamp if `last`, in the origin order.
+fn select_edge_blocks(blocks: Vec<FieldBlock>, first: bool, last: bool) -> Vec<FieldBlock> {
+    let ranges = blocks.iter().filter_map(|b| b.time_range());
+    let (min_ts, max_ts) = ranges.fold((i64::MAX, i64::MIN), |(min, max), (lo, hi)| {
+        (min.min(lo), max.max(hi))
+    });
+
+    blocks
+        .into_iter()
+        .filter(|b| match b.time_range() {
+            Some((lo, hi)) => (first && lo == min_ts) || (last && hi == max_ts),
+            None => false,
+        })
+        .collect()
+}
+
+/// Returns false if no value between the min and the max of the statistics satisfies the domain.
+fn statistics_may_match(stats: &BlockStatistics, domain: &Domain) -> bool {
+    let (min, max) = match *stats {
+        // the sum is NaN if the block has NaN
+        BlockStatistics::F64 { sum, .. } if sum.is_nan() => return true,
+        BlockStatistics::F64 { min, max, .. } => (
+            ScalarValue::Float64(Some(min)),
+            ScalarValue::Float64(Some(max)),
+        ),
+        BlockStatistics::I64 { min, max, .. } => {
+            (ScalarValue::Int64(Some(min)), ScalarValue::Int64(Some(max)))
+        }
+        BlockStatistics::U64 { min, max, .. } => (
+            ScalarValue::UInt64(Some(min)),
+            ScalarValue::UInt64(Some(max)),
+        ),
+    };
+
+    // values of different types are not comparable, keep the block
+    let lower_ok = |bound: Bound<&ScalarValue>| match bound {
+        Bound::Unbounded => true,
+        Bound::Included(v) => max.partial_cmp(v).map_or(true, |o| o.is_ge()),
+        Bound::Excluded(v) => max.partial_cmp(v).map_or(true, |o| o.is_gt()),
+    };
+    let upper_ok = |bound: Bound<&ScalarValue>| match bound {
+        Bound::Unbounded => true,
+        Bound::Included(v) => min.partial_cmp(v).map_or(true, |o| o.is_le()),
+        Bound::Excluded(v) => min.partial_cmp(v).map_or(true, |o| o.is_lt()),
+    };
+
+    match domain {
+        Domain::Range(range_set) => range_set
+            .low_indexed_ranges()
+            .into_iter()
+            .any(|(_, range)| lower_ok(range.start_bound()) && upper_ok(range.end_bound())),
+        Domain::Equtable(vals) => {
+            !vals.is_white_list()
+                || vals.entries().into_iter().any(|entry| {
+                    lower_ok(Bound::Included(entry.value()))
+                        && upper_ok(Bound::Included(entry.value()))
+                })
+        }
+        Domain::All => true,
+        Domain::None => false,
+    }
+}
+
+fn data_to_scalar(data: Option<DataType>) -> Result<ScalarValue, Error> {
+    let value = match data {
+        Some(DataType::F64(_, val)) => ScalarValue::Float64(Some(val)),
+        Some(DataType::I64(_, val)) => ScalarValue::Int64(Some(val)),
+        Some(DataType::U64(_, val)) => ScalarValue::UInt64(Some(val)),
+        Some(DataType::Bool(_, val)) => ScalarValue::Boolean(Some(val)),
+        Some(DataType::Str(_, val)) => ScalarValue::Utf8(Some(
+            String::from_utf8(val.to_vec()).map_err(|_| Error::ErrCharacterSet)?,
+        )),
+        None => ScalarValue::Null,
+    };
+
+    Ok(value)
+}
+
+/// Returns the min, max and sum of the statistics.
+fn statistics_to_scalars(stats: &BlockStatistics) -> (ScalarValue, ScalarValue, ScalarValue) {
+    match *stats {
+        BlockStatistics::F64 { min, max, sum } => (
+            ScalarValue::Float64(Some(min)),
+            ScalarValue::Float64(Some(max)),
+            ScalarValue::Float64(Some(sum)),
+        ),
+        BlockStatistics::I64 { min, max, sum } => (
+            ScalarValue::Int64(Some(min)),
+            ScalarValue::Int64(Some(max)),
+            ScalarValue::Int64(Some(sum)),
+        ),
+        BlockStatistics::U64 { min, max, sum } => (
+            ScalarValue::UInt64(Some(min)),
+            ScalarValue::UInt64(Some(max)),
+            ScalarValue::UInt64(Some(sum)),
+        ),
+    }
+}
+
+/// Returns the sorted and deduplicated timestamps of all fields.
+fn union_timestamps(blocks: &[Option<DataBlock>]) -> Vec<i64> {
+    let mut blocks = blocks.iter().flatten().filter(|b| !b.is_empty());
+    let first = match blocks.next() {
+        Some(b) => b.ts(),
+        None => return vec![],
+    };
+
+    let mut time_index = first.to_vec();
+    let mut aligned = true;
+    for block in blocks {
+        if block.ts() != first {
+            aligned = false;
+        }
+        time_index.extend_from_slice(block.ts());
+    }
+
+    if aligned {
+        time_index.truncate(first.len());
+    } else {
+        time_index.sort_unstable();
+        time_index.dedup();
+    }
+
+    time_index
+}
+
+/// Aligns the values of a field to the timestamps of the series, missing value is None.
+fn align_values<'a, T: Clone>(
+    ts: &'a [i64],
+    val: &'a [T],
+    time_index: &'a [i64],
+) -> impl Iterator<Item = Option<T>> + 'a {
+    let mut pos = 0;
+    time_index.iter().map(move |t| {
+        if pos < ts.len() && ts[pos] == *t {
+            pos += 1;
+            Some(val[pos - 1].clone())
+        } else {
+            None
         }
+    })
+}
+
+/// Builds the arrow array of a field, if the timestamps of the field is the same as the
+/// series, the values are copied in bulk.
+fn field_array(
+    block: Option<&DataBlock>,
+    vtype: ValueType,
+    time_index: &[i64],
+) -> Result<ArrayRef, Error> {
+    let block = match block {
+        Some(b) => b,
+        None => {
+            return Ok(new_null_array(
+                &ArrowDataType::from(ColumnType::Field(vtype)),
+                time_index.len(),
+            ))
+        }
+    };
+    let aligned = block.ts() == time_index;
 
-        let timer = self.metrics.elapsed_point_to_record_batch().timer();
-        let result = {
-            let mut cols = vec![];
-            for item in builder.iter_mut() {
-                cols.push(item.finish())
+    let array: ArrayRef = match block {
+        DataBlock::F64 { ts, val, .. } => {
+            if aligned {
+                Arc::new(Float64Array::from(val.clone()))
+            } else {
+                Arc::new(Float64Array::from_iter(align_values(ts, val, time_index)))
             }
+        }
+        DataBlock::I64 { ts, val, .. } => {
+            if aligned {
+                Arc::new(Int64Array::from(val.clone()))
+            } else {
+                Arc::new(Int64Array::from_iter(align_values(ts, val, time_index)))
+            }
+        }
+        DataBlock::U64 { ts, val, .. } => {
+            if aligned {
+                Arc::new(UInt64Array::from(val.clone()))
+            } else {
+                Arc::new(UInt64Array::from_iter(align_values(ts, val, time_index)))
+            }
+        }
+        DataBlock::Bool { ts, val, .. } => {
+            if aligned {
+                Arc::new(BooleanArray::from(val.clone()))
+            } else {
+                Arc::new(BooleanArray::from_iter(align_values(ts, val, time_index)))
+            }
+        }
+        DataBlock::Str { ts, val, .. } => {
+            let strs = val
+                .iter()
+                .map(|v| std::str::from_utf8(v).map_err(|_| Error::ErrCharacterSet))
+                .collect::<Result<Vec<_>, Error>>()?;
+            Arc::new(StringArray::from_iter(align_values(ts, &strs, time_index)))
+        }
+    };
+
+    Ok(array)
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::arrow::array::{Array, Float64Array};
+    use models::ValueType;
+
+    use datafusion::scalar::ScalarValue;
+
+    use datafusion::arrow::datatypes::DataType as ArrowDataType;
+    use models::predicate::domain::{Domain, Range};
+
+    use super::{
+        field_array, merge_data_blocks, select_edge_blocks, statistics_may_match, union_timestamps,
+        AggregateState, FieldBlock, FieldCursor, SeriesCursor,
+    };
+    use crate::tsm::{codec::DataBlockEncoding, BlockStatistics, DataBlock};
+
+    #[test]
+    fn test_merge_and_align_field_data() {
+        #[rustfmt::skip]
+        let merged = merge_data_blocks(vec![
+            DataBlock::F64 { ts: vec![5, 6], val: vec![5.0, 6.0], enc: DataBlockEncoding::default() },
+            DataBlock::F64 { ts: vec![1, 2], val: vec![1.0, 2.0], enc: DataBlockEncoding::default() },
+        ])
+        .unwrap();
+        assert_eq!(merged.ts(), &[1, 2, 5, 6]);
+
+        #[rustfmt::skip]
+        let merged = merge_data_blocks(vec![
+            DataBlock::F64 { ts: vec![1, 2, 3], val: vec![1.0, 2.0, 3.0], enc: DataBlockEncoding::default() },
+            DataBlock::F64 { ts: vec![2], val: vec![20.0], enc: DataBlockEncoding::default() },
+        ])
+        .unwrap();
+        #[rustfmt::skip]
+        assert_eq!(merged, DataBlock::F64 { ts: vec![1, 2, 3], val: vec![1.0, 20.0, 3.0], enc: DataBlockEncoding::default() });
+
+        #[rustfmt::skip]
+        let other = DataBlock::F64 { ts: vec![0, 3], val: vec![0.5, 3.5], enc: DataBlockEncoding::default() };
+        let blocks = vec![Some(merged), None, Some(other)];
+        let time_index = union_timestamps(&blocks);
+        assert_eq!(time_index, vec![0, 1, 2, 3]);
+
+        let array = field_array(blocks[2].as_ref(), ValueType::Float, &time_index).unwrap();
+        let array = array.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(
+            array.iter().collect::<Vec<_>>(),
+            vec![Some(0.5), None, None, Some(3.5)]
+        );
+
+        let array = field_array(None, ValueType::Float, &time_index).unwrap();
+        assert_eq!(array.null_count(), 4);
+    }
 
-            match RecordBatch::try_new(self.option.df_schema.clone(), cols) {
-                Ok(batch) => Some(Ok(batch)),
-                Err(err) => Some(Err(Error::CommonError {
-                    reason: format!("iterator fail, {}", err),
-                })),
+    #[tokio::test]
+    async fn test_series_cursor() {
+        // the blocks of a field are from the oldest to the newest
+        #[rustfmt::skip]
+        let field_a = FieldCursor::new(vec![
+            FieldBlock::Data(DataBlock::F64 { ts: vec![1, 2, 3], val: vec![1.0, 2.0, 3.0], enc: DataBlockEncoding::default() }),
+            FieldBlock::Data(DataBlock::F64 { ts: vec![5, 6], val: vec![5.0, 6.0], enc: DataBlockEncoding::default() }),
+            FieldBlock::Data(DataBlock::F64 { ts: vec![2, 7], val: vec![20.0, 7.0], enc: DataBlockEncoding::default() }),
+        ]);
+        #[rustfmt::skip]
+        let field_b = FieldCursor::new(vec![
+            FieldBlock::Data(DataBlock::F64 { ts: vec![1, 4, 7], val: vec![1.0, 4.0, 7.0], enc: DataBlockEncoding::default() }),
+        ]);
+        let mut series = SeriesCursor {
+            tags: vec![None, None, None],
+            fields: vec![None, Some(field_a), Some(field_b)],
+        };
+
+        let mut time_index = vec![];
+        let mut values = vec![];
+        loop {
+            let blocks = series.next_blocks(2).await.unwrap();
+            let batch_index = union_timestamps(&blocks);
+            if batch_index.is_empty() {
+                break;
+            }
+            assert!(batch_index.len() <= 2);
+            assert!(blocks[0].is_none());
+
+            let array = field_array(blocks[1].as_ref(), ValueType::Float, &batch_index).unwrap();
+            let array = array.as_any().downcast_ref::<Float64Array>().unwrap();
+            values.extend(array.iter());
+            time_index.extend(batch_index);
+        }
+
+        assert_eq!(time_index, vec![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(
+            values,
+            vec![
+                Some(1.0),
+                Some(20.0),
+                Some(3.0),
+                None,
+                Some(5.0),
+                Some(6.0),
+                Some(7.0)
+            ]
+        );
+        assert!(series.fields.iter().flatten().all(|f| f.decoded.is_empty()));
+    }
+
+    #[test]
+    fn test_aggregate_state() {
+        #[rustfmt::skip]
+        let blocks = vec![
+            DataBlock::I64 { ts: vec![1, 2, 3], val: vec![5, -2, 7], enc: DataBlockEncoding::default() },
+            DataBlock::I64 { ts: vec![0, 9], val: vec![1, 3], enc: DataBlockEncoding::default() },
+        ];
+
+        let mut states = vec![
+            AggregateState::Count(0),
+            AggregateState::Min(None),
+            AggregateState::Max(None),
+            AggregateState::Sum(None),
+            AggregateState::First(None),
+            AggregateState::Last(None),
+        ];
+        for state in states.iter_mut() {
+            for block in blocks.iter() {
+                state.update(block).unwrap();
             }
+        }
+
+        let values = states
+            .into_iter()
+            .flat_map(|s| s.evaluate())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                Some(ScalarValue::Int64(Some(5))),
+                Some(ScalarValue::Int64(Some(-2))),
+                Some(ScalarValue::Int64(Some(7))),
+                Some(ScalarValue::Int64(Some(14))),
+                Some(ScalarValue::TimestampNanosecond(Some(0), None)),
+                Some(ScalarValue::Int64(Some(1))),
+                Some(ScalarValue::TimestampNanosecond(Some(9), None)),
+                Some(ScalarValue::Int64(Some(3))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_state_statistics() {
+        #[rustfmt::skip]
+        let block = DataBlock::F64 { ts: vec![1, 2, 3], val: vec![1.5, -2.0, 4.0], enc: DataBlockEncoding::default() };
+        let stats = BlockStatistics::F64 {
+            min: 0.5,
+            max: 8.0,
+            sum: 10.0,
         };
-        timer.done();
 
-        result
+        let mut states = vec![
+            AggregateState::Min(None),
+            AggregateState::Max(None),
+            AggregateState::Sum(None),
+        ];
+        for state in states.iter_mut() {
+            state.update(&block).unwrap();
+            state.update_statistics(&stats);
+        }
+
+        let values = states
+            .into_iter()
+            .flat_map(|s| s.evaluate())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                Some(ScalarValue::Float64(Some(-2.0))),
+                Some(ScalarValue::Float64(Some(8.0))),
+                Some(ScalarValue::Float64(Some(13.5))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_edge_blocks() {
+        #[rustfmt::skip]
+        let blocks = || vec![
+            FieldBlock::Data(DataBlock::I64 { ts: vec![5, 9], val: vec![5, 9], enc: DataBlockEncoding::default() }),
+            FieldBlock::Data(DataBlock::I64 { ts: vec![1, 3], val: vec![1, 3], enc: DataBlockEncoding::default() }),
+            FieldBlock::Data(DataBlock::I64 { ts: vec![9], val: vec![90], enc: DataBlockEncoding::default() }),
+        ];
+        let ranges =
+            |blocks: Vec<FieldBlock>| blocks.iter().map(|b| b.time_range()).collect::<Vec<_>>();
+
+        assert_eq!(
+            ranges(select_edge_blocks(blocks(), false, true)),
+            vec![Some((5, 9)), Some((9, 9))]
+        );
+        assert_eq!(
+            ranges(select_edge_blocks(blocks(), true, false)),
+            vec![Some((1, 3))]
+        );
+        assert_eq!(select_edge_blocks(blocks(), true, true).len(), 3);
+
+        let mut state = AggregateState::Last(None);
+        let merged = merge_data_blocks(vec![
+            DataBlock::I64 {
+                ts: vec![5, 9],
+                val: vec![5, 9],
+                enc: DataBlockEncoding::default(),
+            },
+            DataBlock::I64 {
+                ts: vec![9],
+                val: vec![90],
+                enc: DataBlockEncoding::default(),
+            },
+        ])
+        .unwrap();
+        state.update(&merged).unwrap();
+        assert!(state.has_value());
+        assert_eq!(
+            state.evaluate(),
+            vec![
+                Some(ScalarValue::TimestampNanosecond(Some(9), None)),
+                Some(ScalarValue::Int64(Some(90))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_statistics_may_match() {
+        let stats = BlockStatistics::F64 {
+            min: 10.0,
+            max: 100.0,
+            sum: 300.0,
+        };
+        let data_type = ArrowDataType::Float64;
+        let gt = |v: f64| {
+            Domain::of_ranges(&[Range::gt(&data_type, &ScalarValue::Float64(Some(v)))]).unwrap()
+        };
+
+        assert!(statistics_may_match(&stats, &gt(50.0)));
+        assert!(!statistics_may_match(&stats, &gt(100.0)));
+        assert!(statistics_may_match(
+            &stats,
+            &Domain::of_values(&data_type, true, &[&ScalarValue::Float64(Some(10.0))])
+        ));
+        assert!(!statistics_may_match(
+            &stats,
+            &Domain::of_values(&data_type, true, &[&ScalarValue::Float64(Some(1.0))])
+        ));
+        // values of different types are not comparable
+        assert!(statistics_may_match(
+            &stats,
+            &Domain::of_values(&data_type, true, &[&ScalarValue::Int64(Some(1))])
+        ));
+
+        let nan_stats = BlockStatistics::F64 {
+            min: 10.0,
+            max: 100.0,
+            sum: f64::NAN,
+        };
+        assert!(statistics_may_match(&nan_stats, &gt(100.0)));
     }
 }
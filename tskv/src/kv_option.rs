@@ -12,6 +12,7 @@ const INDEX_PATH: &str = "index";
 const DATA_PATH: &str = "data";
 const TSM_PATH: &str = "tsm";
 const DELTA_PATH: &str = "delta";
+const MIGRATION_PATH: &str = "migration";
 
 #[derive(Debug, Clone)]
 pub struct Options {
@@ -55,6 +56,10 @@ impl StorageOptions {
         self.path.join(SUMMARY_PATH)
     }
 
+    pub fn migration_dir(&self) -> PathBuf {
+        self.path.join(MIGRATION_PATH)
+    }
+
     pub fn database_dir(&self, database: &str) -> PathBuf {
         self.path.join(DATA_PATH).join(database)
     }
@@ -136,6 +141,7 @@ impl From<&Config> for WalOptions {
 pub struct CacheOptions {
     pub max_buffer_size: u64,
     pub max_immutable_number: u16,
+    pub block_cache_size: u64,
 }
 
 impl From<&Config> for CacheOptions {
@@ -143,6 +149,7 @@ impl From<&Config> for CacheOptions {
         Self {
             max_buffer_size: config.cache.max_buffer_size,
             max_immutable_number: config.cache.max_immutable_number,
+            block_cache_size: config.cache.block_cache_size,
         }
     }
 }
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::time::Duration;
 use std::{collections::HashMap, panic, sync::Arc};
 
@@ -16,7 +17,7 @@ use models::predicate::domain::{ColumnDomains, PredicateRef};
 use snafu::{OptionExt, ResultExt};
 use tokio::sync::watch;
 use tokio::sync::watch::{Receiver, Sender};
-use tokio::sync::RwLock;
+use tokio::sync::{OwnedRwLockWriteGuard, RwLock};
 use tokio::{
     runtime::Runtime,
     sync::{
@@ -38,7 +39,7 @@ use models::{
     ValueType,
 };
 use protos::{
-    kv_service::{WritePointsRpcRequest, WritePointsRpcResponse, WriteRowsRpcRequest},
+    kv_service::{Meta, WritePointsRpcRequest, WritePointsRpcResponse, WriteRowsRpcRequest},
     models as fb_models,
 };
 use trace::{debug, error, info, trace, warn};
@@ -52,7 +53,7 @@ use crate::{
     compaction::{self, run_flush_memtable_job, CompactReq, FlushReq},
     context::GlobalContext,
     database,
-    engine::Engine,
+    engine::{Engine, VnodeSnapshot},
     error::{self, IndexErrSnafu, Result},
     file_utils,
     index::IndexResult,
@@ -61,13 +62,18 @@ use crate::{
     record_file::Reader,
     summary::{self, Summary, SummaryProcessor, SummaryTask, VersionEdit, WriteSummaryRequest},
     tseries_family::{SuperVersion, TimeRange, Version},
-    tsm::{DataBlock, TsmTombstone, MAX_BLOCK_VALUES},
+    tsm::{get_block_cache, init_block_cache, DataBlock, TsmTombstone, MAX_BLOCK_VALUES},
     version_set,
     version_set::VersionSet,
-    wal::{self, WalEntryType, WalManager, WalTask},
-    Error, Task, TseriesFamilyId,
+    wal::{self, VnodeWalEntry, WalCursor, WalDeleteSeries, WalEntryType, WalManager, WalTask},
+    Error, TableHashTreeNode, Task, TseriesFamilyId,
 };
 
+/// The snapshot of a vnode and the WAL after it are released if the WAL is not read
+/// within the time, e.g. the node copying the vnode is down.
+const VNODE_SNAPSHOT_LEASE: Duration = Duration::from_secs(60 * 60);
+const VNODE_SNAPSHOT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Debug)]
 pub struct TsKv {
     options: Arc<Options>,
@@ -83,8 +89,16 @@ pub struct TsKv {
     summary_task_sender: UnboundedSender<SummaryTask>,
     global_seq_task_sender: UnboundedSender<GlobalSequenceTask>,
     close_sender: BroadcastSender<UnboundedSender<()>>,
+    /// The versions of the vnodes being copied, their files are not deleted
+    /// by compactions until the snapshots are released.
+    vnode_snapshots: Arc<parking_lot::Mutex<HashMap<TseriesFamilyId, Arc<Version>>>>,
+    /// The held vnodes, their writes and deletes wait until they are released.
+    held_vnodes: Arc<parking_lot::Mutex<HashMap<TseriesFamilyId, VnodeHold>>>,
 }
 
+/// The lock of a held vnode and the guard holding it.
+type VnodeHold = (Arc<RwLock<()>>, OwnedRwLockWriteGuard<()>);
+
 impl TsKv {
     pub async fn open(
         cluster_options: ClusterConfig,
@@ -93,6 +107,7 @@ impl TsKv {
     ) -> Result<TsKv> {
         let meta_manager: MetaRef = Arc::new(RemoteMetaManager::new(cluster_options));
         let shared_options = Arc::new(opt);
+        init_block_cache(shared_options.cache.block_cache_size);
         let (flush_task_sender, flush_task_receiver) = mpsc::unbounded_channel::<FlushReq>();
         let (compact_task_sender, compact_task_receiver) =
             mpsc::unbounded_channel::<TseriesFamilyId>();
@@ -124,6 +139,8 @@ impl TsKv {
             summary_task_sender: summary_task_sender.clone(),
             global_seq_task_sender: global_seq_task_sender.clone(),
             close_sender,
+            vnode_snapshots: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            held_vnodes: Arc::new(parking_lot::Mutex::new(HashMap::new())),
         };
 
         let wal_manager = core.recover_wal().await;
@@ -143,6 +160,7 @@ impl TsKv {
         )
         .await;
         core.run_summary_job(summary, summary_task_receiver);
+        core.run_vnode_snapshot_job();
         context::run_global_context_job(
             core.runtime.clone(),
             global_seq_task_receiver,
@@ -221,6 +239,12 @@ impl TsKv {
                                     }
                                 }
                             }
+                            Some(WalTask::Delete { id, data, tenant, cb }) => {
+                                let ret = wal_manager.write(WalEntryType::DeleteRange, data, id, tenant).await;
+                                if cb.send(ret).is_err() {
+                                    warn!("send WAL delete result failed.")
+                                }
+                            }
                             _ => {
                                 break;
                             }
@@ -246,6 +270,23 @@ impl TsKv {
         info!("job 'WAL' started.");
     }
 
+    /// Releases the snapshots of the vnodes and the holds of the WAL whose lease has expired.
+    fn run_vnode_snapshot_job(&self) {
+        let global_seq_ctx = self.global_seq_ctx.clone();
+        let vnode_snapshots = self.vnode_snapshots.clone();
+        self.runtime.spawn(async move {
+            let mut ticker = tokio::time::interval(VNODE_SNAPSHOT_CHECK_INTERVAL);
+            loop {
+                ticker.tick().await;
+                for vnode_id in global_seq_ctx.expire_wal_holds() {
+                    if vnode_snapshots.lock().remove(&vnode_id).is_some() {
+                        warn!("snapshot of vnode {} expired", vnode_id);
+                    }
+                }
+            }
+        });
+    }
+
     fn run_flush_job(
         &self,
         mut receiver: UnboundedReceiver<FlushReq>,
@@ -288,6 +329,8 @@ impl TsKv {
                 if let Some(tsf) = ts_family {
                     info!("Starting compaction on ts_family {}", ts_family_id);
                     let start = Instant::now();
+                    let delete_fence = tsf.read().delete_fence();
+                    let _delete_fence = delete_fence.read().await;
                     let compact_req = tsf.read().pick_compaction();
                     if let Some(req) = compact_req {
                         let database = req.database.clone();
@@ -301,13 +344,16 @@ impl TsKv {
                                     vec![version_edit],
                                     summary_tx,
                                 ));
+                                if ret.is_ok() {
+                                    // Deletes wait until the compacted files are in the version.
+                                    let _ = summary_rx.await;
+                                }
                                 sample_tskv_compaction_duration(
                                     database.as_str(),
                                     compact_ts_family.to_string().as_str(),
                                     out_level.to_string().as_str(),
                                     start.elapsed().as_secs_f64(),
                                 )
-                                // TODO Handle summary result using summary_rx.
                             }
                             Ok(None) => {
                                 info!("There is nothing to compact.");
@@ -411,6 +457,16 @@ impl TsKv {
 
         if let Some(db) = self.version_set.read().await.get_db(tenant, database) {
             for (ts_family_id, ts_family) in db.read().await.ts_families().iter() {
+                let delete = WalDeleteSeries {
+                    database: database.to_string(),
+                    series_ids: series_ids.clone(),
+                    field_ids: column_ids.to_vec(),
+                    min_ts: Timestamp::MIN,
+                    max_ts: Timestamp::MAX,
+                };
+                self.write_delete_to_wal(tenant, *ts_family_id, &delete)
+                    .await?;
+
                 ts_family.read().delete_columns(&storage_field_ids);
 
                 let version = ts_family.read().super_version();
@@ -427,11 +483,8 @@ impl TsKv {
 
         Ok(())
     }
-}
 
-#[async_trait::async_trait]
-impl Engine for TsKv {
-    async fn write(
+    async fn write_vnode(
         &self,
         id: TseriesFamilyId,
         write_batch: WritePointsRpcRequest,
@@ -508,6 +561,113 @@ impl Engine for TsKv {
         })
     }
 
+    async fn delete_vnode_series(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: TseriesFamilyId,
+        series_ids: &[SeriesId],
+        field_ids: &[ColumnId],
+        time_range: &TimeRange,
+    ) -> Result<()> {
+        let db = match self.version_set.read().await.get_db(tenant, database) {
+            Some(db) => db,
+            None => return Ok(()),
+        };
+        let ts_family = match db.read().await.get_tsfamily(vnode_id) {
+            Some(ts_family) => ts_family,
+            None => return Ok(()),
+        };
+
+        // Wait for the running flush and compaction, and keep the next ones waiting.
+        let delete_fence = ts_family.read().delete_fence();
+        let _delete_fence = delete_fence.write().await;
+
+        let delete = WalDeleteSeries {
+            database: database.to_string(),
+            series_ids: series_ids.to_vec(),
+            field_ids: field_ids.to_vec(),
+            min_ts: time_range.min_ts,
+            max_ts: time_range.max_ts,
+        };
+        self.write_delete_to_wal(tenant, vnode_id, &delete).await?;
+
+        Self::delete_series_in_tsfamily(&ts_family, series_ids, field_ids, time_range).await
+    }
+
+    /// Waits until the vnode is released if it's held.
+    async fn wait_for_release(&self, vnode_id: TseriesFamilyId) {
+        let lock = self
+            .held_vnodes
+            .lock()
+            .get(&vnode_id)
+            .map(|(lock, _)| lock.clone());
+        if let Some(lock) = lock {
+            let _released = lock.read().await;
+        }
+    }
+
+    /// Writes the delete to the WAL if it's enabled, so the delete is replayed after a restart
+    /// and copied with the writes when the vnode is copied to another node.
+    async fn write_delete_to_wal(
+        &self,
+        tenant: &str,
+        vnode_id: TseriesFamilyId,
+        delete: &WalDeleteSeries,
+    ) -> Result<()> {
+        if !self.options.wal.enabled {
+            return Ok(());
+        }
+
+        let (cb, rx) = oneshot::channel();
+        self.wal_sender
+            .send(WalTask::Delete {
+                id: vnode_id,
+                cb,
+                data: Arc::new(delete.encode()?),
+                tenant: Arc::new(tenant.as_bytes().to_vec()),
+            })
+            .map_err(|err| Error::Send)?;
+        rx.await.context(error::ReceiveSnafu)??;
+
+        Ok(())
+    }
+
+    /// Deletes the data of the series in the caches, and adds the tombstones to the files.
+    async fn delete_series_in_tsfamily(
+        ts_family: &Arc<parking_lot::RwLock<TseriesFamily>>,
+        series_ids: &[SeriesId],
+        field_ids: &[ColumnId],
+        time_range: &TimeRange,
+    ) -> Result<()> {
+        let storage_field_ids: Vec<u64> = series_ids
+            .iter()
+            .flat_map(|sid| field_ids.iter().map(|fid| unite_id(*fid, *sid)))
+            .collect();
+
+        ts_family.read().delete_series(series_ids, time_range);
+        let version = ts_family.read().super_version();
+        for column_file in version.version.column_files(&storage_field_ids, time_range) {
+            column_file
+                .add_tombstone(&storage_field_ids, time_range)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Engine for TsKv {
+    async fn write(
+        &self,
+        id: TseriesFamilyId,
+        write_batch: WritePointsRpcRequest,
+    ) -> Result<WritePointsRpcResponse> {
+        self.wait_for_release(id).await;
+        self.write_vnode(id, write_batch).await
+    }
+
     async fn write_from_wal(
         &self,
         id: TseriesFamilyId,
@@ -563,6 +723,7 @@ impl Engine for TsKv {
     }
 
     async fn remove_tsfamily(&self, tenant: &str, database: &str, id: u32) -> Result<()> {
+        self.release_vnode_snapshot(id);
         if let Some(db) = self.version_set.read().await.get_db(tenant, database) {
             let mut db_wlock = db.write().await;
 
@@ -572,6 +733,7 @@ impl Engine for TsKv {
                 .options
                 .storage
                 .ts_family_dir(&make_owner(tenant, database), id);
+            get_block_cache().invalidate_dir(&ts_dir);
             let result = std::fs::remove_dir_all(&ts_dir);
             info!(
                 "Remove TsFamily data '{}', result: {:?}",
@@ -627,6 +789,7 @@ impl Engine for TsKv {
             .options
             .storage
             .database_dir(&make_owner(tenant, database));
+        get_block_cache().invalidate_dir(&db_dir);
         if let Err(e) = std::fs::remove_dir_all(&db_dir) {
             error!("Failed to remove dir '{}', e: {}", db_dir.display(), e);
         }
@@ -635,6 +798,24 @@ impl Engine for TsKv {
     }
 
     async fn drop_table(&self, tenant: &str, database: &str, table: &str) -> Result<()> {
+        if let Some(db) = self.version_set.read().await.get_db(tenant, database) {
+            let db = db.read().await;
+            if let Some(schema) = db.get_table_schema(table)? {
+                let field_ids: Vec<ColumnId> = schema.columns().iter().map(|c| c.id).collect();
+                for (vnode_id, index) in db.ts_indexes().iter() {
+                    let series_ids = index.read().await.get_series_id_list(table, &[])?;
+                    let delete = WalDeleteSeries {
+                        database: database.to_string(),
+                        series_ids,
+                        field_ids: field_ids.clone(),
+                        min_ts: Timestamp::MIN,
+                        max_ts: Timestamp::MAX,
+                    };
+                    self.write_delete_to_wal(tenant, *vnode_id, &delete).await?;
+                }
+            }
+        }
+
         // TODO Create global DropTable flag for droping the same table at the same time.
         let version_set = self.version_set.clone();
         let database = database.to_string();
@@ -648,32 +829,45 @@ impl Engine for TsKv {
         &self,
         tenant: &str,
         database: &str,
+        vnode_id: TseriesFamilyId,
         series_ids: &[SeriesId],
         field_ids: &[ColumnId],
         time_range: &TimeRange,
     ) -> Result<()> {
-        // let storage_field_ids: Vec<u64> = series_ids
-        //     .iter()
-        //     .flat_map(|sid| field_ids.iter().map(|fid| unite_id(*fid, *sid)))
-        //     .collect();
-        // let res = self.version_set.read().get_db(tenant, database);
-        // if let Some(db) = res {
-        //     let readable_db = db.read();
-        //     for (ts_family_id, ts_family) in readable_db.ts_families() {
-        //         // TODO Cancel current and prevent next flush or compaction in TseriesFamily provisionally.
-        //         ts_family
-        //             .write()
-        //             .delete_series(&storage_field_ids, time_range);
-
-        //         let version = ts_family.read().super_version();
-        //         for column_file in version.version.column_files(&storage_field_ids, time_range) {
-        //             self.runtime
-        //                 .block_on(column_file.add_tombstone(&storage_field_ids, time_range))?;
-        //         }
-        //         // TODO Start next flush or compaction.
-        //     }
-        // }
-        Ok(())
+        self.wait_for_release(vnode_id).await;
+        self.delete_vnode_series(
+            tenant, database, vnode_id, series_ids, field_ids, time_range,
+        )
+        .await
+    }
+
+    async fn delete_series_from_wal(
+        &self,
+        tenant: &str,
+        vnode_id: TseriesFamilyId,
+        delete: &WalDeleteSeries,
+    ) -> Result<()> {
+        let db = match self
+            .version_set
+            .read()
+            .await
+            .get_db(tenant, &delete.database)
+        {
+            Some(db) => db,
+            None => return Ok(()),
+        };
+        let ts_family = match db.read().await.get_tsfamily(vnode_id) {
+            Some(ts_family) => ts_family,
+            None => return Ok(()),
+        };
+
+        Self::delete_series_in_tsfamily(
+            &ts_family,
+            &delete.series_ids,
+            &delete.field_ids,
+            &delete.time_range(),
+        )
+        .await
     }
 
     async fn get_table_schema(
@@ -811,6 +1005,12 @@ impl Engine for TsKv {
             if let Some(tsf) = db_wlock.get_tsfamily(vnode_id) {
                 db_wlock.del_tsfamily(vnode_id, self.summary_task_sender.clone());
             }
+            // The downloaded files may reuse the paths of the files cached before.
+            let ts_dir = self
+                .options
+                .storage
+                .ts_family_dir(&make_owner(tenant, database), vnode_id);
+            get_block_cache().invalidate_dir(&ts_dir);
 
             db_wlock.get_ts_index_or_add(vnode_id).await?;
 
@@ -897,6 +1097,7 @@ impl Engine for TsKv {
     }
 
     async fn drop_vnode(&self, id: TseriesFamilyId) -> Result<()> {
+        self.release_vnode_snapshot(id);
         let r_version_set = self.version_set.read().await;
         let all_db = r_version_set.get_all_db();
         for (db_name, db) in all_db {
@@ -909,6 +1110,7 @@ impl Engine for TsKv {
                 db_wlock.del_tsfamily(id, self.summary_task_sender.clone());
             }
             let tsf_dir = self.options.storage.tsfamily_dir(db_name, id);
+            get_block_cache().invalidate_dir(&tsf_dir);
             if let Err(e) = std::fs::remove_dir_all(&tsf_dir) {
                 error!("Failed to remove dir '{}', e: {}", tsf_dir.display(), e);
             }
@@ -921,11 +1123,179 @@ impl Engine for TsKv {
         Ok(())
     }
 
+    async fn snapshot_vnode(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: TseriesFamilyId,
+    ) -> Result<Option<VnodeSnapshot>> {
+        let db = match self.version_set.read().await.get_db(tenant, database) {
+            Some(db) => db,
+            None => {
+                return Err(SchemaError::DatabaseNotFound {
+                    database: format!("{}.{}", tenant, database),
+                }
+                .into())
+            }
+        };
+        let tsf = match db.read().await.get_tsfamily(vnode_id) {
+            Some(tsf) => tsf,
+            None => return Ok(None),
+        };
+        let owner = db.read().await.owner();
+
+        // The writes before the sequence have been flushed, the writes after it
+        // may be flushed or not, they are read from the WAL when copying the vnode.
+        let seq = tsf.read().seq_no();
+        self.global_seq_ctx
+            .hold_wal(vnode_id, seq, VNODE_SNAPSHOT_LEASE);
+        if let Err(err) = self.flush_tsfamily(tenant, database, vnode_id).await {
+            self.global_seq_ctx.release_wal(vnode_id);
+            return Err(err);
+        }
+
+        let (version, version_edit) = {
+            let tsf = tsf.read();
+            (tsf.version(), tsf.get_version_edit(seq, owner.clone()))
+        };
+
+        let index_dir = self.options.storage.index_dir(&owner, vnode_id);
+        let mut files: Vec<PathBuf> = walkdir::WalkDir::new(&index_dir)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .collect();
+        for level in version.levels_info.iter() {
+            for file in level.files.iter() {
+                let path = file.file_path();
+                if let Some(dir) = path.parent() {
+                    let tombstone = file_utils::make_tsm_tombstone_file_name(dir, file.file_id());
+                    if file_manager::try_exists(&tombstone) {
+                        files.push(tombstone);
+                    }
+                }
+                files.push(path);
+            }
+        }
+
+        self.vnode_snapshots.lock().insert(vnode_id, version);
+        info!(
+            "snapshot vnode {} at seq {}, {} files",
+            vnode_id,
+            seq,
+            files.len()
+        );
+
+        Ok(Some(VnodeSnapshot {
+            seq,
+            version_edit,
+            files,
+        }))
+    }
+
+    fn release_vnode_snapshot(&self, vnode_id: TseriesFamilyId) {
+        self.vnode_snapshots.lock().remove(&vnode_id);
+        self.global_seq_ctx.release_wal(vnode_id);
+    }
+
+    async fn hold_vnode(&self, vnode_id: TseriesFamilyId) {
+        if self.held_vnodes.lock().contains_key(&vnode_id) {
+            return;
+        }
+        let lock = Arc::new(RwLock::new(()));
+        let guard = lock.clone().write_owned().await;
+        self.held_vnodes
+            .lock()
+            .entry(vnode_id)
+            .or_insert((lock, guard));
+    }
+
+    fn release_vnode(&self, vnode_id: TseriesFamilyId) {
+        self.held_vnodes.lock().remove(&vnode_id);
+    }
+
+    async fn copy_wal_entry(
+        &self,
+        tenant: &str,
+        vnode_id: TseriesFamilyId,
+        typ: WalEntryType,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        match typ {
+            WalEntryType::Write => {
+                let write_batch = WritePointsRpcRequest {
+                    version: 1,
+                    meta: Some(Meta {
+                        tenant: tenant.to_string(),
+                        user: None,
+                        password: None,
+                    }),
+                    points: data,
+                    consistency_level: None,
+                };
+                self.write_vnode(vnode_id, write_batch).await?;
+                Ok(())
+            }
+            WalEntryType::DeleteRange => {
+                let delete = WalDeleteSeries::decode(&data)?;
+                self.delete_vnode_series(
+                    tenant,
+                    &delete.database,
+                    vnode_id,
+                    &delete.series_ids,
+                    &delete.field_ids,
+                    &delete.time_range(),
+                )
+                .await
+            }
+            typ => Err(Error::CommonError {
+                reason: format!("unexpected wal entry {:?} to copy", typ),
+            }),
+        }
+    }
+
+    async fn get_vnode_wal(
+        &self,
+        vnode_id: TseriesFamilyId,
+        min_seq: u64,
+        cursor: Option<WalCursor>,
+        max_size: usize,
+    ) -> Result<Vec<VnodeWalEntry>> {
+        if !self.options.wal.enabled {
+            return Err(Error::CommonError {
+                reason: "the writes of vnode can't be read, wal is disabled".to_string(),
+            });
+        }
+
+        // The writes before `min_seq` have been copied, their WAL can be deleted.
+        // The lease of the hold is renewed by every read.
+        self.global_seq_ctx
+            .hold_wal(vnode_id, min_seq, VNODE_SNAPSHOT_LEASE);
+        wal::read_vnode_wal(&self.options.wal.path, vnode_id, min_seq, cursor, max_size).await
+    }
+
+    async fn get_vnode_hash_tree(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: TseriesFamilyId,
+    ) -> Result<Vec<TableHashTreeNode>> {
+        // The hash tree is built from the files, the data in cache must be flushed first.
+        self.flush_tsfamily(tenant, database, vnode_id).await?;
+
+        let db = self.get_db(tenant, database).await?;
+        let db = db.read().await;
+        db.get_ts_family_hash_tree(vnode_id).await
+    }
+
     async fn compact(&self, tenant: &str, database: &str) {
         let database = self.version_set.read().await.get_db(tenant, database);
         if let Some(db) = database {
-            // TODO: stop current and prevent next flush and compaction.
             for (ts_family_id, ts_family) in db.read().await.ts_families() {
+                let delete_fence = ts_family.read().delete_fence();
+                let _delete_fence = delete_fence.read().await;
                 let compact_req = ts_family.read().pick_compaction();
                 if let Some(req) = compact_req {
                     match compaction::run_compaction_job(req, self.global_ctx.clone()).await {
@@ -934,8 +1304,9 @@ impl Engine for TsKv {
                             let ret = self
                                 .summary_task_sender
                                 .send(SummaryTask::new_append_task(vec![version_edit], summary_tx));
-
-                            // let _ = summary_rx.await;
+                            if ret.is_ok() {
+                                let _ = summary_rx.await;
+                            }
                         }
                         Ok(None) => {
                             info!("There is nothing to compact.");
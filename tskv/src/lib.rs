@@ -25,6 +25,9 @@ mod tsm;
 mod version_set;
 mod wal;
 
+pub use compaction::check::{
+    hash_to_string, ColumnHashTreeNode, TableHashTreeNode, TimeRangeHashTreeNode,
+};
 pub use error::{Error, Result};
 pub use kv_option::Options;
 pub use kvcore::TsKv;
@@ -35,6 +38,7 @@ use tokio::sync::oneshot;
 pub use tseries_family::TimeRange;
 pub use tsm::print_tsm_statistics;
 use utils::BloomFilter;
+pub use wal::{VnodeWalEntry, WalCursor, WalDeleteSeries, WalEntryType};
 
 pub type ColumnFileId = u64;
 type TseriesFamilyId = u32;
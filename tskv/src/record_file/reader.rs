@@ -67,6 +67,16 @@ impl Reader {
         })
     }
 
+    /// Returns the position of the next record to read.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Moves to the position of a record, the next read starts from it.
+    pub async fn seek(&mut self, pos: usize) -> Result<()> {
+        self.set_pos(pos).await
+    }
+
     async fn set_pos(&mut self, pos: usize) -> Result<()> {
         if self.pos - self.buf_use == pos {
             self.pos = pos;
@@ -32,7 +32,7 @@ use crate::{
     kv_option::{CacheOptions, Options, StorageOptions},
     memcache::{DataType, MemCache},
     summary::{CompactMeta, VersionEdit},
-    tsm::{ColumnReader, DataBlock, IndexReader, TsmReader, TsmTombstone},
+    tsm::{get_block_cache, ColumnReader, DataBlock, IndexReader, TsmReader, TsmTombstone},
     ColumnFileId, LevelId, TseriesFamilyId,
 };
 use crate::{memcache::RowGroup, tsm::BlockMetaIterator};
@@ -48,14 +48,16 @@ pub struct TimeRange {
 }
 
 impl From<(Bound<i64>, Bound<i64>)> for TimeRange {
-    /// TODO 目前TimeRange只支持闭区间
+    /// TimeRange只支持闭区间，开区间的边界转换为相邻的时间戳
     fn from(range: (Bound<i64>, Bound<i64>)) -> Self {
         let min_ts = match range.0 {
-            Bound::Excluded(v) | Bound::Included(v) => v,
+            Bound::Excluded(v) => v.saturating_add(1),
+            Bound::Included(v) => v,
             _ => Timestamp::MIN,
         };
         let max_ts = match range.1 {
-            Bound::Excluded(v) | Bound::Included(v) => v,
+            Bound::Excluded(v) => v.saturating_sub(1),
+            Bound::Included(v) => v,
             _ => Timestamp::MAX,
         };
 
@@ -238,6 +240,7 @@ impl ColumnFile {
 
     pub fn mark_deleted(&self) {
         self.deleted.store(true, Ordering::Release);
+        get_block_cache().invalidate_file(&self.path);
     }
 
     pub fn is_compacting(&self) -> bool {
@@ -274,7 +277,7 @@ pub struct FieldFileLocation {
     block_it: BlockMetaIterator,
 
     read_index: usize,
-    data_block: DataBlock,
+    data_block: Arc<DataBlock>,
 }
 
 impl FieldFileLocation {
@@ -423,7 +426,7 @@ impl LevelInfo {
             };
             for idx in tsm_reader.index_iterator_opt(field_id) {
                 for blk in idx.block_iterator_opt(time_range) {
-                    if let Ok(blk) = tsm_reader.get_data_block(&blk).await {
+                    if let Ok(blk) = tsm_reader.get_data_block_owned(&blk).await {
                         data.push(blk);
                     }
                 }
@@ -639,6 +642,7 @@ pub struct TseriesFamily {
     immut_ts_min: AtomicI64,
     mut_ts_max: AtomicI64,
     flush_task_sender: UnboundedSender<FlushReq>,
+    delete_fence: Arc<tokio::sync::RwLock<()>>,
 }
 
 impl TseriesFamily {
@@ -680,6 +684,7 @@ impl TseriesFamily {
             immut_ts_min: AtomicI64::new(max_level_ts),
             mut_ts_max: AtomicI64::new(i64::MIN),
             flush_task_sender,
+            delete_fence: Arc::new(tokio::sync::RwLock::new(())),
         }
     }
 
@@ -878,6 +883,13 @@ impl TseriesFamily {
     pub fn seq_no(&self) -> u64 {
         self.seq_no
     }
+
+    /// Flush and compaction hold the read lock until their version edits are applied,
+    /// delete holds the write lock, so that no file is written from the data being deleted
+    /// after the tombstones are added.
+    pub fn delete_fence(&self) -> Arc<tokio::sync::RwLock<()>> {
+        self.delete_fence.clone()
+    }
 }
 
 #[cfg(test)]
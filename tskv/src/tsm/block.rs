@@ -9,9 +9,12 @@ use trace::error;
 use crate::{
     memcache::DataType,
     tseries_family::TimeRange,
-    tsm::codec::{
-        get_bool_codec, get_f64_codec, get_i64_codec, get_str_codec, get_ts_codec, get_u64_codec,
-        DataBlockEncoding,
+    tsm::{
+        codec::{
+            get_bool_codec, get_f64_codec, get_i64_codec, get_str_codec, get_ts_codec,
+            get_u64_codec, DataBlockEncoding,
+        },
+        BlockStatistics,
     },
 };
 
@@ -248,6 +251,32 @@ impl DataBlock {
         }
     }
 
+    /// Returns the min, max and sum of values of this `DataBlock`,
+    /// None if this `DataBlock` is empty or not numeric.
+    pub fn statistics(&self) -> Option<BlockStatistics> {
+        if self.is_empty() {
+            return None;
+        }
+        match self {
+            DataBlock::F64 { val, .. } => Some(BlockStatistics::F64 {
+                min: val.iter().copied().fold(f64::NAN, f64::min),
+                max: val.iter().copied().fold(f64::NAN, f64::max),
+                sum: val.iter().sum(),
+            }),
+            DataBlock::I64 { val, .. } => Some(BlockStatistics::I64 {
+                min: val.iter().copied().min()?,
+                max: val.iter().copied().max()?,
+                sum: val.iter().fold(0_i64, |acc, v| acc.wrapping_add(*v)),
+            }),
+            DataBlock::U64 { val, .. } => Some(BlockStatistics::U64 {
+                min: val.iter().copied().min()?,
+                max: val.iter().copied().max()?,
+                sum: val.iter().fold(0_u64, |acc, v| acc.wrapping_add(*v)),
+            }),
+            DataBlock::Str { .. } | DataBlock::Bool { .. } => None,
+        }
+    }
+
     /// Returns a slice containing the entire timestamps of this `DataBlock`.
     pub fn ts(&self) -> &[i64] {
         match self {
@@ -471,6 +500,135 @@ impl DataBlock {
         self.exclude_by_index(min_idx, max_idx);
     }
 
+    /// Keep only (ts, val) in this `DataBlock` where ts is greater equal than min_ts
+    /// and ts is less equal than the max_ts, the timestamps must be sorted.
+    pub fn retain_time_range(&mut self, time_range: &TimeRange) {
+        let ts_sli = self.ts();
+        let start = ts_sli.partition_point(|ts| *ts < time_range.min_ts);
+        let end = ts_sli.partition_point(|ts| *ts <= time_range.max_ts);
+        if start == 0 && end == ts_sli.len() {
+            return;
+        }
+        let end = end.max(start);
+
+        fn retain<T>(ts: &mut Vec<i64>, val: &mut Vec<T>, start: usize, end: usize) {
+            ts.truncate(end);
+            ts.drain(..start);
+            val.truncate(end);
+            val.drain(..start);
+        }
+
+        match self {
+            DataBlock::U64 { ts, val, .. } => retain(ts, val, start, end),
+            DataBlock::I64 { ts, val, .. } => retain(ts, val, start, end),
+            DataBlock::Str { ts, val, .. } => retain(ts, val, start, end),
+            DataBlock::F64 { ts, val, .. } => retain(ts, val, start, end),
+            DataBlock::Bool { ts, val, .. } => retain(ts, val, start, end),
+        }
+    }
+
+    /// Splits this `DataBlock` into two at the given index, returns the (ts, val) from
+    /// the index on and keeps the ones before it.
+    ///
+    /// **Panics** if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        match self {
+            DataBlock::U64 { ts, val, enc } => DataBlock::U64 {
+                ts: ts.split_off(at),
+                val: val.split_off(at),
+                enc: *enc,
+            },
+            DataBlock::I64 { ts, val, enc } => DataBlock::I64 {
+                ts: ts.split_off(at),
+                val: val.split_off(at),
+                enc: *enc,
+            },
+            DataBlock::Str { ts, val, enc } => DataBlock::Str {
+                ts: ts.split_off(at),
+                val: val.split_off(at),
+                enc: *enc,
+            },
+            DataBlock::F64 { ts, val, enc } => DataBlock::F64 {
+                ts: ts.split_off(at),
+                val: val.split_off(at),
+                enc: *enc,
+            },
+            DataBlock::Bool { ts, val, enc } => DataBlock::Bool {
+                ts: ts.split_off(at),
+                val: val.split_off(at),
+                enc: *enc,
+            },
+        }
+    }
+
+    /// Moves all (ts, val) of `other` to the end of this `DataBlock`.
+    ///
+    /// **Panics** if the `other` is not the same variant of this `DataBlock`.
+    pub fn append(&mut self, other: Self) {
+        match (self, other) {
+            (
+                DataBlock::U64 { ts, val, .. },
+                DataBlock::U64 {
+                    ts: mut ts_other,
+                    val: mut val_other,
+                    ..
+                },
+            ) => {
+                ts.append(&mut ts_other);
+                val.append(&mut val_other);
+            }
+            (
+                DataBlock::I64 { ts, val, .. },
+                DataBlock::I64 {
+                    ts: mut ts_other,
+                    val: mut val_other,
+                    ..
+                },
+            ) => {
+                ts.append(&mut ts_other);
+                val.append(&mut val_other);
+            }
+            (
+                DataBlock::Str { ts, val, .. },
+                DataBlock::Str {
+                    ts: mut ts_other,
+                    val: mut val_other,
+                    ..
+                },
+            ) => {
+                ts.append(&mut ts_other);
+                val.append(&mut val_other);
+            }
+            (
+                DataBlock::F64 { ts, val, .. },
+                DataBlock::F64 {
+                    ts: mut ts_other,
+                    val: mut val_other,
+                    ..
+                },
+            ) => {
+                ts.append(&mut ts_other);
+                val.append(&mut val_other);
+            }
+            (
+                DataBlock::Bool { ts, val, .. },
+                DataBlock::Bool {
+                    ts: mut ts_other,
+                    val: mut val_other,
+                    ..
+                },
+            ) => {
+                ts.append(&mut ts_other);
+                val.append(&mut val_other);
+            }
+            (blk, other) => panic!(
+                "append data block of type {:?} to {:?}",
+                other.field_type(),
+                blk.field_type()
+            ),
+        }
+    }
+
     /// Extract `DataBlock`s to `DataType`s,
     /// returns the minimum timestamp in a series of `DataBlock`s
     fn next_min(
@@ -840,4 +998,72 @@ pub mod test {
             }
         );
     }
+
+    #[test]
+    fn test_data_block_retain_time_range_and_append() {
+        #[rustfmt::skip]
+        let mut blk = DataBlock::F64 {
+            ts: vec![1, 2, 3, 4, 5], val: vec![1.0, 2.0, 3.0, 4.0, 5.0],
+            enc: DataBlockEncoding::default()
+        };
+        blk.retain_time_range(&TimeRange::from((2, 4)));
+        assert_eq!(
+            blk,
+            DataBlock::F64 {
+                ts: vec![2, 3, 4],
+                val: vec![2.0, 3.0, 4.0],
+                enc: DataBlockEncoding::default()
+            }
+        );
+
+        #[rustfmt::skip]
+        blk.append(DataBlock::F64 {
+            ts: vec![7, 8], val: vec![7.0, 8.0],
+            enc: DataBlockEncoding::default()
+        });
+        assert_eq!(blk.ts(), &[2, 3, 4, 7, 8]);
+
+        blk.retain_time_range(&TimeRange::from((5, 6)));
+        assert!(blk.is_empty());
+
+        #[rustfmt::skip]
+        let mut blk = DataBlock::Str {
+            ts: vec![1, 2, 3], val: vec![mini_vec![1], mini_vec![2], mini_vec![3]],
+            enc: DataBlockEncoding::default()
+        };
+        blk.retain_time_range(&TimeRange::from((3, 10)));
+        assert_eq!(
+            blk,
+            DataBlock::Str {
+                ts: vec![3],
+                val: vec![mini_vec![3]],
+                enc: DataBlockEncoding::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_data_block_split_off() {
+        #[rustfmt::skip]
+        let mut blk = DataBlock::I64 {
+            ts: vec![1, 2, 3, 4], val: vec![1, 2, 3, 4],
+            enc: DataBlockEncoding::default()
+        };
+        let tail = blk.split_off(3);
+        assert_eq!(blk.ts(), &[1, 2, 3]);
+        assert_eq!(
+            tail,
+            DataBlock::I64 {
+                ts: vec![4],
+                val: vec![4],
+                enc: DataBlockEncoding::default()
+            }
+        );
+
+        let tail = blk.split_off(3);
+        assert!(tail.is_empty());
+        let tail = blk.split_off(0);
+        assert!(blk.is_empty());
+        assert_eq!(tail.ts(), &[1, 2, 3]);
+    }
 }
@@ -0,0 +1,230 @@
+use std::{
+    mem::size_of,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use metrics::{incr_block_cache_hit, incr_block_cache_miss};
+use moka::sync::Cache;
+use once_cell::sync::OnceCell;
+use trace::{info, warn};
+
+use crate::tsm::{DataBlock, Index};
+
+const INDEX_CACHE_TYPE: &str = "index";
+const BLOCK_CACHE_TYPE: &str = "block";
+
+static INSTANCE: OnceCell<BlockCache> = OnceCell::new();
+
+/// Returns the block cache shared by the node,
+/// the cache is disabled if `init_block_cache` is not called.
+pub fn get_block_cache() -> &'static BlockCache {
+    INSTANCE.get_or_init(|| BlockCache::new(0))
+}
+
+/// Initializes the block cache shared by the node with the capacity in bytes.
+pub fn init_block_cache(capacity: u64) {
+    if INSTANCE.set(BlockCache::new(capacity)).is_err() {
+        warn!(
+            "block cache has been initialized, ignore the capacity {}",
+            capacity
+        );
+    } else {
+        info!("block cache initialized, capacity: {}", capacity);
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+enum CacheKey {
+    /// The index of the tsm file.
+    Index(PathBuf),
+    /// The data block at the offset of the tsm file.
+    Block(PathBuf, u64),
+}
+
+impl CacheKey {
+    fn path(&self) -> &Path {
+        match self {
+            Self::Index(path) | Self::Block(path, _) => path,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum CacheValue {
+    Index(Arc<Index>),
+    Block(Arc<DataBlock>),
+}
+
+impl CacheValue {
+    /// Returns the estimated memory size in bytes.
+    fn weight(&self) -> u32 {
+        let size = match self {
+            Self::Index(index) => {
+                index.data().len() + (index.field_ids().len() + index.offsets().len()) * 8
+            }
+            Self::Block(block) => data_block_size(block),
+        };
+        size.try_into().unwrap_or(u32::MAX)
+    }
+}
+
+fn data_block_size(block: &DataBlock) -> usize {
+    let ts_size = block.len() * size_of::<i64>();
+    let val_size = match block {
+        DataBlock::U64 { val, .. } => val.len() * size_of::<u64>(),
+        DataBlock::I64 { val, .. } => val.len() * size_of::<i64>(),
+        DataBlock::F64 { val, .. } => val.len() * size_of::<f64>(),
+        DataBlock::Str { val, .. } => val.iter().map(|v| v.len()).sum(),
+        DataBlock::Bool { val, .. } => val.len(),
+    };
+    ts_size + val_size
+}
+
+/// A size-bounded cache of the indexes and the decoded data blocks of tsm files,
+/// keyed by the path of tsm files.
+///
+/// Data blocks are cached without excluding tombstones, so deleting data does not
+/// invalidate the cache, while the tsm files rewritten or deleted must be invalidated
+/// by `invalidate_file`, and the directories of the dropped or replaced vnodes must be
+/// invalidated by `invalidate_dir`, as the paths of tsm files may be reused.
+pub struct BlockCache {
+    /// None if the cache is disabled.
+    cache: Option<Cache<CacheKey, CacheValue>>,
+}
+
+impl BlockCache {
+    pub fn new(capacity: u64) -> Self {
+        let cache = if capacity == 0 {
+            None
+        } else {
+            Some(
+                Cache::builder()
+                    .max_capacity(capacity)
+                    .weigher(|_: &CacheKey, v: &CacheValue| v.weight())
+                    .support_invalidation_closures()
+                    .build(),
+            )
+        };
+
+        Self { cache }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.cache.is_some()
+    }
+
+    pub fn get_index(&self, path: &Path) -> Option<Arc<Index>> {
+        let cache = self.cache.as_ref()?;
+        match cache.get(&CacheKey::Index(path.to_path_buf())) {
+            Some(CacheValue::Index(index)) => {
+                incr_block_cache_hit(INDEX_CACHE_TYPE);
+                Some(index)
+            }
+            _ => {
+                incr_block_cache_miss(INDEX_CACHE_TYPE);
+                None
+            }
+        }
+    }
+
+    pub fn insert_index(&self, path: &Path, index: Arc<Index>) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.insert(
+                CacheKey::Index(path.to_path_buf()),
+                CacheValue::Index(index),
+            );
+        }
+    }
+
+    pub fn get_block(&self, path: &Path, offset: u64) -> Option<Arc<DataBlock>> {
+        let cache = self.cache.as_ref()?;
+        match cache.get(&CacheKey::Block(path.to_path_buf(), offset)) {
+            Some(CacheValue::Block(block)) => {
+                incr_block_cache_hit(BLOCK_CACHE_TYPE);
+                Some(block)
+            }
+            _ => {
+                incr_block_cache_miss(BLOCK_CACHE_TYPE);
+                None
+            }
+        }
+    }
+
+    pub fn insert_block(&self, path: &Path, offset: u64, block: Arc<DataBlock>) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.insert(
+                CacheKey::Block(path.to_path_buf(), offset),
+                CacheValue::Block(block),
+            );
+        }
+    }
+
+    /// Invalidates the index and data blocks of the tsm file.
+    pub fn invalidate_file(&self, path: &Path) {
+        if let Some(cache) = self.cache.as_ref() {
+            let path = path.to_path_buf();
+            if let Err(e) = cache.invalidate_entries_if(move |k, _| k.path() == path) {
+                warn!("failed to invalidate block cache: {:?}", e);
+            }
+        }
+    }
+
+    /// Invalidates the indexes and data blocks of all the tsm files under the directory.
+    pub fn invalidate_dir(&self, dir: &Path) {
+        if let Some(cache) = self.cache.as_ref() {
+            let dir = dir.to_path_buf();
+            if let Err(e) = cache.invalidate_entries_if(move |k, _| k.path().starts_with(&dir)) {
+                warn!("failed to invalidate block cache: {:?}", e);
+            }
+        }
+    }
+
+    pub fn entry_count(&self) -> u64 {
+        self.cache.as_ref().map_or(0, |c| c.entry_count())
+    }
+
+    pub fn weighted_size(&self) -> u64 {
+        self.cache.as_ref().map_or(0, |c| c.weighted_size())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::Path, sync::Arc};
+
+    use super::BlockCache;
+    use crate::tsm::{codec::DataBlockEncoding, DataBlock};
+
+    #[test]
+    fn test_block_cache() {
+        let block = Arc::new(DataBlock::I64 {
+            ts: vec![1, 2, 3],
+            val: vec![4, 5, 6],
+            enc: DataBlockEncoding::default(),
+        });
+        let (path_1, path_2) = (Path::new("/tmp/_000001.tsm"), Path::new("/tmp/_000002.tsm"));
+        let path_3 = Path::new("/tmp/vnode_1/_000001.tsm");
+
+        let disabled = BlockCache::new(0);
+        disabled.insert_block(path_1, 5, block.clone());
+        assert!(disabled.get_block(path_1, 5).is_none());
+
+        let cache = BlockCache::new(1024 * 1024);
+        cache.insert_block(path_1, 5, block.clone());
+        cache.insert_block(path_2, 5, block.clone());
+        assert_eq!(cache.get_block(path_1, 5).as_deref(), Some(block.as_ref()));
+        assert!(cache.get_block(path_1, 6).is_none());
+
+        cache.invalidate_file(path_1);
+        assert!(cache.get_block(path_1, 5).is_none());
+        assert!(cache.get_block(path_2, 5).is_some());
+
+        cache.insert_block(path_3, 5, block.clone());
+        cache.invalidate_dir(Path::new("/tmp/vnode_1"));
+        assert!(cache.get_block(path_3, 5).is_none());
+        assert!(cache.get_block(path_2, 5).is_some());
+        cache.invalidate_dir(Path::new("/tmp"));
+        assert!(cache.get_block(path_2, 5).is_none());
+    }
+}
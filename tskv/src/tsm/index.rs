@@ -7,13 +7,15 @@ use crate::{
     error::{Error, Result},
     tseries_family::TimeRange,
     tsm::{
-        BlockMetaIterator, WriteTsmError, WriteTsmResult, BLOCK_META_SIZE, FOOTER_SIZE,
-        INDEX_META_SIZE,
+        block_meta_size, BlockMetaIterator, WriteTsmError, WriteTsmResult, BLOCK_META_SIZE,
+        BLOCK_META_SIZE_V2, FOOTER_SIZE, INDEX_META_SIZE, TSM_VERSION_V1,
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct Index {
+    /// Version of the TSM file
+    version: u8,
     /// In-memory index-block data
     ///
     /// ```text
@@ -33,14 +35,26 @@ pub struct Index {
 
 impl Index {
     #[inline(always)]
-    pub fn new(data: Vec<u8>, field_ids: Vec<FieldId>, offsets: Vec<u64>) -> Self {
+    pub fn new(version: u8, data: Vec<u8>, field_ids: Vec<FieldId>, offsets: Vec<u64>) -> Self {
         Self {
+            version,
             data,
             field_ids,
             offsets,
         }
     }
 
+    #[inline(always)]
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the size of a block meta in `data`.
+    #[inline(always)]
+    pub fn block_meta_size(&self) -> usize {
+        block_meta_size(self.version).unwrap_or(BLOCK_META_SIZE)
+    }
+
     #[inline(always)]
     pub fn data(&self) -> &[u8] {
         self.data.as_slice()
@@ -114,7 +128,8 @@ impl IndexMeta {
         }
         let first_blk_beg = self.index_ref.offsets()[self.index_idx] as usize + INDEX_META_SIZE;
         let min_ts = decode_be_i64(&self.index_ref.data[first_blk_beg..first_blk_beg + 8]);
-        let last_blk_beg = first_blk_beg + BLOCK_META_SIZE * (self.block_count as usize - 1);
+        let last_blk_beg =
+            first_blk_beg + self.index_ref.block_meta_size() * (self.block_count as usize - 1);
         let max_ts = decode_be_i64(&self.index_ref.data[last_blk_beg + 8..last_blk_beg + 16]);
         (min_ts, max_ts)
     }
@@ -228,6 +243,29 @@ impl BlockMeta {
     pub fn val_off(&self) -> u64 {
         decode_be_u64(&self.index_ref.data()[self.block_offset + 36..self.block_offset + 44])
     }
+
+    /// Returns the statistics of values in this block,
+    /// None if the block is not numeric or the TSM file is written before `TSM_VERSION_V2`.
+    pub fn statistics(&self) -> Option<BlockStatistics> {
+        if self.index_ref.version() == TSM_VERSION_V1 {
+            return None;
+        }
+        let beg = self.block_offset + BLOCK_META_SIZE;
+        decode_statistics(&self.index_ref.data()[beg..self.block_offset + BLOCK_META_SIZE_V2]).0
+    }
+
+    /// Returns the number of null values in this block,
+    /// None if the TSM file is written before `TSM_VERSION_V2`.
+    pub fn null_count(&self) -> Option<u32> {
+        if self.index_ref.version() == TSM_VERSION_V1 {
+            return None;
+        }
+        let beg = self.block_offset + BLOCK_META_SIZE;
+        Some(
+            decode_statistics(&self.index_ref.data()[beg..self.block_offset + BLOCK_META_SIZE_V2])
+                .1,
+        )
+    }
 }
 
 impl Display for BlockMeta {
@@ -267,7 +305,7 @@ pub(crate) fn get_data_block_meta_unchecked(
     field_id: FieldId,
     field_type: ValueType,
 ) -> BlockMeta {
-    let base = index_offset + INDEX_META_SIZE + block_idx * BLOCK_META_SIZE;
+    let base = index_offset + INDEX_META_SIZE + block_idx * index.block_meta_size();
     BlockMeta::new(index, field_id, field_type, base)
 }
 
@@ -314,21 +352,36 @@ pub(crate) struct BlockEntry {
     pub offset: u64,
     pub size: u64,
     pub val_offset: u64,
+    pub statistics: Option<BlockStatistics>,
+    pub null_count: u32,
 }
 
 impl BlockEntry {
+    /// Encodes the block entry of the latest TSM version.
     pub(crate) fn encode(&self, buf: &mut [u8]) {
-        assert!(buf.len() >= BLOCK_META_SIZE);
+        assert!(buf.len() >= BLOCK_META_SIZE_V2);
         buf[0..8].copy_from_slice(&self.min_ts.to_be_bytes()[..]);
         buf[8..16].copy_from_slice(&self.max_ts.to_be_bytes()[..]);
         buf[16..20].copy_from_slice(&self.count.to_be_bytes()[..]);
         buf[20..28].copy_from_slice(&self.offset.to_be_bytes()[..]);
         buf[28..36].copy_from_slice(&self.size.to_be_bytes()[..]);
         buf[36..44].copy_from_slice(&self.val_offset.to_be_bytes()[..]);
+        encode_statistics(
+            self.statistics.as_ref(),
+            self.null_count,
+            &mut buf[BLOCK_META_SIZE..BLOCK_META_SIZE_V2],
+        );
     }
 
-    pub(crate) fn decode(data: &[u8]) -> Self {
+    /// Decodes the block entry of the TSM version.
+    pub(crate) fn decode(data: &[u8], version: u8) -> Self {
         assert!(data.len() >= BLOCK_META_SIZE);
+        let (statistics, null_count) = if version == TSM_VERSION_V1 {
+            (None, 0)
+        } else {
+            assert!(data.len() >= BLOCK_META_SIZE_V2);
+            decode_statistics(&data[BLOCK_META_SIZE..BLOCK_META_SIZE_V2])
+        };
         Self {
             min_ts: decode_be_i64(&data[0..8]),
             max_ts: decode_be_i64(&data[8..16]),
@@ -336,6 +389,101 @@ impl BlockEntry {
             offset: decode_be_u64(&data[20..28]),
             size: decode_be_u64(&data[28..36]),
             val_offset: decode_be_u64(&data[36..44]),
+            statistics,
+            null_count,
         }
     }
 }
+
+/// Statistics of the values in a numeric data block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockStatistics {
+    F64 { min: f64, max: f64, sum: f64 },
+    I64 { min: i64, max: i64, sum: i64 },
+    U64 { min: u64, max: u64, sum: u64 },
+}
+
+impl BlockStatistics {
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::F64 { .. } => ValueType::Float,
+            Self::I64 { .. } => ValueType::Integer,
+            Self::U64 { .. } => ValueType::Unsigned,
+        }
+    }
+}
+
+impl Display for BlockStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::F64 { min, max, sum } => write!(f, "Min: {}, Max: {}, Sum: {}", min, max, sum),
+            Self::I64 { min, max, sum } => write!(f, "Min: {}, Max: {}, Sum: {}", min, max, sum),
+            Self::U64 { min, max, sum } => write!(f, "Min: {}, Max: {}, Sum: {}", min, max, sum),
+        }
+    }
+}
+
+/// Encodes the statistics of a block meta:
+///
+/// ```text
+/// +------------+---------+
+/// | value_type | 1 bytes |
+/// | min        | 8 bytes |
+/// | max        | 8 bytes |
+/// | sum        | 8 bytes |
+/// | null_count | 4 bytes |
+/// +------------+---------+
+/// ```
+///
+/// The value_type is `ValueType::Unknown` if the block has no statistics.
+fn encode_statistics(statistics: Option<&BlockStatistics>, null_count: u32, buf: &mut [u8]) {
+    let (value_type, min, max, sum) = match statistics {
+        Some(BlockStatistics::F64 { min, max, sum }) => (
+            ValueType::Float,
+            min.to_be_bytes(),
+            max.to_be_bytes(),
+            sum.to_be_bytes(),
+        ),
+        Some(BlockStatistics::I64 { min, max, sum }) => (
+            ValueType::Integer,
+            min.to_be_bytes(),
+            max.to_be_bytes(),
+            sum.to_be_bytes(),
+        ),
+        Some(BlockStatistics::U64 { min, max, sum }) => (
+            ValueType::Unsigned,
+            min.to_be_bytes(),
+            max.to_be_bytes(),
+            sum.to_be_bytes(),
+        ),
+        None => (ValueType::Unknown, [0_u8; 8], [0_u8; 8], [0_u8; 8]),
+    };
+    buf[0] = value_type.into();
+    buf[1..9].copy_from_slice(&min[..]);
+    buf[9..17].copy_from_slice(&max[..]);
+    buf[17..25].copy_from_slice(&sum[..]);
+    buf[25..29].copy_from_slice(&null_count.to_be_bytes()[..]);
+}
+
+fn decode_statistics(data: &[u8]) -> (Option<BlockStatistics>, u32) {
+    let statistics = match ValueType::from(data[0]) {
+        ValueType::Float => Some(BlockStatistics::F64 {
+            min: f64::from_bits(decode_be_u64(&data[1..9])),
+            max: f64::from_bits(decode_be_u64(&data[9..17])),
+            sum: f64::from_bits(decode_be_u64(&data[17..25])),
+        }),
+        ValueType::Integer => Some(BlockStatistics::I64 {
+            min: decode_be_i64(&data[1..9]),
+            max: decode_be_i64(&data[9..17]),
+            sum: decode_be_i64(&data[17..25]),
+        }),
+        ValueType::Unsigned => Some(BlockStatistics::U64 {
+            min: decode_be_u64(&data[1..9]),
+            max: decode_be_u64(&data[9..17]),
+            sum: decode_be_u64(&data[17..25]),
+        }),
+        _ => None,
+    };
+
+    (statistics, decode_be_u32(&data[25..29]))
+}
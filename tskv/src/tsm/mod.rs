@@ -1,4 +1,5 @@
 mod block;
+mod block_cache;
 pub mod codec;
 mod index;
 mod reader;
@@ -6,6 +7,7 @@ mod tombstone;
 mod writer;
 
 pub use block::*;
+pub use block_cache::{get_block_cache, init_block_cache, BlockCache};
 pub use index::*;
 pub use reader::*;
 use snafu::Snafu;
@@ -18,10 +20,26 @@ pub(crate) const MAX_BLOCK_VALUES: u32 = 1000;
 const HEADER_SIZE: usize = 5;
 const INDEX_META_SIZE: usize = 11;
 const BLOCK_META_SIZE: usize = 44;
+const BLOCK_STATISTICS_SIZE: usize = 29;
+const BLOCK_META_SIZE_V2: usize = BLOCK_META_SIZE + BLOCK_STATISTICS_SIZE; // 73
 const BLOOM_FILTER_SIZE: usize = 64;
 const BLOOM_FILTER_BITS: u64 = 512; // 64 * 8
 const FOOTER_SIZE: usize = BLOOM_FILTER_SIZE + 8; // 72
 
+// TSM_VERSION_V2 appends the statistics of values to each block meta.
+pub(crate) const TSM_VERSION_V1: u8 = 1;
+pub(crate) const TSM_VERSION_V2: u8 = 2;
+
+/// Returns the size of a block meta in TSM files of the version,
+/// or None if the version is unknown.
+pub(crate) fn block_meta_size(version: u8) -> Option<usize> {
+    match version {
+        TSM_VERSION_V1 => Some(BLOCK_META_SIZE),
+        TSM_VERSION_V2 => Some(BLOCK_META_SIZE_V2),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Snafu)]
 pub enum TsmError {}
 
@@ -16,14 +16,15 @@ use crate::{
     file_utils,
     tseries_family::TimeRange,
     tsm::{
+        block_meta_size,
         codec::{
             get_bool_codec, get_encoding, get_f64_codec, get_i64_codec, get_str_codec,
             get_ts_codec, get_u64_codec, DataBlockEncoding,
         },
-        get_data_block_meta_unchecked, get_index_meta_unchecked,
+        get_block_cache, get_data_block_meta_unchecked, get_index_meta_unchecked,
         tombstone::TsmTombstone,
-        BlockEntry, BlockMeta, DataBlock, Index, IndexEntry, IndexMeta, BLOCK_META_SIZE,
-        FOOTER_SIZE, INDEX_META_SIZE, MAX_BLOCK_VALUES,
+        BlockEntry, BlockMeta, DataBlock, Index, IndexEntry, IndexMeta, BLOCK_META_SIZE_V2,
+        FOOTER_SIZE, HEADER_SIZE, INDEX_META_SIZE, MAX_BLOCK_VALUES,
     },
 };
 
@@ -53,8 +54,10 @@ impl From<ReadTsmError> for Error {
 /// Disk-based index reader
 pub struct IndexFile {
     reader: Arc<AsyncFile>,
+    version: u8,
     idx_meta_buf: [u8; INDEX_META_SIZE],
-    blk_meta_buf: [u8; BLOCK_META_SIZE],
+    blk_meta_buf: [u8; BLOCK_META_SIZE_V2],
+    blk_meta_size: usize,
 
     index_offset: u64,
     pos: u64,
@@ -65,6 +68,8 @@ pub struct IndexFile {
 
 impl IndexFile {
     pub(crate) async fn open(reader: Arc<AsyncFile>) -> ReadTsmResult<Self> {
+        let version = read_version(&reader).await?;
+        let blk_meta_size = check_block_meta_size(version)?;
         let file_len = reader.len();
         let mut footer = [0_u8; 8];
         reader
@@ -74,8 +79,10 @@ impl IndexFile {
         let index_offset = u64::from_be_bytes(footer);
         Ok(Self {
             reader,
+            version,
             idx_meta_buf: [0_u8; INDEX_META_SIZE],
-            blk_meta_buf: [0_u8; BLOCK_META_SIZE],
+            blk_meta_buf: [0_u8; BLOCK_META_SIZE_V2],
+            blk_meta_size,
             index_offset,
             pos: index_offset,
             end_pos: file_len - FOOTER_SIZE as u64,
@@ -107,11 +114,11 @@ impl IndexFile {
             return Ok(None);
         }
         self.reader
-            .read_at(self.pos, &mut self.blk_meta_buf[..])
+            .read_at(self.pos, &mut self.blk_meta_buf[..self.blk_meta_size])
             .await
             .context(IOSnafu)?;
-        self.pos += BLOCK_META_SIZE as u64;
-        let entry = BlockEntry::decode(&self.blk_meta_buf);
+        self.pos += self.blk_meta_size as u64;
+        let entry = BlockEntry::decode(&self.blk_meta_buf, self.version);
         self.index_block_idx += 1;
 
         Ok(Some(entry))
@@ -122,6 +129,7 @@ pub async fn print_tsm_statistics(path: impl AsRef<Path>, show_tombstone: bool)
     let reader = TsmReader::open(path).await.unwrap();
     let mut points_cnt = 0_usize;
     println!("============================================================");
+    println!("Version: {}", reader.version());
     for idx in reader.index_iterator() {
         let tr = idx.time_range();
         let mut buffer = String::with_capacity(1024);
@@ -129,10 +137,17 @@ pub async fn print_tsm_statistics(path: impl AsRef<Path>, show_tombstone: bool)
         for blk in idx.block_iterator() {
             buffer.push_str(
                 format!(
-                    "\tBlock | FieldId: {}, MinTime: {}, MaxTime: {}, Count: {}, Offset: {}, Size: {}, ValOffset: {}\n",
+                    "\tBlock | FieldId: {}, MinTime: {}, MaxTime: {}, Count: {}, Offset: {}, Size: {}, ValOffset: {}",
                     blk.field_id(), blk.min_ts(), blk.max_ts(), blk.count(), blk.offset(), blk.size(), blk.val_off()
                 ).as_str()
             );
+            if let Some(stats) = blk.statistics() {
+                buffer.push_str(format!(", {}", stats).as_str());
+            }
+            if let Some(null_count) = blk.null_count() {
+                buffer.push_str(format!(", NullCount: {}", null_count).as_str());
+            }
+            buffer.push('\n');
             points_cnt += blk.count() as usize;
             idx_points_cnt += blk.count() as usize;
         }
@@ -174,6 +189,24 @@ pub async fn print_tsm_statistics(path: impl AsRef<Path>, show_tombstone: bool)
     println!("PointsCount: {}", points_cnt);
 }
 
+/// Reads the version in the header of the TSM file.
+async fn read_version(reader: &AsyncFile) -> ReadTsmResult<u8> {
+    if reader.len() < HEADER_SIZE as u64 {
+        return Err(ReadTsmError::Invalid {
+            reason: format!("TSM file size less than HEADER_SIZE({})", HEADER_SIZE),
+        });
+    }
+    let mut header = [0_u8; HEADER_SIZE];
+    reader.read_at(0, &mut header).await.context(IOSnafu)?;
+    Ok(header[HEADER_SIZE - 1])
+}
+
+fn check_block_meta_size(version: u8) -> ReadTsmResult<usize> {
+    block_meta_size(version).ok_or_else(|| ReadTsmError::Invalid {
+        reason: format!("unknown TSM file version({})", version),
+    })
+}
+
 pub async fn load_index(reader: Arc<AsyncFile>) -> ReadTsmResult<Index> {
     let len = reader.len();
     if len < FOOTER_SIZE as u64 {
@@ -181,6 +214,8 @@ pub async fn load_index(reader: Arc<AsyncFile>) -> ReadTsmResult<Index> {
             reason: format!("TSM file size less than FOOTER_SIZE({})", FOOTER_SIZE),
         });
     }
+    let version = read_version(&reader).await?;
+    let blk_meta_size = check_block_meta_size(version)?;
     let mut buf = [0u8; 8];
 
     // Read index data offset
@@ -203,7 +238,7 @@ pub async fn load_index(reader: Arc<AsyncFile>) -> ReadTsmResult<Index> {
     while pos < data_len {
         offsets.push(pos as u64);
         field_ids.push(decode_be_u64(&data[pos..pos + 8]));
-        pos += INDEX_META_SIZE + BLOCK_META_SIZE * decode_be_u16(&data[pos + 9..pos + 11]) as usize;
+        pos += INDEX_META_SIZE + blk_meta_size * decode_be_u16(&data[pos + 9..pos + 11]) as usize;
     }
 
     // Sort by field id
@@ -219,7 +254,7 @@ pub async fn load_index(reader: Arc<AsyncFile>) -> ReadTsmResult<Index> {
         offsets.swap(i, j);
     }
 
-    Ok(Index::new(data, field_ids, offsets))
+    Ok(Index::new(version, data, field_ids, offsets))
 }
 
 /// Memory-based index reader
@@ -236,6 +271,22 @@ impl IndexReader {
         })
     }
 
+    /// Reads the index of the tsm file at the path through the block cache.
+    pub async fn open_cached(path: &Path, reader: Arc<AsyncFile>) -> Result<Self> {
+        let block_cache = get_block_cache();
+        if let Some(index_ref) = block_cache.get_index(path) {
+            return Ok(Self { index_ref });
+        }
+
+        let index_reader = Self::open(reader).await?;
+        block_cache.insert_index(path, index_reader.index_ref.clone());
+        Ok(index_reader)
+    }
+
+    pub fn version(&self) -> u8 {
+        self.index_ref.version()
+    }
+
     pub fn iter(&self) -> IndexIterator {
         IndexIterator::new(self.index_ref.clone(), self.index_ref.offsets().len(), 0)
     }
@@ -334,13 +385,14 @@ impl BlockMetaIterator {
     /// Set iterator start & end position by time range
     pub(crate) fn filter_time_range(&mut self, time_range: &TimeRange) {
         let TimeRange { min_ts, max_ts } = *time_range;
+        let blk_meta_size = self.index_ref.block_meta_size();
         let base = self.index_offset + INDEX_META_SIZE;
-        let sli = &self.index_ref.data()[base..base + self.block_count as usize * BLOCK_META_SIZE];
+        let sli = &self.index_ref.data()[base..base + self.block_count as usize * blk_meta_size];
         let mut pos = 0_usize;
         let mut idx = 0_usize;
         while pos < sli.len() {
             if min_ts > decode_be_i64(&sli[pos + 8..pos + 16]) {
-                pos += BLOCK_META_SIZE;
+                pos += blk_meta_size;
                 idx += 1;
             } else {
                 // First data block in time range
@@ -354,7 +406,7 @@ impl BlockMetaIterator {
         }
         self.block_meta_idx = idx;
         self.block_meta_idx_end = idx;
-        pos += BLOCK_META_SIZE;
+        pos += blk_meta_size;
         while pos < sli.len() {
             if max_ts < decode_be_i64(&sli[pos..pos + 8]) {
                 return;
@@ -363,7 +415,7 @@ impl BlockMetaIterator {
                 return;
             } else {
                 self.block_meta_idx_end += 1;
-                pos += BLOCK_META_SIZE;
+                pos += blk_meta_size;
             }
         }
     }
@@ -384,13 +436,14 @@ impl Iterator for BlockMetaIterator {
             self.field_type,
         ));
         self.block_meta_idx += 1;
-        self.block_offset += BLOCK_META_SIZE;
+        self.block_offset += self.index_ref.block_meta_size();
         ret
     }
 }
 
 #[derive(Clone)]
 pub struct TsmReader {
+    path: PathBuf,
     reader: Arc<AsyncFile>,
     index_reader: Arc<IndexReader>,
     tombstone: Arc<RwLock<TsmTombstone>>,
@@ -401,16 +454,22 @@ impl TsmReader {
         let path = tsm_path.as_ref().to_path_buf();
         let tsm_id = file_utils::get_tsm_file_id_by_path(&path)?;
         let tsm = Arc::new(file_manager::open_file(tsm_path).await?);
-        let tsm_idx = IndexReader::open(tsm.clone()).await?;
+        let tsm_idx = IndexReader::open_cached(&path, tsm.clone()).await?;
         let tombstone_path = path.parent().unwrap_or_else(|| Path::new("/"));
         let tombstone = TsmTombstone::open(tombstone_path, tsm_id).await?;
         Ok(Self {
+            path,
             reader: tsm,
             index_reader: Arc::new(tsm_idx),
             tombstone: Arc::new(RwLock::new(tombstone)),
         })
     }
 
+    /// Returns the version of the TSM file, block metas have statistics since version 2.
+    pub fn version(&self) -> u8 {
+        self.index_reader.version()
+    }
+
     pub fn index_iterator(&self) -> IndexIterator {
         self.index_reader.iter()
     }
@@ -419,24 +478,42 @@ impl TsmReader {
         self.index_reader.iter_opt(field_id)
     }
 
-    /// Returns a DataBlock without tombstone
-    pub async fn get_data_block(&self, block_meta: &BlockMeta) -> ReadTsmResult<DataBlock> {
-        let blk_range = (block_meta.min_ts(), block_meta.max_ts());
-        let mut buf = vec![0_u8; block_meta.size() as usize];
-        let mut blk = read_data_block(
-            self.reader.clone(),
-            &mut buf,
-            block_meta.field_type(),
-            block_meta.offset(),
-            block_meta.val_off(),
-        )
-        .await?;
-        self.tombstone
-            .read()
-            .data_block_exclude_tombstones(block_meta.field_id(), &mut blk);
+    /// Returns a DataBlock without tombstone, the block is shared with the block cache
+    /// and is copied only if some tombstones must be excluded from it.
+    pub async fn get_data_block(&self, block_meta: &BlockMeta) -> ReadTsmResult<Arc<DataBlock>> {
+        let block_cache = get_block_cache();
+        let mut blk = match block_cache.get_block(&self.path, block_meta.offset()) {
+            Some(blk) => blk,
+            None => {
+                let mut buf = vec![0_u8; block_meta.size() as usize];
+                let blk = read_data_block(
+                    self.reader.clone(),
+                    &mut buf,
+                    block_meta.field_type(),
+                    block_meta.offset(),
+                    block_meta.val_off(),
+                )
+                .await?;
+                let blk = Arc::new(blk);
+                block_cache.insert_block(&self.path, block_meta.offset(), blk.clone());
+                blk
+            }
+        };
+        if self.get_block_tombstone_time_ranges(block_meta).is_some() {
+            self.tombstone
+                .read()
+                .data_block_exclude_tombstones(block_meta.field_id(), Arc::make_mut(&mut blk));
+        }
         Ok(blk)
     }
 
+    /// Returns a DataBlock without tombstone owned by the caller, copied from the block
+    /// cache if it's shared.
+    pub async fn get_data_block_owned(&self, block_meta: &BlockMeta) -> ReadTsmResult<DataBlock> {
+        let blk = self.get_data_block(block_meta).await?;
+        Ok(Arc::try_unwrap(blk).unwrap_or_else(|blk| blk.as_ref().clone()))
+    }
+
     // Reads raw data from file and returns the read data size.
     pub async fn get_raw_data(
         &self,
@@ -692,7 +769,7 @@ pub mod tsm_reader_tests {
         let mut read_data: HashMap<FieldId, Vec<DataBlock>> = HashMap::new();
         for idx in reader.index_iterator() {
             for blk in idx.block_iterator() {
-                let data_blk = reader.get_data_block(&blk).await.unwrap();
+                let data_blk = reader.get_data_block_owned(&blk).await.unwrap();
                 read_data.entry(idx.field_id()).or_default().push(data_blk);
             }
         }
@@ -740,7 +817,7 @@ pub mod tsm_reader_tests {
         let mut read_data: HashMap<FieldId, Vec<DataBlock>> = HashMap::new();
         for idx in reader.index_iterator_opt(2) {
             for blk in idx.block_iterator_opt(&TimeRange::from(time_range)) {
-                let data_blk = reader.get_data_block(&blk).await.unwrap();
+                let data_blk = reader.get_data_block_owned(&blk).await.unwrap();
                 read_data.entry(idx.field_id()).or_default().push(data_blk);
             }
         }
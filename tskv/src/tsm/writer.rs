@@ -14,8 +14,9 @@ use crate::{
     file_system::{file_manager, FileCursor, IFile},
     file_utils,
     tsm::{
-        BlockEntry, BlockMeta, BlockMetaIterator, DataBlock, Index, IndexEntry, IndexMeta,
-        BLOCK_META_SIZE, BLOOM_FILTER_BITS, INDEX_META_SIZE, MAX_BLOCK_VALUES,
+        get_block_cache, BlockEntry, BlockMeta, BlockMetaIterator, DataBlock, Index, IndexEntry,
+        IndexMeta, BLOCK_META_SIZE_V2, BLOOM_FILTER_BITS, INDEX_META_SIZE, MAX_BLOCK_VALUES,
+        TSM_VERSION_V2,
     },
 };
 
@@ -49,6 +50,15 @@ use crate::{
 // │ 8 bytes │1 byte│2 bytes│ 8 bytes │ 8 bytes │4 bytes │8 bytes │8 bytes │8 bytes│
 // └─────────┴──────┴───────┴─────────┴─────────┴────────┴────────┴────────┴───────┘
 //
+// Since version 2, each block meta in the index is followed by the statistics of values.
+//
+// ┌───────────────────────────────────────────────────┐
+// │                   Statistics                      │
+// ├──────────┬─────────┬─────────┬─────────┬──────────┤
+// │ValueType │   Min   │   Max   │   Sum   │NullCount │
+// │  1 byte  │ 8 bytes │ 8 bytes │ 8 bytes │ 4 bytes  │
+// └──────────┴─────────┴─────────┴─────────┴──────────┘
+//
 // ┌─────────────────────────┐
 // │ Footer                  │
 // ├───────────────┬─────────┤
@@ -58,7 +68,7 @@ use crate::{
 
 const HEADER_LEN: u64 = 5;
 const TSM_MAGIC: [u8; 4] = 0x01346613_u32.to_be_bytes();
-const VERSION: [u8; 1] = [1];
+const VERSION: [u8; 1] = [TSM_VERSION_V2];
 
 pub type WriteTsmResult<T, E = WriteTsmError> = std::result::Result<T, E>;
 
@@ -133,7 +143,7 @@ impl IndexBuf {
     pub async fn write_to(&self, writer: &mut FileCursor) -> WriteTsmResult<usize> {
         let mut size = 0_usize;
 
-        let mut buf = vec![0_u8; BLOCK_META_SIZE];
+        let mut buf = vec![0_u8; BLOCK_META_SIZE_V2];
         for (_, idx) in self.buf.iter() {
             idx.encode(&mut buf[..INDEX_META_SIZE])?;
             writer
@@ -144,7 +154,7 @@ impl IndexBuf {
             for blk in idx.blocks.iter() {
                 blk.encode(&mut buf);
                 writer.write(&buf[..]).await.context(IOSnafu)?;
-                size += BLOCK_META_SIZE;
+                size += BLOCK_META_SIZE_V2;
             }
         }
 
@@ -334,6 +344,8 @@ impl TsmWriter {
         }
         self.writer.sync_data().await.context(IOSnafu)?;
         std::fs::rename(&self.tmp_path, &self.final_path).context(IOSnafu)?;
+        // The path may be used by a deleted tsm file
+        get_block_cache().invalidate_file(&self.final_path);
         self.finished = true;
         Ok(())
     }
@@ -399,6 +411,9 @@ async fn write_raw_data_to(
             offset,
             size: block.len() as u64,
             val_offset: offset + ts_block_len,
+            // Blocks copied from TSM files of version 1 have no statistics.
+            statistics: block_meta.statistics(),
+            null_count: block_meta.null_count().unwrap_or(0),
         },
     );
 
@@ -449,6 +464,9 @@ async fn write_block_to(
             offset,
             size: size as u64,
             val_offset: offset + ts_buf.len() as u64 + 4, // CRC32 is 4 bytes
+            statistics: block.statistics(),
+            // DataBlock only stores not null values
+            null_count: 0,
         },
     );
 
@@ -481,6 +499,7 @@ mod test {
         sync::Arc,
     };
 
+    use minivec::MiniVec;
     use models::{FieldId, ValueType};
 
     use crate::file_system::file_manager::{self, get_file_manager, FileManager};
@@ -488,8 +507,8 @@ mod test {
     use crate::{
         memcache::FieldVal,
         tsm::{
-            codec::DataBlockEncoding, new_tsm_writer, ColumnReader, DataBlock, IndexReader,
-            TsmReader, TsmWriter,
+            codec::DataBlockEncoding, new_tsm_writer, BlockStatistics, ColumnReader, DataBlock,
+            IndexReader, TsmReader, TsmWriter, TSM_VERSION_V2,
         },
     };
 
@@ -586,4 +605,37 @@ mod test {
         write_to_tsm(&dir, file_name, &data).await;
         check_tsm(dir.join(file_name), &data).await;
     }
+
+    #[tokio::test]
+    async fn test_tsm_write_statistics() {
+        #[rustfmt::skip]
+        let data = HashMap::from([
+            (1, vec![DataBlock::I64 { ts: vec![1, 2, 3], val: vec![-5, 10, 7], enc: DataBlockEncoding::default() }]),
+            (2, vec![DataBlock::Str { ts: vec![1, 2], val: vec![MiniVec::from("a"), MiniVec::from("b")], enc: DataBlockEncoding::default() }]),
+        ]);
+
+        let dir = Path::new(TEST_PATH).join("2");
+        let file_name = "_000001.tsm";
+        write_to_tsm(&dir, file_name, &data).await;
+        check_tsm(dir.join(file_name), &data).await;
+
+        let file = Arc::new(file_manager::open_file(dir.join(file_name)).await.unwrap());
+        let index = IndexReader::open(file).await.unwrap();
+        assert_eq!(index.version(), TSM_VERSION_V2);
+        for idx_meta in index.iter() {
+            let blk_meta = idx_meta.block_iterator().next().unwrap();
+            assert_eq!(blk_meta.null_count(), Some(0));
+            match idx_meta.field_id() {
+                1 => assert_eq!(
+                    blk_meta.statistics(),
+                    Some(BlockStatistics::I64 {
+                        min: -5,
+                        max: 10,
+                        sum: 12
+                    })
+                ),
+                _ => assert_eq!(blk_meta.statistics(), None),
+            }
+        }
+    }
 }
@@ -31,8 +31,10 @@ use std::{
 use models::{
     auth::user::{ROOT, ROOT_PWD},
     codec::Encoding,
+    ColumnId, SeriesId,
 };
 use protos::kv_service::{Meta, WritePointsRpcRequest};
+use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 use tokio::sync::{oneshot, RwLock};
 use trace::{debug, error, info, warn};
@@ -48,7 +50,7 @@ use crate::{
     record_file::{self, Record, RecordDataType, RecordDataVersion},
     tsm::{codec::get_str_codec, DecodeSnafu, EncodeSnafu},
     version_set::VersionSet,
-    TseriesFamilyId,
+    TimeRange, TseriesFamilyId,
 };
 
 const ENTRY_TYPE_LEN: usize = 1;
@@ -72,6 +74,39 @@ pub enum WalTask {
         // (seq_no, written_size)
         cb: oneshot::Sender<Result<(u64, usize)>>,
     },
+    Delete {
+        id: TseriesFamilyId,
+        /// Encoded [`WalDeleteSeries`].
+        data: Arc<Vec<u8>>,
+        tenant: Arc<Vec<u8>>,
+        // (seq_no, written_size)
+        cb: oneshot::Sender<Result<(u64, usize)>>,
+    },
+}
+
+/// A delete of the data of the series in a time range, saved as a
+/// [`WalEntryType::DeleteRange`] entry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WalDeleteSeries {
+    pub database: String,
+    pub series_ids: Vec<SeriesId>,
+    pub field_ids: Vec<ColumnId>,
+    pub min_ts: i64,
+    pub max_ts: i64,
+}
+
+impl WalDeleteSeries {
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self).map_err(|e| Error::Encode { source: e })
+    }
+
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        bincode::deserialize(buf).map_err(|e| Error::Decode { source: e })
+    }
+
+    pub fn time_range(&self) -> TimeRange {
+        TimeRange::new(self.min_ts, self.max_ts)
+    }
 }
 
 #[repr(u8)]
@@ -434,6 +469,7 @@ impl WalManager {
                                     password: None,
                                 }),
                                 points: dst[0].to_vec(),
+                                consistency_level: None,
                             };
                             engine.write_from_wal(id, req, seq).await.unwrap();
                         }
@@ -441,7 +477,12 @@ impl WalManager {
                             // TODO delete a memcache entry
                         }
                         WalEntryType::DeleteRange => {
-                            // TODO delete range in a memcache
+                            let delete = WalDeleteSeries::decode(e.data())?;
+                            let tenant =
+                                unsafe { String::from_utf8_unchecked(e.tenant().to_vec()) };
+                            engine
+                                .delete_series_from_wal(&tenant, e.vnode_id(), &delete)
+                                .await?;
                         }
                         _ => {}
                     };
@@ -466,6 +507,131 @@ impl WalManager {
     }
 }
 
+/// The position after an entry in the WAL files, the reading of the WAL of a vnode
+/// is continued from it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalCursor {
+    pub file_id: u64,
+    pub pos: u64,
+}
+
+/// A write or a delete of a vnode in the WAL.
+#[derive(Debug, Clone)]
+pub struct VnodeWalEntry {
+    pub seq: u64,
+    /// [`WalEntryType::Write`] or [`WalEntryType::DeleteRange`].
+    pub typ: WalEntryType,
+    pub tenant: String,
+    /// flatbuffers bytes ( models::Points ) of a write, or encoded [`WalDeleteSeries`].
+    pub data: Vec<u8>,
+    pub cursor: WalCursor,
+}
+
+/// Reads the writes and the deletes of the vnode with a sequence greater than `min_seq`
+/// from the WAL files in `dir`, stops after `max_size` bytes of data are read.
+///
+/// The reading starts at `cursor` if it's given and its file still exists, otherwise
+/// the files are scanned from the oldest one.
+///
+/// Returns [`Error::WalDeleted`] if the WAL after `min_seq` has been deleted.
+pub async fn read_vnode_wal(
+    dir: impl AsRef<Path>,
+    vnode_id: TseriesFamilyId,
+    min_seq: u64,
+    cursor: Option<WalCursor>,
+    max_size: usize,
+) -> Result<Vec<VnodeWalEntry>> {
+    let dir = dir.as_ref();
+    let mut file_ids: Vec<u64> = file_manager::list_file_names(dir)
+        .iter()
+        .filter_map(|name| file_utils::get_wal_file_id(name).ok())
+        .collect();
+    file_ids.sort_unstable();
+    let cursor = cursor.filter(|cursor| file_ids.contains(&cursor.file_id));
+
+    let decoder = get_str_codec(Encoding::Zstd);
+    let mut entries = Vec::new();
+    let mut size = 0;
+    let mut first_seq = None;
+    for id in file_ids {
+        if cursor.map_or(false, |cursor| id < cursor.file_id) {
+            continue;
+        }
+        let path = file_utils::make_wal_file(dir, id);
+        if !file_manager::try_exists(&path) {
+            continue;
+        }
+        let mut reader = WalReader::open(&path).await?;
+        if reader.is_empty() {
+            continue;
+        }
+        match cursor {
+            Some(cursor) if cursor.file_id == id => reader.seek(cursor.pos).await?,
+            _ => {
+                if reader.max_sequence != 0 && reader.max_sequence <= min_seq {
+                    first_seq.get_or_insert(reader.min_sequence);
+                    continue;
+                }
+            }
+        }
+
+        loop {
+            let entry = match reader.next_wal_entry().await {
+                Ok(Some(e)) => e,
+                // The last entry of the current WAL file may be being written.
+                Ok(None) | Err(Error::WalTruncated) => break,
+                Err(e) => return Err(e),
+            };
+            let seq = entry.seq();
+            first_seq.get_or_insert(seq);
+            if seq <= min_seq || entry.vnode_id() != vnode_id {
+                continue;
+            }
+
+            let data = match entry.typ {
+                WalEntryType::Write => {
+                    let mut dst = Vec::new();
+                    decoder
+                        .decode(entry.data(), &mut dst)
+                        .context(DecodeSnafu)?;
+                    match dst.first() {
+                        Some(points) => points.to_vec(),
+                        None => continue,
+                    }
+                }
+                WalEntryType::DeleteRange => entry.data().to_vec(),
+                _ => continue,
+            };
+            let tenant =
+                String::from_utf8(entry.tenant().to_vec()).map_err(|_| Error::ErrCharacterSet)?;
+            size += data.len();
+            entries.push(VnodeWalEntry {
+                seq,
+                typ: entry.typ,
+                tenant,
+                data,
+                cursor: WalCursor {
+                    file_id: id,
+                    pos: reader.pos(),
+                },
+            });
+            if size >= max_size {
+                return Ok(entries);
+            }
+        }
+    }
+
+    // The sequences are continuous, a gap before the oldest entry means it's deleted.
+    // The entries before the cursor have been checked when they were read.
+    if let (None, Some(first_seq)) = (cursor, first_seq) {
+        if first_seq > min_seq + 1 {
+            return Err(Error::WalDeleted { seq: min_seq });
+        }
+    }
+
+    Ok(entries)
+}
+
 pub struct WalReader {
     inner: record_file::Reader,
     /// Min write sequence in the wal file, may be 0 if wal file is new or
@@ -522,6 +688,16 @@ impl WalReader {
         Ok(Some(WalEntryBlock::new(data[0].into(), data)))
     }
 
+    /// Returns the position of the next entry to read.
+    pub fn pos(&self) -> u64 {
+        self.inner.pos() as u64
+    }
+
+    /// Moves to the position of an entry, e.g. [`WalCursor::pos`].
+    pub async fn seek(&mut self, pos: u64) -> Result<()> {
+        self.inner.seek(pos as usize).await
+    }
+
     pub fn path(&self) -> PathBuf {
         self.inner.path()
     }
@@ -576,8 +752,8 @@ mod test {
         kv_option::WalOptions,
         tsm::codec::get_str_codec,
         version_set::VersionSet,
-        wal::{self, WalEntryBlock, WalEntryType, WalManager, WalReader},
-        Error, Options, Result, TsKv,
+        wal::{self, WalDeleteSeries, WalEntryBlock, WalEntryType, WalManager, WalReader},
+        Error, Options, Result, TimeRange, TsKv,
     };
 
     fn random_write_data() -> Vec<u8> {
@@ -759,6 +935,100 @@ mod test {
         check_wal_files(dir, data_vec, true).await.unwrap();
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_read_vnode_wal() {
+        let dir = "/tmp/test/wal/5".to_string();
+        let _ = std::fs::remove_dir_all(dir.clone()); // Ignore errors
+        let mut global_config = get_config("../config/config.toml");
+        global_config.wal.path = dir.clone();
+        let wal_config = WalOptions::from(&global_config);
+
+        let gcs = GlobalSequenceContext::empty();
+        gcs.set_min_seq(6);
+        let mut mgr = WalManager::open(Arc::new(wal_config), gcs).await.unwrap();
+        let coder = get_str_codec(Encoding::Zstd);
+        let mut data_vec = Vec::new();
+        for seq in 1..11 {
+            let data = random_write_data();
+            let mut enc_points = Vec::new();
+            coder.encode(&[&data], &mut enc_points).unwrap();
+            data_vec.push(data);
+
+            // The writes of vnode 1 are at the odd sequences.
+            let vnode_id = 2 - seq % 2;
+            mgr.write(
+                WalEntryType::Write,
+                Arc::new(enc_points),
+                vnode_id,
+                Arc::new(b"cnosdb".to_vec()),
+            )
+            .await
+            .unwrap();
+            if seq == 4 {
+                // The files of the sequences less than 6 will be deleted.
+                mgr.roll_wal_file(1).await.unwrap();
+            }
+        }
+        let delete = WalDeleteSeries {
+            database: "db0".to_string(),
+            series_ids: vec![1],
+            field_ids: vec![2],
+            min_ts: 0,
+            max_ts: 100,
+        };
+        mgr.write(
+            WalEntryType::DeleteRange,
+            Arc::new(delete.encode().unwrap()),
+            1,
+            Arc::new(b"cnosdb".to_vec()),
+        )
+        .await
+        .unwrap();
+        mgr.close().await.unwrap();
+
+        let entries = wal::read_vnode_wal(&dir, 1, 6, None, usize::MAX)
+            .await
+            .unwrap();
+        let seqs: Vec<u64> = entries.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![7, 9, 11]);
+        assert_eq!(entries[0].typ, WalEntryType::Write);
+        assert_eq!(entries[0].tenant, "cnosdb");
+        assert_eq!(entries[0].data, data_vec[6]);
+        assert_eq!(entries[2].typ, WalEntryType::DeleteRange);
+        assert_eq!(WalDeleteSeries::decode(&entries[2].data).unwrap(), delete);
+
+        let entries = wal::read_vnode_wal(&dir, 1, 6, None, 1).await.unwrap();
+        assert_eq!(entries.len(), 1);
+
+        // The reading is continued from the cursor of the last entry.
+        let entries = wal::read_vnode_wal(&dir, 1, 7, Some(entries[0].cursor), usize::MAX)
+            .await
+            .unwrap();
+        let seqs: Vec<u64> = entries.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![9, 11]);
+
+        match wal::read_vnode_wal(&dir, 1, 2, None, usize::MAX).await {
+            Err(Error::WalDeleted { seq }) => assert_eq!(seq, 2),
+            _ => panic!("expect wal deleted"),
+        }
+    }
+
+    #[test]
+    fn test_wal_delete_series_codec() {
+        let delete = WalDeleteSeries {
+            database: "db0".to_string(),
+            series_ids: vec![1, 2],
+            field_ids: vec![3],
+            min_ts: -10,
+            max_ts: 100,
+        };
+        let data = delete.encode().unwrap();
+        let decoded = WalDeleteSeries::decode(&data).unwrap();
+        assert_eq!(decoded, delete);
+        assert_eq!(decoded.time_range(), TimeRange::new(-10, 100));
+    }
+
     #[test]
     #[serial]
     fn test_recover_from_wal() {
@@ -68,6 +68,7 @@ mod tests {
                 password: None,
             }),
             points,
+            consistency_level: None,
         };
 
         rt.spawn(async move {
@@ -94,6 +95,7 @@ mod tests {
                 password: None,
             }),
             points,
+            consistency_level: None,
         };
         rt.block_on(async {
             tskv.write(0, request.clone()).await.unwrap();
@@ -137,6 +139,7 @@ mod tests {
                     password: None,
                 }),
                 points,
+                consistency_level: None,
             };
 
             rt.block_on(async {
@@ -162,6 +165,7 @@ mod tests {
                 password: None,
             }),
             points,
+            consistency_level: None,
         };
 
         rt.block_on(async {
@@ -216,6 +220,7 @@ mod tests {
                 password: None,
             }),
             points,
+            consistency_level: None,
         };
 
         rt.block_on(async {